@@ -0,0 +1,90 @@
+//! Timestamped output of the most recent build session, kept in memory so it can be
+//! written to a file (export_build_log) instead of copy-pasted from the log view.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Lines kept per session; older ones are dropped (the export notes how many).
+const MAX_LINES: usize = 100_000;
+
+/// Log lines of the latest session. Lines of older sessions that are still running are ignored.
+#[derive(Default)]
+pub struct BuildLog(Mutex<SessionLog>);
+
+#[derive(Default)]
+struct SessionLog {
+    session: u64,
+    /// Unix seconds and instant the session's first line arrived
+    started: Option<(u64, Instant)>,
+    lines: VecDeque<(Duration, String)>,
+    dropped: usize,
+}
+
+impl SessionLog {
+    fn push(&mut self, session: u64, line: String, now: Instant) {
+        if session < self.session {
+            return;
+        }
+        if session > self.session || self.started.is_none() {
+            let unix = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            *self = SessionLog { session, started: Some((unix, now)), ..Default::default() };
+        }
+        let elapsed = self.started.map_or(Duration::ZERO, |(_, start)| now.duration_since(start));
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back((elapsed, line));
+    }
+
+    /// `[+   1.234s] line` per line, after a header naming the session and its start (UTC).
+    fn render(&self) -> String {
+        let Some((unix, _)) = self.started else { return String::new() };
+        let mut text = format!("# ngmake build log: session {}, started {} UTC\n", self.session, ngmake::history::format_timestamp(unix));
+        if self.dropped > 0 {
+            text.push_str(&format!("# ({} earlier lines dropped)\n", self.dropped));
+        }
+        for (elapsed, line) in &self.lines {
+            text.push_str(&format!("[+{:>8.3}s] {}\n", elapsed.as_secs_f64(), line));
+        }
+        text
+    }
+}
+
+impl BuildLog {
+    /// Records a log line of `session`; a newer session replaces the kept log.
+    pub fn push(&self, session: u64, line: String) {
+        self.0.lock().unwrap().push(session, line, Instant::now());
+    }
+
+    /// Writes the latest session's log to `path`. Returns the number of lines written.
+    pub fn export(&self, path: &Path) -> Result<usize, String> {
+        let log = self.0.lock().unwrap();
+        if log.started.is_none() {
+            return Err("No build output to export yet".to_string());
+        }
+        std::fs::write(path, log.render()).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        Ok(log.lines.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_only_the_latest_session() {
+        let mut log = SessionLog::default();
+        let start = Instant::now();
+        log.push(1, "old".to_string(), start);
+        log.push(2, "first".to_string(), start);
+        log.push(1, "late line of session 1".to_string(), start);
+        log.push(2, "second".to_string(), start + Duration::from_millis(1500));
+        let text = log.render();
+        let lines: Vec<&str> = text.lines().skip(1).collect();
+        assert_eq!(lines, ["[+   0.000s] first", "[+   1.500s] second"]);
+        assert!(text.starts_with("# ngmake build log: session 2, started "));
+    }
+}
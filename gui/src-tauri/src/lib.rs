@@ -1,16 +1,87 @@
-use ngmake::{find_workspace_root, parse_and_graph, build_and_collect_output, build_and_stream_output, clean_project_and_stream_output, convert_cmake_to_toml_files};
+use ngmake::{find_workspace_root, parse_and_graph, build_and_collect_output, build_and_stream_events, clean_project_and_stream_events, target_run_command, convert_cmake_to_toml_files, load_build_history, BuildControl, BuildEvent, BuildStats};
+use ngmake::edit::{edit_config_file, write_config_file, FlagKind};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
-use std::sync::Arc;
+use std::time::Duration;
+use std::sync::{Arc, Mutex};
 use tauri::{Emitter, EventTarget, Manager};
 
-/// Shared flag for build cancellation (set by cancel_build, read by build loop).
-struct BuildCancel(Arc<AtomicBool>);
+mod buildlog;
+mod settings;
 
+/// Running build/clean/run sessions by ID. Each run_build_async / run_clean_async / run_target_async
+/// gets its own ID and control handle, so several builds can run and be cancelled independently.
+#[derive(Default)]
+struct BuildSessions {
+    next_id: AtomicU64,
+    active: Mutex<HashMap<u64, Arc<BuildControl>>>,
+}
+
+impl BuildSessions {
+    fn start(&self) -> (u64, Arc<BuildControl>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let control = Arc::new(BuildControl::new());
+        self.active.lock().unwrap().insert(id, control.clone());
+        (id, control)
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<BuildControl>> {
+        self.active.lock().unwrap().get(&id).cloned()
+    }
+
+    fn finish(&self, id: u64) {
+        self.active.lock().unwrap().remove(&id);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.active.lock().unwrap().is_empty()
+    }
+}
+
+/// The active file watcher (see start_watch). Dropping it stops watching and ends the debounce thread.
+#[derive(Default)]
+struct FileWatcher(Mutex<Option<notify::RecommendedWatcher>>);
+
+fn session_control(app: &tauri::AppHandle, session: u64) -> Result<Arc<BuildControl>, String> {
+    app.state::<BuildSessions>()
+        .get(session)
+        .ok_or_else(|| format!("No running build with session {}", session))
+}
+
+/// Parses a build.toml for display and records it as the most recent project.
+#[tauri::command]
+fn parse_build_toml(app: tauri::AppHandle, path: String) -> Result<ngmake::ProjectInfo, String> {
+    let path = PathBuf::from(path);
+    let info = parse_and_graph(path.as_path())?;
+    // A settings file that can't be written shouldn't stop the project from loading
+    let _ = settings::add_recent(&app, &path);
+    Ok(info)
+}
+
+#[tauri::command]
+fn get_settings(app: tauri::AppHandle) -> settings::Settings {
+    settings::load(&app)
+}
+
+/// Stores jobs, preset and layout. The recent projects are kept as recorded by parse_build_toml.
+#[tauri::command]
+fn set_settings(app: tauri::AppHandle, settings: settings::Settings) -> Result<(), String> {
+    let settings = settings::Settings { recent_projects: settings::load(&app).recent_projects, ..settings };
+    settings::save(&app, &settings)
+}
+
+/// Recently opened build.toml files that still exist, most recent first.
+#[tauri::command]
+fn recent_projects(app: tauri::AppHandle) -> Vec<String> {
+    settings::recent(&app)
+}
+
+/// All problems in the build.toml (and its includes) with file/line/column, for inline display.
 #[tauri::command]
-fn parse_build_toml(path: String) -> Result<ngmake::ProjectInfo, String> {
-    parse_and_graph(PathBuf::from(path).as_path())
+fn validate_build_toml(path: String) -> Vec<ngmake::validate::ConfigError> {
+    ngmake::validate::validate_config(PathBuf::from(path).as_path())
 }
 
 #[tauri::command]
@@ -23,6 +94,34 @@ fn write_file(path: String, content: String) -> Result<(), String> {
     std::fs::write(&path, content).map_err(|e| e.to_string())
 }
 
+/// Structured build.toml edits (comments and formatting are preserved). Each command edits
+/// the given file only; targets from included files must be edited in their own build.toml.
+#[tauri::command]
+fn add_target(config_path: String, name: String, target_type: ngmake::TargetType, sources: Vec<String>) -> Result<(), String> {
+    edit_config_file(PathBuf::from(config_path).as_path(), |doc| doc.add_target(&name, &target_type, &sources))
+}
+
+#[tauri::command]
+fn remove_target(config_path: String, name: String) -> Result<(), String> {
+    edit_config_file(PathBuf::from(config_path).as_path(), |doc| doc.remove_target(&name))
+}
+
+#[tauri::command]
+fn add_dependency(config_path: String, target: String, dep: String) -> Result<(), String> {
+    edit_config_file(PathBuf::from(config_path).as_path(), |doc| doc.add_dependency(&target, &dep))
+}
+
+/// kind: "flags", "compiler_flags" or "linker_flags". An empty list removes the key.
+#[tauri::command]
+fn set_flags(config_path: String, target: String, kind: FlagKind, flags: Vec<String>) -> Result<(), String> {
+    edit_config_file(PathBuf::from(config_path).as_path(), |doc| doc.set_flags(&target, kind, &flags))
+}
+
+#[tauri::command]
+fn rename_target(config_path: String, old_name: String, new_name: String) -> Result<(), String> {
+    edit_config_file(PathBuf::from(config_path).as_path(), |doc| doc.rename_target(&old_name, &new_name))
+}
+
 #[tauri::command]
 fn run_build(
     config_path: String,
@@ -41,27 +140,127 @@ fn run_build(
     Ok(BuildOutput { success, lines })
 }
 
-/// Payload for build-finished event (thread-safe serialization to frontend).
+/// `build-event` payload: a typed event tagged with its session.
 #[derive(Clone, serde::Serialize)]
-struct BuildFinishedPayload {
-    success: bool,
-    total: usize,
-    successful: usize,
-    failed: usize,
+struct SessionEvent {
+    session: u64,
+    event: BuildEvent,
+}
+
+/// `build-output` payload: one log line of a session.
+#[derive(Clone, serde::Serialize)]
+struct SessionLine {
+    session: u64,
+    line: String,
+}
+
+/// `target-status` payload: a target's new state (see ngmake::TargetState), for the graph view.
+#[derive(Clone, serde::Serialize)]
+struct SessionTargetStatus {
+    session: u64,
+    target: String,
+    #[serde(flatten)]
+    status: ngmake::TargetState,
 }
 
-/// Request cancellation of the current build. Build will stop after the current job.
+/// `build-finished` payload: final stats of a session.
+#[derive(Clone, serde::Serialize)]
+struct SessionFinished {
+    session: u64,
+    #[serde(flatten)]
+    stats: BuildStats,
+}
+
+/// Emits one event to the main window (scheduled on the main thread):
+/// `build-event` carries every typed event, `build-output` the legacy log line
+/// (for the log view), `target-status` each target's state (for the graph view)
+/// and `build-finished` the final stats. Log lines are also kept for export_build_log.
+fn emit_event(app: &tauri::AppHandle, session: u64, event: BuildEvent) {
+    if let Some(line) = event.legacy_line() {
+        app.state::<buildlog::BuildLog>().push(session, line);
+    }
+    let app_emit = app.clone();
+    let _ = app.run_on_main_thread(move || {
+        let window = EventTarget::webview_window("main");
+        match &event {
+            BuildEvent::Finished { stats } => {
+                let _ = app_emit.emit_to(window.clone(), "build-finished", SessionFinished { session, stats: *stats });
+            }
+            BuildEvent::TargetStatus { target, status } => {
+                let payload = SessionTargetStatus { session, target: target.clone(), status: *status };
+                let _ = app_emit.emit_to(window.clone(), "target-status", payload);
+            }
+            _ => {
+                if let Some(line) = event.legacy_line() {
+                    let _ = app_emit.emit_to(window.clone(), "build-output", SessionLine { session, line });
+                }
+            }
+        }
+        let _ = app_emit.emit_to(window, "build-event", SessionEvent { session, event });
+    });
+}
+
+/// Forwards a session's events until the channel closes.
+fn forward_events(app: &tauri::AppHandle, session: u64, rx: mpsc::Receiver<BuildEvent>) {
+    for event in rx {
+        emit_event(app, session, event);
+    }
+}
+
+fn failed_stats() -> BuildStats {
+    BuildStats { success: false, failed: 1, ..Default::default() }
+}
+
+/// `build-paused` payload.
+#[derive(Clone, serde::Serialize)]
+struct SessionPaused {
+    session: u64,
+    paused: bool,
+}
+
+/// Request cancellation of a build session. Running compiler/linker processes are killed.
+/// Finished sessions are ignored.
+#[tauri::command]
+fn cancel_build(app: tauri::AppHandle, session: u64) -> Result<(), String> {
+    if let Ok(control) = session_control(&app, session) {
+        control.cancel();
+        // Wake a paused build so it can wind down
+        control.resume();
+    }
+    Ok(())
+}
+
+/// Pauses a build session: no new jobs start, running ones finish, progress is kept.
+#[tauri::command]
+fn pause_build(app: tauri::AppHandle, session: u64) -> Result<(), String> {
+    set_paused(&app, session, true)
+}
+
+/// Resumes a paused build session.
 #[tauri::command]
-fn cancel_build(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(state) = app.try_state::<BuildCancel>() {
-        state.0.store(true, Ordering::Relaxed);
+fn resume_build(app: tauri::AppHandle, session: u64) -> Result<(), String> {
+    set_paused(&app, session, false)
+}
+
+fn set_paused(app: &tauri::AppHandle, session: u64, paused: bool) -> Result<(), String> {
+    let control = session_control(app, session)?;
+    if paused {
+        control.pause();
+    } else {
+        control.resume();
     }
+    let line = if paused { "[INFO] Build paused." } else { "[INFO] Build resumed." };
+    emit_event(app, session, BuildEvent::output(None, line));
+    let _ = app.emit_to(EventTarget::webview_window("main"), "build-paused", SessionPaused { session, paused });
     Ok(())
 }
 
 /// Starts build (or clean+build) in a background thread and emits progress via events.
+/// Returns the session ID that tags all events of this build and is passed to
+/// cancel_build / pause_build / resume_build.
 /// Emits are scheduled on the main thread so the frontend receives them.
 /// jobs: None = auto. ignore_errors: continue after a target fails (like make -i).
+/// preset: a [presets] entry of build.toml (see list_presets); targets and jobs override it.
 #[tauri::command]
 fn run_build_async(
     app: tauri::AppHandle,
@@ -70,137 +269,318 @@ fn run_build_async(
     clean: bool,
     jobs: Option<u32>,
     ignore_errors: bool,
-) -> Result<(), String> {
-    let cancel = app.try_state::<BuildCancel>().map(|s| s.0.clone()).unwrap_or_else(|| Arc::new(AtomicBool::new(false)));
-    cancel.store(false, Ordering::Relaxed);
+    preset: Option<String>,
+) -> Result<u64, String> {
+    Ok(start_build(app, config_path, targets, clean, jobs, ignore_errors, preset))
+}
+
+/// Rebuilds one target from scratch: removes its objects and output (like `ngm --force-rebuild`),
+/// then builds it and its dependencies. Returns the session ID, like run_build_async.
+#[tauri::command]
+fn rebuild_target_async(app: tauri::AppHandle, config_path: String, target: String, jobs: Option<u32>) -> Result<u64, String> {
+    let path = PathBuf::from(&config_path);
+    let root = find_workspace_root(path.as_path()).unwrap_or(path);
+    ngmake::force_rebuild(root.as_path(), std::slice::from_ref(&target))?;
+    Ok(start_build(app, config_path, Some(vec![target]), false, jobs, false, None))
+}
+
+fn start_build(
+    app: tauri::AppHandle,
+    config_path: String,
+    targets: Option<Vec<String>>,
+    clean: bool,
+    jobs: Option<u32>,
+    ignore_errors: bool,
+    preset: Option<String>,
+) -> u64 {
+    let (session, control) = app.state::<BuildSessions>().start();
     std::thread::spawn(move || {
-        let (tx, rx) = mpsc::channel();
-        let _ = tx.send("[INFO] Build started.".to_string());
-        let path = PathBuf::from(&config_path);
-        // Use workspace root if this file is included by a parent build.toml (CMake-like: build from root).
-        let path = match find_workspace_root(path.as_path()) {
-            Some(root) => {
-                let _ = tx.send(format!("[INFO] Using workspace root: {}", root.display()));
-                root
-            }
-            None => path,
-        };
-        let tx_for_error = tx.clone();
-        let jobs_usize = jobs.map(|j| j as usize);
-        let build_handle = std::thread::spawn(move || {
-            match build_and_stream_output(path.as_path(), targets, clean, jobs_usize, ignore_errors, tx, Some(cancel)) {
-                Ok(_) => {}
-                Err(e) => {
-                    let _ = tx_for_error.send(format!("[ERROR] {}", e));
-                    let _ = tx_for_error.send("__ngmake_FINISH__\tfalse\t0\t0\t1".to_string());
-                }
-            }
-        });
-        for line in rx {
-            if line.starts_with("__ngmake_FINISH__") {
-                let parts: Vec<&str> = line.split('\t').collect();
-                let success = parts.get(1).map(|s| *s == "true").unwrap_or(false);
-                let total = parts.get(2).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let successful = parts.get(3).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let failed = parts.get(4).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let payload = BuildFinishedPayload {
-                    success,
-                    total,
-                    successful,
-                    failed,
-                };
-                let app_emit = app.clone();
-                let _ = app.run_on_main_thread(move || {
-                    let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-finished", payload);
-                });
-            } else {
-                let app_emit = app.clone();
-                let _ = app.run_on_main_thread(move || {
-                    let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-output", line);
-                });
+        build_in_session(&app, session, control, &config_path, targets, clean, jobs, ignore_errors, preset.as_deref());
+        app.state::<BuildSessions>().finish(session);
+    });
+    session
+}
+
+/// Runs a build in the current thread, emitting its events for `session`.
+/// Returns the config actually built (workspace root if config_path is included by a parent)
+/// when the build succeeded.
+#[allow(clippy::too_many_arguments)]
+fn build_in_session(
+    app: &tauri::AppHandle,
+    session: u64,
+    control: Arc<BuildControl>,
+    config_path: &str,
+    targets: Option<Vec<String>>,
+    clean: bool,
+    jobs: Option<u32>,
+    ignore_errors: bool,
+    preset: Option<&str>,
+) -> Option<PathBuf> {
+    let (tx, rx) = mpsc::channel();
+    let _ = tx.send(BuildEvent::output(None, "[INFO] Build started."));
+    let path = PathBuf::from(config_path);
+    // Use workspace root if this file is included by a parent build.toml (CMake-like: build from root).
+    let path = match find_workspace_root(path.as_path()) {
+        Some(root) => {
+            let _ = tx.send(BuildEvent::output(None, format!("[INFO] Using workspace root: {}", root.display())));
+            root
+        }
+        None => path,
+    };
+    let tx_for_error = tx.clone();
+    let jobs_usize = jobs.map(|j| j as usize);
+    let build_path = path.clone();
+    let preset = preset.map(str::to_string);
+    let build_handle = std::thread::spawn(move || {
+        match build_and_stream_events(build_path.as_path(), targets, clean, jobs_usize, ignore_errors, preset.as_deref(), tx, Some(control)) {
+            Ok(success) => success,
+            Err(e) => {
+                let _ = tx_for_error.send(BuildEvent::output(None, format!("[ERROR] {}", e)));
+                let _ = tx_for_error.send(BuildEvent::Finished { stats: failed_stats() });
+                false
             }
         }
-        if let Err(e) = build_handle.join() {
-            let err_msg = format!("[ERROR] Build thread panicked: {:?}", e);
-            let app_emit = app.clone();
-            let _ = app.run_on_main_thread(move || {
-                let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-output", err_msg);
-                let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-finished", BuildFinishedPayload {
-                    success: false,
-                    total: 0,
-                    successful: 0,
-                    failed: 1,
-                });
-            });
+    });
+    forward_events(app, session, rx);
+    match build_handle.join() {
+        Ok(true) => Some(path),
+        Ok(false) => None,
+        Err(e) => {
+            emit_event(app, session, BuildEvent::output(None, format!("[ERROR] Build thread panicked: {:?}", e)));
+            emit_event(app, session, BuildEvent::Finished { stats: failed_stats() });
+            None
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct RunLine {
+    session: u64,
+    /// "stdout" or "stderr"
+    stream: &'static str,
+    line: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct RunFinished {
+    session: u64,
+    /// Exit code; None if the program could not be started or was killed by a signal
+    code: Option<i32>,
+    killed: bool,
+}
+
+/// Builds `target` if needed, then runs it with `args` (working directory = project root,
+/// LD_LIBRARY_PATH set for its shared library deps), both with `preset` if given (see
+/// run_build_async). The build emits the usual build events;
+/// the program's output is emitted as "run-output" lines and its exit as "run-finished".
+/// Returns the session ID; kill_run / cancel_build with it stops the build or the program.
+#[tauri::command]
+fn run_target_async(
+    app: tauri::AppHandle,
+    config_path: String,
+    target: String,
+    args: Vec<String>,
+    jobs: Option<u32>,
+    preset: Option<String>,
+) -> Result<u64, String> {
+    let (session, control) = app.state::<BuildSessions>().start();
+    std::thread::spawn(move || {
+        let targets = Some(vec![target.clone()]);
+        match build_in_session(&app, session, control.clone(), &config_path, targets, false, jobs, false, preset.as_deref()) {
+            Some(config) if !control.is_cancelled() => run_program(&app, session, &control, &config, &target, &args, preset.as_deref()),
+            _ => emit_run(&app, "run-finished", RunFinished { session, code: None, killed: control.is_cancelled() }),
         }
+        app.state::<BuildSessions>().finish(session);
     });
+    Ok(session)
+}
+
+/// Kills the program (or the build preceding it) started by run_target_async.
+#[tauri::command]
+fn kill_run(app: tauri::AppHandle, session: u64) -> Result<(), String> {
+    session_control(&app, session)?.cancel();
     Ok(())
 }
 
+fn emit_run<S: serde::Serialize + Clone>(app: &tauri::AppHandle, event: &str, payload: S) {
+    let _ = app.emit_to(EventTarget::webview_window("main"), event, payload);
+}
+
+fn run_program(app: &tauri::AppHandle, session: u64, control: &BuildControl, config: &std::path::Path, target: &str, args: &[String], preset: Option<&str>) {
+    use std::io::BufRead;
+    use std::process::Stdio;
+
+    let spawned = target_run_command(config, target, args, preset).and_then(|mut cmd| {
+        // Own process group, so kill_run also stops the processes the program starts
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        cmd.spawn().map_err(|e| format!("Failed to start {}: {}", target, e))
+    });
+    let mut child = match spawned {
+        Ok(c) => c,
+        Err(e) => {
+            emit_run(app, "run-output", RunLine { session, stream: "stderr", line: format!("[ERROR] {}", e) });
+            emit_run(app, "run-finished", RunFinished { session, code: None, killed: false });
+            return;
+        }
+    };
+    emit_run(app, "run-output", RunLine { session, stream: "stdout", line: format!("[INFO] Running {} {}", target, args.join(" ")) });
+
+    let mut readers = Vec::new();
+    let stdout = child.stdout.take().map(|p| ("stdout", Box::new(p) as Box<dyn std::io::Read + Send>));
+    let stderr = child.stderr.take().map(|p| ("stderr", Box::new(p) as Box<dyn std::io::Read + Send>));
+    for (stream, pipe) in stdout.into_iter().chain(stderr) {
+        let app = app.clone();
+        readers.push(std::thread::spawn(move || {
+            for line in std::io::BufReader::new(pipe).lines().map_while(Result::ok) {
+                emit_run(&app, "run-output", RunLine { session, stream, line });
+            }
+        }));
+    }
+
+    let mut killed = false;
+    let code = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status.code(),
+            Ok(None) => {
+                if !killed && control.is_cancelled() {
+                    ngmake::compiler::kill_process_group(child.id());
+                    killed = true;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(_) => break None,
+        }
+    };
+    for r in readers {
+        let _ = r.join();
+    }
+    emit_run(app, "run-finished", RunFinished { session, code, killed });
+}
+
 /// Cleans the project only (removes .o, .a, .so, exes in output dirs). Emits same events as build for UI.
+/// Returns the session ID tagging the events.
 #[tauri::command]
-fn run_clean_async(app: tauri::AppHandle, config_path: String) -> Result<(), String> {
+fn run_clean_async(app: tauri::AppHandle, config_path: String) -> Result<u64, String> {
+    let (session, _control) = app.state::<BuildSessions>().start();
     std::thread::spawn(move || {
         let (tx, rx) = mpsc::channel();
         let path = PathBuf::from(&config_path);
         let path = match find_workspace_root(path.as_path()) {
             Some(root) => {
-                let _ = tx.send(format!("[INFO] Using workspace root: {}", root.display()));
+                let _ = tx.send(BuildEvent::output(None, format!("[INFO] Using workspace root: {}", root.display())));
                 root
             }
             None => path,
         };
         let tx_for_error = tx.clone();
         let clean_handle = std::thread::spawn(move || {
-            match clean_project_and_stream_output(path.as_path(), tx) {
+            match clean_project_and_stream_events(path.as_path(), tx) {
                 Ok(()) => {}
                 Err(e) => {
-                    let _ = tx_for_error.send(format!("[ERROR] {}", e));
-                    let _ = tx_for_error.send("__ngmake_FINISH__\tfalse\t0\t0\t1".to_string());
+                    let _ = tx_for_error.send(BuildEvent::output(None, format!("[ERROR] {}", e)));
+                    let _ = tx_for_error.send(BuildEvent::Finished { stats: failed_stats() });
                 }
             }
         });
-        for line in rx {
-            if line.starts_with("__ngmake_FINISH__") {
-                let parts: Vec<&str> = line.split('\t').collect();
-                let success = parts.get(1).map(|s| *s == "true").unwrap_or(false);
-                let total = parts.get(2).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let successful = parts.get(3).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let failed = parts.get(4).and_then(|s| s.parse().ok()).unwrap_or(0);
-                let payload = BuildFinishedPayload {
-                    success,
-                    total,
-                    successful,
-                    failed,
-                };
-                let app_emit = app.clone();
-                let _ = app.run_on_main_thread(move || {
-                    let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-finished", payload);
-                });
-            } else {
-                let app_emit = app.clone();
-                let _ = app.run_on_main_thread(move || {
-                    let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-output", line);
-                });
+        forward_events(&app, session, rx);
+        if let Err(e) = clean_handle.join() {
+            emit_event(&app, session, BuildEvent::output(None, format!("[ERROR] Clean thread panicked: {:?}", e)));
+            emit_event(&app, session, BuildEvent::Finished { stats: failed_stats() });
+        }
+        app.state::<BuildSessions>().finish(session);
+    });
+    Ok(session)
+}
+
+/// Returns recorded builds (oldest first) for charting build times over time.
+#[tauri::command]
+fn get_build_history(config_path: String) -> Result<Vec<ngmake::history::BuildRecord>, String> {
+    load_build_history(PathBuf::from(config_path).as_path())
+}
+
+/// Quiet period after the last file event before files-changed is emitted (editors save in bursts).
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Files of the project at config_path whose changes trigger files-changed (see WatchedFiles):
+/// only build.toml files while it doesn't parse.
+fn watched_files(config_path: &str) -> ngmake::snapshot::WatchedFiles {
+    ngmake::parse_build_file(std::path::Path::new(config_path), false)
+        .map(|project| ngmake::snapshot::WatchedFiles::new(&project))
+        .unwrap_or_default()
+}
+
+#[derive(Clone, serde::Serialize)]
+struct FilesChanged {
+    paths: Vec<String>,
+    /// Session of the build started for this change (auto_build), if any.
+    session: Option<u64>,
+}
+
+/// Watches the project directory (workspace root if config_path is included by a parent build.toml)
+/// and emits "files-changed" with the changed paths after a short debounce.
+/// auto_build: also start a build for each change (skipped while another build is running).
+/// Replaces any previous watcher.
+#[tauri::command]
+fn start_watch(app: tauri::AppHandle, config_path: String, auto_build: bool, jobs: Option<u32>) -> Result<(), String> {
+    use notify::Watcher;
+
+    let dir = ngmake::project_root_dir(std::path::Path::new(&config_path));
+    // Derived from the project's sources, inputs and [compilers]; refreshed when a build.toml changes
+    let watched = Arc::new(Mutex::new(watched_files(&config_path)));
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let filter = Arc::clone(&watched);
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.kind.is_access() {
+                return;
+            }
+            let watched = filter.lock().unwrap();
+            for p in event.paths.into_iter().filter(|p| watched.contains(p)) {
+                let _ = tx.send(p);
             }
         }
-        if let Err(e) = clean_handle.join() {
-            let err_msg = format!("[ERROR] Clean thread panicked: {:?}", e);
-            let app_emit = app.clone();
-            let _ = app.run_on_main_thread(move || {
-                let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-output", err_msg);
-                let _ = app_emit.emit_to(EventTarget::webview_window("main"), "build-finished", BuildFinishedPayload {
-                    success: false,
-                    total: 0,
-                    successful: 0,
-                    failed: 1,
-                });
-            });
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(&dir, notify::RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    *app.state::<FileWatcher>().0.lock().unwrap() = Some(watcher);
+
+    std::thread::spawn(move || {
+        // Ends when the watcher (and with it the sender) is dropped by stop_watch / start_watch.
+        while let Ok(first) = rx.recv() {
+            let mut changed = vec![first];
+            while let Ok(p) = rx.recv_timeout(WATCH_DEBOUNCE) {
+                changed.push(p);
+            }
+            changed.sort();
+            changed.dedup();
+            if changed.iter().any(|p| p.file_name().is_some_and(|n| n == "build.toml")) {
+                *watched.lock().unwrap() = watched_files(&config_path);
+            }
+            let session = if auto_build && app.state::<BuildSessions>().is_idle() {
+                Some(start_build(app.clone(), config_path.clone(), None, false, jobs, false, None))
+            } else {
+                None
+            };
+            let paths = changed.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+            let _ = app.emit_to(EventTarget::webview_window("main"), "files-changed", FilesChanged { paths, session });
         }
     });
     Ok(())
 }
 
+/// Stops the watcher started by start_watch (no-op if none is running).
+#[tauri::command]
+fn stop_watch(app: tauri::AppHandle) {
+    app.state::<FileWatcher>().0.lock().unwrap().take();
+}
+
 /// Returns the number of logical CPUs (for jobs selector). At least 1.
 #[tauri::command]
 fn get_max_jobs() -> u32 {
@@ -223,6 +603,26 @@ fn open_file_dialog() -> Result<Option<String>, String> {
     Ok(path.map(|p| p.to_string_lossy().to_string()))
 }
 
+/// Writes the timestamped output of the most recent build session to `path`.
+/// Returns the number of lines written.
+#[tauri::command]
+fn export_build_log(app: tauri::AppHandle, path: String) -> Result<usize, String> {
+    app.state::<buildlog::BuildLog>().export(PathBuf::from(path).as_path())
+}
+
+#[tauri::command]
+fn save_log_dialog() -> Result<Option<String>, String> {
+    use rfd::FileDialog;
+
+    let path = FileDialog::new()
+        .add_filter("Log Files", &["log", "txt"])
+        .set_file_name("ngmake-build.log")
+        .set_title("Export build log")
+        .save_file();
+
+    Ok(path.map(|p| p.to_string_lossy().to_string()))
+}
+
 #[tauri::command]
 fn open_cmake_dialog() -> Result<Option<String>, String> {
     use rfd::FileDialog;
@@ -248,6 +648,7 @@ fn convert_cmake_to_toml(cmake_path: String) -> Result<ConvertResult, String> {
     
     // Write all build.toml files to disk
     let mut build_toml_files = Vec::new();
+    let mut root_toml = None;
     for (rel_path, content) in &toml_files {
         let full_path = if rel_path == "build.toml" {
             base_path.join("build.toml")
@@ -260,21 +661,38 @@ fn convert_cmake_to_toml(cmake_path: String) -> Result<ConvertResult, String> {
             fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
         }
         
-        fs::write(&full_path, content).map_err(|e| format!("Failed to write {}: {}", full_path.display(), e))?;
+        let written = write_config_file(&full_path, content)?;
+        if rel_path == "build.toml" {
+            root_toml = Some(written);
+        }
         build_toml_files.push(full_path.to_string_lossy().to_string());
     }
     
-    // Get root build.toml content
-    let root_toml = toml_files.get("build.toml")
-        .ok_or("Root build.toml not found")?;
+    // Root build.toml content as written (merged into an existing file)
+    let root_toml = root_toml.ok_or("Root build.toml not found")?;
     
     Ok(ConvertResult {
-        toml_content: root_toml.clone(),
+        toml_content: root_toml,
         project_root: base_path.to_string_lossy().to_string(),
         build_toml_files,
     })
 }
 
+/// The [presets] of the project (name → description, targets, jobs, defines), for the preset dropdown.
+#[tauri::command]
+fn list_presets(config_path: String) -> Result<std::collections::BTreeMap<String, ngmake::preset::Preset>, String> {
+    ngmake::list_presets(PathBuf::from(config_path).as_path())
+}
+
+/// Writes compile_commands.json for IDEs; `out_path` defaults to the root build.toml's directory.
+/// Returns the path written.
+#[tauri::command]
+fn export_compile_commands(config_path: String, out_path: Option<String>) -> Result<String, String> {
+    let out = out_path.map(PathBuf::from);
+    let (path, _) = ngmake::export_compile_commands(PathBuf::from(config_path).as_path(), out.as_deref())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 fn find_build_toml_files(root_path: String) -> Result<Vec<String>, String> {
     use std::fs;
@@ -519,8 +937,7 @@ namespace {} {{
     
     // Write build.toml
     let build_toml_path = project_path.join("build.toml");
-    fs::write(&build_toml_path, &toml_content)
-        .map_err(|e| format!("Failed to write build.toml: {}", e))?;
+    write_config_file(&build_toml_path, &toml_content)?;
     
     Ok(InitResult {
         toml_content,
@@ -552,20 +969,43 @@ struct BuildOutput {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(BuildCancel(Arc::new(AtomicBool::new(false))))
+        .manage(BuildSessions::default())
+        .manage(FileWatcher::default())
+        .manage(buildlog::BuildLog::default())
         .invoke_handler(tauri::generate_handler![
             parse_build_toml,
+            get_settings,
+            set_settings,
+            recent_projects,
+            validate_build_toml,
             read_file,
             write_file,
+            add_target,
+            remove_target,
+            add_dependency,
+            set_flags,
+            rename_target,
             run_build,
             run_build_async,
+            rebuild_target_async,
             cancel_build,
+            pause_build,
+            resume_build,
             run_clean_async,
+            run_target_async,
+            kill_run,
             get_max_jobs,
+            get_build_history,
+            start_watch,
+            stop_watch,
             open_file_dialog,
             open_cmake_dialog,
             convert_cmake_to_toml,
             find_build_toml_files,
+            export_compile_commands,
+            list_presets,
+            export_build_log,
+            save_log_dialog,
             init_project,
         ])
         .run(tauri::generate_context!())
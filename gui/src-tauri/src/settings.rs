@@ -0,0 +1,96 @@
+//! GUI settings and recent projects, kept in `settings.json` in the app data directory
+//! so the GUI doesn't start cold on every launch.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+const FILE_NAME: &str = "settings.json";
+
+/// Recent build.toml paths kept (most recent first).
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Recently opened build.toml files, most recent first
+    pub recent_projects: Vec<String>,
+    /// Last used parallel jobs (None = auto)
+    pub jobs: Option<u32>,
+    /// Last selected build.toml preset (None = no preset)
+    pub preset: Option<String>,
+    /// Window layout (active tab, panel sizes, ...); owned by the frontend
+    pub layout: serde_json::Value,
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("No app data directory: {}", e))?;
+    Ok(dir.join(FILE_NAME))
+}
+
+/// Stored settings; defaults if there are none yet or the file is unreadable.
+pub fn load(app: &tauri::AppHandle) -> Settings {
+    settings_path(app).map(|path| read(&path)).unwrap_or_default()
+}
+
+pub fn save(app: &tauri::AppHandle, settings: &Settings) -> Result<(), String> {
+    write(&settings_path(app)?, settings)
+}
+
+fn read(path: &Path) -> Settings {
+    std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+fn write(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    // Write then rename, so a crash never leaves a half-written file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("Failed to write '{}': {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Moves `config_path` to the front of the recent projects.
+pub fn add_recent(app: &tauri::AppHandle, config_path: &Path) -> Result<(), String> {
+    let path = config_path.to_string_lossy().to_string();
+    let mut settings = load(app);
+    settings.recent_projects.retain(|p| *p != path);
+    settings.recent_projects.insert(0, path);
+    settings.recent_projects.truncate(MAX_RECENT);
+    save(app, &settings)
+}
+
+/// Recent projects whose build.toml still exists.
+pub fn recent(app: &tauri::AppHandle) -> Vec<String> {
+    load(app).recent_projects.into_iter().filter(|p| Path::new(p).is_file()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_survive_a_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("ngmake_gui_settings_{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        let settings = Settings {
+            recent_projects: vec!["/work/app/build.toml".to_string()],
+            jobs: Some(6),
+            preset: Some("release".to_string()),
+            layout: serde_json::json!({ "activeTab": "graph" }),
+        };
+        write(&path, &settings).unwrap();
+        let loaded = read(&path);
+        assert_eq!(loaded.recent_projects, settings.recent_projects);
+        assert_eq!(loaded.jobs, Some(6));
+        assert_eq!(loaded.preset.as_deref(), Some("release"));
+        assert_eq!(loaded.layout, settings.layout);
+
+        // Files written before presets were stored still load
+        std::fs::write(&path, r#"{"jobs": 4}"#).unwrap();
+        assert_eq!(read(&path).preset, None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -15,7 +15,10 @@ function App() {
   const [buildLog, setBuildLog] = useState([]);
   const [buildRunning, setBuildRunning] = useState(false);
   const [parseError, setParseError] = useState(null);
+  const [configProblems, setConfigProblems] = useState([]); // validate_build_toml: { file, line, column, severity, message }
   const [buildSuccess, setBuildSuccess] = useState(null);
+  const [buildDiagnostics, setBuildDiagnostics] = useState(null); // { warnings, errors } of the last finished build
+  const [targetStatus, setTargetStatus] = useState({}); // target → { state, done, total } of the current build
   const [projectRoot, setProjectRoot] = useState(null);
   const [buildTomlFiles, setBuildTomlFiles] = useState([]);
   const [showNewProjectModal, setShowNewProjectModal] = useState(false);
@@ -24,11 +27,55 @@ function App() {
   const [showBuildModal, setShowBuildModal] = useState(false);
   const [buildModalClean, setBuildModalClean] = useState(false); // true = Clean & Build
   const [buildIgnoreErrors, setBuildIgnoreErrors] = useState(false);
+  const [autoBuild, setAutoBuild] = useState(false); // rebuild when sources or build.toml change
+  const [presets, setPresets] = useState({}); // [presets] of build.toml: name → { description, targets, jobs, defines }
+  const [buildPreset, setBuildPreset] = useState(""); // "" = no preset
+  const [runTarget, setRunTarget] = useState("");
+  const [runArgs, setRunArgs] = useState("");
+  const [programRunning, setProgramRunning] = useState(false);
+
+  const [recentProjects, setRecentProjects] = useState([]);
+  const [settingsLoaded, setSettingsLoaded] = useState(false);
 
   useEffect(() => {
     invoke("get_max_jobs").then((n) => setMaxJobs(n)).catch(() => setMaxJobs(8));
   }, []);
 
+  // Restore jobs, preset, layout and recent projects from the last session
+  useEffect(() => {
+    invoke("get_settings")
+      .then((settings) => {
+        setBuildJobs(settings?.jobs ?? 0);
+        setBuildPreset(settings?.preset ?? "");
+        if (settings?.layout?.activeTab) setActiveTab(settings.layout.activeTab);
+      })
+      .catch(() => {})
+      .finally(() => setSettingsLoaded(true));
+    invoke("recent_projects").then((paths) => setRecentProjects(Array.isArray(paths) ? paths : [])).catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    if (!settingsLoaded) return;
+    invoke("set_settings", {
+      settings: { jobs: buildJobs === 0 ? null : buildJobs, preset: buildPreset || null, layout: { activeTab } },
+    }).catch(() => {});
+  }, [settingsLoaded, buildJobs, buildPreset, activeTab]);
+
+  useEffect(() => {
+    if (!configPath) {
+      setPresets({});
+      setBuildPreset("");
+      return;
+    }
+    invoke("list_presets", { configPath })
+      .then((list) => {
+        const next = list && typeof list === "object" ? list : {};
+        setPresets(next);
+        setBuildPreset((prev) => (prev in next ? prev : ""));
+      })
+      .catch(() => setPresets({}));
+  }, [configPath, projectInfo]);
+
   const loadFile = useCallback(async (path) => {
     setConfigPath(path);
     setParseError(null);
@@ -41,9 +88,15 @@ function App() {
       setParseError(String(e));
       return;
     }
+    invoke("validate_build_toml", { path })
+      .then((problems) => setConfigProblems(Array.isArray(problems) ? problems : []))
+      .catch(() => setConfigProblems([]));
     try {
       const info = await invoke("parse_build_toml", { path });
       setProjectInfo(info);
+      // A dependency cycle: the graph is shown with the cycle highlighted
+      if (info?.error) setParseError(info.error);
+      invoke("recent_projects").then((paths) => setRecentProjects(Array.isArray(paths) ? paths : [])).catch(() => {});
     } catch (e) {
       setParseError(String(e));
       setProjectInfo(null);
@@ -128,6 +181,7 @@ function App() {
         await invoke("write_file", { path: suggestedPath, content: result.toml_content || result.tomlContent });
         const info = await invoke("parse_build_toml", { path: suggestedPath });
         setProjectInfo(info);
+        if (info?.error) setParseError(info.error);
       } catch (parseErr) {
         console.error("Parse error:", parseErr);
         setParseError(`Parsing error: ${parseErr}. The file was converted but may need manual editing.`);
@@ -160,6 +214,9 @@ function App() {
 
   // Build event unlisteners (thread-safe: backend emits on channel, frontend updates state on main thread)
   const buildUnlistenRef = useRef([]);
+  // Session ID of the build/clean this window started; events of other sessions are ignored
+  const sessionRef = useRef(null);
+  const isOtherSession = (p) => sessionRef.current != null && p?.session != null && p.session !== sessionRef.current;
   const listenersReadyRef = useRef(false);
   useEffect(() => {
     listenersReadyRef.current = false;
@@ -170,7 +227,8 @@ function App() {
     };
     listen("build-output", (event) => {
       const raw = event?.payload ?? event;
-      const line = typeof raw === "string" ? raw : (raw?.payload != null ? String(raw.payload) : String(raw));
+      if (isOtherSession(raw)) return;
+      const line = typeof raw === "string" ? raw : (raw?.line != null ? String(raw.line) : String(raw));
       setBuildLog((prev) => {
         if (prev.length > 0 && prev[prev.length - 1] === line) return prev;
         return [...prev, line];
@@ -181,9 +239,20 @@ function App() {
     }).catch((err) => {
       console.error("build-output listen failed:", err);
     });
+    listen("target-status", (event) => {
+      const p = (event?.payload ?? event) || {};
+      if (isOtherSession(p) || !p.target) return;
+      setTargetStatus((prev) => ({ ...prev, [p.target]: { state: p.state, done: p.done, total: p.total } }));
+    }, { target: "main" }).then((fn) => {
+      buildUnlistenRef.current.push(fn);
+    }).catch((err) => {
+      console.error("target-status listen failed:", err);
+    });
     listen("build-finished", (event) => {
       const p = (event?.payload ?? event) || {};
+      if (isOtherSession(p)) return;
       setBuildSuccess(p.success ?? false);
+      setBuildDiagnostics({ warnings: p.warnings ?? 0, errors: p.errors ?? 0 });
       setBuildRunning(false);
     }, { target: "main" }).then((fn) => {
       buildUnlistenRef.current.push(fn);
@@ -198,31 +267,136 @@ function App() {
     };
   }, []);
 
+  // Output of programs started with Run (run_target_async) goes to the build log
+  useEffect(() => {
+    const unlisten = [];
+    listen("run-output", (event) => {
+      const p = (event?.payload ?? event) || {};
+      if (isOtherSession(p)) return;
+      const line = p.stream === "stderr" ? `stderr: ${p.line}` : String(p.line);
+      setBuildLog((prev) => [...prev, line]);
+    }, { target: "main" }).then((fn) => unlisten.push(fn));
+    listen("run-finished", (event) => {
+      const p = (event?.payload ?? event) || {};
+      if (isOtherSession(p)) return;
+      const status = p.killed ? "killed" : p.code != null ? `exit code ${p.code}` : "not started";
+      setBuildLog((prev) => [...prev, `[INFO] Program finished (${status}).`]);
+      setProgramRunning(false);
+    }, { target: "main" }).then((fn) => unlisten.push(fn));
+    return () => unlisten.forEach((fn) => typeof fn === "function" && fn());
+  }, []);
+
+  const handleRun = useCallback(async () => {
+    if (!configPath || !runTarget) return;
+    setBuildRunning(true);
+    setProgramRunning(true);
+    setBuildLog([]);
+    setBuildSuccess(null);
+    setBuildDiagnostics(null);
+    setTargetStatus({});
+    setActiveTab("build");
+    try {
+      sessionRef.current = null;
+      sessionRef.current = await invoke("run_target_async", {
+        configPath,
+        target: runTarget,
+        args: runArgs.split(/\s+/).filter(Boolean),
+        jobs: buildJobs === 0 ? null : buildJobs,
+        preset: buildPreset || null,
+      });
+    } catch (e) {
+      setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
+      setBuildSuccess(false);
+      setBuildRunning(false);
+      setProgramRunning(false);
+    }
+  }, [configPath, runTarget, runArgs, buildJobs, buildPreset]);
+
+  const handleKillRun = useCallback(() => {
+    if (sessionRef.current == null) return;
+    invoke("kill_run", { session: sessionRef.current }).catch(() => {});
+  }, []);
+
+  // Auto build on save: the backend watcher starts the build and reports its session in files-changed
+  useEffect(() => {
+    if (!configPath || !autoBuild) return;
+    let unlisten = null;
+    listen("files-changed", (event) => {
+      const p = (event?.payload ?? event) || {};
+      if (p.session == null) return;
+      sessionRef.current = p.session;
+      setBuildLog([]);
+      setBuildSuccess(null);
+      setBuildDiagnostics(null);
+      setTargetStatus({});
+    setBuildDiagnostics(null);
+      setBuildRunning(true);
+      setActiveTab("build");
+    }, { target: "main" }).then((fn) => {
+      unlisten = fn;
+    });
+    invoke("start_watch", { configPath, autoBuild: true, jobs: buildJobs === 0 ? null : buildJobs }).catch((e) => {
+      setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
+      setAutoBuild(false);
+    });
+    return () => {
+      invoke("stop_watch").catch(() => {});
+      if (typeof unlisten === "function") unlisten();
+    };
+  }, [configPath, autoBuild, buildJobs]);
+
   const handleBuild = useCallback(async (clean, jobs, ignoreErrors) => {
     if (!configPath) return;
     setShowBuildModal(false);
     setBuildRunning(true);
     setBuildLog([]);
     setBuildSuccess(null);
+    setBuildDiagnostics(null);
+    setTargetStatus({});
     setActiveTab("build");
     await new Promise((r) => setTimeout(r, 200));
     try {
-      await invoke("run_build_async", {
+      sessionRef.current = null;
+      sessionRef.current = await invoke("run_build_async", {
         configPath,
         targets: null,
         clean,
         jobs: jobs === 0 ? null : jobs,
         ignoreErrors: !!ignoreErrors,
+        preset: buildPreset || null,
       });
     } catch (e) {
       setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
       setBuildSuccess(false);
       setBuildRunning(false);
     }
-  }, [configPath]);
+  }, [configPath, buildPreset]);
+
+  const handleRebuildTarget = useCallback(async (target) => {
+    if (!configPath) return;
+    setBuildRunning(true);
+    setBuildLog([]);
+    setBuildSuccess(null);
+    setBuildDiagnostics(null);
+    setTargetStatus({});
+    try {
+      sessionRef.current = null;
+      sessionRef.current = await invoke("rebuild_target_async", {
+        configPath,
+        target,
+        jobs: buildJobs === 0 ? null : buildJobs,
+      });
+    } catch (e) {
+      setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
+      setBuildSuccess(false);
+      setBuildRunning(false);
+      setActiveTab("build");
+    }
+  }, [configPath, buildJobs]);
 
   const handleCancelBuild = useCallback(() => {
-    invoke("cancel_build").catch(() => {});
+    if (sessionRef.current == null) return;
+    invoke("cancel_build", { session: sessionRef.current }).catch(() => {});
   }, []);
 
   const handleClean = useCallback(async () => {
@@ -230,10 +404,13 @@ function App() {
     setBuildRunning(true);
     setBuildLog([]);
     setBuildSuccess(null);
+    setBuildDiagnostics(null);
+    setTargetStatus({});
     setActiveTab("build");
     await new Promise((r) => setTimeout(r, 200));
     try {
-      await invoke("run_clean_async", { configPath });
+      sessionRef.current = null;
+      sessionRef.current = await invoke("run_clean_async", { configPath });
     } catch (e) {
       setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
       setBuildSuccess(false);
@@ -241,6 +418,28 @@ function App() {
     }
   }, [configPath]);
 
+  const handleExportLog = useCallback(async () => {
+    try {
+      const path = await invoke("save_log_dialog");
+      if (!path) return;
+      const count = await invoke("export_build_log", { path });
+      setBuildLog((prev) => [...prev, `[INFO] Exported ${count} log lines to ${path}`]);
+    } catch (e) {
+      setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
+    }
+  }, []);
+
+  const handleExportCompileCommands = useCallback(async () => {
+    if (!configPath) return;
+    try {
+      const path = await invoke("export_compile_commands", { configPath, outPath: null });
+      setBuildLog((prev) => [...prev, `  Wrote ${path}`]);
+    } catch (e) {
+      setBuildLog((prev) => [...prev, `[ERROR] ${e}`]);
+    }
+    setActiveTab("build");
+  }, [configPath]);
+
   return (
     <div className="h-screen flex flex-col bg-gradient-to-br from-slate-950 via-slate-900 to-slate-950 overflow-hidden">
       {/* Modern Header */}
@@ -294,6 +493,22 @@ function App() {
               </button>
             )}
           </div>
+
+          {recentProjects.length > 0 && (
+            <select
+              value=""
+              onChange={(e) => e.target.value && loadFile(e.target.value)}
+              className="px-2 py-1.5 rounded-md bg-slate-800/40 border border-slate-700/50 text-slate-300 text-xs max-w-[200px]"
+              title="Open a recent project"
+            >
+              <option value="">Recent…</option>
+              {recentProjects.map((path) => (
+                <option key={path} value={path}>
+                  {path.split("/").slice(-2).join("/")}
+                </option>
+              ))}
+            </select>
+          )}
         </div>
 
         {/* Build Actions + Parallel jobs below */}
@@ -344,7 +559,73 @@ function App() {
                 </svg>
                 Clean & Build
               </button>
+              <button
+                onClick={handleExportCompileCommands}
+                disabled={buildRunning}
+                title="Write compile_commands.json for clangd and IDEs"
+                className="px-4 py-2.5 rounded-lg bg-slate-800/50 hover:bg-slate-800 border border-slate-700/50 hover:border-slate-600 text-slate-200 hover:text-white font-medium text-sm transition-all duration-200 disabled:opacity-50 disabled:cursor-not-allowed flex items-center gap-2"
+              >
+                <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-4l-4 4m0 0l-4-4m4 4V4" />
+                </svg>
+                compile_commands
+              </button>
             </div>
+            <div className="flex items-center gap-2">
+              {Object.keys(presets).length > 0 && (
+                <select
+                  value={buildPreset}
+                  onChange={(e) => setBuildPreset(e.target.value)}
+                  className="px-2 py-1 rounded bg-slate-800 border border-slate-700 text-slate-200 text-xs"
+                  title="Build preset ([presets] in build.toml)"
+                >
+                  <option value="">No preset</option>
+                  {Object.entries(presets).map(([name, preset]) => (
+                    <option key={name} value={name} title={preset?.description ?? ""}>{name}</option>
+                  ))}
+                </select>
+              )}
+              <select
+                value={runTarget}
+                onChange={(e) => setRunTarget(e.target.value)}
+                className="px-2 py-1 rounded bg-slate-800 border border-slate-700 text-slate-200 text-xs"
+              >
+                <option value="">Run target…</option>
+                {(projectInfo?.graph_nodes ?? [])
+                  .filter((n) => n.target_type === "executable")
+                  .map((n) => (
+                    <option key={n.id} value={n.id}>{n.label}</option>
+                  ))}
+              </select>
+              <input
+                value={runArgs}
+                onChange={(e) => setRunArgs(e.target.value)}
+                placeholder="arguments"
+                className="w-32 px-2 py-1 rounded bg-slate-800 border border-slate-700 text-slate-200 text-xs"
+              />
+              {programRunning ? (
+                <button onClick={handleKillRun} className="px-3 py-1 rounded bg-red-600/80 hover:bg-red-600 text-white text-xs font-medium">
+                  Kill
+                </button>
+              ) : (
+                <button
+                  onClick={handleRun}
+                  disabled={!runTarget || buildRunning}
+                  className="px-3 py-1 rounded bg-slate-800/50 hover:bg-slate-800 border border-slate-700/50 text-slate-200 text-xs font-medium disabled:opacity-50 disabled:cursor-not-allowed"
+                >
+                  Run
+                </button>
+              )}
+            </div>
+            <label className="flex items-center gap-2 cursor-pointer">
+              <input
+                type="checkbox"
+                checked={autoBuild}
+                onChange={(e) => setAutoBuild(e.target.checked)}
+                className="w-4 h-4 rounded border-slate-600 bg-slate-800 text-emerald-500 focus:ring-emerald-500 focus:ring-offset-0"
+              />
+              <span className="text-xs text-slate-400">Auto build on save</span>
+            </label>
           </div>
         )}
       </header>
@@ -429,6 +710,18 @@ function App() {
         </div>
       )}
 
+      {/* Config problems with locations */}
+      {configProblems.length > 0 && (
+        <div className="mx-8 mt-4 px-5 py-3 rounded-xl bg-slate-900/60 border border-amber-800/40 text-xs font-mono max-h-40 overflow-auto">
+          {configProblems.map((p, i) => (
+            <div key={i} className={p.severity === "error" ? "text-red-300" : "text-amber-300"}>
+              <span className="text-slate-400">{p.file.split("/").pop()}:{p.line}:{p.column}</span>{" "}
+              {p.severity}: {p.message}
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* Modern Tab Navigation */}
       {configPath && (
         <nav className="flex gap-2 px-8 py-4 bg-slate-900/40 backdrop-blur-xl border-b border-slate-800/50">
@@ -476,6 +769,20 @@ function App() {
                 {buildLog.length}
               </span>
             )}
+            extraBadge={buildDiagnostics && (buildDiagnostics.warnings > 0 || buildDiagnostics.errors > 0) && (
+              <>
+                {buildDiagnostics.errors > 0 && (
+                  <span className="ml-1 px-2 py-0.5 rounded-full text-xs font-semibold border bg-red-500/20 text-red-400 border-red-500/30" title="Errors">
+                    {buildDiagnostics.errors}
+                  </span>
+                )}
+                {buildDiagnostics.warnings > 0 && (
+                  <span className="ml-1 px-2 py-0.5 rounded-full text-xs font-semibold border bg-amber-500/20 text-amber-400 border-amber-500/30" title="Warnings">
+                    {buildDiagnostics.warnings}
+                  </span>
+                )}
+              </>
+            )}
           >
             Build Output
           </TabButton>
@@ -499,7 +806,11 @@ function App() {
             />
           </>
         ) : activeTab === "graph" ? (
-          <DependencyGraph projectInfo={projectInfo} />
+          <DependencyGraph
+            projectInfo={projectInfo}
+            targetStatus={targetStatus}
+            onRebuild={buildRunning ? null : handleRebuildTarget}
+          />
         ) : activeTab === "files" ? (
           <div className="h-full flex min-h-0">
             <div className="w-72 flex-shrink-0 border-r border-slate-800 overflow-hidden flex flex-col">
@@ -532,6 +843,7 @@ function App() {
               success={buildSuccess}
               buildRunning={buildRunning}
               onCancelBuild={handleCancelBuild}
+              onExportLog={handleExportLog}
               className="flex-1 min-h-0"
             />
           </div>
@@ -661,7 +973,7 @@ function ActionCard({ onClick, icon, title, description, gradient, iconBg, badge
   );
 }
 
-function TabButton({ active, onClick, children, icon, badge }) {
+function TabButton({ active, onClick, children, icon, badge, extraBadge }) {
   return (
     <button
       onClick={onClick}
@@ -674,6 +986,7 @@ function TabButton({ active, onClick, children, icon, badge }) {
       {icon}
       <span>{children}</span>
       {badge}
+      {extraBadge}
       {active && (
         <div className="absolute bottom-0 left-0 right-0 h-0.5 bg-gradient-to-r from-emerald-500 via-teal-500 to-cyan-600 rounded-full"></div>
       )}
@@ -1,6 +1,6 @@
 import { useEffect, useRef, useState, useMemo } from "react";
 
-export default function BuildLog({ lines, success, buildRunning = false, onCancelBuild, className = "" }) {
+export default function BuildLog({ lines, success, buildRunning = false, onCancelBuild, onExportLog, className = "" }) {
   const logEndRef = useRef(null);
   const [autoScroll, setAutoScroll] = useState(true);
   const [filter, setFilter] = useState("all");
@@ -36,7 +36,10 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
     let currentLevel = null;
 
     const applyMessageToTarget = (t, rest) => {
-      if (rest.includes("[OK]") || rest.includes("Success") || rest.includes("successfully")) {
+      if (rest.includes("[HINT]")) {
+        // Suggested fix for the error above (e.g. the include_dir of a missing header)
+        t.messages.push({ type: "hint", text: rest });
+      } else if (rest.includes("[OK]") || rest.includes("Success") || rest.includes("successfully")) {
         t.status = "success";
         t.messages.push({ type: "success", text: rest });
       } else if (rest.includes("[ERROR]") || (rest.toLowerCase().includes("failed") && !rest.includes("up-to-date"))) {
@@ -89,7 +92,7 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
           targetMap.set(name, t);
         }
         if (rest.includes("===") && rest.includes("Building target")) {
-          t.type = rest.includes("Executable") ? "executable" : rest.includes("StaticLib") ? "static_lib" : rest.includes("SharedLib") ? "shared_lib" : "unknown";
+          t.type = rest.includes("Executable") ? "executable" : rest.includes("StaticLib") ? "static_lib" : rest.includes("SharedLib") ? "shared_lib" : rest.includes("Interface") ? "interface" : rest.includes("Custom") ? "custom" : rest.includes("Group") ? "group" : rest.includes("Benchmark") ? "benchmark" : rest.includes("Test") ? "test" : "unknown";
         }
         applyMessageToTarget(t, rest);
         continue;
@@ -129,7 +132,7 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
           if (!currentTarget || currentTarget.name !== name) {
             currentTarget = {
               name,
-              type: line.includes("Executable") ? "executable" : line.includes("StaticLib") ? "static_lib" : line.includes("SharedLib") ? "shared_lib" : "unknown",
+              type: line.includes("Executable") ? "executable" : line.includes("StaticLib") ? "static_lib" : line.includes("SharedLib") ? "shared_lib" : line.includes("Interface") ? "interface" : line.includes("Custom") ? "custom" : line.includes("Group") ? "group" : line.includes("Benchmark") ? "benchmark" : line.includes("Test") ? "test" : "unknown",
               status: "building",
               messages: [],
               compileCount: 0,
@@ -284,11 +287,21 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
       executable: "bg-red-500/15 text-red-400 border-red-500/40",
       static_lib: "bg-teal-500/15 text-teal-400 border-teal-500/40",
       shared_lib: "bg-blue-500/15 text-blue-400 border-blue-500/40",
+      interface: "bg-slate-500/15 text-slate-400 border-slate-500/40",
+      custom: "bg-amber-500/15 text-amber-400 border-amber-500/40",
+      group: "bg-violet-500/15 text-violet-400 border-violet-500/40",
+      benchmark: "bg-sky-500/15 text-sky-400 border-sky-500/40",
+      test: "bg-lime-500/15 text-lime-400 border-lime-500/40",
     };
     const labels = {
       executable: "Exe",
       static_lib: "Static",
       shared_lib: "Shared",
+      interface: "Iface",
+      custom: "Custom",
+      group: "Group",
+      benchmark: "Bench",
+      test: "Test",
     };
     return (
       <span className={`inline-flex items-center justify-center min-w-[3.5rem] px-1.5 py-0.5 rounded text-[10px] font-medium border ${colors[type] || colors.static_lib}`}>
@@ -392,6 +405,17 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
               />
               Auto
             </label>
+            {onExportLog && (
+              <button
+                type="button"
+                onClick={onExportLog}
+                disabled={lines.length === 0}
+                className="px-2 py-1 rounded text-xs font-medium text-gray-400 hover:text-white hover:bg-gray-800 disabled:opacity-40 disabled:hover:bg-transparent"
+                title="Save the last build's output with timestamps"
+              >
+                Export
+              </button>
+            )}
           </div>
         </div>
       </div>
@@ -508,6 +532,7 @@ export default function BuildLog({ lines, success, buildRunning = false, onCance
                             msg.type === "success" ? "bg-green-500/10 text-green-300" :
                             msg.type === "compile" ? "bg-blue-500/10 text-blue-300" :
                             msg.type === "link" ? "bg-purple-500/10 text-purple-300" :
+                            msg.type === "hint" ? "bg-amber-500/10 text-amber-300" :
                             "bg-gray-800/50 text-gray-300"
                           }`}
                         >
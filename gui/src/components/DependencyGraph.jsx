@@ -9,21 +9,50 @@ import ReactFlow, {
 } from "reactflow";
 import "reactflow/dist/style.css";
 
-const nodeTypes = ["executable", "static_lib", "shared_lib"];
+const nodeTypes = ["executable", "static_lib", "shared_lib", "interface", "custom", "group", "benchmark", "test"];
 // Palette: emerald / teal / cyan (matches app logo)
 const colors = {
   executable: "#06b6d4", // cyan-500
   static_lib: "#14b8a6", // teal-500
   shared_lib: "#10b981", // emerald-500
+  interface: "#64748b", // slate-500
+  custom: "#f59e0b", // amber-500
+  group: "#8b5cf6", // violet-500
+  benchmark: "#0ea5e9", // sky-500
+  test: "#84cc16", // lime-500
 };
 
 const typeLabels = {
   executable: "Executable",
   static_lib: "Static Library",
   shared_lib: "Shared Library",
+  interface: "Interface Library",
+  custom: "Custom Command",
+  group: "Group",
+  benchmark: "Benchmark",
+  test: "Test",
 };
 
-function buildLayout(nodes, edges) {
+// Live build state (target-status events); the border turns this color while building
+const statusColors = {
+  queued: "#64748b", // slate-500
+  compiling: "#3b82f6", // blue-500
+  linking: "#a855f7", // purple-500
+  succeeded: "#22c55e", // green-500
+  failed: "#ef4444", // red-500
+  skipped: "#475569", // slate-600
+};
+
+function statusLabel(status) {
+  if (status.state === "compiling") return `compiling ${status.done}/${status.total}`;
+  return status.state;
+}
+
+const cycleColor = "#ef4444";
+
+function buildLayout(nodes, edges, cycleEdges, targetStatus, onRebuild) {
+  const cycleKeys = new Set(cycleEdges.map((e) => `${e.from}->${e.to}`));
+  const cycleNodes = new Set(cycleEdges.flatMap((e) => [e.from, e.to]));
   const levels = new Map();
   for (const n of nodes) {
     const list = levels.get(n.level) ?? [];
@@ -49,6 +78,10 @@ function buildLayout(nodes, edges) {
 
   const flowNodes = nodes.map((n) => {
     const nodeColor = colors[n.target_type] ?? "#14b8a6";
+    const status = targetStatus?.[n.id];
+    const borderColor = cycleNodes.has(n.id)
+      ? cycleColor
+      : status ? statusColors[status.state] ?? nodeColor : nodeColor;
     return {
       id: n.id,
       type: "default",
@@ -76,13 +109,28 @@ function buildLayout(nodes, edges) {
             >
               {typeLabels[n.target_type] || n.target_type}
             </span>
+            {status && (
+              <span className="mt-1.5 text-[11px] font-mono" style={{ color: borderColor }}>
+                {statusLabel(status)}
+              </span>
+            )}
+            {onRebuild && n.target_type !== "interface" && n.target_type !== "group" && (
+              <button
+                type="button"
+                onClick={(e) => { e.stopPropagation(); onRebuild(n.id); }}
+                className="nodrag absolute top-2 right-2 px-1.5 py-0.5 rounded text-[10px] font-medium text-slate-400 hover:text-white hover:bg-slate-700/80"
+                title="Remove this target's objects and output, then build it"
+              >
+                Rebuild
+              </button>
+            )}
           </div>
         ),
       },
       sourcePosition: Position.Right,
       targetPosition: Position.Left,
       style: {
-        borderColor: nodeColor,
+        borderColor,
         borderWidth: 2,
         width: nodeWidth,
         height: nodeHeight,
@@ -99,15 +147,17 @@ function buildLayout(nodes, edges) {
 
   const flowEdges = edges.map((e, i) => {
     const targetNode = nodes.find((n) => n.id === e.to);
-    const edgeColor = colors[targetNode?.target_type] ?? "#14b8a6";
+    const inCycle = cycleKeys.has(`${e.from}->${e.to}`);
+    const edgeColor = inCycle ? cycleColor : colors[targetNode?.target_type] ?? "#14b8a6";
     return {
       id: `e-${e.from}-${e.to}-${i}`,
       source: e.from,
       target: e.to,
       type: "smoothstep",
-      animated: true,
+      animated: !inCycle,
+      zIndex: inCycle ? 1 : 0,
       style: {
-        strokeWidth: 2.5,
+        strokeWidth: inCycle ? 3.5 : 2.5,
         stroke: edgeColor,
         filter: `drop-shadow(0 0 4px ${edgeColor}60)`,
       },
@@ -123,7 +173,7 @@ function buildLayout(nodes, edges) {
   return { flowNodes, flowEdges };
 }
 
-export default function DependencyGraph({ projectInfo }) {
+export default function DependencyGraph({ projectInfo, targetStatus, onRebuild }) {
   const { flowNodes, flowEdges } = useMemo(() => {
     if (!projectInfo) return { flowNodes: [], flowEdges: [] };
     
@@ -132,8 +182,8 @@ export default function DependencyGraph({ projectInfo }) {
       return { flowNodes: [], flowEdges: [] };
     }
     
-    return buildLayout(projectInfo.graph_nodes, projectInfo.graph_edges);
-  }, [projectInfo]);
+    return buildLayout(projectInfo.graph_nodes, projectInfo.graph_edges, projectInfo.cycle_edges ?? [], targetStatus, onRebuild);
+  }, [projectInfo, targetStatus, onRebuild]);
 
   if (!projectInfo) {
     return (
@@ -0,0 +1,259 @@
+// ============================================================================
+// bench.rs — Benchmark targets and `ngm bench`
+// ============================================================================
+//
+// A `type = "benchmark"` target is compiled and linked like an executable
+// but is left to `ngm bench`, which builds the selected benchmarks, runs
+// each one `repetitions` times in turn (never in parallel, so they don't
+// compete for the machine) with its `args`, and reports the wall time:
+//
+//   [[target]]
+//   name = "bench_parser"
+//   type = "benchmark"
+//   sources = ["bench/parser.cpp"]
+//   deps = ["parser"]
+//   args = ["--input", "bench/data/large.json"]
+//   repetitions = 10
+//
+// Every `ngm bench` appends one JSON line to `.oximake/bench.jsonl` (or the
+// --results file): time, git commit and per-benchmark run times. The median
+// of each benchmark is compared with the previous line of the file, so a
+// regression shows up right away and the file can be plotted over commits.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ResolvedProject;
+use crate::history;
+use crate::run;
+
+/// Runs per benchmark when neither the target nor --repetitions sets it.
+pub const DEFAULT_REPETITIONS: usize = 5;
+
+/// `.oximake/bench.jsonl`
+pub fn results_path(root_dir: &Path) -> PathBuf {
+    history::state_dir(root_dir).join("bench.jsonl")
+}
+
+/// Runs of one benchmark.
+#[derive(Debug, Clone, Default)]
+pub struct BenchResult {
+    pub target_name: String,
+    pub args: Vec<String>,
+    /// Wall time of each successful run
+    pub runs: Vec<Duration>,
+    /// Why the benchmark stopped (failed to start, non-zero exit)
+    pub error: Option<String>,
+    /// Standard output of the last run
+    pub output: String,
+}
+
+impl BenchResult {
+    pub fn min(&self) -> Option<Duration> {
+        self.runs.iter().min().copied()
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.runs.iter().max().copied()
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (!self.runs.is_empty()).then(|| self.runs.iter().sum::<Duration>() / self.runs.len() as u32)
+    }
+
+    pub fn median(&self) -> Option<Duration> {
+        let mut sorted = self.runs.clone();
+        sorted.sort();
+        match sorted.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(sorted[n / 2]),
+            n => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2),
+        }
+    }
+}
+
+/// Runs the built benchmark `name` (working directory = project root). `repetitions`
+/// overrides the target's; the first failing run ends the benchmark.
+pub fn run_benchmark(project: &ResolvedProject, name: &str, repetitions: Option<usize>) -> BenchResult {
+    let args = project.targets.get(name).map(|t| t.args.clone()).unwrap_or_default();
+    let count = repetitions.or_else(|| project.targets.get(name).and_then(|t| t.repetitions)).unwrap_or(DEFAULT_REPETITIONS).max(1);
+    let mut result = BenchResult { target_name: name.to_string(), args: args.clone(), ..Default::default() };
+    for _ in 0..count {
+        let mut command: Command = match run::run_command(project, name, &args) {
+            Ok(c) => c,
+            Err(e) => {
+                result.error = Some(e);
+                break;
+            }
+        };
+        let start = Instant::now();
+        let output = command.stdin(Stdio::null()).output();
+        let elapsed = start.elapsed();
+        match output {
+            Ok(out) if out.status.success() => {
+                result.runs.push(elapsed);
+                result.output = String::from_utf8_lossy(&out.stdout).to_string();
+            }
+            Ok(out) => {
+                let stderr = String::from_utf8_lossy(&out.stderr);
+                result.error = Some(format!("'{}' exited with {}\n{}", name, out.status, stderr.trim_end()));
+                break;
+            }
+            Err(e) => {
+                result.error = Some(format!("Failed to run '{}': {}", name, e));
+                break;
+            }
+        }
+    }
+    result
+}
+
+/// `git rev-parse HEAD` in `root_dir`, if it is a git checkout.
+pub fn git_commit(root_dir: &Path) -> Option<String> {
+    let dir = if root_dir.as_os_str().is_empty() { Path::new(".") } else { root_dir };
+    let out = Command::new("git").args(["rev-parse", "HEAD"]).current_dir(dir).stderr(Stdio::null()).output().ok()?;
+    let commit = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (out.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+fn millis(d: Duration) -> f64 {
+    (d.as_secs_f64() * 1e6).round() / 1e3
+}
+
+/// One line of the results file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchRecord {
+    pub timestamp: u64,
+    pub project: String,
+    pub commit: Option<String>,
+    pub benchmarks: Vec<RecordedBenchmark>,
+}
+
+/// One benchmark of a record, in milliseconds (None when it failed).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedBenchmark {
+    pub name: String,
+    pub args: Vec<String>,
+    pub success: bool,
+    pub runs_ms: Vec<f64>,
+    pub median_ms: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+}
+
+/// The record of this run.
+pub fn results_record(project: &str, commit: Option<&str>, results: &[BenchResult]) -> BenchRecord {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let benchmarks = results
+        .iter()
+        .map(|r| RecordedBenchmark {
+            name: r.target_name.clone(),
+            args: r.args.clone(),
+            success: r.error.is_none(),
+            runs_ms: r.runs.iter().map(|d| millis(*d)).collect(),
+            median_ms: r.median().map(millis),
+            mean_ms: r.mean().map(millis),
+            min_ms: r.min().map(millis),
+            max_ms: r.max().map(millis),
+        })
+        .collect();
+    BenchRecord { timestamp, project: project.to_string(), commit: commit.map(str::to_string), benchmarks }
+}
+
+/// Appends `record` as one line to the results file.
+pub fn append_results(path: &Path, record: &BenchRecord) -> Result<(), String> {
+    use std::io::Write;
+    let line = serde_json::to_string(record).map_err(|e| format!("Failed to serialize benchmark results: {}", e))?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// The last recorded run: its commit and the median of each benchmark in milliseconds.
+pub fn previous_results(path: &Path) -> Option<(Option<String>, HashMap<String, f64>)> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let record: BenchRecord = serde_json::from_str(text.lines().rev().find(|l| !l.trim().is_empty())?).ok()?;
+    let medians = record.benchmarks.into_iter().filter_map(|b| Some((b.name, b.median_ms?))).collect();
+    Some((record.commit, medians))
+}
+
+/// One line per benchmark: median, spread and the change against `previous`.
+pub fn format_results(results: &[BenchResult], previous: Option<&(Option<String>, HashMap<String, f64>)>) -> Vec<String> {
+    let width = results.iter().map(|r| r.target_name.len()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for r in results {
+        let (Some(median), Some(min), Some(max)) = (r.median(), r.min(), r.max()) else {
+            lines.push(format!("  {:<width$}  [FAILED]", r.target_name));
+            continue;
+        };
+        let mut line = format!(
+            "  {:<width$}  median {:>10.3} ms  (min {:.3}, max {:.3}, {} runs)",
+            r.target_name,
+            millis(median),
+            millis(min),
+            millis(max),
+            r.runs.len()
+        );
+        if let Some((commit, medians)) = previous {
+            if let Some(before) = medians.get(&r.target_name).filter(|b| **b > 0.0) {
+                let change = (millis(median) - before) / before * 100.0;
+                let against = commit.as_deref().map(|c| &c[..c.len().min(8)]).unwrap_or("previous run");
+                line.push_str(&format!("  {:+.1}% vs {}", change, against));
+            }
+        }
+        if r.error.is_some() {
+            line.push_str("  [FAILED]");
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ResolvedTarget, TargetType};
+    use crate::dag;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_benchmarks_are_selected_and_results_compared_with_the_last_record() {
+        let mut project = ResolvedProject::default();
+        for (name, target_type) in [("bench_io", TargetType::Benchmark), ("bench_sort", TargetType::Benchmark), ("app", TargetType::Executable)] {
+            project.targets.insert(name.to_string(), ResolvedTarget { name: name.to_string(), target_type, ..Default::default() });
+        }
+        let benchmarks = |names: &[&str]| {
+            let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+            dag::targets_of_type(&project, &names, TargetType::Benchmark)
+        };
+        assert_eq!(benchmarks(&[]).unwrap(), vec!["bench_io", "bench_sort"]);
+        assert_eq!(benchmarks(&["*"]).unwrap(), vec!["bench_io", "bench_sort"]);
+        assert_eq!(benchmarks(&["app"]).unwrap_err(), "Target 'app' is a executable, not a benchmark");
+
+        let ms = Duration::from_millis;
+        let sort = BenchResult { target_name: "bench_sort".to_string(), runs: vec![ms(12), ms(10), ms(30), ms(11)], ..Default::default() };
+        assert_eq!(sort.median(), Some(Duration::from_micros(11500)));
+        assert_eq!(sort.mean(), Some(Duration::from_micros(15750)));
+
+        let dir = TempDir::new("bench");
+        let path = dir.join("bench.jsonl");
+        append_results(&path, &results_record("demo", Some("0123456789abcdef"), std::slice::from_ref(&sort))).unwrap();
+        let faster = BenchResult { runs: vec![ms(10), ms(10), ms(13)], ..sort };
+        let previous = previous_results(&path).unwrap();
+        assert_eq!(previous.1.get("bench_sort"), Some(&11.5));
+        let lines = format_results(&[faster], Some(&previous));
+        assert!(lines[0].ends_with("(min 10.000, max 13.000, 3 runs)  -13.0% vs 01234567"), "{}", lines[0]);
+    }
+}
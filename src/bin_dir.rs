@@ -0,0 +1,106 @@
+// ============================================================================
+// bin_dir.rs — `[project] bin_dir`: all binaries in one place
+// ============================================================================
+//
+// After a successful build, the outputs of the built targets are gathered so
+// nobody has to hunt through per-target output directories:
+//
+//   executables          → <bin_dir>/
+//   shared libraries     → <bin_dir>/../lib/  (DLLs stay next to the .exe
+//                          files in <bin_dir>/, where Windows looks for them)
+//
+// Files are copied (skipped when the copy is already current) or, with
+// `bin_symlinks = true`, symlinked; on platforms without symlinks they are
+// always copied. Static libraries and custom outputs are not gathered.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use crate::compiler;
+use crate::config::{ResolvedProject, TargetType};
+
+/// Copies or links the executables and shared libraries of `targets` into the
+/// project's bin_dir / lib dir. Returns the paths written; nothing without bin_dir.
+pub fn gather_outputs(project: &ResolvedProject, targets: &[String]) -> Result<Vec<PathBuf>, String> {
+    let Some(bin_dir) = &project.bin_dir else { return Ok(Vec::new()) };
+    let lib_dir = bin_dir.parent().map(|p| p.join("lib")).unwrap_or_else(|| PathBuf::from("lib"));
+    let mut written = Vec::new();
+    for name in targets {
+        let Some(target) = project.targets.get(name) else { continue };
+        let output = compiler::output_path(target);
+        let dir = match target.target_type {
+            TargetType::Executable => bin_dir,
+            TargetType::SharedLib if output.extension().is_some_and(|e| e == "dll") => bin_dir,
+            TargetType::SharedLib => &lib_dir,
+            _ => continue,
+        };
+        let Some(file_name) = output.file_name() else { continue };
+        if !output.is_file() {
+            continue;
+        }
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        let dest = dir.join(file_name);
+        if place(&output, &dest, project.bin_symlinks)? {
+            written.push(dest);
+        }
+    }
+    Ok(written)
+}
+
+/// Puts `output` at `dest`. False if `dest` was already current.
+fn place(output: &Path, dest: &Path, symlink: bool) -> Result<bool, String> {
+    let error = |e: std::io::Error| format!("Failed to place '{}' in '{}': {}", output.display(), dest.display(), e);
+    let source = std::path::absolute(output).map_err(error)?;
+    #[cfg(unix)]
+    if symlink {
+        if std::fs::read_link(dest).is_ok_and(|link| link == source) {
+            return Ok(false);
+        }
+        let _ = std::fs::remove_file(dest);
+        std::os::unix::fs::symlink(&source, dest).map_err(error)?;
+        return Ok(true);
+    }
+    #[cfg(not(unix))]
+    let _ = symlink;
+    if dest.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+        std::fs::remove_file(dest).map_err(error)?;
+    } else if is_current_copy(&source, dest) {
+        return Ok(false);
+    }
+    std::fs::copy(&source, dest).map_err(error)?;
+    Ok(true)
+}
+
+/// `dest` has the size of `source` and is not older.
+fn is_current_copy(source: &Path, dest: &Path) -> bool {
+    match (std::fs::metadata(source), std::fs::metadata(dest)) {
+        (Ok(s), Ok(d)) => s.len() == d.len() && matches!((s.modified(), d.modified()), (Ok(st), Ok(dt)) if st <= dt),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::ResolvedTarget;
+
+    #[test]
+    fn test_executables_go_to_bin_dir_and_shared_libs_to_lib() {
+        let dir = TempDir::new("bin_dir");
+        std::fs::create_dir_all(dir.join("build")).unwrap();
+        for file in ["app", "libcore.so", "libutil.a"] {
+            std::fs::write(dir.join("build").join(file), file).unwrap();
+        }
+        let mut project = ResolvedProject { bin_dir: Some(dir.join("dist/bin")), ..Default::default() };
+        for (name, target_type) in [("app", TargetType::Executable), ("core", TargetType::SharedLib), ("util", TargetType::StaticLib)] {
+            let target = ResolvedTarget { name: name.to_string(), target_type, output_dir: dir.join("build"), ..Default::default() };
+            project.targets.insert(name.to_string(), target);
+        }
+        let targets: Vec<String> = ["util", "core", "app"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(gather_outputs(&project, &targets).unwrap(), vec![dir.join("dist/lib/libcore.so"), dir.join("dist/bin/app")]);
+        assert_eq!(std::fs::read_to_string(dir.join("dist/bin/app")).unwrap(), "app");
+        // Current copies are left alone
+        assert!(gather_outputs(&project, &targets).unwrap().is_empty());
+    }
+}
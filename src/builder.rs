@@ -7,17 +7,30 @@
 // in a level complete, the next level is processed. Build stops on any error.
 // ============================================================================
 
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
+
+use crate::cache::ObjectCache;
+use crate::color;
 use crate::compiler::{self, CompileResult};
 use crate::config::ResolvedProject;
 use crate::dag::{self, BuildOrder};
+use crate::diagnostics::{self, Diagnostic, DiagnosticCounts, Severity};
+use crate::hints;
+use crate::toolchain;
+use crate::events::{self, BuildEvent, BuildStats, TargetState};
+use crate::history::{self, JobDurations};
 use crate::options::BuildOptions;
+use crate::progress::{self, Progress, Status, StatusLine};
+use crate::remote::RemoteHosts;
 
 // ---------------------------------------------------------------------------
 // Build result
@@ -30,6 +43,102 @@ pub struct BuildResult {
     pub total_targets: usize,
     pub successful_targets: usize,
     pub failed_targets: usize,
+    /// Start/end of every compile and link job, in completion order
+    pub jobs: Vec<JobTiming>,
+    /// Wall time of the job-scheduling phase
+    pub wall_time: Duration,
+    /// Number of worker threads (-j)
+    pub workers: usize,
+    /// Warnings and errors per target, parsed from the compiler/linker output
+    pub diagnostics: BTreeMap<String, DiagnosticCounts>,
+    /// The parsed warnings and errors themselves, per target
+    pub diagnostic_messages: BTreeMap<String, Vec<Diagnostic>>,
+    /// Targets never built: a dependency failed, or the keep-going limit stopped the build
+    pub not_attempted: Vec<String>,
+}
+
+impl BuildResult {
+    pub fn diagnostic_totals(&self) -> DiagnosticCounts {
+        let mut totals = DiagnosticCounts::default();
+        for counts in self.diagnostics.values() {
+            totals.add(*counts);
+        }
+        totals
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Compile,
+    Link,
+}
+
+/// Timing of one compile or link job. `start`/`end` are relative to build start.
+#[derive(Debug, Clone)]
+pub struct JobTiming {
+    pub kind: JobKind,
+    pub target_name: String,
+    /// Source file for compile jobs
+    pub source: Option<PathBuf>,
+    /// Worker thread that ran the job (0..jobs)
+    pub worker: usize,
+    pub start: Duration,
+    pub end: Duration,
+    pub success: bool,
+    /// Compile skipped because the object file was already up-to-date
+    pub up_to_date: bool,
+    /// Object restored from the [cache] (also counts as up_to_date)
+    pub cached: bool,
+}
+
+impl JobTiming {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Build control (cancel, pause/resume)
+// ---------------------------------------------------------------------------
+/// Shared handle to steer a running build from another thread (GUI buttons).
+#[derive(Debug, Default)]
+pub struct BuildControl {
+    cancel: Arc<AtomicBool>,
+    paused: AtomicBool,
+}
+
+impl BuildControl {
+    pub fn new() -> BuildControl {
+        BuildControl::default()
+    }
+
+    /// Control whose cancellation is driven by an existing flag.
+    pub fn with_cancel_flag(cancel: Arc<AtomicBool>) -> BuildControl {
+        BuildControl { cancel, paused: AtomicBool::new(false) }
+    }
+
+    /// Kills running jobs and stops the build.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Stops dispatching new jobs; running jobs finish and all state is kept.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -38,14 +147,15 @@ pub struct BuildResult {
 
 /// Builds the whole project in the given order. Targets in each level
 /// are built in parallel threads.
-/// When `output_tx` is Some, each message line is sent to the channel (for streaming to GUI).
-/// When `cancel` is Some and becomes true, the build stops after the current job.
+/// When `output_tx` is Some, build events are sent to the channel (for streaming to GUI).
+/// When `control` is Some, the build can be cancelled (running jobs are killed)
+/// or paused/resumed (no new jobs start while paused).
 pub fn build_project(
     project: &ResolvedProject,
     order: &BuildOrder,
     options: &BuildOptions,
-    output_tx: Option<mpsc::Sender<String>>,
-    cancel: Option<Arc<AtomicBool>>,
+    output_tx: Option<mpsc::Sender<BuildEvent>>,
+    control: Option<Arc<BuildControl>>,
 ) -> BuildResult {
     let total_targets = order.levels.iter().map(|l| l.len()).sum::<usize>();
     let quiet = options.show_quiet_output();
@@ -53,31 +163,32 @@ pub fn build_project(
 
     if !quiet {
         if verbose {
-            println!("\n╔══════════════════════════════════════════════════╗");
-            println!("║            C++ BUILD STARTING                    ║");
-            println!("║  Project: {:<40} ║", project.name);
-            println!("║  Version: {:<40} ║", project.version);
-            println!("║  Target count: {:<30} ║", total_targets);
-            println!("╚══════════════════════════════════════════════════╝\n");
+            println!("\n  {} {} v{} ({} targets)\n", color::bold("Building"), project.name, project.version, total_targets);
         } else {
-            println!("  Building {} targets...\n", total_targets);
+            println!("  {} {} targets...\n", color::bold("Building"), total_targets);
+        }
+    }
+
+    // Objects compiled by a different compiler version are removed first (see toolchain.rs)
+    for line in toolchain::invalidate_changed(project, order) {
+        if !quiet {
+            println!("  {}", line);
+        }
+        if let Some(tx) = &output_tx {
+            let _ = tx.send(BuildEvent::output(None, line));
         }
     }
 
-    let n_jobs = options.jobs.unwrap_or_else(|| {
-        std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(1)
-            .max(1)
-    });
+    // Without -j: one job per CPU, or more when a launcher (distcc) distributes compiles
+    let n_jobs = options.jobs.unwrap_or_else(|| local_parallelism().max(compiler::launcher_jobs(project).unwrap_or(0)));
 
-    let result = build_ninja_style(project, order, n_jobs, options, output_tx.as_ref(), cancel);
+    let result = build_ninja_style(project, order, n_jobs, options, output_tx.as_ref(), control);
 
     if !quiet && result.success && verbose {
-        println!("╔══════════════════════════════════════════════════╗");
-        println!("║            BUILD COMPLETED SUCCESSFULLY          ║");
-        println!("║  Total: {} targets, {} successful           ║", result.total_targets, result.successful_targets);
-        println!("╚══════════════════════════════════════════════════╝");
+        println!(
+            "{}",
+            color::green(&format!("  Build completed: {} of {} targets successful", result.successful_targets, result.total_targets))
+        );
     }
 
     result
@@ -85,6 +196,7 @@ pub fn build_project(
 
 // ---------------------------------------------------------------------------
 // Ninja-style: single global job queue of compile + link jobs; -j N = exactly N concurrent jobs.
+// Workers take the ready job with the longest remaining critical path first.
 // ---------------------------------------------------------------------------
 #[derive(Clone)]
 enum Job {
@@ -102,6 +214,189 @@ enum Job {
     },
 }
 
+/// Limits on link jobs running at the same time (compiles are limited by -j only).
+#[derive(Debug, Clone, Copy, Default)]
+struct LinkLimits {
+    /// Link pool size (`link_jobs`)
+    jobs: Option<usize>,
+    /// MiB shared by running links, each counting its target's `link_memory`
+    memory: Option<u64>,
+}
+
+/// Link jobs running right now.
+#[derive(Debug, Default)]
+struct LinkUsage {
+    running: usize,
+    memory: u64,
+}
+
+impl LinkLimits {
+    fn new(project: &ResolvedProject, options: &BuildOptions) -> LinkLimits {
+        let weighted = project.targets.values().any(|t| t.link_memory > 0);
+        let memory = options
+            .link_memory_limit
+            .or(project.link_memory_limit)
+            .or_else(|| weighted.then(physical_memory_mib).flatten());
+        // Links always run locally: with a launcher, -j may count remote slots
+        let jobs = options
+            .link_jobs
+            .or(project.link_jobs)
+            .or_else(|| project.compiler_launcher.as_ref().map(|_| local_parallelism()));
+        LinkLimits { jobs, memory }
+    }
+
+    /// True if a link needing `memory` MiB may start. A link always starts when
+    /// none is running, even if its estimate exceeds the limit.
+    fn admits(&self, usage: &LinkUsage, memory: u64) -> bool {
+        usage.running == 0
+            || (self.jobs.is_none_or(|jobs| usage.running < jobs)
+                && self.memory.is_none_or(|limit| usage.memory + memory <= limit))
+    }
+}
+
+/// Number of CPUs of this machine (at least 1).
+fn local_parallelism() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).max(1)
+}
+
+/// Total physical memory in MiB (Linux: /proc/meminfo), None if unknown.
+fn physical_memory_mib() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib / 1024)
+}
+
+/// Critical-path-first scheduling: a job's priority is the estimated time (ms) from its
+/// start to the end of the longest dependency chain it is on. Durations come from
+/// previous builds (history::JobDurations); jobs never seen before count 1 ms, so
+/// without history the longest chain of targets still goes first.
+#[derive(Debug, Default)]
+struct Priorities {
+    durations: JobDurations,
+    /// Target → longest estimated chain of targets depending on it (0 if none)
+    downstream: HashMap<String, u64>,
+}
+
+impl Priorities {
+    fn new(project: &ResolvedProject, order: &BuildOrder, durations: JobDurations) -> Priorities {
+        let mut priorities = Priorities { durations, downstream: HashMap::new() };
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (name, target) in &project.targets {
+            for dep in &target.deps {
+                dependents.entry(dep.as_str()).or_default().push(name.as_str());
+            }
+        }
+        // Reverse topological order: dependents are done before their dependencies
+        for name in order.levels.iter().flatten().rev() {
+            let chain = dependents
+                .get(name.as_str())
+                .into_iter()
+                .flatten()
+                .filter_map(|d| Some(priorities.span(project, d) + priorities.downstream.get(*d)?))
+                .max()
+                .unwrap_or(0);
+            priorities.downstream.insert(name.clone(), chain);
+        }
+        priorities
+    }
+
+    fn estimate(&self, kind: JobKind, key: &str) -> u64 {
+        self.durations.estimate(kind, key).unwrap_or(0).max(1)
+    }
+
+    /// Estimated build time of one target: its slowest compile plus its link.
+    fn span(&self, project: &ResolvedProject, name: &str) -> u64 {
+        let Some(target) = project.targets.get(name) else { return 0 };
+        let compile = target.sources.iter().map(|s| self.estimate(JobKind::Compile, &s.display().to_string())).max();
+        compile.unwrap_or(0) + self.estimate(JobKind::Link, name)
+    }
+
+    fn of(&self, job: &Job) -> u64 {
+        match job {
+            Job::Compile { target_name, source, .. } => {
+                self.estimate(JobKind::Compile, &source.display().to_string()) + self.of_link(target_name)
+            }
+            Job::Link { target_name, .. } => self.of_link(target_name),
+        }
+    }
+
+    fn of_link(&self, target_name: &str) -> u64 {
+        self.estimate(JobKind::Link, target_name) + self.downstream.get(target_name).copied().unwrap_or(0)
+    }
+}
+
+/// `[project.retry]` with its patterns compiled: failed jobs whose output matches run again.
+#[derive(Debug, Clone)]
+struct Retry {
+    count: u32,
+    matcher: regex::Regex,
+    delay: Duration,
+}
+
+impl Retry {
+    fn new(project: &ResolvedProject) -> Option<Retry> {
+        let policy = project.retry.as_ref()?;
+        Some(Retry {
+            count: policy.count,
+            matcher: policy.matcher().ok()?,
+            delay: Duration::from_millis(policy.delay_ms),
+        })
+    }
+
+    /// If a job failing with `output` after `retries` reruns runs again: waits and
+    /// returns the `[RETRY]` notice for its log.
+    fn again(&self, retries: u32, what: &str, output: &str) -> Option<String> {
+        if retries >= self.count {
+            return None;
+        }
+        let matched = self.matcher.find(output)?;
+        thread::sleep(self.delay * (retries + 1));
+        Some(format!(
+            "  [RETRY] {} failed with \"{}\"; retry {} of {}",
+            what,
+            matched.as_str(),
+            retries + 1,
+            self.count
+        ))
+    }
+}
+
+/// A failed job's output without the command lines (they could match a retry pattern).
+fn failure_output(messages: &[String]) -> String {
+    let lines: Vec<&str> = messages.iter().map(String::as_str).filter(|m| !m.contains("Command:")).collect();
+    lines.join("\n")
+}
+
+/// Jobs waiting for a worker (None = worker exits) with their priority, and the links currently running.
+#[derive(Default)]
+struct JobQueue {
+    jobs: VecDeque<(u64, Option<Job>)>,
+    links: LinkUsage,
+    priorities: Priorities,
+}
+
+impl JobQueue {
+    fn push(&mut self, job: Option<Job>) {
+        let priority = job.as_ref().map(|j| self.priorities.of(j)).unwrap_or(0);
+        self.jobs.push_back((priority, job));
+    }
+}
+
+type SharedQueue = Arc<(Mutex<JobQueue>, Condvar)>;
+
+/// Messages from workers to the scheduler.
+enum WorkerMsg {
+    Started {
+        kind: JobKind,
+        target_name: String,
+        source: Option<PathBuf>,
+        worker: usize,
+        start: Duration,
+    },
+    Finished(JobResult, JobTiming),
+}
+
 enum JobResult {
     Compile {
         target_name: String,
@@ -113,20 +408,44 @@ enum JobResult {
     Link(CompileResult),
 }
 
+/// Environment variables read by compilers, linkers and launchers (printed with -vvv).
+const COMPILER_ENVIRONMENT: &[&str] = &[
+    "PATH", "CPATH", "C_INCLUDE_PATH", "CPLUS_INCLUDE_PATH", "LIBRARY_PATH", "LD_LIBRARY_PATH", "GCC_EXEC_PREFIX",
+    "SOURCE_DATE_EPOCH", "TMPDIR", "DISTCC_HOSTS", "INCLUDE_SERVER_PORT", "CCACHE_DIR",
+];
+
+/// Sends a `TargetStatus` event for library consumers (no-op without a channel).
+fn send_status(tx: Option<&mpsc::Sender<BuildEvent>>, target: &str, status: TargetState) {
+    if let Some(tx) = tx {
+        let _ = tx.send(BuildEvent::TargetStatus { target: target.to_string(), status });
+    }
+}
+
 fn build_ninja_style(
     project: &ResolvedProject,
     order: &BuildOrder,
     n_jobs: usize,
     options: &BuildOptions,
-    output_tx: Option<&mpsc::Sender<String>>,
-    cancel: Option<Arc<AtomicBool>>,
+    output_tx: Option<&mpsc::Sender<BuildEvent>>,
+    control: Option<Arc<BuildControl>>,
 ) -> BuildResult {
     let total_targets: usize = order.levels.iter().map(|l| l.len()).sum();
     let quiet = options.show_quiet_output();
-    let verbose = options.show_verbose_output();
+    let verbosity = options.verbosity();
+    let verbose = verbosity > 0;
 
     if let Some(tx) = output_tx {
-        let _ = tx.send(format!("__ngmake_TOTAL__\t{}", total_targets));
+        let _ = tx.send(BuildEvent::Started { total_targets });
+        for name in order.levels.iter().flatten() {
+            send_status(output_tx, name, TargetState::Queued);
+        }
+    }
+    if verbosity >= 3 {
+        for name in COMPILER_ENVIRONMENT {
+            if let Some(value) = std::env::var_os(name) {
+                println!("  [DEBUG] env {}={}", name, value.to_string_lossy());
+            }
+        }
     }
 
     let built_targets: Arc<Mutex<HashMap<String, PathBuf>>> = Arc::new(Mutex::new(HashMap::new()));
@@ -143,59 +462,209 @@ fn build_ninja_style(
         d
     };
 
-    let job_queue: Arc<(Mutex<VecDeque<Option<Job>>>, Condvar)> =
-        Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
-    let (result_tx, result_rx) = mpsc::sync_channel::<JobResult>(0);
+    let durations = history::load_durations(&project.root_dir);
+    let queue = JobQueue { priorities: Priorities::new(project, order, durations.clone()), ..Default::default() };
+    let job_queue: SharedQueue = Arc::new((Mutex::new(queue), Condvar::new()));
+    let link_limits = LinkLimits::new(project, options);
+    let retry = Retry::new(project);
+    // Remote build hosts run compiles in addition to the local workers
+    let remote = project.remote.as_ref().map(|r| Arc::new(RemoteHosts::new(r)));
+    let cache = project.cache.as_ref().map(|c| Arc::new(ObjectCache::new(c, &project.root_dir)));
+    let (result_tx, result_rx) = mpsc::sync_channel::<WorkerMsg>(0);
     let project_ref = Arc::new(project.targets.clone());
-    let n_workers = n_jobs.max(1);
+    let n_workers = n_jobs.max(1) + remote.as_ref().map_or(0, |r| r.slots());
+    let build_start = Instant::now();
+    // Cancellable builds track their compiler/linker processes so cancel can kill them
+    let children = control.as_ref().map(|_| Arc::new(compiler::ChildProcesses::default()));
+    let build_done = Arc::new(AtomicBool::new(false));
+    if let (Some(control), Some(children)) = (&control, &children) {
+        let control = Arc::clone(control);
+        let children = Arc::clone(children);
+        let build_done = Arc::clone(&build_done);
+        thread::spawn(move || {
+            while !build_done.load(Ordering::Relaxed) {
+                if control.is_cancelled() {
+                    children.kill_all();
+                    break;
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+    }
 
-    for _ in 0..n_workers {
+    for worker in 0..n_workers {
         let job_queue = Arc::clone(&job_queue);
         let result_tx = result_tx.clone();
         let project_ref = Arc::clone(&project_ref);
+        let children = children.clone();
+        let control = control.clone();
+        let retry = retry.clone();
+        let remote = remote.clone();
+        let cache = cache.clone();
         let _ = thread::spawn(move || {
+            compiler::set_child_processes(children);
+            let link_memory = |name: &str| project_ref.get(name).map(|t| t.link_memory).unwrap_or(0);
             loop {
+                // Highest-priority job that may start (earliest queued on ties): links wait,
+                // while compiles behind them run, as long as the link limits are reached
                 let job = {
                     let mut q = job_queue.0.lock().unwrap();
-                    while q.is_empty() {
+                    loop {
+                        let state = &*q;
+                        let next = state
+                            .jobs
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (_, job))| match job {
+                                Some(Job::Link { target_name, .. }) => link_limits.admits(&state.links, link_memory(target_name)),
+                                _ => true,
+                            })
+                            .max_by_key(|(pos, (priority, _))| (*priority, std::cmp::Reverse(*pos)))
+                            .map(|(pos, _)| pos);
+                        if let Some(pos) = next {
+                            let job = q.jobs.remove(pos).and_then(|(_, job)| job);
+                            if let Some(Job::Link { target_name, .. }) = &job {
+                                q.links.running += 1;
+                                q.links.memory += link_memory(target_name);
+                            }
+                            break job;
+                        }
                         q = job_queue.1.wait(q).unwrap();
                     }
-                    q.pop_front().flatten()
                 };
                 let Some(job) = job else { break };
+                // Paused: hold the job until resumed (a cancel fails it right away)
+                if let Some(control) = &control {
+                    while control.is_paused() && !control.is_cancelled() {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                }
+                let start = build_start.elapsed();
                 match job {
                     Job::Compile { target_name, source_idx, source, obj_path } => {
                         let Some(target) = project_ref.get(&target_name) else { continue };
-                        match compiler::compile_one_source_or_skip(target, &source, &obj_path) {
-                            Ok((path, messages)) => {
-                                let _ = result_tx.send(JobResult::Compile {
-                                    target_name,
+                        let _ = result_tx.send(WorkerMsg::Started {
+                            kind: JobKind::Compile,
+                            target_name: target_name.clone(),
+                            source: Some(source.clone()),
+                            worker,
+                            start,
+                        });
+                        let up_to_date = compiler::object_up_to_date(&source, &obj_path);
+                        let mut notices = Vec::new();
+                        let debug = (verbosity >= 3).then_some(&mut notices);
+                        let cache_key = cache.as_ref().filter(|_| !up_to_date).and_then(|c| c.key(target, &source, debug));
+                        let restored = match (&cache, &cache_key) {
+                            (Some(cache), Some(key)) => cache.restore(key, &obj_path),
+                            _ => None,
+                        };
+                        if cache.is_some() && !up_to_date && verbosity >= 3 {
+                            notices.push(match (&cache_key, restored) {
+                                (Some(key), Some(from)) => format!("  [DEBUG] cache {}: hit ({})", key, from),
+                                (Some(key), None) => format!("  [DEBUG] cache {}: miss", key),
+                                (None, _) => format!("  [DEBUG] cache: {} is not cacheable", source.display()),
+                            });
+                        }
+                        let outcome = if let Some(from) = restored {
+                            Ok((obj_path.clone(), vec![format!("  [CACHED] {} → {} ({} cache)", source.display(), obj_path.display(), from)]))
+                        } else {
+                            let mut attempts = 0;
+                            loop {
+                                let outcome = match &remote {
+                                    Some(remote) if !up_to_date => remote.compile(target, &source, &obj_path),
+                                    _ => None,
+                                }
+                                .unwrap_or_else(|| compiler::compile_one_source_or_skip(target, &source, &obj_path));
+                                let notice = match (&outcome, &retry) {
+                                    (Err(e), Some(retry)) => retry.again(attempts, &source.display().to_string(), e),
+                                    _ => None,
+                                };
+                                match notice {
+                                    Some(notice) => {
+                                        notices.push(notice);
+                                        attempts += 1;
+                                    }
+                                    None => break outcome,
+                                }
+                            }
+                        };
+                        if let (Some(cache), Some(key), Ok(_), None) = (&cache, &cache_key, &outcome, restored) {
+                            cache.store(key, &obj_path);
+                        }
+                        let (result, success) = match outcome {
+                            Ok((path, messages)) => (
+                                JobResult::Compile {
+                                    target_name: target_name.clone(),
                                     source_idx,
                                     obj_path: path,
                                     success: true,
-                                    messages,
-                                });
-                            }
-                            Err(e) => {
-                                let _ = result_tx.send(JobResult::Compile {
-                                    target_name,
+                                    messages: notices.into_iter().chain(messages).collect(),
+                                },
+                                true,
+                            ),
+                            Err(e) => (
+                                JobResult::Compile {
+                                    target_name: target_name.clone(),
                                     source_idx,
                                     obj_path: obj_path.clone(),
                                     success: false,
-                                    messages: vec![format!("  [ERROR] {}", e)],
-                                });
-                            }
-                        }
+                                    messages: notices.into_iter().chain([format!("  [ERROR] {}", e)]).collect(),
+                                },
+                                false,
+                            ),
+                        };
+                        let timing = JobTiming {
+                            kind: JobKind::Compile,
+                            target_name,
+                            source: Some(source),
+                            worker,
+                            start,
+                            end: build_start.elapsed(),
+                            success,
+                            up_to_date: up_to_date || restored.is_some(),
+                            cached: restored.is_some(),
+                        };
+                        let _ = result_tx.send(WorkerMsg::Finished(result, timing));
                     }
                     Job::Link { target_name, object_files, built_deps, link_deps } => {
                         let Some(target) = project_ref.get(&target_name) else { continue };
-                        let result = compiler::run_link_step(
-                            target,
-                            &object_files,
-                            &built_deps,
-                            link_deps.as_deref(),
-                        );
-                        let _ = result_tx.send(JobResult::Link(result));
+                        let _ = result_tx.send(WorkerMsg::Started {
+                            kind: JobKind::Link,
+                            target_name: target_name.clone(),
+                            source: None,
+                            worker,
+                            start,
+                        });
+                        let link = || compiler::run_link_step(target, &object_files, &built_deps, link_deps.as_deref());
+                        let mut result = link();
+                        let mut notices = Vec::new();
+                        while let Some(notice) = retry
+                            .as_ref()
+                            .filter(|_| !result.success)
+                            .and_then(|r| r.again(notices.len() as u32, &target_name, &failure_output(&result.messages)))
+                        {
+                            notices.push(notice);
+                            result = link();
+                        }
+                        result.messages.splice(0..0, notices);
+                        {
+                            let mut q = job_queue.0.lock().unwrap();
+                            q.links.running -= 1;
+                            q.links.memory -= target.link_memory;
+                            job_queue.1.notify_all();
+                        }
+                        let timing = JobTiming {
+                            kind: JobKind::Link,
+                            target_name,
+                            source: None,
+                            worker,
+                            start,
+                            end: build_start.elapsed(),
+                            success: result.success,
+                            up_to_date: false,
+                            cached: false,
+                        };
+                        let _ = result_tx.send(WorkerMsg::Finished(JobResult::Link(result), timing));
                     }
                 }
             }
@@ -208,18 +677,61 @@ fn build_ninja_style(
     let mut failed = 0;
     let mut in_flight = 0usize;
     let mut build_failed = false;
+    // Failed jobs; at the --keep-going limit queued jobs are dropped and no new ones start
+    let failure_limit = options.failure_limit();
+    let mut failed_jobs = 0usize;
+    let mut stopped = false;
+    let mut failed_names: HashSet<String> = HashSet::new();
+    let mut job_timings: Vec<JobTiming> = Vec::new();
+    let mut diagnostic_counts: BTreeMap<String, DiagnosticCounts> = BTreeMap::new();
+    let mut diagnostic_messages: BTreeMap<String, Vec<Diagnostic>> = BTreeMap::new();
+
+    // Live "[done/total] pct elapsed ~eta left, compiling x, y" line (plain CLI
+    // mode only); on a terminal it replaces the per-target progress lines
+    let show_status = !quiet && !verbose && output_tx.is_none();
+    let mut status = StatusLine::new();
+    let mut progress = show_status
+        .then(|| Progress::new(project, order, &durations, n_workers));
+    // Source each worker is compiling right now, for the progress line
+    let mut active: Vec<Option<(String, Option<PathBuf>)>> = vec![None; n_workers];
+    let status_format = options.status_format.as_deref();
+    let json_events = options.json_events();
+
+    fn refresh_status(status: &mut StatusLine, progress: Option<&Progress>, format: Option<&str>, active: &[Option<(String, Option<PathBuf>)>], build_start: Instant) {
+        if let Some(p) = progress {
+            let mut targets: Vec<&str> = active.iter().flatten().map(|(t, _)| t.as_str()).collect();
+            targets.sort();
+            targets.dedup();
+            let files: Vec<&Path> = active.iter().flatten().filter_map(|(_, s)| s.as_deref()).collect();
+            status.update(&p.line(format, &targets, &files, build_start.elapsed()));
+        }
+    }
+
+    // Plain "[ 3/12  25%] app" line for a finished target, or the status format
+    let target_line = |progress: Option<&Progress>, done: usize, name: &str, success: bool| {
+        let note = if success { String::new() } else { format!(" {}", color::red("(failed)")) };
+        match status_format {
+            Some(format) => {
+                let status = Status { done, total: total_targets, elapsed: build_start.elapsed(), eta: progress.and_then(Progress::eta), targets: &[name], files: &[] };
+                format!("  {}{}", progress::format_status(format, &status), note)
+            }
+            None => {
+                let pct = (done * 100).checked_div(total_targets).unwrap_or(0);
+                format!("  [{:>3}/{} {:>3}%] {}{}", done, total_targets, pct, color::target(name), note)
+            }
+        }
+    };
 
     let level0 = order.levels.first().map(|l| l.as_slice()).unwrap_or(&[]);
     let mut headers_sent: HashSet<String> = HashSet::new();
 
-    fn send_header(tx: Option<&mpsc::Sender<String>>, name: &str, target: &crate::config::ResolvedTarget, headers_sent: &mut HashSet<String>) {
+    fn send_header(tx: Option<&mpsc::Sender<BuildEvent>>, name: &str, target: &crate::config::ResolvedTarget, headers_sent: &mut HashSet<String>) {
         if let Some(tx) = tx {
             if headers_sent.insert(name.to_string()) {
-                let line = format!(
-                    "=== Building target '{}' (type: {:?}) ===",
-                    name, target.target_type
-                );
-                let _ = tx.send(format!("[TARGET:{}] {}", name, line));
+                let _ = tx.send(BuildEvent::TargetStarted {
+                    target: name.to_string(),
+                    target_type: target.target_type.clone(),
+                });
             }
         }
     }
@@ -228,9 +740,9 @@ fn build_ninja_style(
         project: &ResolvedProject,
         target_name: &str,
         order: &BuildOrder,
-        job_queue: &Arc<(Mutex<VecDeque<Option<Job>>>, Condvar)>,
+        job_queue: &SharedQueue,
         built_targets: &Arc<Mutex<HashMap<String, PathBuf>>>,
-        output_tx: Option<&mpsc::Sender<String>>,
+        output_tx: Option<&mpsc::Sender<BuildEvent>>,
         headers_sent: &mut HashSet<String>,
     ) -> usize {
         let target = match project.targets.get(target_name) {
@@ -238,11 +750,11 @@ fn build_ninja_style(
             None => return 0,
         };
         send_header(output_tx, target_name, target, headers_sent);
-        if target.sources.is_empty() {
+        if target.sources.is_empty() || !target.target_type.compiles_sources() {
             let link_deps = link_deps_for_target(project, target_name, order);
             let built = built_targets.lock().unwrap().clone();
             let built_deps = built_deps_subset(&built, link_deps.as_deref());
-            job_queue.0.lock().unwrap().push_back(Some(Job::Link {
+            job_queue.0.lock().unwrap().push(Some(Job::Link {
                 target_name: target_name.to_string(),
                 object_files: vec![],
                 built_deps,
@@ -251,23 +763,17 @@ fn build_ninja_style(
             job_queue.1.notify_all();
             return 1;
         }
-        let obj_dir = target.output_dir.join("obj").join(target_name);
+        send_status(output_tx, target_name, TargetState::Compiling { done: 0, total: target.sources.len() });
         let mut n = 0usize;
         for (idx, source) in target.sources.iter().enumerate() {
-            let obj_name = source
-                .file_stem()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string()
-                + ".o";
-            let obj_path = obj_dir.join(&obj_name);
+            let obj_path = compiler::object_path(target, source);
             let job = Job::Compile {
                 target_name: target_name.to_string(),
                 source_idx: idx,
                 source: source.clone(),
                 obj_path,
             };
-            job_queue.0.lock().unwrap().push_back(Some(job));
+            job_queue.0.lock().unwrap().push(Some(job));
             n += 1;
         }
         job_queue.1.notify_all();
@@ -283,41 +789,112 @@ fn build_ninja_style(
         compile_jobs_added.lock().unwrap().insert(target_name.to_string());
     }
 
+    // Indexed on the first missing-include error (see hints.rs)
+    let header_index = hints::HeaderIndex::new(project);
     while in_flight > 0 {
-        let result = match result_rx.recv() {
-            Ok(r) => r,
-            Err(_) => break,
+        let is_cancelled = || control.as_ref().map(|c| c.is_cancelled()).unwrap_or(false);
+        let (mut result, timing) = match result_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(WorkerMsg::Started { kind, target_name, source, worker, start }) => {
+                if json_events {
+                    events::emit(&events::job_started(kind, &target_name, source.as_deref(), worker, start));
+                }
+                if kind == JobKind::Link {
+                    send_status(output_tx, &target_name, TargetState::Linking);
+                }
+                active[worker] = Some((target_name, source));
+                refresh_status(&mut status, progress.as_ref(), status_format, &active, build_start);
+                continue;
+            }
+            Ok(WorkerMsg::Finished(result, timing)) => (result, timing),
+            // Nothing finished (long job or paused): still react to cancel
+            Err(mpsc::RecvTimeoutError::Timeout) if !is_cancelled() => {
+                refresh_status(&mut status, progress.as_ref(), status_format, &active, build_start);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(tx) = output_tx {
+                    let _ = tx.send(BuildEvent::output(None, "[INFO] Build cancelled by user."));
+                }
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
         };
         in_flight -= 1;
+        active[timing.worker] = None;
+        if json_events {
+            events::emit(&events::job_finished(&timing));
+        }
+        let messages = match &mut result {
+            JobResult::Compile { messages, .. } => messages,
+            JobResult::Link(r) => &mut r.messages,
+        };
+        let mut parsed = diagnostics::parse_diagnostics(&messages.join("\n"));
+        for d in parsed.iter_mut().filter(|d| d.severity == Severity::Error) {
+            let header = hints::missing_include(&d.message);
+            let target = project.targets.get(&timing.target_name);
+            if let (Some(header), Some(target)) = (header, target) {
+                d.hint = hints::missing_include_hint(project, target, header, &header_index);
+                if let Some(hint) = &d.hint {
+                    messages.push(format!("  [HINT] {}", hint));
+                }
+            }
+        }
+        let counts = DiagnosticCounts::count(&parsed);
+        if counts != DiagnosticCounts::default() {
+            diagnostic_counts.entry(timing.target_name.clone()).or_default().add(counts);
+            let found = parsed.iter().filter(|d| d.severity != Severity::Note).cloned();
+            diagnostic_messages.entry(timing.target_name.clone()).or_default().extend(found);
+        }
+        if json_events || output_tx.is_some() {
+            if let (Some(tx), Some(source)) = (output_tx, &timing.source) {
+                let _ = tx.send(BuildEvent::FileCompiled {
+                    target: timing.target_name.clone(),
+                    source: source.clone(),
+                    success: timing.success,
+                    up_to_date: timing.up_to_date,
+                    duration_ms: timing.duration().as_millis() as u64,
+                });
+            }
+            for d in parsed {
+                if json_events {
+                    events::emit(&events::diagnostic(&timing.target_name, &d));
+                }
+                if let Some(tx) = output_tx {
+                    let _ = tx.send(BuildEvent::Diagnostic { target: timing.target_name.clone(), diagnostic: d });
+                }
+            }
+        }
+        if let Some(p) = progress.as_mut() {
+            p.finish(&timing);
+        }
+        job_timings.push(timing);
 
         match result {
             JobResult::Compile { target_name, source_idx, obj_path, success, messages } => {
-                if !quiet && (verbose || output_tx.is_some()) {
-                    for msg in &messages {
-                        let is_error = msg.contains("[ERROR]");
-                        if is_error { eprintln!("{}", msg); } else if verbose || !msg.contains("Command:") { println!("{}", msg); }
-                    }
-                } else if !quiet && !verbose {
-                    for msg in &messages {
-                        if msg.contains("[ERROR]") { eprintln!("{}", msg); }
-                    }
+                if !quiet {
+                    print_job_output(&target_name, &messages, verbosity, output_tx.is_some(), &mut status);
                 }
                 if let Some(tx) = output_tx {
                     for msg in &messages {
                         // Send each line separately so GUI shows full compiler stderr (IPC may not preserve newlines in one payload)
                         for line in msg.split('\n') {
-                            let _ = tx.send(format!("[TARGET:{}] {}", target_name, line));
+                            let _ = tx.send(BuildEvent::output(Some(&target_name), line));
                         }
                     }
                 }
                 if !success {
                     build_failed = true;
-                    failed += 1;
-                    if !quiet && !verbose && output_tx.is_none() {
-                        let done = successful + failed;
-                        let pct = if total_targets > 0 { (done * 100) / total_targets } else { 0 };
-                        println!("  [{:>3}/{} {:>3}%] {} (failed)", done, total_targets, pct, target_name);
+                    failed_jobs += 1;
+                    let first_failure = failed_names.insert(target_name.clone());
+                    if first_failure {
+                        failed += 1;
+                        send_status(output_tx, &target_name, TargetState::Failed);
+                    }
+                    if first_failure && show_status {
+                        status.clear();
+                        println!("{}", target_line(progress.as_ref(), successful + failed, &target_name, false));
                     }
+                    refresh_status(&mut status, progress.as_ref(), status_format, &active, build_start);
                     all_results.push(CompileResult {
                         target_name: target_name.clone(),
                         success: false,
@@ -328,14 +905,17 @@ fn build_ninja_style(
                 }
                 obj_files.lock().unwrap().entry(target_name.clone()).or_default()[source_idx] = Some(obj_path);
                 let of = obj_files.lock().unwrap().get(&target_name).cloned().unwrap_or_default();
+                if !failed_names.contains(&target_name) {
+                    let done = of.iter().filter(|o| o.is_some()).count();
+                    send_status(output_tx, &target_name, TargetState::Compiling { done, total: of.len() });
+                }
                 let all_done = of.iter().all(Option::is_some);
-                if all_done {
-                    let target = project.targets.get(&target_name).unwrap();
+                if all_done && !stopped {
                     let object_files: Vec<PathBuf> = of.into_iter().map(|o| o.unwrap()).collect();
                     let link_deps = link_deps_for_target(project, &target_name, order);
                     let built = built_targets.lock().unwrap().clone();
                     let built_deps = built_deps_subset(&built, link_deps.as_deref());
-                    job_queue.0.lock().unwrap().push_back(Some(Job::Link {
+                    job_queue.0.lock().unwrap().push(Some(Job::Link {
                         target_name: target_name.clone(),
                         object_files,
                         built_deps,
@@ -346,15 +926,19 @@ fn build_ninja_style(
                 }
             }
             JobResult::Link(result) => {
+                let state = if result.success { TargetState::Succeeded } else { TargetState::Failed };
+                send_status(output_tx, &result.target_name, state);
                 if result.success {
                     built_targets.lock().unwrap().insert(result.target_name.clone(), result.output_path.clone());
                     successful += 1;
                 } else {
                     failed += 1;
+                    failed_jobs += 1;
+                    failed_names.insert(result.target_name.clone());
                     build_failed = true;
                 }
-                let continue_on_failure = options.ignore_errors;
-                if result.success || continue_on_failure {
+                // Dependents of a failed target are never started (reported as not attempted)
+                if result.success && !stopped {
                     for dep_name in dependents.get(&result.target_name).cloned().unwrap_or_default() {
                         let added = compile_jobs_added.lock().unwrap().contains(dep_name.as_str());
                         if added {
@@ -373,55 +957,91 @@ fn build_ninja_style(
                         }
                     }
                 }
-                if !quiet && (verbose || output_tx.is_some()) {
-                    for msg in &result.messages {
-                        let is_error = msg.contains("[ERROR]");
-                        if is_error { eprintln!("{}", msg); } else if verbose || !msg.contains("Command:") { println!("{}", msg); }
-                    }
-                } else if !quiet && !verbose {
-                    for msg in &result.messages {
-                        if msg.contains("[ERROR]") { eprintln!("{}", msg); }
-                    }
+                if !quiet {
+                    print_job_output(&result.target_name, &result.messages, verbosity, output_tx.is_some(), &mut status);
                 }
-                if !quiet && !verbose && output_tx.is_none() {
-                    let done = successful + failed;
-                    let pct = if total_targets > 0 { (done * 100) / total_targets } else { 0 };
-                    println!("  [{:>3}/{} {:>3}%] {}", done, total_targets, pct, result.target_name);
+                // Failures always get a line; successes only without a status line
+                if show_status && !(result.success && status.is_enabled()) {
+                    status.clear();
+                    println!("{}", target_line(progress.as_ref(), successful + failed, &result.target_name, result.success));
                 }
                 if let Some(tx) = output_tx {
                     for msg in &result.messages {
                         for line in msg.split('\n') {
-                            let _ = tx.send(format!("[TARGET:{}] {}", result.target_name, line));
+                            let _ = tx.send(BuildEvent::output(Some(&result.target_name), line));
                         }
                     }
                 }
                 all_results.push(result);
             }
         }
-        if cancel.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or(false) {
+        if !stopped && failure_limit.is_some_and(|limit| failed_jobs >= limit) {
+            stopped = true;
+            // Running jobs finish; queued ones never start
+            let mut q = job_queue.0.lock().unwrap();
+            in_flight -= q.jobs.len();
+            q.jobs.clear();
+            if let Some(tx) = output_tx {
+                let _ = tx.send(BuildEvent::output(None, format!("[INFO] Stopping after {} failed job(s).", failed_jobs)));
+            }
+        }
+        refresh_status(&mut status, progress.as_ref(), status_format, &active, build_start);
+        if is_cancelled() {
             if let Some(tx) = output_tx {
-                let _ = tx.send("[INFO] Build cancelled by user.".to_string());
+                let _ = tx.send(BuildEvent::output(None, "[INFO] Build cancelled by user."));
             }
             break;
         }
     }
 
+    status.clear();
+    build_done.store(true, Ordering::Relaxed);
+
+    let cancelled = control.as_ref().map(|c| c.is_cancelled()).unwrap_or(false);
     {
         let mut q = job_queue.0.lock().unwrap();
+        if cancelled {
+            // Drop queued jobs so workers stop instead of draining the queue
+            q.jobs.clear();
+        }
         for _ in 0..n_workers {
-            q.push_back(None);
+            q.push(None);
         }
         job_queue.1.notify_all();
     }
 
-    let cancelled = cancel.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or(false);
     let success = !cancelled && !build_failed && failed == 0;
+    let built = built_targets.lock().unwrap();
+    let not_attempted: Vec<String> = order
+        .levels
+        .iter()
+        .flatten()
+        .filter(|name| !built.contains_key(*name) && !failed_names.contains(*name))
+        .cloned()
+        .collect();
+    drop(built);
+    for name in &not_attempted {
+        send_status(output_tx, name, TargetState::Skipped);
+    }
+    let mut totals = DiagnosticCounts::default();
+    for counts in diagnostic_counts.values() {
+        totals.add(*counts);
+    }
     if let Some(tx) = output_tx {
-        let _ = tx.send(format!(
-            "--- {} targets, {} successful, {} failed ---",
-            total_targets, successful, failed
-        ));
-        let _ = tx.send(format!("__ngmake_FINISH__\t{}\t{}\t{}\t{}", success, total_targets, successful, failed));
+        let _ = tx.send(BuildEvent::output(None, format!(
+            "--- {} targets, {} successful, {} failed, {} not attempted{} ---",
+            total_targets, successful, failed, not_attempted.len(), totals.suffix()
+        )));
+        let _ = tx.send(BuildEvent::Finished {
+            stats: BuildStats {
+                success,
+                total: total_targets,
+                successful,
+                failed,
+                warnings: totals.warnings,
+                errors: totals.errors,
+            },
+        });
     }
 
     BuildResult {
@@ -430,82 +1050,59 @@ fn build_ninja_style(
         total_targets,
         successful_targets: successful,
         failed_targets: failed,
+        jobs: job_timings,
+        wall_time: build_start.elapsed(),
+        workers: n_workers,
+        diagnostics: diagnostic_counts,
+        diagnostic_messages,
+        not_attempted,
     }
 }
 
-/// Level-by-level build (when no -j: all targets in each level in parallel).
-fn build_level_by_level(
-    project: &ResolvedProject,
-    order: &BuildOrder,
-    options: &BuildOptions,
-) -> BuildResult {
-    let total_targets = order.levels.iter().map(|l| l.len()).sum::<usize>();
-    let built_targets: Arc<Mutex<HashMap<String, PathBuf>>> = Arc::new(Mutex::new(HashMap::new()));
-    let quiet = options.show_quiet_output();
-    let verbose = options.show_verbose_output();
-    let mut all_results = Vec::new();
-    let mut successful = 0;
-    let mut failed = 0;
-
-    for (level_idx, level) in order.levels.iter().enumerate() {
-        if !quiet {
-            println!(
-                "┌─── Level {} ─── ({} target(s): {:?}) ───┐",
-                level_idx,
-                level.len(),
-                level
-            );
-        }
-
-        let level_results = build_level_parallel(level, project, &built_targets, order, None);
-
-        let mut level_failed = false;
-        for result in &level_results {
-            if !quiet {
-                for msg in &result.messages {
-                    let is_error = msg.contains("[ERROR]");
-                    if is_error {
-                        eprintln!("{}", msg);
-                    } else if verbose || !msg.contains("Command:") {
-                        println!("{}", msg);
-                    }
-                }
-            }
-            if result.success {
-                successful += 1;
-            } else {
-                failed += 1;
-                level_failed = true;
-            }
-        }
-        all_results.extend(level_results);
+/// Prints a finished job's messages as one block with a single write, so the output
+/// of jobs finishing together never interleaves (like ninja). Every line starts with
+/// the target name in its color.
+fn print_job_output(target: &str, messages: &[String], verbosity: u8, gui: bool, status: &mut StatusLine) {
+    let Some((block, is_error)) = job_output_block(messages, verbosity, gui) else { return };
+    let prefix = color::target(target);
+    let block: String = block
+        .lines()
+        .map(|line| format!("  {}: {}\n", prefix, color::line(line.strip_prefix("  ").unwrap_or(line))))
+        .collect();
+    status.clear();
+    if is_error {
+        let _ = std::io::stderr().lock().write_all(block.as_bytes());
+    } else {
+        let _ = std::io::stdout().lock().write_all(block.as_bytes());
+    }
+}
 
-        if level_failed {
-            if !quiet {
-                eprintln!(
-                    "\n[ERROR] One or more targets in level {} failed. Stopping build.",
-                    level_idx
-                );
-            }
-            return BuildResult {
-                success: false,
-                results: all_results,
-                total_targets,
-                successful_targets: successful,
-                failed_targets: failed,
-            };
-        }
-        if !quiet {
-            println!("└─── Level {} done ───┘\n", level_idx);
-        }
+/// The lines of a job's output to show, joined, and whether they contain an error
+/// (the block then goes to stderr). Only lines up to `verbosity` are shown (see
+/// message_level); the GUI always gets level 1.
+fn job_output_block(messages: &[String], verbosity: u8, gui: bool) -> Option<(String, bool)> {
+    let shown: Vec<&String> = messages
+        .iter()
+        .filter(|msg| message_level(msg) <= verbosity.max(gui as u8))
+        .collect();
+    if shown.is_empty() {
+        return None;
     }
+    let is_error = shown.iter().any(|msg| msg.contains("[ERROR]"));
+    Some((shown.iter().map(|msg| format!("{}\n", msg)).collect(), is_error))
+}
 
-    BuildResult {
-        success: true,
-        results: all_results,
-        total_targets,
-        successful_targets: successful,
-        failed_targets: failed,
+/// Verbosity level from which a job message is shown: errors always, progress
+/// with -v, command lines with -vv, `[DEBUG]` details with -vvv.
+fn message_level(msg: &str) -> u8 {
+    if msg.contains("[ERROR]") || msg.contains("[HINT]") {
+        0
+    } else if msg.contains("[DEBUG]") {
+        3
+    } else if msg.contains("Command:") {
+        2
+    } else {
+        1
     }
 }
 
@@ -519,10 +1116,10 @@ fn link_deps_for_target(
 ) -> Option<Vec<String>> {
     let target = project.targets.get(target_name)?;
     match target.target_type {
-        crate::config::TargetType::Executable | crate::config::TargetType::SharedLib => {
+        crate::config::TargetType::Executable | crate::config::TargetType::Benchmark | crate::config::TargetType::Test | crate::config::TargetType::SharedLib => {
             Some(dag::transitive_deps_in_link_order(project, target_name, order))
         }
-        crate::config::TargetType::StaticLib => None,
+        _ => None,
     }
 }
 
@@ -542,95 +1139,240 @@ fn built_deps_subset(
     out
 }
 
-// ---------------------------------------------------------------------------
-// Build targets in one level in parallel (up to N concurrent when -j is set)
-// ---------------------------------------------------------------------------
-fn build_level_parallel(
-    level: &[String],
-    project: &ResolvedProject,
-    built_targets: &Arc<Mutex<HashMap<String, PathBuf>>>,
-    order: &BuildOrder,
-    jobs: Option<usize>,
-) -> Vec<CompileResult> {
-    let chunk_size = jobs.unwrap_or(level.len());
-    if chunk_size == 0 {
-        return vec![];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_links_share_the_memory_limit() {
+        let limits = LinkLimits { jobs: None, memory: Some(8192) };
+        let idle = LinkUsage::default();
+        let one_big = LinkUsage { running: 1, memory: 6144 };
+        assert!(limits.admits(&idle, 12288), "a link always runs alone");
+        assert!(limits.admits(&one_big, 2048));
+        assert!(!limits.admits(&one_big, 4096));
+        assert!(LinkLimits::default().admits(&one_big, 4096));
     }
 
-    let mut all_results = Vec::new();
+    #[test]
+    fn test_jobs_on_the_longest_chain_go_first() {
+        // app ← core ← base is the long chain; tool (no dependents) is slow to link but alone
+        let target = |name: &str, deps: &[&str]| crate::config::ResolvedTarget {
+            name: name.to_string(),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            sources: vec![PathBuf::from(format!("{}.cpp", name))],
+            ..Default::default()
+        };
+        let mut project = ResolvedProject::default();
+        for t in [target("base", &[]), target("core", &["base"]), target("app", &["core"]), target("tool", &[])] {
+            project.targets.insert(t.name.clone(), t);
+        }
+        let order = dag::build_order(&project).unwrap();
+        let link = |name: &str| Job::Link { target_name: name.to_string(), object_files: vec![], built_deps: HashMap::new(), link_deps: None };
+        let compile = |name: &str| Job::Compile {
+            target_name: name.to_string(),
+            source_idx: 0,
+            source: PathBuf::from(format!("{}.cpp", name)),
+            obj_path: PathBuf::new(),
+        };
+
+        let unknown = Priorities::new(&project, &order, JobDurations::default());
+        assert!(unknown.of(&compile("base")) > unknown.of(&compile("tool")));
+        assert!(unknown.of(&compile("base")) > unknown.of(&link("base")));
 
-    for chunk in level.chunks(chunk_size) {
-        let chunk_results = build_chunk_parallel(chunk, project, built_targets, order);
-        all_results.extend(chunk_results);
+        let mut durations = JobDurations::default();
+        durations.link.insert("tool".to_string(), 5000);
+        durations.link.insert("app".to_string(), 200);
+        let history = Priorities::new(&project, &order, durations);
+        assert_eq!(history.of(&link("base")), 1 + (1 + 1) + (1 + 200));
+        assert!(history.of(&compile("tool")) > history.of(&compile("base")));
     }
 
-    all_results
-}
+    #[test]
+    fn test_job_output_is_one_block() {
+        let messages = vec![
+            "  Command: g++ -c a.cpp".to_string(),
+            "    stderr: a.cpp:1:1: warning: unused".to_string(),
+            "  [ERROR] Compile failed:\na.cpp:2:1: error: oops".to_string(),
+        ];
+        let (block, is_error) = job_output_block(&messages, 0, true).unwrap();
+        assert_eq!(block, format!("{}\n{}\n", messages[1], messages[2]));
+        assert!(is_error);
+        assert_eq!(job_output_block(&messages, 0, false).unwrap().0, format!("{}\n", messages[2]));
+        assert_eq!(job_output_block(&messages[..1], 1, false), None);
+        assert_eq!(job_output_block(&messages[..1], 2, false), Some((format!("{}\n", messages[0]), false)));
+        assert_eq!(job_output_block(&messages[..2], 0, false), None);
 
-/// Build a subset of targets in parallel (one thread per target in chunk)
-fn build_chunk_parallel(
-    chunk: &[String],
-    project: &ResolvedProject,
-    built_targets: &Arc<Mutex<HashMap<String, PathBuf>>>,
-    order: &BuildOrder,
-) -> Vec<CompileResult> {
-    if chunk.is_empty() {
-        return vec![];
-    }
-    if chunk.len() == 1 {
-        let target_name = &chunk[0];
-        if let Some(target) = project.targets.get(target_name) {
-            let bt = built_targets.lock().unwrap().clone();
-            let link_deps = link_deps_for_target(project, target_name, order);
-            let result = compiler::build_target(target, &bt, link_deps.as_deref());
-            if result.success {
-                built_targets
-                    .lock()
-                    .unwrap()
-                    .insert(result.target_name.clone(), result.output_path.clone());
-            }
-            return vec![result];
+        let debug = ["  [DEBUG] cache 0123: miss".to_string()];
+        assert_eq!(job_output_block(&debug, 2, false), None);
+        assert!(job_output_block(&debug, 3, false).is_some());
+    }
+
+    #[test]
+    fn test_dependents_of_failed_targets_are_not_attempted() {
+        let custom = |name: &str, command: &str, deps: &[&str]| crate::config::ResolvedTarget {
+            name: name.to_string(),
+            target_type: crate::config::TargetType::Custom,
+            commands: vec![vec![command.to_string()]],
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            working_dir: std::env::temp_dir(),
+            ..Default::default()
+        };
+        let mut project = ResolvedProject { root_dir: std::env::temp_dir(), ..Default::default() };
+        for t in [custom("gen", "false", &[]), custom("app", "true", &["gen"]), custom("docs", "true", &[])] {
+            project.targets.insert(t.name.clone(), t);
         }
-        return vec![];
+        let order = dag::build_order(&project).unwrap();
+
+        let keep_going = BuildOptions { quiet: true, keep_going: Some(0), jobs: Some(1), ..Default::default() };
+        let result = build_project(&project, &order, &keep_going, None, None);
+        assert!(!result.success);
+        assert_eq!((result.successful_targets, result.failed_targets), (1, 1));
+        assert_eq!(result.not_attempted, vec!["app".to_string()]);
+
+        // Default: stop after the first failure; gen goes first (longest chain)
+        let stop = BuildOptions { quiet: true, jobs: Some(1), ..Default::default() };
+        let result = build_project(&project, &order, &stop, None, None);
+        assert_eq!(result.failed_targets, 1);
+        assert!(result.not_attempted.contains(&"app".to_string()));
     }
 
-    let mut handles = Vec::new();
-    for target_name in chunk {
-        if let Some(target) = project.targets.get(target_name) {
-            let target_clone = target.clone();
-            let bt_arc = Arc::clone(built_targets);
-            let link_deps = link_deps_for_target(project, target_name, order);
-            let bt_snapshot = bt_arc.lock().unwrap().clone();
+    #[cfg(unix)]
+    #[test]
+    fn test_transient_failures_are_retried() {
+        let dir = TempDir::new("retry");
+        // Fails once with a "file locked" error, then succeeds
+        let flaky = "if [ -e tried ]; then exit 0; fi; touch tried; echo 'app.exe: file locked' >&2; exit 1";
+        let target = crate::config::ResolvedTarget {
+            name: "flaky".to_string(),
+            target_type: crate::config::TargetType::Custom,
+            commands: vec![vec!["sh".to_string(), "-c".to_string(), flaky.to_string()]],
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        project.targets.insert(target.name.clone(), target);
+        let order = dag::build_order(&project).unwrap();
+        let options = BuildOptions { quiet: true, ..Default::default() };
+
+        project.retry = Some(crate::config::RetryPolicy { count: 1, matches: vec!["file locked".to_string()], delay_ms: 0 });
+        let result = build_project(&project, &order, &options, None, None);
+        assert!(result.success);
+        assert!(result.results[0].messages[0].starts_with("  [RETRY] flaky failed with \"file locked\""));
+
+        std::fs::remove_file(dir.join("tried")).unwrap();
+        project.retry = Some(crate::config::RetryPolicy { count: 1, matches: vec!["disk full".to_string()], delay_ms: 0 });
+        assert!(!build_project(&project, &order, &options, None, None).success);
+    }
 
-            let handle = thread::spawn(move || {
-                let result = compiler::build_target(&target_clone, &bt_snapshot, link_deps.as_deref());
-                (result, bt_arc)
-            });
-            handles.push(handle);
+    /// Custom target running `script` with `sh -c` in `dir`.
+    #[cfg(unix)]
+    fn shell_target(name: &str, script: &str, deps: &[&str], dir: &Path) -> crate::config::ResolvedTarget {
+        crate::config::ResolvedTarget {
+            name: name.to_string(),
+            target_type: crate::config::TargetType::Custom,
+            commands: vec![vec!["sh".to_string(), "-c".to_string(), script.to_string()]],
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
         }
     }
 
-    let mut results = Vec::new();
-    for handle in handles {
-        match handle.join() {
-            Ok((result, bt_arc)) => {
-                if result.success {
-                    bt_arc
-                        .lock()
-                        .unwrap()
-                        .insert(result.target_name.clone(), result.output_path.clone());
-                }
-                results.push(result);
-            }
-            Err(_) => {
-                results.push(CompileResult {
-                    target_name: "unknown".to_string(),
-                    success: false,
-                    output_path: PathBuf::new(),
-                    messages: vec!["[ERROR] Thread panicked unexpectedly!".to_string()],
-                });
-            }
+    /// Waits up to 10s for `path` to appear.
+    #[cfg(unix)]
+    fn wait_for(path: &Path) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !path.exists() {
+            assert!(Instant::now() < deadline, "{} never appeared", path.display());
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Whether any live (non-zombie) process is in process group `pgid`.
+    #[cfg(target_os = "linux")]
+    fn process_group_alive(pgid: &str) -> bool {
+        std::fs::read_dir("/proc").unwrap().flatten().any(|entry| {
+            let stat = std::fs::read_to_string(entry.path().join("stat")).unwrap_or_default();
+            // pid (comm) state ppid pgrp ...; comm may contain spaces
+            let fields: Vec<&str> = stat.rsplit_once(')').map(|(_, rest)| rest.split_whitespace().collect()).unwrap_or_default();
+            fields.len() > 2 && fields[2] == pgid && fields[0] != "Z"
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cancel_kills_the_running_process_group() {
+        let dir = TempDir::new("cancel");
+        // The shell leads its own process group; the background sleep is a grandchild in it
+        let script = "sleep 30 & echo $$ > pgid; wait";
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        for t in [shell_target("slow", script, &[], &dir), shell_target("after", "touch after", &["slow"], &dir)] {
+            project.targets.insert(t.name.clone(), t);
         }
+        let order = dag::build_order(&project).unwrap();
+        let control = Arc::new(BuildControl::new());
+        let (tx, rx) = mpsc::channel();
+
+        let build = {
+            let control = Arc::clone(&control);
+            let options = BuildOptions { quiet: true, jobs: Some(1), ..Default::default() };
+            thread::spawn(move || build_project(&project, &order, &options, Some(tx), Some(control)))
+        };
+        wait_for(&dir.join("pgid"));
+        let pgid = std::fs::read_to_string(dir.join("pgid")).unwrap().trim().to_string();
+        assert!(process_group_alive(&pgid));
+
+        let cancelled_at = Instant::now();
+        control.cancel();
+        let result = build.join().unwrap();
+        assert!(cancelled_at.elapsed() < Duration::from_secs(10), "cancel waited for the command");
+        assert!(!process_group_alive(&pgid), "process group {} survived the cancel", pgid);
+
+        assert!(!result.success);
+        assert_eq!(result.not_attempted, vec!["after".to_string()]);
+        assert!(!dir.join("after").exists());
+        let events: Vec<BuildEvent> = rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(e, BuildEvent::Output { line, .. } if line == "[INFO] Build cancelled by user.")));
+        assert!(events.iter().any(|e| matches!(e, BuildEvent::Finished { stats } if !stats.success)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_paused_build_starts_no_jobs_until_resumed() {
+        let dir = TempDir::new("pause");
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        let first = shell_target("first", "touch first.started; while [ ! -e release ]; do sleep 0.02; done", &[], &dir);
+        for t in [first, shell_target("second", "touch second.started", &["first"], &dir)] {
+            project.targets.insert(t.name.clone(), t);
+        }
+        let order = dag::build_order(&project).unwrap();
+        let control = Arc::new(BuildControl::new());
+
+        let build = {
+            let control = Arc::clone(&control);
+            let options = BuildOptions { quiet: true, jobs: Some(1), ..Default::default() };
+            thread::spawn(move || build_project(&project, &order, &options, None, Some(control)))
+        };
+        wait_for(&dir.join("first.started"));
+        // The running job finishes while paused; the next one must wait
+        control.pause();
+        dir.write("release", "");
+        thread::sleep(Duration::from_millis(500));
+        assert!(!dir.join("second.started").exists(), "a job started while paused");
+        assert!(!build.is_finished());
+
+        control.resume();
+        let result = build.join().unwrap();
+        assert!(result.success);
+        assert_eq!(result.successful_targets, 2);
+        assert!(dir.join("second.started").exists());
+    }
+
+    #[test]
+    fn test_link_pool_caps_running_links() {
+        let limits = LinkLimits { jobs: Some(2), memory: None };
+        assert!(limits.admits(&LinkUsage { running: 1, memory: 0 }, 0));
+        assert!(!limits.admits(&LinkUsage { running: 2, memory: 0 }, 0));
     }
-    results
 }
@@ -0,0 +1,255 @@
+// ============================================================================
+// cache.rs — Content-addressed object cache shared across checkouts (`[cache]`)
+// ============================================================================
+//
+// Object files are stored under a key computed from what the compiler sees,
+// not from file times, so a clean checkout (CI, a teammate's machine) can
+// reuse objects compiled elsewhere:
+//
+//   key = hash(source hash, include closure hash, command hash)
+//
+//   source          contents of the source file
+//   include closure path (relative to the project root) and contents of
+//                   every header the compiler reads (`<compiler> -M`)
+//   command         compiler identity (`--version`) and compile arguments,
+//                   with the object path left out and the project root
+//                   replaced by "." so different checkout paths share keys
+//
+// Objects live in a local directory (<dir>/<key[..2]>/<key>.o). With
+// `remote`, misses are also looked up in an HTTP(S) server (curl GET/PUT of
+// <url>/<key>.o) or an S3 bucket (aws s3 cp), and new objects are uploaded
+// unless `read_only` (e.g. teammates read what CI writes).
+//
+// The hash is SHA-256 (sha256.rs): a crafted source or header can't collide
+// with another entry's key, so a shared cache can't be poisoned that way.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiler;
+use crate::sha256;
+use crate::config::ResolvedTarget;
+
+/// The `[cache]` table of the root build.toml.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CacheConfig {
+    /// Local cache directory, relative to the root build.toml (default: .oximake/cache)
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Shared backend: "https://cache.example.com/ngmake" or "s3://bucket/prefix"
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Only download from `remote`, never upload
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// The object cache of a build.
+#[derive(Debug)]
+pub struct ObjectCache {
+    config: CacheConfig,
+    root: String,
+    dir: PathBuf,
+    /// Compiler program → `--version` output
+    compiler_ids: Mutex<HashMap<String, String>>,
+}
+
+impl ObjectCache {
+    pub fn new(config: &CacheConfig, root_dir: &Path) -> ObjectCache {
+        let dir = match &config.dir {
+            Some(dir) => root_dir.join(dir),
+            None => crate::history::state_dir(root_dir).join("cache"),
+        };
+        let root = root_dir.canonicalize().unwrap_or_else(|_| root_dir.to_path_buf());
+        ObjectCache { config: config.clone(), root: root.to_string_lossy().to_string(), dir, compiler_ids: Mutex::new(HashMap::new()) }
+    }
+
+    /// Cache key of compiling `source` for `target`; None for sources built by a
+    /// `[compilers]` rule or when the compiler can't list the headers. With `debug`,
+    /// the headers found are logged to it as `[DEBUG]` lines.
+    pub fn key(&self, target: &ResolvedTarget, source: &Path, debug: Option<&mut Vec<String>>) -> Option<String> {
+        let extension = source.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        if target.compilers.contains_key(extension) {
+            return None;
+        }
+        let program = target.compiler.command();
+        let mut args = compiler::compile_args(target, source, Path::new("-"));
+        let output = args.iter().position(|a| a == "-o")?;
+        args.drain(output..output + 2);
+
+        // Command: compiler identity and arguments without the object path
+        let mut command = self.compiler_id(program).into_bytes();
+        for arg in &args {
+            command.extend_from_slice(self.relative(arg).as_bytes());
+            command.push(0);
+        }
+
+        // Include closure: every header the compiler reads for this source
+        args[0] = "-M".to_string();
+        let deps = Command::new(program).args(&args).arg("-o").arg("-").output().ok()?;
+        if !deps.status.success() {
+            return None;
+        }
+        let headers: Vec<String> = make_dependencies(&String::from_utf8_lossy(&deps.stdout)).into_iter().skip(1).collect();
+        if let Some(messages) = debug {
+            messages.push(format!("  [DEBUG] depfile of {}: {} headers", source.display(), headers.len()));
+            messages.extend(headers.iter().map(|h| format!("  [DEBUG]   {}", h)));
+        }
+        let mut closure = Vec::new();
+        for header in &headers {
+            // Each part is hashed on its own, so path/contents boundaries can't shift
+            closure.extend_from_slice(sha256::hex(self.relative(header).as_bytes()).as_bytes());
+            closure.extend_from_slice(sha256::file_hex(Path::new(header)).ok()?.as_bytes());
+        }
+
+        let parts = [sha256::file_hex(source).ok()?, sha256::hex(&closure), sha256::hex(&command)];
+        Some(sha256::hex(parts.concat().as_bytes()))
+    }
+
+    /// Copies the object stored under `key` to `obj_path`, from the local directory or
+    /// else the remote backend. Returns where it came from ("local" / "remote").
+    pub fn restore(&self, key: &str, obj_path: &Path) -> Option<&'static str> {
+        if let Some(parent) = obj_path.parent() {
+            std::fs::create_dir_all(parent).ok()?;
+        }
+        let local = self.local_path(key);
+        if std::fs::copy(&local, obj_path).is_ok() {
+            return Some("local");
+        }
+        let remote = self.config.remote.as_ref()?;
+        std::fs::create_dir_all(local.parent()?).ok()?;
+        let download = local.with_extension(format!("o.{}", std::process::id()));
+        let fetched = if let Some(url) = remote.strip_prefix("s3://") {
+            run(Command::new("aws").args(["s3", "cp", "--quiet", &format!("s3://{}/{}.o", url.trim_end_matches('/'), key)]).arg(&download))
+        } else {
+            run(Command::new("curl").args(["-fsS", "-o"]).arg(&download).arg(format!("{}/{}.o", remote.trim_end_matches('/'), key)))
+        };
+        if !fetched || std::fs::rename(&download, &local).is_err() {
+            let _ = std::fs::remove_file(&download);
+            return None;
+        }
+        std::fs::copy(&local, obj_path).ok().map(|_| "remote")
+    }
+
+    /// Stores a freshly compiled object under `key` (and uploads it unless read-only).
+    pub fn store(&self, key: &str, obj_path: &Path) {
+        let local = self.local_path(key);
+        let Some(parent) = local.parent() else { return };
+        // Copy, then rename: concurrent builds never see a partial object
+        let partial = local.with_extension(format!("o.{}", std::process::id()));
+        if std::fs::create_dir_all(parent).is_err()
+            || std::fs::copy(obj_path, &partial).is_err()
+            || std::fs::rename(&partial, &local).is_err()
+        {
+            let _ = std::fs::remove_file(&partial);
+            return;
+        }
+        let Some(remote) = self.config.remote.as_ref().filter(|_| !self.config.read_only) else { return };
+        let uploaded = if let Some(url) = remote.strip_prefix("s3://") {
+            run(Command::new("aws").args(["s3", "cp", "--quiet"]).arg(&local).arg(format!("s3://{}/{}.o", url.trim_end_matches('/'), key)))
+        } else {
+            run(Command::new("curl").args(["-fsS", "-T"]).arg(&local).arg(format!("{}/{}.o", remote.trim_end_matches('/'), key)))
+        };
+        if !uploaded {
+            eprintln!("[WARN] Failed to upload {} to the build cache '{}'", obj_path.display(), remote);
+        }
+    }
+
+    fn local_path(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(format!("{}.o", key))
+    }
+
+    /// `arg` with the project root replaced by ".", so checkouts in different places share keys.
+    fn relative(&self, arg: &str) -> String {
+        arg.replace(&self.root, ".")
+    }
+
+    fn compiler_id(&self, program: &str) -> String {
+        let mut ids = self.compiler_ids.lock().unwrap();
+        ids.entry(program.to_string())
+            .or_insert_with(|| {
+                Command::new(program)
+                    .arg("--version")
+                    .output()
+                    .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+                    .unwrap_or_else(|_| program.to_string())
+            })
+            .clone()
+    }
+}
+
+/// Runs a download/upload command quietly; true on success.
+fn run(cmd: &mut Command) -> bool {
+    cmd.output().is_ok_and(|o| o.status.success())
+}
+
+/// Files of a make rule (`main.o: main.cpp util.h \` ...): the source first, then the headers.
+fn make_dependencies(rule: &str) -> Vec<String> {
+    let rule = rule.replace("\\\r\n", " ").replace("\\\n", " ");
+    let Some((_, files)) = rule.split_once(": ") else { return Vec::new() };
+    let mut deps = Vec::new();
+    let mut current = String::new();
+    let mut chars = files.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&' ') => current.push(chars.next().unwrap_or(' ')),
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    deps.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        deps.push(current);
+    }
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_keys_follow_sources_headers_and_flags() {
+        let dir = TempDir::new("cache");
+        std::fs::create_dir_all(dir.join("include dir")).unwrap();
+        dir.write("include dir/util.h", "int util(void);\n");
+        dir.write("main.c", "#include \"util.h\"\nint main(void) { return util(); }\n");
+        let target = ResolvedTarget {
+            compiler: crate::config::Compiler::Gcc,
+            include_dirs: vec![dir.join("include dir")],
+            ..Default::default()
+        };
+        let cache = ObjectCache::new(&CacheConfig { dir: Some("cache".to_string()), remote: None, read_only: false }, &dir);
+        let source = dir.join("main.c");
+        let key = cache.key(&target, &source, None).unwrap();
+        assert!(key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()), "{}", key);
+        let mut debug = Vec::new();
+        assert_eq!(cache.key(&target, &source, Some(&mut debug)).unwrap(), key);
+        assert!(debug[0].starts_with(&format!("  [DEBUG] depfile of {}: ", source.display())), "{:?}", debug);
+        assert!(debug.iter().any(|line| line.starts_with("  [DEBUG]   ") && line.ends_with("util.h")), "{:?}", debug);
+
+        dir.write("include dir/util.h", "int util(void); /* changed */\n");
+        let header_changed = cache.key(&target, &source, None).unwrap();
+        assert_ne!(header_changed, key);
+        let flags_changed = cache.key(&ResolvedTarget { compiler_flags: vec!["-O2".to_string()], ..target.clone() }, &source, None).unwrap();
+        assert_ne!(flags_changed, header_changed);
+
+        let obj = dir.join("obj/main.o");
+        std::fs::create_dir_all(obj.parent().unwrap()).unwrap();
+        std::fs::write(&obj, "object").unwrap();
+        assert_eq!(cache.restore(&header_changed, &obj), None);
+        cache.store(&header_changed, &obj);
+        std::fs::remove_file(&obj).unwrap();
+        assert_eq!(cache.restore(&header_changed, &obj), Some("local"));
+        assert_eq!(std::fs::read_to_string(&obj).unwrap(), "object");
+    }
+}
@@ -110,25 +110,14 @@ fn parse_args(args_str: &str) -> Vec<String> {
     args
 }
 
-// Resolve CMake variables like ${VAR} or $ENV{VAR}
-fn resolve_variable(var: &str, variables: &HashMap<String, String>) -> Option<String> {
-    if var.starts_with("${") && var.ends_with('}') {
-        let var_name = &var[2..var.len() - 1];
-        variables.get(var_name).cloned()
-    } else if var.starts_with("$ENV{") && var.ends_with('}') {
-        let env_name = &var[5..var.len() - 1];
-        std::env::var(env_name).ok()
-    } else {
-        None
-    }
-}
-
 // Resolve all variables in a string (recursive to handle nested variables)
 fn resolve_variables(text: &str, variables: &HashMap<String, String>) -> String {
     let mut result = text.to_string();
     let mut changed = true;
     let max_iterations = 10; // Prevent infinite loops
     let mut iterations = 0;
+    let var_pattern = regex::Regex::new(r#"\$\{([^}]+)\}"#).ok();
+    let env_pattern = regex::Regex::new(r#"\$ENV\{([^}]+)\}"#).ok();
     
     // Keep resolving until no more variables are found or max iterations reached
     while changed && iterations < max_iterations {
@@ -136,8 +125,7 @@ fn resolve_variables(text: &str, variables: &HashMap<String, String>) -> String
         iterations += 1;
         
         // Replace ${VAR} patterns
-        let var_pattern = regex::Regex::new(r#"\$\{([^}]+)\}"#).ok();
-        if let Some(re) = var_pattern {
+        if let Some(re) = &var_pattern {
             let mut replacements = Vec::new();
             for cap in re.captures_iter(&result) {
                 if let Some(var_name) = cap.get(1) {
@@ -154,8 +142,7 @@ fn resolve_variables(text: &str, variables: &HashMap<String, String>) -> String
         }
         
         // Replace $ENV{VAR} patterns
-        let env_pattern = regex::Regex::new(r#"\$ENV\{([^}]+)\}"#).ok();
-        if let Some(re) = env_pattern {
+        if let Some(re) = &env_pattern {
             let mut replacements = Vec::new();
             let result_snapshot = result.clone();
             for cap in re.captures_iter(&result_snapshot) {
@@ -635,14 +622,13 @@ fn parse_cmake_lists_recursive(
                 }
             }
             
-            let target_type = if lib_type == "static" {
-                "static_lib"
-            } else if lib_type == "shared" {
+            // Unknown library types are treated as static_lib
+            let target_type = if lib_type == "shared" {
                 "shared_lib"
             } else if lib_type == "interface" {
-                "static_lib" // Treat INTERFACE as static_lib with empty sources
+                "interface"
             } else {
-                "static_lib" // Default
+                "static_lib"
             };
             
             // Check if target already exists
@@ -964,12 +950,12 @@ fn convert_to_toml_with_includes(
                     };
                     
                     // Only add if it's a valid file path (contains .cpp, .c, .cc, .cxx, etc.)
-                    if final_source.ends_with(".cpp") || final_source.ends_with(".c") || 
-                       final_source.ends_with(".cc") || final_source.ends_with(".cxx") ||
-                       final_source.ends_with(".C") {
-                        if !valid_sources.contains(&final_source) {
-                            valid_sources.push(final_source);
-                        }
+                    if (final_source.ends_with(".cpp") || final_source.ends_with(".c") ||
+                        final_source.ends_with(".cc") || final_source.ends_with(".cxx") ||
+                        final_source.ends_with(".C"))
+                        && !valid_sources.contains(&final_source)
+                    {
+                        valid_sources.push(final_source);
                     }
                 }
             }
@@ -987,6 +973,7 @@ fn convert_to_toml_with_includes(
             "executable" => "executable",
             "static_lib" => "static_lib",
             "shared_lib" => "shared_lib",
+            "interface" => "interface",
             _ => "static_lib",
         };
         
@@ -1010,11 +997,7 @@ fn convert_to_toml_with_includes(
             .iter()
             .filter_map(|dir| {
                 // Remove "mongo-cxx-driver/" prefix if present
-                let dir_clean = if dir.starts_with("mongo-cxx-driver/") {
-                    &dir[17..]
-                } else {
-                    dir
-                };
+                let dir_clean = dir.strip_prefix("mongo-cxx-driver/").unwrap_or(dir);
                 
                 // Filter out invalid include directories (comments, unresolved variables, etc.)
                 if dir_clean.is_empty() || 
@@ -1034,11 +1017,7 @@ fn convert_to_toml_with_includes(
         
         // Automatically add source directory as include directory
         if let Some(ref source_dir) = target.source_dir {
-            let source_dir_clean = if source_dir.starts_with("mongo-cxx-driver/") {
-                &source_dir[17..]
-            } else {
-                source_dir
-            };
+            let source_dir_clean = source_dir.strip_prefix("mongo-cxx-driver/").unwrap_or(source_dir);
             if !source_dir_clean.is_empty() && !include_dirs_to_add.contains(&source_dir_clean.to_string()) {
                 include_dirs_to_add.push(source_dir_clean.to_string());
             }
@@ -1118,19 +1097,25 @@ fn convert_to_toml_with_includes(
             toml.push_str("]\n");
         }
         
-        // Write flags
-        let mut all_flags = target.flags.clone();
-        all_flags.extend(target.compile_definitions.iter().map(|d| format!("-D{}", d)));
+        // Write preprocessor definitions
+        if !target.compile_definitions.is_empty() {
+            toml.push_str("defines = [\n");
+            for def in &target.compile_definitions {
+                toml.push_str(&format!("    \"{}\",\n", def));
+            }
+            toml.push_str("]\n");
+        }
         
-        if !all_flags.is_empty() {
+        // Write flags
+        if !target.flags.is_empty() {
             toml.push_str("flags = [\n");
-            for flag in &all_flags {
+            for flag in &target.flags {
                 toml.push_str(&format!("    \"{}\",\n", flag));
             }
             toml.push_str("]\n");
         }
         
-        toml.push_str("\n");
+        toml.push('\n');
     }
     
     Ok(toml)
@@ -0,0 +1,151 @@
+// ============================================================================
+// cmake_export.rs — CMake config package for libraries (`cmake_export = true`)
+// ============================================================================
+//
+// Lets CMake projects consume ngmake libraries with `find_package(<name>)`
+// and `target_link_libraries(app PRIVATE <name>::<target>)`, so a mixed
+// codebase can move to ngmake one library at a time. `ngm package` installs
+// three files, named from [project] name:
+//
+//   lib/cmake/<name>/<name>Config.cmake          entry point for find_package
+//   lib/cmake/<name>/<name>ConfigVersion.cmake   version check (same major)
+//   lib/cmake/<name>/<name>Targets.cmake         one IMPORTED target per
+//                                                exported library
+//
+// Paths are relative to the installed file, so the archive or deb/rpm prefix
+// can be anywhere. Each imported target carries include/ as its include
+// directory and links its library deps (exported ones as <name>::<dep>) and
+// its `libs`.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{ResolvedProject, ResolvedTarget, TargetType};
+use crate::package;
+
+/// Targets among `names` that are exported: libraries with `cmake_export = true`.
+pub fn export_targets<'a>(project: &'a ResolvedProject, names: &[String]) -> Vec<&'a ResolvedTarget> {
+    names
+        .iter()
+        .filter_map(|name| project.targets.get(name))
+        .filter(|t| t.cmake_export && matches!(t.target_type, TargetType::StaticLib | TargetType::SharedLib))
+        .collect()
+}
+
+/// Contents of <name>Targets.cmake for the exported `targets`.
+pub fn targets_file(project: &ResolvedProject, targets: &[&ResolvedTarget]) -> String {
+    let mut out = String::from("# Generated by ngmake\n\n");
+    out.push_str("get_filename_component(_IMPORT_PREFIX \"${CMAKE_CURRENT_LIST_DIR}/../../..\" ABSOLUTE)\n");
+    for target in targets {
+        let imported = format!("{}::{}", project.name, target.name);
+        let kind = if target.target_type == TargetType::SharedLib { "SHARED" } else { "STATIC" };
+        let location = package::packaged_path(target).unwrap_or_default();
+        let mut link: Vec<String> = Vec::new();
+        for dep in target.deps.iter().filter_map(|d| project.targets.get(d)) {
+            if !matches!(dep.target_type, TargetType::StaticLib | TargetType::SharedLib) {
+                continue;
+            }
+            if targets.iter().any(|t| t.name == dep.name) {
+                link.push(format!("{}::{}", project.name, dep.name));
+            } else if let Some(path) = package::packaged_path(dep) {
+                link.push(format!("${{_IMPORT_PREFIX}}/{}", path.display()));
+            }
+        }
+        link.extend(target.libs.iter().cloned());
+        out.push_str(&format!("\nif(NOT TARGET {})\n  add_library({} {} IMPORTED)\n", imported, imported, kind));
+        out.push_str(&format!("  set_target_properties({} PROPERTIES\n", imported));
+        out.push_str(&format!("    IMPORTED_LOCATION \"${{_IMPORT_PREFIX}}/{}\"\n", location.display()));
+        out.push_str("    INTERFACE_INCLUDE_DIRECTORIES \"${_IMPORT_PREFIX}/include\"");
+        if !link.is_empty() {
+            out.push_str(&format!("\n    INTERFACE_LINK_LIBRARIES \"{}\"", link.join(";")));
+        }
+        out.push_str(")\nendif()\n");
+    }
+    out.push_str("\nunset(_IMPORT_PREFIX)\n");
+    out
+}
+
+/// Contents of <name>Config.cmake.
+pub fn config_file(project: &ResolvedProject) -> String {
+    format!(
+        "# Generated by ngmake\n\ninclude(\"${{CMAKE_CURRENT_LIST_DIR}}/{}Targets.cmake\")\nset({}_FOUND TRUE)\n",
+        project.name, project.name
+    )
+}
+
+/// Contents of <name>ConfigVersion.cmake: compatible with requests for the same
+/// major version that are not newer than [project] version.
+pub fn version_file(project: &ResolvedProject) -> String {
+    let major = project.version.split('.').next().unwrap_or_default();
+    format!(
+        "# Generated by ngmake\n\n\
+         set(PACKAGE_VERSION \"{version}\")\n\
+         if(PACKAGE_FIND_VERSION VERSION_GREATER PACKAGE_VERSION OR NOT PACKAGE_FIND_VERSION_MAJOR STREQUAL \"{major}\")\n  \
+         set(PACKAGE_VERSION_COMPATIBLE FALSE)\n\
+         else()\n  \
+         set(PACKAGE_VERSION_COMPATIBLE TRUE)\n  \
+         if(PACKAGE_FIND_VERSION STREQUAL PACKAGE_VERSION)\n    \
+         set(PACKAGE_VERSION_EXACT TRUE)\n  \
+         endif()\n\
+         endif()\n",
+        version = project.version,
+        major = major
+    )
+}
+
+/// Writes the CMake package files for the exported targets among `names` into `dir`.
+/// Returns (file, path in the archive layout); empty when nothing is exported.
+pub fn write_installed(project: &ResolvedProject, names: &[String], dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let targets = export_targets(project, names);
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    let installed = Path::new("lib/cmake").join(&project.name);
+    let files = [
+        (format!("{}Config.cmake", project.name), config_file(project)),
+        (format!("{}ConfigVersion.cmake", project.name), version_file(project)),
+        (format!("{}Targets.cmake", project.name), targets_file(project, &targets)),
+    ];
+    let mut written = Vec::new();
+    for (file_name, text) in files {
+        let path = dir.join(&file_name);
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        written.push((path, installed.join(file_name)));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targets_file_imports_exported_libraries() {
+        let mut project = ResolvedProject { name: "demo".to_string(), version: "1.2.3".to_string(), ..Default::default() };
+        let base = ResolvedTarget {
+            name: "base".to_string(),
+            target_type: TargetType::StaticLib,
+            output_dir: PathBuf::from("/w/build"),
+            ..Default::default()
+        };
+        let net = ResolvedTarget {
+            name: "net".to_string(),
+            target_type: TargetType::StaticLib,
+            libs: vec!["ssl".to_string()],
+            deps: vec!["base".to_string()],
+            output_dir: PathBuf::from("/w/build"),
+            cmake_export: true,
+            ..Default::default()
+        };
+        project.targets.insert("base".to_string(), base);
+        project.targets.insert("net".to_string(), net);
+        let targets = export_targets(&project, &["base".to_string(), "net".to_string()]);
+        assert_eq!(targets.len(), 1);
+        let text = targets_file(&project, &targets);
+        assert!(text.contains("add_library(demo::net STATIC IMPORTED)"));
+        assert!(text.contains("IMPORTED_LOCATION \"${_IMPORT_PREFIX}/lib/libnet.a\""));
+        assert!(text.contains("INTERFACE_LINK_LIBRARIES \"${_IMPORT_PREFIX}/lib/libbase.a;ssl\")"));
+        assert!(version_file(&project).contains("NOT PACKAGE_FIND_VERSION_MAJOR STREQUAL \"1\""));
+    }
+}
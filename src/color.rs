@@ -0,0 +1,89 @@
+// ============================================================================
+// color.rs — ANSI colors for terminal output
+// ============================================================================
+//
+// Colors are on when stdout is a terminal, unless NO_COLOR is set
+// (https://no-color.org) or --color=never is given; --color=always forces
+// them (e.g. piping into `less -R`). Until init() runs (GUI, daemon, tests)
+// they are off. Every helper returns the text unchanged when colors are off,
+// so callers never check.
+// ============================================================================
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::options::ColorChoice;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Target name colors, picked by a hash of the name so a target keeps its color.
+const TARGET_COLORS: &[&str] = &["36", "35", "34", "32", "96", "95", "94", "92"];
+
+/// Turns colors on or off for the rest of the process.
+pub fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                && std::env::var("TERM").map_or(true, |t| t != "dumb")
+                && std::io::stdout().is_terminal()
+        }
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn paint(code: &str, text: &str) -> String {
+    if enabled() {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn red(text: &str) -> String {
+    paint("31", text)
+}
+
+pub fn yellow(text: &str) -> String {
+    paint("33", text)
+}
+
+pub fn green(text: &str) -> String {
+    paint("32", text)
+}
+
+pub fn bold(text: &str) -> String {
+    paint("1", text)
+}
+
+pub fn dim(text: &str) -> String {
+    paint("2", text)
+}
+
+/// `name` in the color of that target.
+pub fn target(name: &str) -> String {
+    let hash = name.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    paint(TARGET_COLORS[hash % TARGET_COLORS.len()], name)
+}
+
+/// An output line colored by what it reports: errors and failed tests red, warnings yellow,
+/// skipped and cached jobs and debug details dim.
+pub fn line(text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    if text.contains("[ERROR]") || text.contains("[FAIL]") || text.contains("error:") {
+        red(text)
+    } else if text.contains("[WARN]") || text.contains("warning:") {
+        yellow(text)
+    } else if ["[SKIP]", "[CACHED]", "[DEBUG]"].iter().any(|m| text.contains(m)) {
+        dim(text)
+    } else {
+        text.to_string()
+    }
+}
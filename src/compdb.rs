@@ -0,0 +1,104 @@
+// ============================================================================
+// compdb.rs — compile_commands.json for IDEs and clang tooling
+// ============================================================================
+//
+// Writes the JSON compilation database clangd, clang-tidy and most IDEs read:
+// one entry per source with the exact command the build runs
+// (compiler::compile_command, so [compilers] rules are included):
+//
+//   [
+//     {
+//       "directory": "/work/app",
+//       "file": "src/main.cpp",
+//       "arguments": ["g++", "-c", "src/main.cpp", "-o", "build/obj/app/main.o", ...],
+//       "output": "build/obj/app/main.o"
+//     }
+//   ]
+//
+// "directory" is the working directory ngm compiles in; relative paths in
+// the entries are relative to it. Prebuilt, interface and custom targets
+// have no sources and produce no entries.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiler;
+use crate::config::ResolvedProject;
+
+/// Default file name (clangd looks for it in the source tree's parent directories).
+pub const FILE_NAME: &str = "compile_commands.json";
+
+/// One entry of the database.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CompileCommand {
+    pub directory: String,
+    pub file: String,
+    pub arguments: Vec<String>,
+    pub output: String,
+}
+
+/// The compilation database of every target (by target name, sources in build order).
+pub fn database(project: &ResolvedProject, directory: &Path) -> Vec<CompileCommand> {
+    let mut targets: Vec<_> = project.targets.values().filter(|t| !t.prebuilt).collect();
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut entries = Vec::new();
+    for target in targets {
+        for source in &target.sources {
+            let object = compiler::object_path(target, source);
+            let (program, args) = compiler::compile_command(target, source, &object);
+            entries.push(CompileCommand {
+                directory: directory.to_string_lossy().to_string(),
+                file: source.to_string_lossy().to_string(),
+                arguments: std::iter::once(program).chain(args).collect(),
+                output: object.to_string_lossy().to_string(),
+            });
+        }
+    }
+    entries
+}
+
+/// Writes the compilation database to `out` (parent directories are created).
+/// Returns the number of entries.
+pub fn write(project: &ResolvedProject, out: &Path) -> Result<usize, String> {
+    let directory = std::env::current_dir().map_err(|e| format!("Failed to read the working directory: {}", e))?;
+    let database = database(project, &directory);
+    let text = serde_json::to_string_pretty(&database).map_err(|e| format!("Failed to serialize '{}': {}", out.display(), e))?;
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+    }
+    std::fs::write(out, text + "\n").map_err(|e| format!("Failed to write '{}': {}", out.display(), e))?;
+    Ok(database.len())
+}
+
+/// Default output: compile_commands.json next to the root build.toml.
+pub fn default_path(project: &ResolvedProject) -> PathBuf {
+    project.root_dir.join(FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ResolvedTarget, TargetType};
+
+    #[test]
+    fn test_one_entry_per_source_with_the_build_command() {
+        let mut project = ResolvedProject::default();
+        let target = ResolvedTarget {
+            name: "app".to_string(),
+            target_type: TargetType::Executable,
+            sources: vec![PathBuf::from("src/main.cpp")],
+            include_dirs: vec![PathBuf::from("include")],
+            output_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+        project.targets.insert("app".to_string(), target);
+        let database = database(&project, Path::new("/work"));
+        assert_eq!(database.len(), 1);
+        assert_eq!(database[0].directory, "/work");
+        assert_eq!(database[0].file, "src/main.cpp");
+        let arguments = &database[0].arguments;
+        assert_eq!(&arguments[..6], ["g++", "-c", "src/main.cpp", "-o", "build/obj/app/main.o", "-I"]);
+    }
+}
@@ -12,11 +12,16 @@
 // LD_LIBRARY_PATH is set automatically for shared library resolution.
 // ============================================================================
 
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 use rayon::prelude::*;
-use crate::config::{ResolvedTarget, TargetType};
+use crate::config::{Compiler, Linker, ResolvedProject, ResolvedTarget, TargetType};
 
 // ---------------------------------------------------------------------------
 // Cross-platform path for compiler args: use forward slashes so the compiler
@@ -28,14 +33,25 @@ use crate::config::{ResolvedTarget, TargetType};
 // ---------------------------------------------------------------------------
 fn path_arg(p: &Path) -> String {
     let s = p.to_string_lossy().to_string();
-    let s = if s.starts_with(r"\\?\") { s[4..].to_string() } else { s };
+    let s = s.strip_prefix(r"\\?\").map(str::to_string).unwrap_or(s);
     s.replace('\\', "/")
 }
 
-/// Shared library filename: lib{name}.so on Unix, lib{name}.dll on Windows.
-fn shared_lib_filename(name: &str) -> String {
-    let ext = if cfg!(windows) { "dll" } else { "so" };
-    format!("lib{}.{}", name, ext)
+/// Path of the file a target produces. The base name is `output_name` (default: the
+/// target name). Libraries get `lib_prefix` (default "lib") and `lib_suffix`
+/// (default ".a", or ".so" / ".dll" for shared libraries): lib{name}.a, lib{name}.so.
+pub fn output_path(target: &ResolvedTarget) -> PathBuf {
+    let base = target.output_name.as_deref().unwrap_or(&target.name);
+    let default_suffix = match target.target_type {
+        TargetType::Executable | TargetType::Benchmark | TargetType::Test => return target.output_dir.join(base),
+        TargetType::StaticLib => ".a",
+        TargetType::SharedLib if cfg!(windows) => ".dll",
+        TargetType::SharedLib => ".so",
+        TargetType::Interface | TargetType::Custom | TargetType::Group => return PathBuf::new(),
+    };
+    let prefix = target.lib_prefix.as_deref().unwrap_or("lib");
+    let suffix = target.lib_suffix.as_deref().unwrap_or(default_suffix);
+    target.output_dir.join(format!("{}{}{}", prefix, base, suffix))
 }
 
 // ---------------------------------------------------------------------------
@@ -68,18 +84,8 @@ pub fn build_target(
         target.name, target.target_type
     ));
 
-    // Skip targets with no sources (INTERFACE libraries, etc.)
-    if target.sources.is_empty() {
-        messages.push(format!(
-            "[SKIP] Target '{}' has no sources (INTERFACE library or empty target). Skipping build.",
-            target.name
-        ));
-        return CompileResult {
-            target_name: target.name.clone(),
-            success: true, // Mark as successful since we're intentionally skipping
-            output_path: PathBuf::new(),
-            messages,
-        };
+    if let Some(result) = build_without_sources(target, &mut messages) {
+        return result;
     }
 
     // Create output directory
@@ -99,27 +105,10 @@ pub fn build_target(
     let mut to_compile: Vec<(PathBuf, PathBuf)> = Vec::new();
 
     for source in &target.sources {
-        let obj_name = source
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string()
-            + ".o";
-        let obj_path = obj_dir.join(&obj_name);
+        let obj_path = object_path(target, source);
         object_files.push(obj_path.clone());
 
-        let should_compile = if obj_path.exists() {
-            let source_mtime = std::fs::metadata(source).and_then(|m| m.modified()).ok();
-            let obj_mtime = std::fs::metadata(&obj_path).and_then(|m| m.modified()).ok();
-            match (source_mtime, obj_mtime) {
-                (Some(src_time), Some(obj_time)) => src_time > obj_time,
-                _ => true,
-            }
-        } else {
-            true
-        };
-
-        if !should_compile {
+        if object_up_to_date(source, &obj_path) {
             messages.push(format!("  [SKIP] {} (up-to-date)", source.display()));
             continue;
         }
@@ -154,55 +143,10 @@ pub fn build_target(
     let deps_for_link = link_deps.unwrap_or(&target.deps);
     
     // Determine final output path
-    let final_output_path = match target.target_type {
-        TargetType::Executable => target.output_dir.join(&target.name),
-        TargetType::StaticLib => {
-            let lib_name = format!("lib{}.a", target.name);
-            target.output_dir.join(&lib_name)
-        }
-        TargetType::SharedLib => {
-            let lib_name = shared_lib_filename(&target.name);
-            target.output_dir.join(&lib_name)
-        }
-    };
+    let final_output_path = output_path(target);
 
     // Check if we need to relink (incremental build)
-    let needs_relink = if final_output_path.exists() {
-        // Check if any object file is newer than the output
-        let output_mtime = std::fs::metadata(&final_output_path)
-            .and_then(|m| m.modified())
-            .ok();
-        
-        if let Some(out_time) = output_mtime {
-            // Check if any object file is newer
-            let obj_newer = object_files.iter().any(|obj| {
-                std::fs::metadata(obj)
-                    .and_then(|m| m.modified())
-                    .map(|t| t > out_time)
-                    .unwrap_or(false)
-            });
-            
-            // Also check if any dependency library is newer
-            let dep_newer = deps_for_link.iter().any(|dep_name| {
-                if let Some(dep_path) = built_targets.get(dep_name) {
-                    std::fs::metadata(dep_path)
-                        .and_then(|m| m.modified())
-                        .map(|t| t > out_time)
-                        .unwrap_or(false)
-                } else {
-                    false
-                }
-            });
-            
-            needs_rebuild || obj_newer || dep_newer
-        } else {
-            // Can't get mtime, always relink to be safe
-            true
-        }
-    } else {
-        // Output doesn't exist, must link
-        true
-    };
+    let needs_relink = needs_rebuild || link_dirty(&final_output_path, &link_stamp_path(target), &object_files, deps_for_link, built_targets).is_some();
 
     let final_output = if !needs_relink {
         messages.push(format!(
@@ -211,11 +155,12 @@ pub fn build_target(
         ));
         Ok(final_output_path)
     } else {
-        match target.target_type {
-            TargetType::Executable => link_executable(target, &object_files, built_targets, deps_for_link, &mut messages),
-            TargetType::StaticLib => create_static_lib(target, &object_files, &mut messages),
-            TargetType::SharedLib => link_shared_lib(target, &object_files, built_targets, deps_for_link, &mut messages),
-        }
+        link_with_restat(target, &final_output_path, &mut messages, |messages| match target.target_type {
+            TargetType::Executable | TargetType::Benchmark | TargetType::Test => link_executable(target, &object_files, built_targets, deps_for_link, messages),
+            TargetType::StaticLib => create_static_lib(target, &object_files, messages),
+            TargetType::SharedLib => link_shared_lib(target, &object_files, built_targets, deps_for_link, messages),
+            TargetType::Interface | TargetType::Custom | TargetType::Group => Ok(PathBuf::new()),
+        })
     };
 
     match final_output {
@@ -244,6 +189,84 @@ pub fn build_target(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Incremental checks: why an object, a link output or custom target outputs
+// have to be rebuilt (None = up to date). `ngm explain` prints these reasons.
+// ---------------------------------------------------------------------------
+/// Reason a file is rebuilt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dirty {
+    /// The file doesn't exist (for custom targets: also a missing input)
+    Missing(PathBuf),
+    /// `input` was modified after `output`
+    Newer { input: PathBuf, output: PathBuf },
+    /// The dependency's output changed after the target was linked
+    DependencyRelinked(String),
+    /// The modification time of the file can't be read
+    UnknownTime(PathBuf),
+    /// Custom target without declared outputs: its commands always run
+    NoOutputs,
+}
+
+impl std::fmt::Display for Dirty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dirty::Missing(path) => write!(f, "{} does not exist", path.display()),
+            Dirty::Newer { input, output } => write!(f, "{} is newer than {}", input.display(), output.display()),
+            Dirty::DependencyRelinked(dep) => write!(f, "output of dependency '{}' changed", dep),
+            Dirty::UnknownTime(path) => write!(f, "modification time of {} is unknown", path.display()),
+            Dirty::NoOutputs => write!(f, "custom target without outputs (always runs)"),
+        }
+    }
+}
+
+fn mtime(path: &Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Object exists and is not older than its source.
+pub fn object_up_to_date(source: &Path, obj_path: &Path) -> bool {
+    object_dirty(source, obj_path).is_none()
+}
+
+/// Why `source` has to be compiled into `obj_path` again.
+pub fn object_dirty(source: &Path, obj_path: &Path) -> Option<Dirty> {
+    if !obj_path.exists() {
+        return Some(Dirty::Missing(obj_path.to_path_buf()));
+    }
+    match (mtime(source), mtime(obj_path)) {
+        (Some(src_time), Some(obj_time)) if src_time <= obj_time => None,
+        (Some(_), Some(_)) => Some(Dirty::Newer { input: source.to_path_buf(), output: obj_path.to_path_buf() }),
+        (None, _) => Some(Dirty::UnknownTime(source.to_path_buf())),
+        (_, None) => Some(Dirty::UnknownTime(obj_path.to_path_buf())),
+    }
+}
+
+/// Why `output` has to be linked again: it is missing, an object is newer, or the output
+/// of one of `deps` (looked up in `built_targets`) is newer. The output counts as linked
+/// when `stamp` was written if that is later (see link_with_restat).
+pub fn link_dirty(
+    output: &Path,
+    stamp: &Path,
+    object_files: &[PathBuf],
+    deps: &[String],
+    built_targets: &std::collections::HashMap<String, PathBuf>,
+) -> Option<Dirty> {
+    if !output.exists() {
+        return Some(Dirty::Missing(output.to_path_buf()));
+    }
+    let Some(out_time) = mtime(output) else {
+        return Some(Dirty::UnknownTime(output.to_path_buf()));
+    };
+    let out_time = mtime(stamp).map_or(out_time, |t| t.max(out_time));
+    if let Some(obj) = object_files.iter().find(|obj| mtime(obj).is_some_and(|t| t > out_time)) {
+        return Some(Dirty::Newer { input: obj.clone(), output: output.to_path_buf() });
+    }
+    deps.iter()
+        .find(|dep| built_targets.get(*dep).and_then(|p| mtime(p)).is_some_and(|t| t > out_time))
+        .map(|dep| Dirty::DependencyRelinked(dep.clone()))
+}
+
 // ---------------------------------------------------------------------------
 // Compile one source or skip if up-to-date (for Ninja-style single-job use)
 // ---------------------------------------------------------------------------
@@ -255,17 +278,7 @@ pub fn compile_one_source_or_skip(
     if let Some(parent) = obj_path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    let should_compile = if obj_path.exists() {
-        let source_mtime = std::fs::metadata(source).and_then(|m| m.modified()).ok();
-        let obj_mtime = std::fs::metadata(obj_path).and_then(|m| m.modified()).ok();
-        match (source_mtime, obj_mtime) {
-            (Some(src_time), Some(obj_time)) => src_time > obj_time,
-            _ => true,
-        }
-    } else {
-        true
-    };
-    if !should_compile {
+    if object_up_to_date(source, obj_path) {
         let mut msgs = Vec::new();
         msgs.push(format!("  [SKIP] {} (up-to-date)", source.display()));
         return Ok((obj_path.to_path_buf(), msgs));
@@ -273,6 +286,99 @@ pub fn compile_one_source_or_skip(
     compile_one_source(target, source, obj_path)
 }
 
+// ---------------------------------------------------------------------------
+// Object file path and compile arguments for one source
+// ---------------------------------------------------------------------------
+/// Object file for `source`: `{output_dir}/obj/{target}/{stem}.o`.
+pub fn object_path(target: &ResolvedTarget, source: &Path) -> PathBuf {
+    let obj_name = source
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+        + ".o";
+    target.output_dir.join("obj").join(&target.name).join(obj_name)
+}
+
+/// `--force-rebuild`: removes the target's objects (`{output_dir}/obj/{target}/`), its
+/// output and split debug info, or a custom target's outputs, so the next build recompiles
+/// and relinks it. Dependents relink because the new output is newer. Returns what was removed.
+pub fn invalidate_target(target: &ResolvedTarget) -> Result<Vec<PathBuf>, String> {
+    if target.prebuilt {
+        return Err(format!("'{}' is a prebuilt [dependencies] library and is never built", target.name));
+    }
+    let obj_dir = target.output_dir.join("obj").join(&target.name);
+    let mut files = target.outputs.clone();
+    files.extend(Some(output_path(target)).filter(|p| !p.as_os_str().is_empty()));
+    files.extend(debug_info_path(target));
+    let mut removed = Vec::new();
+    for path in std::iter::once(obj_dir).chain(files) {
+        let result = match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(&path),
+            Ok(_) => std::fs::remove_file(&path),
+            Err(_) => continue,
+        };
+        result.map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+/// Program and arguments that compile `source` into `obj_path`: the `[compilers]` rule
+/// for the source's extension if there is one, else the target's compiler.
+pub fn compile_command(target: &ResolvedTarget, source: &Path, obj_path: &Path) -> (String, Vec<String>) {
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let Some(rule) = target.compilers.get(extension) else {
+        return (target.compiler.command().to_string(), compile_args(target, source, obj_path));
+    };
+    let mut args = Vec::new();
+    for arg in &rule.args {
+        match arg.as_str() {
+            "{include_dirs}" => args.extend(target.include_dirs.iter().map(|d| format!("-I{}", path_arg(d)))),
+            "{defines}" => args.extend(target.defines.iter().map(|d| format!("-D{}", d))),
+            "{flags}" => args.extend(target.compiler_flags.iter().chain(&target.flags).cloned()),
+            _ => args.push(arg.replace("{source}", &path_arg(source)).replace("{object}", &path_arg(obj_path))),
+        }
+    }
+    (rule.command.clone(), args)
+}
+
+/// Compiler arguments (without the compiler itself) used to compile `source` into `obj_path`.
+/// Shared by the build and by tools that replay the exact compile command (e.g. iwyu).
+pub fn compile_args(target: &ResolvedTarget, source: &Path, obj_path: &Path) -> Vec<String> {
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let mut args = vec!["-c".to_string()];
+    // Objective-C sources: name the language explicitly so every driver (gcc, g++, clang) agrees
+    match extension {
+        "m" => args.extend(["-x".to_string(), "objective-c".to_string()]),
+        "mm" => args.extend(["-x".to_string(), "objective-c++".to_string()]),
+        _ => {}
+    }
+    args.extend([path_arg(source), "-o".to_string(), path_arg(obj_path)]);
+    if target.target_type == TargetType::SharedLib {
+        args.push("-fPIC".to_string());
+    }
+    for include_dir in &target.include_dirs {
+        args.push("-I".to_string());
+        args.push(path_arg(include_dir));
+    }
+    let is_c = matches!(extension, "c" | "m");
+    let standard = if is_c { target.c_standard } else { target.cxx_standard };
+    if let Some(std) = standard {
+        args.push(std.flag(!is_c));
+    }
+    if target.objc_arc && matches!(extension, "m" | "mm") {
+        args.push("-fobjc-arc".to_string());
+    }
+    if cfg!(windows) && target.split_debug && target.compiler == Compiler::Clang {
+        args.push("-gcodeview".to_string());
+    }
+    args.extend(target.defines.iter().map(|d| format!("-D{}", d)));
+    args.extend(target.compiler_flags.iter().cloned());
+    args.extend(target.flags.iter().cloned());
+    args
+}
+
 // ---------------------------------------------------------------------------
 // Compile a single source file (used for parallel compilation)
 // ---------------------------------------------------------------------------
@@ -288,33 +394,49 @@ fn compile_one_source(
         obj_path.display()
     ));
 
-    let mut cmd = Command::new(target.compiler.command());
-    cmd.arg("-c");
-    cmd.arg(path_arg(source));
-    cmd.arg("-o").arg(path_arg(obj_path));
-    if target.target_type == TargetType::SharedLib {
-        cmd.arg("-fPIC");
-    }
-    for include_dir in &target.include_dirs {
-        cmd.arg("-I").arg(path_arg(include_dir));
-    }
-    if let Some(std) = target.cxx_standard {
-        cmd.arg(format!("-std=c++{}", std));
-    }
-    for flag in &target.compiler_flags {
-        cmd.arg(flag);
-    }
-    for flag in &target.flags {
-        cmd.arg(flag);
-    }
+    let (program, args) = compile_command(target, source, obj_path);
     let ld_path = build_ld_library_path(&target.lib_dirs);
-    if !ld_path.is_empty() {
-        cmd.env("LD_LIBRARY_PATH", &ld_path);
-    }
+    let command = |launcher: Option<&String>| {
+        let mut cmd = match launcher {
+            Some(launcher) => {
+                let mut cmd = Command::new(launcher);
+                cmd.arg(&program);
+                cmd
+            }
+            None => Command::new(&program),
+        };
+        cmd.args(&args);
+        if !ld_path.is_empty() {
+            cmd.env("LD_LIBRARY_PATH", &ld_path);
+        }
+        cmd
+    };
 
+    // The launcher only wraps the target's compiler, not [compilers] rules
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let launcher = target.compiler_launcher.as_ref().filter(|_| !target.compilers.contains_key(extension));
+    let mut cmd = command(launcher);
     msgs.push(format!("    Command: {:?}", cmd));
+    let mut result = run_tracked(cmd, target.timeout());
+    if let Some(launcher) = launcher {
+        // The launcher itself failed (not installed, distcc's own exit codes 100-128): compile locally
+        let failed = match &result {
+            Ok(output) => output.status.code().is_some_and(|code| (100..=128).contains(&code)),
+            Err(e) => e.kind() != std::io::ErrorKind::TimedOut && e.kind() != std::io::ErrorKind::Other,
+        };
+        if failed {
+            let reason = match &result {
+                Ok(output) => format!("exit {:?}", output.status.code()),
+                Err(e) => e.to_string(),
+            };
+            msgs.push(format!("  [WARN] {} failed ({}); compiling locally", launcher, reason));
+            cmd = command(None);
+            msgs.push(format!("    Command: {:?}", cmd));
+            result = run_tracked(cmd, target.timeout());
+        }
+    }
 
-    match cmd.output() {
+    match result {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -331,7 +453,7 @@ fn compile_one_source(
                     output.status.code()
                 );
                 if !stderr.is_empty() {
-                    err.push_str("\n");
+                    err.push('\n');
                     err.push_str(stderr.trim());
                 }
                 if !stdout.is_empty() {
@@ -346,6 +468,198 @@ fn compile_one_source(
     }
 }
 
+/// Workers to use while `[project] compiler_launcher` distributes compiles: `launcher_jobs`,
+/// else for distcc the slots of its hosts (`distcc -j`). Outside pump mode (`pump ngm`)
+/// every job is preprocessed locally, so distcc gets at most two jobs per local CPU.
+pub fn launcher_jobs(project: &ResolvedProject) -> Option<usize> {
+    if project.launcher_jobs.is_some() {
+        return project.launcher_jobs;
+    }
+    let launcher = project.compiler_launcher.as_ref()?;
+    if Path::new(launcher).file_stem().is_none_or(|stem| stem != "distcc") {
+        return None;
+    }
+    let output = Command::new(launcher).arg("-j").output().ok()?;
+    let slots: usize = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    let pump = std::env::var_os("INCLUDE_SERVER_PORT").is_some();
+    let local = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    Some(if pump { slots } else { slots.min(2 * local) })
+}
+
+/// Builds targets that don't compile sources: interface libraries and groups are
+/// skipped (successfully; a group's deps were built before it), prebuilt libraries are used as they are, custom targets run
+/// their commands, and any other target without sources is an error. None for
+/// targets that compile sources.
+fn build_without_sources(target: &ResolvedTarget, messages: &mut Vec<String>) -> Option<CompileResult> {
+    let mut output_path = PathBuf::new();
+    let success = if target.target_type == TargetType::Interface {
+        messages.push(format!("[SKIP] Target '{}' is an interface library (nothing to build)", target.name));
+        true
+    } else if target.target_type == TargetType::Group {
+        messages.push(format!("  [OK] Group '{}' → {}", target.name, target.deps.join(", ")));
+        true
+    } else if target.prebuilt {
+        let file = self::output_path(target);
+        if file.is_file() {
+            messages.push(format!("[SKIP] Target '{}' is prebuilt ({})", target.name, file.display()));
+            output_path = file;
+            true
+        } else {
+            messages.push(format!("  [ERROR] Prebuilt library of '{}' not found: {}", target.name, file.display()));
+            false
+        }
+    } else if target.target_type == TargetType::Custom {
+        match run_custom_commands(target, messages) {
+            Ok(path) => {
+                output_path = path;
+                true
+            }
+            Err(e) => {
+                messages.push(format!("  [ERROR] {}", e));
+                false
+            }
+        }
+    } else if target.sources.is_empty() {
+        messages.push(format!(
+            "  [ERROR] Target '{}' has no sources (use type = \"interface\" for a header-only library)",
+            target.name
+        ));
+        false
+    } else {
+        return None;
+    };
+    Some(CompileResult {
+        target_name: target.name.clone(),
+        success,
+        output_path,
+        messages: std::mem::take(messages),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Custom targets: run the commands unless every output is newer than every input
+// ---------------------------------------------------------------------------
+fn run_custom_commands(target: &ResolvedTarget, messages: &mut Vec<String>) -> Result<PathBuf, String> {
+    let first_output = target.outputs.first().cloned().unwrap_or_default();
+    if outputs_dirty(&target.inputs, &target.outputs).is_none() {
+        messages.push(format!("  [SKIP] Custom target '{}' (up-to-date)", target.name));
+        return Ok(first_output);
+    }
+    for output in &target.outputs {
+        if let Some(parent) = output.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+    }
+    for args in &target.commands {
+        let Some((program, rest)) = args.split_first() else { continue };
+        let shown = match args.as_slice() {
+            [_, flag, command] if flag == "-c" || flag == "/C" => command.clone(),
+            _ => args.join(" "),
+        };
+        messages.push(format!("  [CUSTOM] {}", shown));
+        let mut cmd = Command::new(program);
+        cmd.args(rest).current_dir(&target.working_dir);
+        run_command(cmd, target.timeout(), messages)?;
+    }
+    if let Some(missing) = target.outputs.iter().find(|o| !o.exists()) {
+        return Err(format!("Custom target '{}' did not create declared output {}", target.name, missing.display()));
+    }
+    Ok(first_output)
+}
+
+/// Why a custom target's commands run: up to date (None) if there are outputs, all
+/// exist, and none is older than any input. A missing input counts as changed.
+pub fn outputs_dirty(inputs: &[PathBuf], outputs: &[PathBuf]) -> Option<Dirty> {
+    if outputs.is_empty() {
+        return Some(Dirty::NoOutputs);
+    }
+    if let Some(missing) = outputs.iter().find(|o| mtime(o).is_none()) {
+        return Some(Dirty::Missing(missing.clone()));
+    }
+    let oldest = outputs.iter().min_by_key(|o| mtime(o))?;
+    let oldest_time = mtime(oldest)?;
+    inputs.iter().find_map(|input| match mtime(input) {
+        None => Some(Dirty::Missing(input.clone())),
+        Some(t) if t > oldest_time => Some(Dirty::Newer { input: input.clone(), output: oldest.clone() }),
+        Some(_) => None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Restat: keep byte-identical libraries (like Ninja's `restat = 1`)
+// ---------------------------------------------------------------------------
+// Dependents relink when a library's modification time is newer than their
+// own output. A library rebuilt with identical contents (a comment-only edit,
+// a deterministic archive of the same objects) would still cascade relinks,
+// so the previous file is moved aside before linking and put back when the
+// new one is byte-identical, keeping its old time. The library itself then
+// looks older than its objects; link_stamp_path records when it was last
+// checked so it isn't relinked on every build. Thin archives only hold
+// member paths and aren't compared: their bytes can stay the same while the
+// objects change.
+/// Written when a relink left the output unchanged; link_dirty treats it as the link time.
+pub fn link_stamp_path(target: &ResolvedTarget) -> PathBuf {
+    target.output_dir.join("obj").join(&target.name).join("link.stamp")
+}
+
+fn link_with_restat(
+    target: &ResolvedTarget,
+    output: &Path,
+    messages: &mut Vec<String>,
+    link: impl FnOnce(&mut Vec<String>) -> Result<PathBuf, String>,
+) -> Result<PathBuf, String> {
+    let restat = match target.target_type {
+        TargetType::StaticLib => !target.thin_archive,
+        TargetType::SharedLib => true,
+        _ => false,
+    };
+    let mut previous_name = output.file_name().unwrap_or_default().to_os_string();
+    previous_name.push(".prev");
+    let previous = output.with_file_name(previous_name);
+    // A file that can't be moved (e.g. a DLL in use) is just linked over
+    let kept = restat && output.is_file() && std::fs::rename(output, &previous).is_ok();
+    let result = link(messages);
+    if !kept {
+        return result;
+    }
+    match &result {
+        Ok(path) if same_contents(path, &previous) => {
+            std::fs::rename(&previous, path).map_err(|e| format!("Failed to restore '{}': {}", path.display(), e))?;
+            let stamp = link_stamp_path(target);
+            if let Some(dir) = stamp.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            std::fs::write(&stamp, "").map_err(|e| format!("Failed to write '{}': {}", stamp.display(), e))?;
+            messages.push(format!("  [RESTAT] {} is unchanged; dependents are not relinked", path.display()));
+        }
+        _ => {
+            let _ = std::fs::remove_file(&previous);
+        }
+    }
+    result
+}
+
+/// Both files exist and have the same bytes.
+fn same_contents(a: &Path, b: &Path) -> bool {
+    use std::io::Read;
+    let (Ok(meta_a), Ok(meta_b)) = (std::fs::metadata(a), std::fs::metadata(b)) else { return false };
+    if meta_a.len() != meta_b.len() {
+        return false;
+    }
+    let (Ok(file_a), Ok(file_b)) = (std::fs::File::open(a), std::fs::File::open(b)) else { return false };
+    let (mut file_a, mut file_b) = (std::io::BufReader::new(file_a), std::io::BufReader::new(file_b));
+    let (mut buf_a, mut buf_b) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let Ok(n) = file_a.read(&mut buf_a) else { return false };
+        if n == 0 {
+            return true;
+        }
+        if file_b.read_exact(&mut buf_b[..n]).is_err() || buf_a[..n] != buf_b[..n] {
+            return false;
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Run only the link step (for Ninja-style: link job after all compiles for target)
 // ---------------------------------------------------------------------------
@@ -356,62 +670,25 @@ pub fn run_link_step(
     link_deps: Option<&[String]>,
 ) -> CompileResult {
     let mut messages = Vec::new();
-    if object_files.is_empty() && target.sources.is_empty() {
-        messages.push(format!("[SKIP] Target '{}' has no sources. Skipping build.", target.name));
-        return CompileResult {
-            target_name: target.name.clone(),
-            success: true,
-            output_path: PathBuf::new(),
-            messages,
-        };
+    if let Some(result) = build_without_sources(target, &mut messages) {
+        return result;
     }
     let deps_for_link = link_deps.unwrap_or(&target.deps);
-    let final_output_path = match target.target_type {
-        TargetType::Executable => target.output_dir.join(&target.name),
-        TargetType::StaticLib => {
-            let lib_name = format!("lib{}.a", target.name);
-            target.output_dir.join(&lib_name)
-        }
-        TargetType::SharedLib => {
-            let lib_name = shared_lib_filename(&target.name);
-            target.output_dir.join(&lib_name)
-        }
-    };
+    let final_output_path = output_path(target);
     if let Some(parent) = final_output_path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
-    let needs_relink = if final_output_path.exists() {
-        let output_mtime = std::fs::metadata(&final_output_path).and_then(|m| m.modified()).ok();
-        if let Some(out_time) = output_mtime {
-            let obj_newer = object_files.iter().any(|obj| {
-                std::fs::metadata(obj)
-                    .and_then(|m| m.modified())
-                    .map(|t| t > out_time)
-                    .unwrap_or(false)
-            });
-            let dep_newer = deps_for_link.iter().any(|dep_name| {
-                built_targets
-                    .get(dep_name)
-                    .and_then(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
-                    .map(|t| t > out_time)
-                    .unwrap_or(false)
-            });
-            obj_newer || dep_newer
-        } else {
-            true
-        }
-    } else {
-        true
-    };
+    let needs_relink = link_dirty(&final_output_path, &link_stamp_path(target), object_files, deps_for_link, built_targets).is_some();
     let final_output = if !needs_relink {
         messages.push(format!("  [SKIP] Linking '{}' (up-to-date)", target.name));
         Ok(final_output_path)
     } else {
-        match target.target_type {
-            TargetType::Executable => link_executable(target, object_files, built_targets, deps_for_link, &mut messages),
-            TargetType::StaticLib => create_static_lib(target, object_files, &mut messages),
-            TargetType::SharedLib => link_shared_lib(target, object_files, built_targets, deps_for_link, &mut messages),
-        }
+        link_with_restat(target, &final_output_path, &mut messages, |messages| match target.target_type {
+            TargetType::Executable | TargetType::Benchmark | TargetType::Test => link_executable(target, object_files, built_targets, deps_for_link, messages),
+            TargetType::StaticLib => create_static_lib(target, object_files, messages),
+            TargetType::SharedLib => link_shared_lib(target, object_files, built_targets, deps_for_link, messages),
+            TargetType::Interface | TargetType::Custom | TargetType::Group => Ok(PathBuf::new()),
+        })
     };
     match final_output {
         Ok(path) => {
@@ -445,7 +722,7 @@ fn link_executable(
     dep_names: &[String],
     messages: &mut Vec<String>,
 ) -> Result<PathBuf, String> {
-    let output_path = target.output_dir.join(&target.name);
+    let output_path = output_path(target);
 
     messages.push(format!(
         "  [LINK] Creating executable: {}",
@@ -475,6 +752,18 @@ fn link_executable(
         cmd.arg("-l").arg(lib);
     }
 
+    // macOS frameworks (-framework)
+    for framework in &target.frameworks {
+        cmd.arg("-framework").arg(framework);
+    }
+
+    // Selected linker (-fuse-ld)
+    if let Some(linker) = &target.linker {
+        cmd.arg(linker.flag());
+    }
+
+    add_pdb_link_args(&mut cmd, target);
+
     // Linker flags only (compiler flags are not passed to link)
     for flag in &target.linker_flags {
         cmd.arg(flag);
@@ -486,7 +775,8 @@ fn link_executable(
         cmd.env("LD_LIBRARY_PATH", &ld_path);
     }
 
-    run_command(cmd, messages)?;
+    run_command(cmd, target.timeout(), messages)?;
+    strip_debug_info(target, &output_path, messages)?;
     Ok(output_path)
 }
 
@@ -498,25 +788,42 @@ fn create_static_lib(
     object_files: &[PathBuf],
     messages: &mut Vec<String>,
 ) -> Result<PathBuf, String> {
-    let lib_name = format!("lib{}.a", target.name);
-    let output_path = target.output_dir.join(&lib_name);
+    let output_path = output_path(target);
 
     messages.push(format!(
         "  [ARCHIVE] Creating static library: {}",
         output_path.display()
     ));
 
-    // Archive with ar
+    // ar only adds and replaces members: start over so objects of removed
+    // sources don't linger, and so switching to/from a thin archive works.
+    let _ = std::fs::remove_file(&output_path);
+
+    run_command(archive_command(target, &output_path, object_files), target.timeout(), messages)?;
+    Ok(output_path)
+}
+
+/// `ar` command archiving `object_files` into `output_path`: deterministic (zero
+/// timestamps/uids/modes), thin if requested. Apple's ar has no D modifier and
+/// reads ZERO_AR_DATE instead.
+fn archive_command(target: &ResolvedTarget, output_path: &Path, object_files: &[PathBuf]) -> Command {
     let mut cmd = Command::new("ar");
-    cmd.arg("rcs");
-    cmd.arg(path_arg(&output_path));
+    let mut modifiers = String::from("rcs");
+    if cfg!(target_os = "macos") {
+        cmd.env("ZERO_AR_DATE", "1");
+    } else {
+        modifiers.push('D');
+    }
+    if target.thin_archive {
+        modifiers.push('T');
+    }
+    cmd.arg(modifiers);
+    cmd.arg(path_arg(output_path));
 
     for obj in object_files {
         cmd.arg(path_arg(obj));
     }
-
-    run_command(cmd, messages)?;
-    Ok(output_path)
+    cmd
 }
 
 // ---------------------------------------------------------------------------
@@ -529,8 +836,7 @@ fn link_shared_lib(
     dep_names: &[String],
     messages: &mut Vec<String>,
 ) -> Result<PathBuf, String> {
-    let lib_name = shared_lib_filename(&target.name);
-    let output_path = target.output_dir.join(&lib_name);
+    let output_path = output_path(target);
 
     messages.push(format!(
         "  [LINK] Creating shared library: {}",
@@ -561,6 +867,18 @@ fn link_shared_lib(
         cmd.arg("-l").arg(lib);
     }
 
+    // macOS frameworks (-framework)
+    for framework in &target.frameworks {
+        cmd.arg("-framework").arg(framework);
+    }
+
+    // Selected linker (-fuse-ld)
+    if let Some(linker) = &target.linker {
+        cmd.arg(linker.flag());
+    }
+
+    add_pdb_link_args(&mut cmd, target);
+
     // Linker flags only
     for flag in &target.linker_flags {
         cmd.arg(flag);
@@ -572,10 +890,112 @@ fn link_shared_lib(
         cmd.env("LD_LIBRARY_PATH", &ld_path);
     }
 
-    run_command(cmd, messages)?;
+    run_command(cmd, target.timeout(), messages)?;
+    strip_debug_info(target, &output_path, messages)?;
     Ok(output_path)
 }
 
+// ---------------------------------------------------------------------------
+// Strip / split debug info of a linked executable or shared library
+// ---------------------------------------------------------------------------
+// split_debug: the debug info moves to <output>.debug (objcopy
+// --only-keep-debug) and the output gets a GNU debuglink to it, which gdb and
+// lldb follow. On macOS dsymutil writes <output>.dSYM instead.
+// strip: the output loses its symbol table too (local symbols only for
+// shared libraries, whose exports must stay).
+// Windows + clang: objects get CodeView debug info (-gcodeview) and lld
+// writes <output stem>.pdb while linking. Each target has its own PDB and
+// every object carries its own debug info, so parallel compiles and links
+// never write to a shared PDB.
+/// Separate debug info file (`split_debug = true`) of an executable or shared library.
+pub fn debug_info_path(target: &ResolvedTarget) -> Option<PathBuf> {
+    if !target.split_debug || !matches!(target.target_type, TargetType::Executable | TargetType::Benchmark | TargetType::Test | TargetType::SharedLib) {
+        return None;
+    }
+    let output = output_path(target);
+    if writes_pdb(target) {
+        return Some(output.with_extension("pdb"));
+    }
+    let extension = if cfg!(target_os = "macos") { "dSYM" } else { "debug" };
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    Some(output.with_file_name(name))
+}
+
+/// `split_debug` on Windows with clang: the linker writes a PDB (see above).
+fn writes_pdb(target: &ResolvedTarget) -> bool {
+    cfg!(windows) && target.split_debug && target.compiler == Compiler::Clang
+}
+
+/// Link arguments that make lld write the PDB of `writes_pdb` targets.
+fn add_pdb_link_args(cmd: &mut Command, target: &ResolvedTarget) {
+    let Some(pdb) = debug_info_path(target).filter(|_| writes_pdb(target)) else { return };
+    if target.linker.is_none() {
+        cmd.arg(Linker::Lld.flag());
+    }
+    cmd.arg(format!("-Wl,--pdb={}", path_arg(&pdb)));
+}
+
+fn strip_debug_info(target: &ResolvedTarget, output: &Path, messages: &mut Vec<String>) -> Result<(), String> {
+    if !target.strip && !target.split_debug {
+        return Ok(());
+    }
+    if let Some(debug) = debug_info_path(target).filter(|_| !writes_pdb(target)) {
+        messages.push(format!("  [DEBUG] Splitting debug info: {}", debug.display()));
+        if cfg!(target_os = "macos") {
+            let _ = std::fs::remove_dir_all(&debug);
+        }
+        for cmd in split_debug_commands(target, output, &debug) {
+            run_command(cmd, target.timeout(), messages)?;
+        }
+        // objcopy already stripped the output along with adding the debuglink
+        if !cfg!(target_os = "macos") {
+            return Ok(());
+        }
+    }
+    if target.strip {
+        messages.push(format!("  [STRIP] {}", output.display()));
+        run_command(strip_command(target, output), target.timeout(), messages)?;
+    }
+    Ok(())
+}
+
+/// Commands moving the debug info of `output` to `debug`: dsymutil on macOS, else
+/// objcopy --only-keep-debug and an objcopy that strips `output` and adds the debuglink.
+fn split_debug_commands(target: &ResolvedTarget, output: &Path, debug: &Path) -> Vec<Command> {
+    if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("dsymutil");
+        cmd.arg(path_arg(output)).arg("-o").arg(path_arg(debug));
+        return vec![cmd];
+    }
+    let mut keep = Command::new("objcopy");
+    keep.arg("--only-keep-debug").arg(path_arg(output)).arg(path_arg(debug));
+    let mut link = Command::new("objcopy");
+    link.arg(match (target.strip, target.target_type == TargetType::SharedLib) {
+        (false, _) => "--strip-debug",
+        (true, true) => "--strip-unneeded",
+        (true, false) => "--strip-all",
+    });
+    link.arg(format!("--add-gnu-debuglink={}", path_arg(debug))).arg(path_arg(output));
+    vec![keep, link]
+}
+
+/// `strip` command for `output`; shared libraries keep the symbols they export.
+fn strip_command(target: &ResolvedTarget, output: &Path) -> Command {
+    let shared = target.target_type == TargetType::SharedLib;
+    let mut cmd = Command::new("strip");
+    if cfg!(target_os = "macos") {
+        if shared {
+            cmd.arg("-x");
+        }
+    } else {
+        cmd.arg(if shared { "--strip-unneeded" } else { "--strip-all" });
+    }
+    cmd.arg(path_arg(output));
+    cmd
+}
+
 // ---------------------------------------------------------------------------
 // Add link arguments for built dependency libraries
 // ---------------------------------------------------------------------------
@@ -601,22 +1021,67 @@ fn add_dependency_link_args(
                     .unwrap_or_default()
                     .to_string_lossy()
                     .to_string();
-                let lib_name = if filename.starts_with("lib") {
-                    &filename[3..]
-                } else {
-                    &filename[..]
-                };
+                let lib_name = filename.strip_prefix("lib").unwrap_or(&filename);
                 cmd.arg(format!("-l{}", lib_name));
             }
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// Linker availability
+// ---------------------------------------------------------------------------
+/// Checks that every linker selected by the targets in `target_names` is
+/// installed (`ld.<name>` or `<name>` in PATH), so a missing mold/lld is
+/// reported once before the build instead of as a link error per target.
+pub fn verify_linkers(project: &ResolvedProject, target_names: &[String]) -> Result<(), String> {
+    let mut checked: Vec<&Linker> = Vec::new();
+    for name in target_names {
+        let Some(target) = project.targets.get(name) else { continue };
+        if !target.target_type.compiles_sources() || target.target_type == TargetType::StaticLib {
+            continue;
+        }
+        let Some(linker) = &target.linker else { continue };
+        if checked.contains(&linker) {
+            continue;
+        }
+        checked.push(linker);
+        let candidates = [format!("ld.{}", linker.name()), linker.name().to_string()];
+        if !candidates.iter().any(|c| find_in_path(c).is_some()) {
+            return Err(format!(
+                "Linker '{}' (target '{}') not found: expected '{}' in PATH",
+                linker.name(),
+                target.name,
+                candidates[0]
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Looks up an executable in PATH.
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        let candidate = dir.join(program);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if cfg!(windows) {
+            let exe = dir.join(format!("{}.exe", program));
+            if exe.is_file() {
+                return Some(exe);
+            }
+        }
+    }
+    None
+}
+
 // ---------------------------------------------------------------------------
 // Build LD_LIBRARY_PATH
 // ---------------------------------------------------------------------------
 /// Combines the given library directories with the current LD_LIBRARY_PATH.
-fn build_ld_library_path(lib_dirs: &[PathBuf]) -> String {
+pub fn build_ld_library_path(lib_dirs: &[PathBuf]) -> String {
     let mut paths: Vec<String> = lib_dirs
         .iter()
         .map(|p| p.to_string_lossy().to_string())
@@ -637,10 +1102,10 @@ fn build_ld_library_path(lib_dirs: &[PathBuf]) -> String {
 // ---------------------------------------------------------------------------
 // Helper: run command and capture output
 // ---------------------------------------------------------------------------
-fn run_command(mut cmd: Command, messages: &mut Vec<String>) -> Result<(), String> {
+fn run_command(cmd: Command, timeout: Option<Duration>, messages: &mut Vec<String>) -> Result<(), String> {
     messages.push(format!("    Command: {:?}", cmd));
 
-    match cmd.output() {
+    match run_tracked(cmd, timeout) {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -666,6 +1131,117 @@ fn run_command(mut cmd: Command, messages: &mut Vec<String>) -> Result<(), Strin
     }
 }
 
+// ---------------------------------------------------------------------------
+// Child process tracking (immediate cancellation)
+// ---------------------------------------------------------------------------
+/// Compiler/linker processes started by a build's worker threads. Each child
+/// runs in its own process group, so `kill_all` also stops the processes the
+/// driver spawned (cc1plus, as, collect2/ld).
+#[derive(Debug, Default)]
+pub struct ChildProcesses {
+    pids: Mutex<HashSet<u32>>,
+    cancelled: AtomicBool,
+}
+
+impl ChildProcesses {
+    /// Kills every running child and refuses to start new ones.
+    pub fn kill_all(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        for &pid in self.pids.lock().unwrap().iter() {
+            kill_process_group(pid);
+        }
+    }
+
+    /// Runs `cmd` to completion; with a `timeout` its process group is killed once
+    /// the time is up and the result is a TimedOut error.
+    fn run(&self, mut cmd: Command, timeout: Option<Duration>, input: Stdio) -> std::io::Result<Output> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(std::io::Error::other("build cancelled"));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd.stdin(input).stdout(Stdio::piped()).stderr(Stdio::piped());
+        let child = cmd.spawn()?;
+        let pid = child.id();
+        {
+            let mut pids = self.pids.lock().unwrap();
+            pids.insert(pid);
+            // kill_all may have run between the check above and the spawn
+            if self.cancelled.load(Ordering::Relaxed) {
+                kill_process_group(pid);
+            }
+        }
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let watchdog = timeout.map(|limit| {
+            std::thread::spawn(move || {
+                let expired = done_rx.recv_timeout(limit) == Err(mpsc::RecvTimeoutError::Timeout);
+                if expired {
+                    kill_process_group(pid);
+                }
+                expired
+            })
+        });
+        let output = child.wait_with_output();
+        let _ = done_tx.send(());
+        let timed_out = watchdog.is_some_and(|w| w.join().unwrap_or(false));
+        self.pids.lock().unwrap().remove(&pid);
+        match timeout {
+            Some(limit) if timed_out => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("killed after the {}s timeout (timeout_secs)", limit.as_secs()),
+            )),
+            _ => output,
+        }
+    }
+}
+
+thread_local! {
+    static CHILD_PROCESSES: RefCell<Option<Arc<ChildProcesses>>> = const { RefCell::new(None) };
+}
+
+/// Registers the current (worker) thread's child processes in `children`.
+/// Without a registry, commands run as plain children of this process.
+pub fn set_child_processes(children: Option<Arc<ChildProcesses>>) {
+    CHILD_PROCESSES.with(|c| *c.borrow_mut() = children);
+}
+
+fn run_tracked(cmd: Command, timeout: Option<Duration>) -> std::io::Result<Output> {
+    run_tracked_with_input(cmd, timeout, Stdio::null())
+}
+
+/// Like run_tracked, with `input` as the command's stdin.
+pub(crate) fn run_tracked_with_input(mut cmd: Command, timeout: Option<Duration>, input: Stdio) -> std::io::Result<Output> {
+    match (CHILD_PROCESSES.with(|c| c.borrow().clone()), timeout) {
+        (Some(children), _) => children.run(cmd, timeout, input),
+        (None, Some(_)) => ChildProcesses::default().run(cmd, timeout, input),
+        (None, None) => cmd.stdin(input).output(),
+    }
+}
+
+/// Kills the process `pid` and everything it started. On Unix `pid` must lead its own
+/// process group (spawned with `process_group(0)`); Windows kills the process tree.
+#[cfg(unix)]
+pub fn kill_process_group(pid: u32) {
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    const SIGKILL: i32 = 9;
+    // The child is its own process group leader, so -pid addresses the whole group
+    unsafe {
+        kill(-(pid as i32), SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill_process_group(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .output();
+}
+
 // ---------------------------------------------------------------------------
 // Print LD_LIBRARY_PATH to console (for debugging)
 // ---------------------------------------------------------------------------
@@ -689,3 +1265,260 @@ pub fn print_ld_library_path_info() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::{CompilerRule, Standard};
+
+    #[test]
+    fn test_objective_c_sources_get_language_standard_and_arc_flags() {
+        let target = ResolvedTarget {
+            cxx_standard: Standard::parse("17").ok(),
+            c_standard: Standard::parse("11").ok(),
+            objc_arc: true,
+            ..Default::default()
+        };
+        let args = compile_args(&target, Path::new("AppDelegate.m"), Path::new("AppDelegate.o"));
+        assert_eq!(args[..3], ["-c", "-x", "objective-c"]);
+        assert!(args.contains(&"-std=c11".to_string()) && args.contains(&"-fobjc-arc".to_string()));
+
+        let args = compile_args(&target, Path::new("bridge.mm"), Path::new("bridge.o"));
+        assert_eq!(args[..3], ["-c", "-x", "objective-c++"]);
+        assert!(args.contains(&"-std=c++17".to_string()));
+
+        let args = compile_args(&target, Path::new("main.cpp"), Path::new("main.o"));
+        assert!(!args.contains(&"-x".to_string()) && !args.contains(&"-fobjc-arc".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hung_commands_are_killed_after_the_timeout() {
+        let target = ResolvedTarget {
+            name: "slow".to_string(),
+            commands: vec![vec!["sleep".to_string(), "30".to_string()]],
+            working_dir: std::env::temp_dir(),
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let err = run_custom_commands(&target, &mut Vec::new()).unwrap_err();
+        assert!(err.contains("timeout"), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_custom_targets_rerun_only_when_inputs_are_newer() {
+        let dir = TempDir::new("custom_target");
+        let input = dir.write("assets/logo.svg", "<svg/>");
+        let output = dir.join("gen/logo.h");
+        let target = ResolvedTarget {
+            name: "assets".to_string(),
+            target_type: TargetType::Custom,
+            // Counts its runs in runs.log
+            commands: vec![vec!["sh".to_string(), "-c".to_string(), "echo run >> runs.log; cp assets/logo.svg gen/logo.h".to_string()]],
+            inputs: vec![input.clone()],
+            outputs: vec![output.clone()],
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        let runs = || std::fs::read_to_string(dir.join("runs.log")).unwrap_or_default().lines().count();
+
+        let mut messages = Vec::new();
+        assert_eq!(run_custom_commands(&target, &mut messages), Ok(output.clone()));
+        assert_eq!(runs(), 1);
+        assert_eq!(messages[0], "  [CUSTOM] echo run >> runs.log; cp assets/logo.svg gen/logo.h");
+
+        messages.clear();
+        run_custom_commands(&target, &mut messages).unwrap();
+        assert_eq!(runs(), 1);
+        assert_eq!(messages, ["  [SKIP] Custom target 'assets' (up-to-date)"]);
+
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options().write(true).open(&input).unwrap().set_modified(later).unwrap();
+        assert!(matches!(outputs_dirty(&target.inputs, &target.outputs), Some(Dirty::Newer { .. })));
+        run_custom_commands(&target, &mut Vec::new()).unwrap();
+        assert_eq!(runs(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_custom_command_fails_the_build() {
+        let dir = TempDir::new("custom_target_fails");
+        let target = ResolvedTarget {
+            name: "gen".to_string(),
+            target_type: TargetType::Custom,
+            commands: vec![vec!["sh".to_string(), "-c".to_string(), "echo 'gen: no such schema' >&2; exit 3".to_string()]],
+            outputs: vec![dir.join("gen.h")],
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        let mut project = crate::config::ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        project.targets.insert(target.name.clone(), target);
+        let order = crate::dag::build_order(&project).unwrap();
+        let options = crate::options::BuildOptions { quiet: true, ..Default::default() };
+
+        let result = crate::builder::build_project(&project, &order, &options, None, None);
+        assert!(!result.success);
+        assert_eq!(result.failed_targets, 1);
+        let error = result.results[0].messages.iter().find(|m| m.starts_with("  [ERROR]")).unwrap();
+        assert!(error.contains("exit code: Some(3)") && error.contains("gen: no such schema"), "{}", error);
+    }
+
+    #[test]
+    fn test_missing_launcher_falls_back_to_local_compilation() {
+        let dir = TempDir::new("launcher");
+        dir.write("main.c", "int main(void) { return 0; }\n");
+        let target = ResolvedTarget {
+            compiler: crate::config::Compiler::Gcc,
+            compiler_launcher: Some("ngmake-missing-distcc".to_string()),
+            ..Default::default()
+        };
+        let (obj, messages) = compile_one_source(&target, &dir.join("main.c"), &dir.join("main.o")).unwrap();
+        assert!(obj.exists());
+        assert!(messages.iter().any(|m| m.contains("[WARN] ngmake-missing-distcc failed")), "{:?}", messages);
+    }
+
+    #[test]
+    fn test_invalidate_target_removes_only_that_targets_objects_and_output() {
+        let dir = TempDir::new("force");
+        let target = ResolvedTarget { name: "core".to_string(), target_type: TargetType::StaticLib, output_dir: dir.to_path_buf(), ..Default::default() };
+        let other = dir.join("obj").join("app").join("main.o");
+        std::fs::create_dir_all(dir.join("obj").join("core")).unwrap();
+        std::fs::create_dir_all(other.parent().unwrap()).unwrap();
+        std::fs::write(dir.join("obj").join("core").join("core.o"), "").unwrap();
+        std::fs::write(output_path(&target), "").unwrap();
+        std::fs::write(&other, "").unwrap();
+
+        let removed = invalidate_target(&target).unwrap();
+        assert_eq!(removed, vec![dir.join("obj").join("core"), dir.join("libcore.a")]);
+        assert!(!output_path(&target).exists() && other.exists());
+        assert!(invalidate_target(&target).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_identical_relinked_libraries_keep_their_modification_time() {
+        let dir = TempDir::new("restat");
+        let target = ResolvedTarget { name: "core".to_string(), target_type: TargetType::StaticLib, output_dir: dir.to_path_buf(), ..Default::default() };
+        let output = output_path(&target);
+        std::fs::write(&output, "archive v1").unwrap();
+        let old_time = mtime(&output).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        let relink = |contents: &'static str| {
+            let mut messages = Vec::new();
+            let path = link_with_restat(&target, &output, &mut messages, |_| std::fs::write(&output, contents).map(|_| output.clone()).map_err(|e| e.to_string()));
+            (path.unwrap(), messages)
+        };
+        let (_, messages) = relink("archive v1");
+        assert_eq!(mtime(&output), Some(old_time));
+        assert!(messages[0].contains("[RESTAT]"), "{:?}", messages);
+        assert!(link_dirty(&output, &link_stamp_path(&target), &[], &[], &Default::default()).is_none());
+
+        let (_, messages) = relink("archive v2");
+        assert!(messages.is_empty() && mtime(&output) > Some(old_time));
+        assert!(!dir.join("libcore.a.prev").exists());
+    }
+
+    /// Program and arguments of `cmd`, for comparing generated command lines.
+    fn command_line(cmd: &Command) -> Vec<String> {
+        std::iter::once(cmd.get_program()).chain(cmd.get_args()).map(|a| a.to_string_lossy().to_string()).collect()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_static_libraries_are_deterministic_and_optionally_thin() {
+        let objects = [PathBuf::from("obj/core/a.o"), PathBuf::from("obj/core/b.o")];
+        let target = ResolvedTarget { name: "core".to_string(), target_type: TargetType::StaticLib, ..Default::default() };
+        let cmd = archive_command(&target, Path::new("libcore.a"), &objects);
+        assert_eq!(command_line(&cmd), ["ar", "rcsD", "libcore.a", "obj/core/a.o", "obj/core/b.o"]);
+        assert_eq!(cmd.get_envs().count(), 0);
+
+        let thin = ResolvedTarget { thin_archive: true, ..target };
+        assert_eq!(command_line(&archive_command(&thin, Path::new("libcore.a"), &objects))[1], "rcsDT");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_split_debug_and_strip_generate_objcopy_and_strip_arguments() {
+        let app = ResolvedTarget { name: "app".to_string(), split_debug: true, output_dir: PathBuf::from("out"), ..Default::default() };
+        let debug = debug_info_path(&app).unwrap();
+        assert_eq!(debug, Path::new("out/app.debug"));
+        let lines = |target: &ResolvedTarget| -> Vec<Vec<String>> {
+            split_debug_commands(target, Path::new("out/app"), &debug).iter().map(command_line).collect()
+        };
+        assert_eq!(lines(&app), [
+            vec!["objcopy", "--only-keep-debug", "out/app", "out/app.debug"],
+            vec!["objcopy", "--strip-debug", "--add-gnu-debuglink=out/app.debug", "out/app"],
+        ]);
+        assert_eq!(lines(&ResolvedTarget { strip: true, ..app.clone() })[1][1], "--strip-all");
+        let shared = ResolvedTarget { strip: true, target_type: TargetType::SharedLib, ..app.clone() };
+        assert_eq!(lines(&shared)[1][1], "--strip-unneeded");
+
+        assert_eq!(command_line(&strip_command(&app, Path::new("out/app"))), ["strip", "--strip-all", "out/app"]);
+        assert_eq!(command_line(&strip_command(&shared, Path::new("out/libapp.so"))), ["strip", "--strip-unneeded", "out/libapp.so"]);
+        // Static libraries have no separate debug info
+        assert_eq!(debug_info_path(&ResolvedTarget { target_type: TargetType::StaticLib, ..app }), None);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_rearchiving_unchanged_objects_gives_identical_bytes() {
+        let dir = TempDir::new("deterministic_ar");
+        let object = dir.write("a.o", "object");
+        let target = ResolvedTarget { name: "core".to_string(), target_type: TargetType::StaticLib, output_dir: dir.to_path_buf(), ..Default::default() };
+        let archive = create_static_lib(&target, std::slice::from_ref(&object), &mut Vec::new()).unwrap();
+        let first = std::fs::read(&archive).unwrap();
+
+        let later = std::time::SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&object).unwrap().set_modified(later).unwrap();
+        create_static_lib(&target, &[object], &mut Vec::new()).unwrap();
+        assert_eq!(std::fs::read(&archive).unwrap(), first);
+    }
+
+    #[test]
+    fn test_compiler_rules_fill_in_the_argument_template() {
+        let mut target = ResolvedTarget {
+            include_dirs: vec![PathBuf::from("include")],
+            defines: vec!["FAST".to_string()],
+            compiler_flags: vec!["--target=avx2".to_string()],
+            ..Default::default()
+        };
+        let rule = CompilerRule {
+            command: "ispc".to_string(),
+            args: ["{source}", "-o", "{object}", "{include_dirs}", "{defines}", "{flags}", "--header-outfile={object}.h"].map(String::from).to_vec(),
+        };
+        target.compilers.insert("ispc".to_string(), rule);
+        let (program, args) = compile_command(&target, Path::new("simd.ispc"), Path::new("simd.o"));
+        assert_eq!(program, "ispc");
+        assert_eq!(args, ["simd.ispc", "-o", "simd.o", "-Iinclude", "-DFAST", "--target=avx2", "--header-outfile=simd.o.h"]);
+
+        let (program, _) = compile_command(&target, Path::new("main.cpp"), Path::new("main.o"));
+        assert_eq!(program, "g++");
+    }
+
+    #[test]
+    fn test_missing_linkers_are_reported_and_static_libs_skipped() {
+        let mut project = ResolvedProject::default();
+        for (name, target_type) in [("app", TargetType::Executable), ("core", TargetType::StaticLib)] {
+            let target = ResolvedTarget {
+                name: name.to_string(),
+                target_type,
+                linker: Some(Linker::Mold),
+                ..Default::default()
+            };
+            project.targets.insert(name.to_string(), target);
+        }
+
+        assert_eq!(verify_linkers(&project, &["core".to_string()]), Ok(()));
+
+        let result = verify_linkers(&project, &["core".to_string(), "app".to_string()]);
+        if find_in_path("ld.mold").is_none() && find_in_path("mold").is_none() {
+            assert_eq!(result, Err("Linker 'mold' (target 'app') not found: expected 'ld.mold' in PATH".to_string()));
+        } else {
+            assert_eq!(result, Ok(()));
+        }
+    }
+}
@@ -7,17 +7,29 @@
 // ============================================================================
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use crate::discovery::SourceFinder;
+use crate::interpolate::{expand, expand_all, file_variables, Variables};
+use crate::protobuf::ProtobufConfig;
+use crate::qt::QtConfig;
+use crate::remote::RemoteConfig;
+use crate::cache::CacheConfig;
+use crate::package::PackageConfig;
+use crate::prebuilt::DependencyConfig;
+use crate::lock::LockedPackage;
+use crate::preset::Preset;
+use crate::memcheck::MemcheckConfig;
 
 // ---------------------------------------------------------------------------
 // Compiler type: GCC, GPP (g++), or Clang
 // ---------------------------------------------------------------------------
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Compiler {
     Gcc,
     #[serde(rename = "g++")]
+    #[default]
     Gpp,
     Clang,
 }
@@ -33,28 +45,214 @@ impl Compiler {
     }
 }
 
-impl Default for Compiler {
-    fn default() -> Self {
-        Compiler::Gpp
+// ---------------------------------------------------------------------------
+// Language standard: `17` or "c++17" → -std=c++17, "gnu++17" → -std=gnu++17
+// (C: `11` / "c11" → -std=c11, "gnu11" → -std=gnu11)
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "StandardValue", into = "StandardValue")]
+pub struct Standard {
+    pub version: u32,
+    /// GNU dialect (gnu++17 / gnu11) instead of strict ISO
+    pub gnu: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StandardValue {
+    Number(u32),
+    Name(String),
+}
+
+impl TryFrom<StandardValue> for Standard {
+    type Error = String;
+
+    fn try_from(value: StandardValue) -> Result<Self, String> {
+        match value {
+            StandardValue::Number(version) => Ok(Standard { version, gnu: false }),
+            StandardValue::Name(name) => Standard::parse(&name),
+        }
+    }
+}
+
+impl From<Standard> for StandardValue {
+    fn from(std: Standard) -> StandardValue {
+        if std.gnu {
+            StandardValue::Name(format!("gnu{}", std.version))
+        } else {
+            StandardValue::Number(std.version)
+        }
+    }
+}
+
+impl Standard {
+    /// Parses "17", "c++17", "gnu++17", "c11" or "gnu11".
+    pub fn parse(name: &str) -> Result<Standard, String> {
+        let (gnu, number) = [("gnu++", true), ("c++", false), ("gnu", true), ("c", false)]
+            .iter()
+            .find_map(|(prefix, gnu)| name.strip_prefix(prefix).map(|rest| (*gnu, rest)))
+            .unwrap_or((false, name));
+        number
+            .parse()
+            .map(|version| Standard { version, gnu })
+            .map_err(|_| format!("invalid language standard '{}' (expected e.g. 17, \"c++17\" or \"gnu++17\")", name))
+    }
+
+    /// The -std option for C++ (`cxx` = true) or C sources.
+    pub fn flag(&self, cxx: bool) -> String {
+        let dialect = match (self.gnu, cxx) {
+            (false, true) => "c++",
+            (true, true) => "gnu++",
+            (false, false) => "c",
+            (true, false) => "gnu",
+        };
+        format!("-std={}{}", dialect, self.version)
     }
 }
 
 // ---------------------------------------------------------------------------
-// Target type: executable, static library, or shared library
+// Linker selection: passed to the compiler driver as -fuse-ld=<name>
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Linker {
+    Bfd,
+    Gold,
+    Lld,
+    Mold,
+}
+
+impl Linker {
+    /// Linker name as understood by -fuse-ld
+    pub fn name(&self) -> &str {
+        match self {
+            Linker::Bfd => "bfd",
+            Linker::Gold => "gold",
+            Linker::Lld => "lld",
+            Linker::Mold => "mold",
+        }
+    }
+
+    /// Driver flag selecting this linker
+    pub fn flag(&self) -> String {
+        format!("-fuse-ld={}", self.name())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compiler rule: `[compilers]` maps a source extension to the command that
+// compiles it (e.g. ispc for .ispc), instead of the target's compiler
 // ---------------------------------------------------------------------------
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CompilerRule {
+    /// Program to run (e.g. "ispc", "nvcc")
+    pub command: String,
+    /// Argument template: "{source}" and "{object}" are replaced in each argument;
+    /// "{include_dirs}", "{defines}" and "{flags}" expand to -I/-D/compiler flag lists
+    #[serde(default = "default_rule_args")]
+    pub args: Vec<String>,
+}
+
+fn default_rule_args() -> Vec<String> {
+    ["-c", "{source}", "-o", "{object}", "{include_dirs}", "{defines}", "{flags}"].map(String::from).to_vec()
+}
+
+// ---------------------------------------------------------------------------
+// Retry policy: `[project.retry]` reruns failed compile/link jobs whose output
+// looks like a transient failure (file locks, antivirus scans on Windows)
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Extra attempts per job
+    #[serde(default = "default_retry_count")]
+    pub count: u32,
+    /// Regular expressions (case-insensitive) matched against the failed job's output
+    #[serde(default = "default_retry_matches")]
+    pub matches: Vec<String>,
+    /// Wait before the n-th retry: n × delay_ms
+    #[serde(default = "default_retry_delay_ms")]
+    pub delay_ms: u64,
+}
+
+fn default_retry_count() -> u32 {
+    2
+}
+
+fn default_retry_matches() -> Vec<String> {
+    [
+        "resource temporarily unavailable",
+        "file (is )?locked",
+        "text file busy",
+        "being used by another process",
+        "permission denied",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn default_retry_delay_ms() -> u64 {
+    500
+}
+
+impl RetryPolicy {
+    /// One case-insensitive regex matching any of `matches`.
+    pub fn matcher(&self) -> Result<regex::Regex, String> {
+        let alternatives: Vec<String> = self.matches.iter().map(|m| format!("(?:{})", m)).collect();
+        regex::RegexBuilder::new(&alternatives.join("|"))
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("invalid retry pattern: {}", e))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Target type: executable, static library, or shared library
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum TargetType {
+    #[default]
     Executable,
     #[serde(rename = "static_lib")]
     StaticLib,
     #[serde(rename = "shared_lib")]
     SharedLib,
+    /// Header-only library: no build step; its include_dirs, defines and libs apply to dependents
+    Interface,
+    /// Runs `commands` to turn `inputs` into `outputs` (code generation, asset packaging)
+    Custom,
+    /// Only `deps`: building it builds them (make-style phony target, e.g. "all-tests")
+    Group,
+    /// Executable run by `ngm bench` (see bench.rs)
+    Benchmark,
+    /// Executable run by `ngm test`; passes when it exits with 0 (see test_runner.rs)
+    Test,
 }
 
-impl Default for TargetType {
-    fn default() -> Self {
-        TargetType::Executable
+impl TargetType {
+    /// Name as written in build.toml (`type = "..."`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetType::Executable => "executable",
+            TargetType::StaticLib => "static_lib",
+            TargetType::SharedLib => "shared_lib",
+            TargetType::Interface => "interface",
+            TargetType::Custom => "custom",
+            TargetType::Group => "group",
+            TargetType::Benchmark => "benchmark",
+            TargetType::Test => "test",
+        }
+    }
+
+    /// True for targets linked into a program (executable, benchmark, test).
+    pub fn is_executable(&self) -> bool {
+        matches!(self, TargetType::Executable | TargetType::Benchmark | TargetType::Test)
+    }
+
+    /// True for targets built by compiling sources and linking/archiving them
+    /// (executable, benchmark, test, static_lib, shared_lib); only these are linked into dependents.
+    pub fn compiles_sources(&self) -> bool {
+        matches!(self, TargetType::Executable | TargetType::Benchmark | TargetType::Test | TargetType::StaticLib | TargetType::SharedLib)
     }
 }
 
@@ -66,7 +264,7 @@ pub struct TargetConfig {
     /// Unique target name (e.g. "mylib", "myapp")
     pub name: String,
 
-    /// Target type: executable, static_lib, shared_lib
+    /// Target type: executable, static_lib, shared_lib, interface, custom, group, benchmark, test
     #[serde(default, rename = "type")]
     pub target_type: TargetType,
 
@@ -74,6 +272,10 @@ pub struct TargetConfig {
     #[serde(default)]
     pub sources: Vec<String>,
 
+    /// Patterns removed from the expanded sources (e.g. "src/experimental/**", "**/*_win.cpp")
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
     /// Include directories (-I)
     #[serde(default)]
     pub include_dirs: Vec<String>,
@@ -86,39 +288,418 @@ pub struct TargetConfig {
     #[serde(default)]
     pub libs: Vec<String>,
 
+    /// macOS frameworks to link (e.g. "Cocoa" → -framework Cocoa)
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+
+    /// Preprocessor definitions (e.g. "NDEBUG", "FOO_VERSION=3" → -DNDEBUG -DFOO_VERSION=3)
+    #[serde(default)]
+    pub defines: Vec<String>,
+
     /// Legacy flags: applied at compile time (prefer compiler_flags / linker_flags)
     #[serde(default)]
-    pub flags: Vec<String>,
+    pub flags: FlagList,
+
+    /// C++ standard (e.g. 17 → -std=c++17, "gnu++20" → -std=gnu++20). Overrides [project] cxx_standard.
+    #[serde(default)]
+    pub cxx_standard: Option<Standard>,
+
+    /// C standard for .c and .m sources (e.g. 11 → -std=c11, "gnu11"). Overrides [project] c_standard.
+    #[serde(default)]
+    pub c_standard: Option<Standard>,
+
+    /// Compile Objective-C / Objective-C++ sources (.m, .mm) with automatic reference counting (-fobjc-arc)
+    #[serde(default)]
+    pub objc_arc: Option<bool>,
 
-    /// C++ standard (e.g. 17 → -std=c++17). Optional; if unset, no -std is added.
+    /// Seconds after which a compile/link command of this target is killed and fails.
+    /// Overrides [project] timeout_secs.
     #[serde(default)]
-    pub cxx_standard: Option<u32>,
+    pub timeout_secs: Option<u64>,
+
+    /// Estimated peak memory of this target's link in MiB. Links run concurrently only
+    /// while their estimates fit in the project's `link_memory_limit`.
+    #[serde(default)]
+    pub link_memory: Option<u64>,
 
     /// Compiler-only flags (compile step; e.g. "-O2", "-Wall")
     #[serde(default)]
-    pub compiler_flags: Vec<String>,
+    pub compiler_flags: FlagList,
 
     /// Linker-only flags (link step; e.g. "-Wl,--as-needed")
     #[serde(default)]
-    pub linker_flags: Vec<String>,
+    pub linker_flags: FlagList,
+
+    /// Don't prepend the [defaults] flags, compiler_flags and linker_flags
+    #[serde(default)]
+    pub no_default_flags: bool,
+
+    /// Flags removed from flags, compiler_flags and linker_flags once [defaults] and
+    /// dependency flags are merged in (e.g. "-Werror")
+    #[serde(default)]
+    pub remove_flags: Vec<String>,
 
     /// Other target names this target depends on (DAG deps)
     #[serde(default)]
     pub deps: Vec<String>,
 
-    /// Compiler to use (gcc, g++, clang)
+    /// Ad-hoc groups for `ngm build --tag` / `--exclude-tag` (e.g. "tests", "tools")
     #[serde(default)]
-    pub compiler: Compiler,
+    pub tags: Vec<String>,
+
+    /// Not built by a build without -t (examples, benchmarks), unless a built target depends on it
+    #[serde(default)]
+    pub exclude_from_all: bool,
+
+    /// Compiler to use (gcc, g++, clang; default: [defaults] compiler, else g++)
+    #[serde(default)]
+    pub compiler: Option<Compiler>,
+
+    /// Linker for executables and shared libs (bfd, gold, lld, mold). Overrides [project] linker.
+    #[serde(default)]
+    pub linker: Option<Linker>,
 
-    /// Output directory (default: "build")
-    #[serde(default = "default_output_dir")]
-    pub output_dir: String,
+    /// Output directory (default: [defaults] output_dir, else "build")
+    #[serde(default)]
+    pub output_dir: Option<String>,
+
+    /// Output file base name (default: the target name), e.g. "acme_core" → libacme_core.a
+    #[serde(default)]
+    pub output_name: Option<String>,
+
+    /// Library file prefix (default: "lib")
+    #[serde(default)]
+    pub lib_prefix: Option<String>,
+
+    /// Library file suffix including the dot (default: ".a", ".so" or ".dll")
+    #[serde(default)]
+    pub lib_suffix: Option<String>,
+
+    /// Libraries: write a pkg-config file (<output_dir>/pkgconfig/<name>.pc, and
+    /// lib/pkgconfig/<name>.pc in `ngm package` archives)
+    #[serde(default)]
+    pub pkg_config: bool,
+
+    /// Libraries: export as <project>::<name> in the CMake package files
+    /// (lib/cmake/<project>/ in `ngm package` archives)
+    #[serde(default)]
+    pub cmake_export: bool,
+
+    /// Executables and shared libs: strip symbols after linking. Overridable per OS.
+    #[serde(default)]
+    pub strip: Option<bool>,
+
+    /// Executables and shared libs: move debug info to <output>.debug (macOS:
+    /// <output>.dSYM) after linking. Overridable per OS.
+    #[serde(default)]
+    pub split_debug: Option<bool>,
+
+    /// Static libraries: write a thin archive that references the object files
+    /// instead of copying them (faster for huge libraries; not relocatable)
+    #[serde(default)]
+    pub thin_archive: bool,
+
+    /// Custom targets: shell commands run in order in the build.toml directory
+    #[serde(default)]
+    pub commands: Vec<String>,
+
+    /// Custom targets: files the commands read (glob patterns supported)
+    #[serde(default)]
+    pub inputs: Vec<String>,
+
+    /// Custom targets: files the commands write; the commands are skipped while all
+    /// outputs are newer than every input
+    #[serde(default)]
+    pub outputs: Vec<String>,
+
+    /// Benchmarks and tests: arguments `ngm bench` / `ngm test` run the program with
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Benchmarks: runs per `ngm bench` (default: bench::DEFAULT_REPETITIONS)
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
+    /// Tests: seconds after which `ngm test` kills the test and counts it as failed
+    #[serde(default)]
+    pub test_timeout_secs: Option<u64>,
+
+    /// Tests: reruns of a failed test before it counts as failed (flaky suites)
+    #[serde(default)]
+    pub retries: Option<u32>,
+
+    /// Qt support: `qt = { modules = ["Widgets"], auto_moc = true }` runs moc/uic/rcc
+    /// and adds the Qt include/lib paths (see qt.rs)
+    #[serde(default)]
+    pub qt: Option<QtConfig>,
+
+    /// Protocol Buffers: `[target.protobuf]` with `sources = ["proto/*.proto"]` runs
+    /// protoc and compiles the generated .pb.cc files (see protobuf.rs)
+    #[serde(default)]
+    pub protobuf: Option<ProtobufConfig>,
+
+    /// Overrides applied when building on Linux (`[target.linux]` after `[[target]]`)
+    #[serde(default)]
+    pub linux: Option<PlatformConfig>,
+
+    /// Overrides applied when building on macOS (`[target.macos]`)
+    #[serde(default)]
+    pub macos: Option<PlatformConfig>,
+
+    /// Overrides applied when building on Windows (`[target.windows]`)
+    #[serde(default)]
+    pub windows: Option<PlatformConfig>,
 }
 
 fn default_output_dir() -> String {
     "build".to_string()
 }
 
+impl TargetConfig {
+    /// The compiler the target's flags are selected for.
+    fn compiler(&self) -> Compiler {
+        self.compiler.clone().unwrap_or_default()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Flag lists: either a plain list, or per-compiler sub-keys selected by the
+// target's resolved compiler:
+//
+//   [target.compiler_flags]
+//   all   = ["-O2"]
+//   gcc   = ["-Wno-maybe-uninitialized"]
+//   clang = ["-Wno-unused-private-field"]
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum FlagList {
+    List(Vec<String>),
+    PerCompiler(PerCompilerFlags),
+}
+
+impl Default for FlagList {
+    fn default() -> Self {
+        FlagList::List(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct PerCompilerFlags {
+    /// Flags for every compiler
+    #[serde(default)]
+    pub all: Vec<String>,
+    /// Only when the target compiles with gcc or g++
+    #[serde(default)]
+    pub gcc: Vec<String>,
+    /// Only when the target compiles with clang
+    #[serde(default)]
+    pub clang: Vec<String>,
+    /// Only when the target compiles with MSVC (accepted; not selected by the gcc/clang backends)
+    #[serde(default)]
+    pub msvc: Vec<String>,
+}
+
+impl FlagList {
+    /// The flags that apply when compiling with `compiler`.
+    pub fn select(&self, compiler: &Compiler) -> Vec<String> {
+        match self {
+            FlagList::List(flags) => flags.clone(),
+            FlagList::PerCompiler(p) => {
+                let specific = match compiler {
+                    Compiler::Gcc | Compiler::Gpp => &p.gcc,
+                    Compiler::Clang => &p.clang,
+                };
+                p.all.iter().chain(specific).cloned().collect()
+            }
+        }
+    }
+
+    /// This list followed by `other` (per-compiler keys are merged key by key).
+    fn merged(&self, other: &FlagList) -> FlagList {
+        let per_compiler = |f: &FlagList| match f {
+            FlagList::List(flags) => PerCompilerFlags { all: flags.clone(), ..Default::default() },
+            FlagList::PerCompiler(p) => p.clone(),
+        };
+        match (self, other) {
+            (FlagList::List(a), FlagList::List(b)) => FlagList::List(a.iter().chain(b).cloned().collect()),
+            _ => {
+                let (a, b) = (per_compiler(self), per_compiler(other));
+                let join = |x: &[String], y: &[String]| x.iter().chain(y).cloned().collect();
+                FlagList::PerCompiler(PerCompilerFlags {
+                    all: join(&a.all, &b.all),
+                    gcc: join(&a.gcc, &b.gcc),
+                    clang: join(&a.clang, &b.clang),
+                    msvc: join(&a.msvc, &b.msvc),
+                })
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Per-OS target overrides: lists are appended, single values replace
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlatformConfig {
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    #[serde(default)]
+    pub lib_dirs: Vec<String>,
+    #[serde(default)]
+    pub libs: Vec<String>,
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub defines: Vec<String>,
+    #[serde(default)]
+    pub flags: FlagList,
+    #[serde(default)]
+    pub compiler_flags: FlagList,
+    #[serde(default)]
+    pub linker_flags: FlagList,
+    #[serde(default)]
+    pub remove_flags: Vec<String>,
+    #[serde(default)]
+    pub deps: Vec<String>,
+    #[serde(default)]
+    pub cxx_standard: Option<Standard>,
+    #[serde(default)]
+    pub c_standard: Option<Standard>,
+    #[serde(default)]
+    pub objc_arc: Option<bool>,
+    #[serde(default)]
+    pub compiler: Option<Compiler>,
+    #[serde(default)]
+    pub linker: Option<Linker>,
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    #[serde(default)]
+    pub output_name: Option<String>,
+    #[serde(default)]
+    pub lib_prefix: Option<String>,
+    #[serde(default)]
+    pub lib_suffix: Option<String>,
+    #[serde(default)]
+    pub strip: Option<bool>,
+    #[serde(default)]
+    pub split_debug: Option<bool>,
+}
+
+impl TargetConfig {
+    /// The override table for the OS ngmake runs on, if the target has one.
+    fn current_platform(&self) -> Option<&PlatformConfig> {
+        match std::env::consts::OS {
+            "linux" => self.linux.as_ref(),
+            "macos" => self.macos.as_ref(),
+            "windows" => self.windows.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// This target with the current OS's overrides merged in.
+    fn with_platform_overrides(&self) -> TargetConfig {
+        let mut t = self.clone();
+        let Some(p) = self.current_platform() else { return t };
+        t.sources.extend(p.sources.iter().cloned());
+        t.exclude.extend(p.exclude.iter().cloned());
+        t.include_dirs.extend(p.include_dirs.iter().cloned());
+        t.lib_dirs.extend(p.lib_dirs.iter().cloned());
+        t.libs.extend(p.libs.iter().cloned());
+        t.frameworks.extend(p.frameworks.iter().cloned());
+        t.defines.extend(p.defines.iter().cloned());
+        t.flags = t.flags.merged(&p.flags);
+        t.compiler_flags = t.compiler_flags.merged(&p.compiler_flags);
+        t.linker_flags = t.linker_flags.merged(&p.linker_flags);
+        t.remove_flags.extend(p.remove_flags.iter().cloned());
+        t.deps.extend(p.deps.iter().cloned());
+        if p.cxx_standard.is_some() {
+            t.cxx_standard = p.cxx_standard;
+        }
+        if p.c_standard.is_some() {
+            t.c_standard = p.c_standard;
+        }
+        if p.objc_arc.is_some() {
+            t.objc_arc = p.objc_arc;
+        }
+        if p.strip.is_some() {
+            t.strip = p.strip;
+        }
+        if p.split_debug.is_some() {
+            t.split_debug = p.split_debug;
+        }
+        if let Some(compiler) = &p.compiler {
+            t.compiler = Some(compiler.clone());
+        }
+        if p.linker.is_some() {
+            t.linker = p.linker.clone();
+        }
+        if let Some(dir) = &p.output_dir {
+            t.output_dir = Some(dir.clone());
+        }
+        if p.output_name.is_some() {
+            t.output_name = p.output_name.clone();
+        }
+        if p.lib_prefix.is_some() {
+            t.lib_prefix = p.lib_prefix.clone();
+        }
+        if p.lib_suffix.is_some() {
+            t.lib_suffix = p.lib_suffix.clone();
+        }
+        t
+    }
+
+    /// This target with the root [defaults] merged in (see DefaultsConfig).
+    fn with_defaults(&self, d: &DefaultsConfig) -> TargetConfig {
+        let mut t = self.clone();
+        if !t.no_default_flags {
+            t.flags = d.flags.merged(&t.flags);
+            t.compiler_flags = d.compiler_flags.merged(&t.compiler_flags);
+            t.linker_flags = d.linker_flags.merged(&t.linker_flags);
+        }
+        t.defines.splice(0..0, d.defines.iter().cloned());
+        if t.compiler.is_none() {
+            t.compiler = d.compiler.clone();
+        }
+        if t.output_dir.is_none() {
+            t.output_dir = d.output_dir.clone();
+        }
+        t
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Project defaults (`[defaults]` in the root build.toml), merged into every
+// target of the workspace:
+//   - flags, compiler_flags, linker_flags, defines: the defaults come first,
+//     then the target's own (later flags win; no_default_flags skips flags)
+//   - include_dirs: relative to the root build.toml, searched after the
+//     target's own include_dirs
+//   - compiler, output_dir: used when the target doesn't set its own;
+//     output_dir is relative to each target's build.toml, like "build"
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DefaultsConfig {
+    #[serde(default)]
+    pub flags: FlagList,
+    #[serde(default)]
+    pub compiler_flags: FlagList,
+    #[serde(default)]
+    pub linker_flags: FlagList,
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    #[serde(default)]
+    pub defines: Vec<String>,
+    #[serde(default)]
+    pub compiler: Option<Compiler>,
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Project config: top-level build.toml
 // ---------------------------------------------------------------------------
@@ -132,14 +713,100 @@ pub struct ProjectConfig {
     #[serde(default = "default_version")]
     pub version: String,
 
-    /// C++ standard for the whole project (only root [project] is used; overrides from includes are ignored)
+    /// Default C++ standard for all targets (only root [project] is used; targets may set their own)
+    #[serde(default)]
+    pub cxx_standard: Option<Standard>,
+
+    /// Default C standard for all targets (only root [project] is used; targets may set their own)
+    #[serde(default)]
+    pub c_standard: Option<Standard>,
+
+    /// Default linker for all targets (only root [project] is used)
+    #[serde(default)]
+    pub linker: Option<Linker>,
+
+    /// Preprocessor definitions for every target (only root [project] is used)
+    #[serde(default)]
+    pub defines: Vec<String>,
+
+    /// Source extension → compiler rule, e.g. `ispc = { command = "ispc", args = [...] }`
+    /// (only root build.toml is used)
+    #[serde(default)]
+    pub compilers: BTreeMap<String, CompilerRule>,
+
+    /// Default command timeout in seconds for all targets (only root [project] is used)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+
+    /// Rerun failed jobs whose output matches a transient error (only root [project] is used)
     #[serde(default)]
-    pub cxx_standard: Option<u32>,
+    pub retry: Option<RetryPolicy>,
+
+    /// Memory available to concurrent links in MiB (default: physical memory; only root [project] is used)
+    #[serde(default)]
+    pub link_memory_limit: Option<u64>,
+
+    /// Maximum number of concurrent links (default: -j; only root [project] is used)
+    #[serde(default)]
+    pub link_jobs: Option<usize>,
+
+    /// Program wrapping every compile, e.g. "ccache", "distcc", "icecc" (only root [project] is used)
+    #[serde(default)]
+    pub compiler_launcher: Option<String>,
+
+    /// Workers while compiles are distributed by the launcher (default: `distcc -j`; only root [project] is used)
+    #[serde(default)]
+    pub launcher_jobs: Option<usize>,
+
+    /// Directory collecting all executables after a successful build, shared libraries
+    /// go to a `lib` directory next to it (only root [project] is used)
+    #[serde(default)]
+    pub bin_dir: Option<String>,
+
+    /// Symlink into bin_dir instead of copying (only root [project] is used)
+    #[serde(default)]
+    pub bin_symlinks: bool,
+
+    /// SSH build hosts for distributed compilation (`[remote]`, only root build.toml is used)
+    #[serde(default)]
+    pub remote: Option<RemoteConfig>,
+
+    /// Content-addressed object cache (`[cache]`, only root build.toml is used)
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+
+    /// valgrind settings of `ngm test --memcheck` (`[memcheck]`, only root build.toml is used)
+    #[serde(default)]
+    pub memcheck: Option<MemcheckConfig>,
+
+    /// Contents of `ngm package` archives (`[package]`, only root build.toml is used)
+    #[serde(default)]
+    pub package: Option<PackageConfig>,
+
+    /// Prebuilt packages of other ngmake projects (`[dependencies]`, only root build.toml is used)
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencyConfig>,
+
+    /// Named build configurations for `ngm build --preset` (`[presets]`, only root build.toml is used)
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+
+    /// Required compiler versions, e.g. `requires = { gcc = ">=12", clang = ">=15" }` (root only)
+    #[serde(default)]
+    pub requires: BTreeMap<String, String>,
+
+    /// Targets a build without -t builds (default: all without exclude_from_all; root only)
+    #[serde(default)]
+    pub default_targets: Vec<String>,
 
     /// Paths to submodule build.toml files (nested configs)
     #[serde(default)]
     pub includes: Vec<String>,
 
+    /// Settings merged into every target (`[defaults]`, only root build.toml is used)
+    #[serde(default)]
+    pub defaults: DefaultsConfig,
+
     /// Target list
     #[serde(default, rename = "target")]
     pub targets: Vec<TargetConfig>,
@@ -173,74 +840,298 @@ fn default_module_name() -> String {
 // ---------------------------------------------------------------------------
 // Flattened structure holding all targets
 // ---------------------------------------------------------------------------
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ResolvedProject {
     /// Project name
     pub name: String,
     /// Project version
     pub version: String,
-    /// C++ standard for the whole project (from root [project] only; applied to all targets)
-    pub cxx_standard: Option<u32>,
+    /// Directory of the root build.toml (workspace root)
+    pub root_dir: PathBuf,
+    /// Default C++ standard (from root [project] only; targets without their own use it)
+    pub cxx_standard: Option<Standard>,
+    /// Default C standard (from root [project] only; targets without their own use it)
+    pub c_standard: Option<Standard>,
+    /// Default linker (from root [project] only; targets without their own linker use it)
+    pub linker: Option<Linker>,
+    /// Project-wide preprocessor definitions (from root [project] only; already part of each target's defines)
+    pub defines: Vec<String>,
+    /// Extension → compiler rule (from root build.toml only; copied into each target)
+    pub compilers: BTreeMap<String, CompilerRule>,
+    /// Default command timeout in seconds (from root [project] only; already applied to targets)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Retry policy for failed jobs (from root [project] only)
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Memory available to concurrent links in MiB (from root [project] only)
+    #[serde(default)]
+    pub link_memory_limit: Option<u64>,
+    /// Maximum number of concurrent links (from root [project] only)
+    #[serde(default)]
+    pub link_jobs: Option<usize>,
+    /// Program wrapping every compile (from root [project] only; already applied to targets)
+    #[serde(default)]
+    pub compiler_launcher: Option<String>,
+    /// Workers while compiles are distributed by the launcher (from root [project] only)
+    #[serde(default)]
+    pub launcher_jobs: Option<usize>,
+    /// Directory collecting all executables after a successful build (from root [project] only)
+    #[serde(default)]
+    pub bin_dir: Option<PathBuf>,
+    /// Symlink into bin_dir instead of copying (from root [project] only)
+    #[serde(default)]
+    pub bin_symlinks: bool,
+    /// SSH build hosts for distributed compilation (from root build.toml only)
+    #[serde(default)]
+    pub remote: Option<RemoteConfig>,
+    /// Content-addressed object cache (from root build.toml only)
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+    /// valgrind settings of `ngm test --memcheck` (from root build.toml only)
+    #[serde(default)]
+    pub memcheck: Option<MemcheckConfig>,
+    /// Contents of `ngm package` archives (from root build.toml only)
+    #[serde(default)]
+    pub package: Option<PackageConfig>,
+    /// Prebuilt packages whose libraries are imported as `<name>::<library>` (from root build.toml only)
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencyConfig>,
+    /// oximake.lock entries of the archive dependencies; builds write them (see lock.rs)
+    #[serde(default)]
+    pub locked_packages: Vec<LockedPackage>,
+    /// Named build configurations (from root build.toml only, see preset.rs)
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    /// Compiler family ("gcc", "clang") → version requirement (from root build.toml only, see toolchain.rs)
+    #[serde(default)]
+    pub requires: BTreeMap<String, String>,
+    /// Targets built without -t (from root build.toml only, see dag::default_selection)
+    #[serde(default)]
+    pub default_targets: Vec<String>,
+    /// Every build.toml that was read (root first, then includes)
+    pub config_files: Vec<PathBuf>,
     /// All targets (root + submodules), name → target map
     pub targets: HashMap<String, ResolvedTarget>,
 }
 
 /// Resolved target: paths are absolute
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ResolvedTarget {
     pub name: String,
     pub target_type: TargetType,
     /// Expanded source file paths (globs resolved)
     pub sources: Vec<PathBuf>,
+    /// `sources` and `exclude` patterns as written (relative to `working_dir`), kept so
+    /// snapshots can re-expand the globs (see glob_sources)
+    #[serde(default)]
+    pub source_patterns: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
     pub include_dirs: Vec<PathBuf>,
     pub lib_dirs: Vec<PathBuf>,
     pub libs: Vec<String>,
+    /// macOS frameworks, passed as -framework <name>
+    pub frameworks: Vec<String>,
+    /// Preprocessor definitions, passed as -D<define>
+    pub defines: Vec<String>,
     pub flags: Vec<String>,
     /// C++ standard (e.g. 17 → -std=c++17)
-    pub cxx_standard: Option<u32>,
+    pub cxx_standard: Option<Standard>,
+    /// C standard for .c and .m sources (e.g. 11 → -std=c11)
+    pub c_standard: Option<Standard>,
+    /// -fobjc-arc for .m / .mm sources
+    pub objc_arc: bool,
+    /// Compile/link commands are killed after this many seconds (None = no limit)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Estimated peak link memory in MiB (0 = not counted against link_memory_limit)
+    #[serde(default)]
+    pub link_memory: u64,
+    /// Program the compiler runs under, e.g. "distcc" (root [project] compiler_launcher)
+    #[serde(default)]
+    pub compiler_launcher: Option<String>,
+    /// Extension → compiler rule for sources not built with `compiler` (root `[compilers]`)
+    pub compilers: BTreeMap<String, CompilerRule>,
     pub compiler_flags: Vec<String>,
     pub linker_flags: Vec<String>,
     pub deps: Vec<String>,
+    /// Groups for `--tag` / `--exclude-tag`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Left out of builds without -t (see dag::default_selection)
+    #[serde(default)]
+    pub exclude_from_all: bool,
     pub compiler: Compiler,
+    /// Linker selected via -fuse-ld (None = compiler default)
+    pub linker: Option<Linker>,
     pub output_dir: PathBuf,
+    /// Output file base name, prefix and suffix overrides (see compiler::output_path)
+    pub output_name: Option<String>,
+    pub lib_prefix: Option<String>,
+    pub lib_suffix: Option<String>,
+    /// Libraries: write a pkg-config file (see pkg_config.rs)
+    #[serde(default)]
+    pub pkg_config: bool,
+    /// Libraries: export to CMake consumers (see cmake_export.rs)
+    #[serde(default)]
+    pub cmake_export: bool,
+    /// Executables and shared libs: strip / split debug info (see compiler::strip_debug_info)
+    pub strip: bool,
+    pub split_debug: bool,
+    /// Static libraries: thin archive (see compiler::create_static_lib)
+    #[serde(default)]
+    pub thin_archive: bool,
+    /// Library of a `[dependencies]` package: linked as is, never built (see prebuilt.rs)
+    #[serde(default)]
+    pub prebuilt: bool,
+    /// Custom targets: commands (argument lists) run in `working_dir`
+    pub commands: Vec<Vec<String>>,
+    /// Custom targets: files read by the commands
+    pub inputs: Vec<PathBuf>,
+    /// Custom targets: files written by the commands
+    pub outputs: Vec<PathBuf>,
+    /// Benchmarks and tests: program arguments; benchmarks: runs per `ngm bench`
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+    /// Tests: run timeout and reruns on failure (see test_runner.rs)
+    #[serde(default)]
+    pub test_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// Directory of the target's build.toml
+    pub working_dir: PathBuf,
+}
+
+impl ResolvedTarget {
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout_secs.map(std::time::Duration::from_secs)
+    }
+
+    /// Custom target `<name>_<kind>` that runs `commands` (code generators) for this
+    /// target. This target depends on it, so generation runs before compilation.
+    pub fn generator(&mut self, kind: &str, commands: Vec<Vec<String>>, inputs: Vec<PathBuf>, outputs: Vec<PathBuf>) -> ResolvedTarget {
+        let name = format!("{}_{}", self.name, kind);
+        self.deps.push(name.clone());
+        ResolvedTarget {
+            name,
+            target_type: TargetType::Custom,
+            commands,
+            inputs,
+            outputs,
+            working_dir: self.working_dir.clone(),
+            output_dir: self.output_dir.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Workspace root detection (for GUI: build from root when opening a leaf)
 // ---------------------------------------------------------------------------
 
+/// File that stops the workspace root search: no build.toml above its directory is used.
+pub const ROOT_MARKER_FILE: &str = ".oximake-root";
+
 /// Given a path to a build.toml (e.g. a leaf like `libs/security/build.toml`),
 /// walks up the directory tree and returns the path to the first parent
-/// `build.toml` that lists this file in its `includes`. If found, building
+/// `build.toml` that lists this file in its `includes` or `[workspace] members`. If found, building
 /// should use that root config so all cross-module deps are available (CMake-like).
 /// Returns `None` if no such parent exists (e.g. already at root or not included).
+///
+/// The search stops at a build.toml marked as a workspace root (`workspace = true` or a
+/// `[workspace]` table) and at a directory containing `.oximake-root`, so an unrelated
+/// build.toml further up is never picked. A marked file is its own root.
 pub fn find_workspace_root(current_build_toml: &Path) -> Option<PathBuf> {
     let current = current_build_toml.canonicalize().ok()?;
+    if read_toml(&current).is_some_and(|root| is_workspace_root(&root)) {
+        return None;
+    }
     let mut dir = current.parent()?;
     loop {
         let candidate_root = dir.join("build.toml");
-        if candidate_root.exists() {
-            let content = std::fs::read_to_string(&candidate_root).ok()?;
-            let root: toml::Value = toml::from_str(&content).ok()?;
+        if candidate_root.exists() && candidate_root.canonicalize().ok()? != current {
+            let root = read_toml(&candidate_root)?;
             let includes = root
                 .get("project")
                 .and_then(|p| p.get("includes"))
                 .or_else(|| root.get("includes"))
-                .and_then(|v| v.as_array())?;
-            for inc in includes {
-                let s = inc.as_str()?;
-                let resolved = dir.join(s);
+                .and_then(|v| v.as_array());
+            let vars = file_variables(root.get("variables"), &Variables::new(), &Variables::new()).unwrap_or_default();
+            let included = includes
+                .into_iter()
+                .flatten()
+                .filter_map(|inc| expand(inc.as_str()?, &vars).ok())
+                .map(|s| dir.join(s))
+                .chain(workspace_members(&root, dir, &vars).unwrap_or_default());
+            for resolved in included {
                 if let Ok(canon) = resolved.canonicalize() {
                     if canon == current {
                         return candidate_root.canonicalize().ok();
                     }
                 }
             }
+            if is_workspace_root(&root) {
+                return None;
+            }
+        }
+        if dir.join(ROOT_MARKER_FILE).exists() {
+            return None;
         }
         dir = dir.parent()?;
     }
 }
 
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// `workspace = true` or a `[workspace]` table.
+fn is_workspace_root(root: &toml::Value) -> bool {
+    match root.get("workspace") {
+        Some(toml::Value::Boolean(marked)) => *marked,
+        Some(toml::Value::Table(_)) => true,
+        _ => false,
+    }
+}
+
+fn duplicate_target_error(name: &str, first: &Path, second: &Path) -> String {
+    if first == second {
+        format!("Target '{}' is defined twice in '{}'", name, first.display())
+    } else {
+        format!(
+            "Target '{}' is defined in both '{}' and '{}'; target names must be unique across all build.toml files",
+            name,
+            first.display(),
+            second.display()
+        )
+    }
+}
+
+/// build.toml files of the directories matched by `[workspace] members` (glob patterns
+/// relative to `dir`, e.g. "libs/*"). Matched directories without a build.toml are skipped.
+fn workspace_members(root: &toml::Value, dir: &Path, vars: &Variables) -> Result<Vec<PathBuf>, String> {
+    let Some(members) = root.get("workspace").and_then(|w| w.get("members")).and_then(|m| m.as_array()) else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    for member in members.iter().filter_map(|m| m.as_str()) {
+        let pattern = dir.join(expand(member, vars)?);
+        let entries = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| format!("invalid workspace member pattern '{}': {}", member, e))?;
+        let mut matched: Vec<PathBuf> = entries
+            .flatten()
+            .map(|p| if p.is_dir() { p.join("build.toml") } else { p })
+            .filter(|p| p.is_file())
+            .collect();
+        matched.sort();
+        files.extend(matched);
+    }
+    Ok(files)
+}
+
 // ---------------------------------------------------------------------------
 // Recursive parsing: loads build.toml and all includes
 // ---------------------------------------------------------------------------
@@ -251,59 +1142,243 @@ pub fn find_workspace_root(current_build_toml: &Path) -> Option<PathBuf> {
 /// that root is used instead — single root, CMake-like: build always from root.
 /// If `verbose` is true, submodule load messages are printed.
 pub fn parse_build_file(path: &Path, verbose: bool) -> Result<ResolvedProject, String> {
+    parse_build_file_with_vars(path, verbose, &Variables::new())
+}
+
+/// Like parse_build_file, with `${name}` values given on the command line (`--set name=value`);
+/// they override the `[variables]` tables of all build.toml files.
+pub fn parse_build_file_with_vars(path: &Path, verbose: bool, overrides: &Variables) -> Result<ResolvedProject, String> {
     // Resolve to workspace root when this file is included by a parent (single-root build).
     let path_to_load: std::path::PathBuf = find_workspace_root(path)
         .unwrap_or_else(|| path.to_path_buf());
     if verbose && path_to_load != path {
         println!("[CONFIG] Using workspace root: {} (requested: {})", path_to_load.display(), path.display());
     }
+    parse_build_file_standalone(&path_to_load, verbose, overrides)
+}
 
+/// Like parse_build_file_with_vars, but `path` is the root even if a parent build.toml
+/// includes it (`--no-workspace`).
+pub fn parse_build_file_standalone(path_to_load: &Path, verbose: bool, overrides: &Variables) -> Result<ResolvedProject, String> {
     let mut all_targets: HashMap<String, ResolvedTarget> = HashMap::new();
-    let mut project_name = default_project_name();
-    let mut project_version = default_version();
-    let mut project_cxx_standard: Option<u32> = None;
+    let mut settings = RootSettings {
+        name: default_project_name(),
+        version: default_version(),
+        cxx_standard: None,
+        c_standard: None,
+        linker: None,
+        defines: Vec::new(),
+        compilers: BTreeMap::new(),
+        timeout_secs: None,
+        retry: None,
+        link_memory_limit: None,
+        link_jobs: None,
+        compiler_launcher: None,
+        launcher_jobs: None,
+        bin_dir: None,
+        bin_symlinks: false,
+        remote: None,
+        cache: None,
+        memcheck: None,
+        package: None,
+        dependencies: BTreeMap::new(),
+        presets: BTreeMap::new(),
+        requires: BTreeMap::new(),
+        default_targets: Vec::new(),
+        defaults: DefaultsConfig::default(),
+        default_include_dirs: Vec::new(),
+        config_files: Vec::new(),
+        remove_flags: HashMap::new(),
+        target_files: HashMap::new(),
+        sources: SourceFinder::new(),
+    };
 
-    parse_recursive(&path_to_load, &mut all_targets, &mut project_name, &mut project_version, &mut project_cxx_standard, true, verbose)?;
+    parse_recursive(path_to_load, &mut all_targets, &mut settings, true, verbose, overrides, overrides)?;
     
     // Get base_dir for resolving relative paths in fallback include directories
     let base_dir = path_to_load.parent().unwrap_or_else(|| Path::new("."));
+
+    // [dependencies]: libraries of prebuilt packages, before propagation so dependents get their include dirs
+    if crate::offline::enabled() {
+        if let Some(missing) = crate::offline::missing_downloads(&settings.dependencies, base_dir) {
+            return Err(missing);
+        }
+    }
+    let mut locked = Vec::new();
+    for (name, dependency) in &settings.dependencies {
+        let imported = crate::prebuilt::import(name, dependency, base_dir)?;
+        locked.extend(imported.locked);
+        for target in imported.targets {
+            if all_targets.contains_key(&target.name) {
+                return Err(format!("[dependencies] {}: target '{}' is already defined", name, target.name));
+            }
+            if verbose {
+                println!("[CONFIG] Imported prebuilt target: {}", target.name);
+            }
+            all_targets.insert(target.name.clone(), target);
+        }
+    }
+    crate::lock::verify(base_dir, &locked)?;
     
     // Propagate include directories, libs, and flags from dependencies (CMake INTERFACE propagation)
+    resolve_generated_sources(&mut all_targets)?;
     propagate_dependency_properties(&mut all_targets, base_dir);
 
-    // Root [project] cxx_standard applies to all targets; child overrides are ignored
-    if let Some(std) = project_cxx_standard {
+    // remove_flags: after [defaults] and the flags inherited from dependencies
+    for (name, removed) in &settings.remove_flags {
+        if let Some(target) = all_targets.get_mut(name) {
+            for flags in [&mut target.flags, &mut target.compiler_flags, &mut target.linker_flags] {
+                flags.retain(|f| !removed.contains(f));
+            }
+        }
+    }
+
+    // Root [project] language standards are defaults; a target's own standard takes precedence
+    for target in all_targets.values_mut() {
+        target.cxx_standard = target.cxx_standard.or(settings.cxx_standard);
+        target.c_standard = target.c_standard.or(settings.c_standard);
+    }
+
+    // Root [project] defines apply to all targets, before the target's own
+    if !settings.defines.is_empty() {
         for target in all_targets.values_mut() {
-            target.cxx_standard = Some(std);
+            target.defines.splice(0..0, settings.defines.iter().cloned());
+        }
+    }
+
+    // Root [compilers] rules apply to every target
+    if !settings.compilers.is_empty() {
+        for target in all_targets.values_mut() {
+            target.compilers = settings.compilers.clone();
+        }
+    }
+
+    // Root [project] timeout is the default; a target's own timeout takes precedence
+    for target in all_targets.values_mut() {
+        target.timeout_secs = target.timeout_secs.or(settings.timeout_secs);
+    }
+
+    // Root [project] compiler_launcher wraps the compiles of every target
+    if let Some(launcher) = &settings.compiler_launcher {
+        for target in all_targets.values_mut() {
+            target.compiler_launcher = Some(launcher.clone());
+        }
+    }
+
+    for name in &settings.default_targets {
+        if !all_targets.contains_key(name) {
+            return Err(format!("[project] default_targets: unknown target '{}'", name));
+        }
+    }
+
+    // Root [project] linker is the default; a target's own linker takes precedence
+    if let Some(linker) = &settings.linker {
+        for target in all_targets.values_mut() {
+            if target.linker.is_none() {
+                target.linker = Some(linker.clone());
+            }
         }
     }
 
     Ok(ResolvedProject {
-        name: project_name,
-        version: project_version,
-        cxx_standard: project_cxx_standard,
+        name: settings.name,
+        version: settings.version,
+        root_dir: if base_dir.as_os_str().is_empty() { PathBuf::from(".") } else { base_dir.to_path_buf() },
+        cxx_standard: settings.cxx_standard,
+        c_standard: settings.c_standard,
+        linker: settings.linker,
+        defines: settings.defines,
+        compilers: settings.compilers,
+        timeout_secs: settings.timeout_secs,
+        retry: settings.retry,
+        link_memory_limit: settings.link_memory_limit,
+        link_jobs: settings.link_jobs,
+        compiler_launcher: settings.compiler_launcher,
+        launcher_jobs: settings.launcher_jobs,
+        bin_dir: settings.bin_dir,
+        bin_symlinks: settings.bin_symlinks,
+        remote: settings.remote,
+        cache: settings.cache,
+        memcheck: settings.memcheck,
+        package: settings.package,
+        dependencies: settings.dependencies,
+        locked_packages: locked,
+        presets: settings.presets,
+        requires: settings.requires,
+        default_targets: settings.default_targets,
+        config_files: settings.config_files,
         targets: all_targets,
     })
 }
 
+/// Project-wide settings read from the root [project] table only,
+/// plus the list of config files visited while parsing.
+struct RootSettings {
+    name: String,
+    version: String,
+    cxx_standard: Option<Standard>,
+    c_standard: Option<Standard>,
+    linker: Option<Linker>,
+    defines: Vec<String>,
+    compilers: BTreeMap<String, CompilerRule>,
+    timeout_secs: Option<u64>,
+    retry: Option<RetryPolicy>,
+    link_memory_limit: Option<u64>,
+    link_jobs: Option<usize>,
+    compiler_launcher: Option<String>,
+    launcher_jobs: Option<usize>,
+    bin_dir: Option<PathBuf>,
+    bin_symlinks: bool,
+    remote: Option<RemoteConfig>,
+    cache: Option<CacheConfig>,
+    memcheck: Option<MemcheckConfig>,
+    package: Option<PackageConfig>,
+    dependencies: BTreeMap<String, DependencyConfig>,
+    presets: BTreeMap<String, Preset>,
+    requires: BTreeMap<String, String>,
+    default_targets: Vec<String>,
+    defaults: DefaultsConfig,
+    /// [defaults] include_dirs, resolved against the root build.toml directory
+    default_include_dirs: Vec<PathBuf>,
+    config_files: Vec<PathBuf>,
+    /// Target name → its remove_flags, applied after dependency propagation
+    remove_flags: HashMap<String, Vec<String>>,
+    /// Target name → build.toml defining it (canonical), to detect name collisions
+    target_files: HashMap<String, PathBuf>,
+    /// Directory listings shared by the source globs of all targets
+    sources: SourceFinder,
+}
+
 /// Recursive parse: reads a build.toml, resolves its targets, and processes includes.
 fn parse_recursive(
     path: &Path,
     all_targets: &mut HashMap<String, ResolvedTarget>,
-    project_name: &mut String,
-    project_version: &mut String,
-    project_cxx_standard: &mut Option<u32>,
+    settings: &mut RootSettings,
     is_root: bool,
     verbose: bool,
+    inherited: &Variables,
+    overrides: &Variables,
 ) -> Result<(), String> {
+    // A file reached through several includes (e.g. root includes utils, and
+    // libs/security/build.toml also includes utils when loaded via root) is loaded once
+    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if settings.config_files.iter().any(|f| f.canonicalize().is_ok_and(|f| f == canonical_path)) {
+        return Ok(());
+    }
+
     // Read file
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+    settings.config_files.push(path.to_path_buf());
 
     // Parse as TOML: root uses [project], included files use [module]
     let toml_value: toml::Value = toml::from_str(&content)
         .map_err(|e| format!("TOML parse error in '{}': {}", path.display(), e))?;
 
+    // [variables]: inherited from the including file, extended by this one
+    let vars = file_variables(toml_value.get("variables"), inherited, overrides)
+        .map_err(|e| format!("{} in '{}'", e, path.display()))?;
+
     let (base_dir, targets, includes) = if let toml::Value::Table(ref root) = toml_value {
         let targets_array = root.get("target").cloned().unwrap_or_else(|| toml::Value::Array(vec![]));
 
@@ -313,23 +1388,80 @@ fn parse_recursive(
                 let mut proj = toml::Value::Table(toml::map::Map::new());
                 proj.as_table_mut().unwrap().insert("name".to_string(), toml::Value::String("unnamed_project".to_string()));
                 proj.as_table_mut().unwrap().insert("version".to_string(), toml::Value::String("0.1.0".to_string()));
-                if let Some(name) = root.get("name") { proj.as_table_mut().unwrap().insert("name".to_string(), name.clone()); }
-                if let Some(version) = root.get("version") { proj.as_table_mut().unwrap().insert("version".to_string(), version.clone()); }
-                if let Some(includes) = root.get("includes") { proj.as_table_mut().unwrap().insert("includes".to_string(), includes.clone()); }
-                if let Some(cxx) = root.get("cxx_standard") { proj.as_table_mut().unwrap().insert("cxx_standard".to_string(), cxx.clone()); }
+                // Without [project], the project settings are top-level keys
+                for spec in crate::validate::PROJECT_KEYS {
+                    if let Some(value) = root.get(spec.name) {
+                        proj.as_table_mut().unwrap().insert(spec.name.to_string(), value.clone());
+                    }
+                }
                 proj
             });
             let mut config_table = project_table.as_table().unwrap().clone();
             config_table.insert("target".to_string(), targets_array);
+            // [compilers], [remote], [cache], [memcheck], [package], [dependencies], [presets] and [defaults] are top-level tables even when [project] exists
+            for table in ["compilers", "remote", "cache", "memcheck", "package", "dependencies", "presets", "defaults"] {
+                if let Some(value) = root.get(table) {
+                    config_table.entry(table).or_insert_with(|| value.clone());
+                }
+            }
             let config: ProjectConfig = toml::from_str(&toml::to_string(&toml::Value::Table(config_table))
                 .map_err(|e| format!("TOML serialize error in '{}': {}", path.display(), e))?)
                 .map_err(|e| format!("TOML deserialize error in '{}': {}", path.display(), e))?;
-            *project_name = config.name.clone();
-            *project_version = config.version.clone();
+            settings.name = config.name.clone();
+            settings.version = config.version.clone();
             if config.cxx_standard.is_some() {
-                *project_cxx_standard = config.cxx_standard;
+                settings.cxx_standard = config.cxx_standard;
+            }
+            if config.c_standard.is_some() {
+                settings.c_standard = config.c_standard;
+            }
+            if config.linker.is_some() {
+                settings.linker = config.linker.clone();
             }
+            settings.defines = expand_all(&config.defines, &vars)
+                .map_err(|e| format!("[project] defines: {} in '{}'", e, path.display()))?;
+            settings.timeout_secs = config.timeout_secs;
+            if let Some(retry) = &config.retry {
+                retry.matcher().map_err(|e| format!("[project] retry: {} in '{}'", e, path.display()))?;
+            }
+            settings.retry = config.retry.clone();
+            settings.link_memory_limit = config.link_memory_limit;
+            settings.link_jobs = config.link_jobs;
+            settings.compiler_launcher = config.compiler_launcher.clone();
+            settings.launcher_jobs = config.launcher_jobs;
             let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+            settings.bin_dir = config.bin_dir.as_ref().map(|dir| base_dir.join(dir));
+            settings.bin_symlinks = config.bin_symlinks;
+            settings.remote = config.remote.clone();
+            settings.cache = config.cache.clone();
+            settings.memcheck = config.memcheck.clone();
+            settings.package = config.package.clone();
+            settings.dependencies = config.dependencies.clone();
+            for name in config.presets.keys() {
+                crate::preset::check_name(name).map_err(|e| format!("[presets] {} in '{}'", e, path.display()))?;
+            }
+            settings.presets = config.presets.clone();
+            for (family, requirement) in &config.requires {
+                if !crate::toolchain::FAMILIES.contains(&family.as_str()) {
+                    return Err(format!(
+                        "[project] requires: unknown compiler '{}' (expected one of {}) in '{}'",
+                        family,
+                        crate::toolchain::FAMILIES.join(", "),
+                        path.display()
+                    ));
+                }
+                crate::toolchain::VersionReq::parse(requirement)
+                    .map_err(|e| format!("[project] requires.{}: {} in '{}'", family, e, path.display()))?;
+            }
+            settings.requires = config.requires.clone();
+            settings.default_targets = config.default_targets.clone();
+            settings.default_include_dirs = expand_all(&config.defaults.include_dirs, &vars)
+                .map_err(|e| format!("[defaults] include_dirs: {} in '{}'", e, path.display()))?
+                .iter()
+                .map(|d| base_dir.join(d))
+                .collect();
+            settings.defaults = config.defaults.clone();
+            settings.compilers = config.compilers.into_iter().map(|(ext, rule)| (ext.trim_start_matches('.').to_string(), rule)).collect();
             (base_dir, config.targets, config.includes)
         } else {
             // Included file: use [module] (fallback to [project] for backward compat)
@@ -354,14 +1486,33 @@ fn parse_recursive(
         return Err(format!("Invalid TOML structure in '{}'", path.display()));
     };
 
-    // Resolve and add each target
+    // Resolve and add each target; target names must be unique across all files
     for target in &targets {
-        let resolved = resolve_target(target, base_dir)?;
-        // Allow same target to appear from multiple includes (e.g. root includes utils, and
-        // libs/security/build.toml also includes utils when loaded via root). First definition wins.
-        if !all_targets.contains_key(&resolved.name) {
-            all_targets.insert(resolved.name.clone(), resolved);
+        if let Some(file) = settings.target_files.get(&target.name) {
+            return Err(duplicate_target_error(&target.name, file, &canonical_path));
+        }
+        let mut resolved = resolve_target(&target.with_defaults(&settings.defaults), base_dir, &vars, &settings.sources)?;
+        let removed = expand_all(&target.with_platform_overrides().remove_flags, &vars)
+            .map_err(|e| format!("target '{}': remove_flags: {}", target.name, e))?;
+        if !removed.is_empty() {
+            settings.remove_flags.insert(target.name.clone(), removed);
+        }
+        for dir in &settings.default_include_dirs {
+            if !matches!(resolved.target_type, TargetType::Custom | TargetType::Group) && !resolved.include_dirs.contains(dir) {
+                resolved.include_dirs.push(dir.clone());
+            }
         }
+        let generators = generator_targets(target, &mut resolved)?;
+        for generated in generators.iter().map(|g| &g.name).chain([&resolved.name]) {
+            if let Some(file) = settings.target_files.get(generated) {
+                return Err(duplicate_target_error(generated, file, &canonical_path));
+            }
+            settings.target_files.insert(generated.clone(), canonical_path.clone());
+        }
+        for generator in generators {
+            all_targets.insert(generator.name.clone(), generator);
+        }
+        all_targets.insert(resolved.name.clone(), resolved);
     }
 
     // Debug: check if includes are being parsed
@@ -375,6 +1526,7 @@ fn parse_recursive(
 
     // Recursively load submodule build.toml files
     for include_path in &includes {
+        let include_path = &expand(include_path, &vars).map_err(|e| format!("{} in '{}'", e, path.display()))?;
         let full_path = base_dir.join(include_path);
         let canonical = full_path.canonicalize().map_err(|e| {
             format!(
@@ -392,7 +1544,16 @@ fn parse_recursive(
             );
         }
 
-        parse_recursive(&canonical, all_targets, project_name, project_version, project_cxx_standard, false, verbose)?;
+        parse_recursive(&canonical, all_targets, settings, false, verbose, &vars, overrides)?;
+    }
+
+    // [workspace] members: build.toml files found by glob, loaded like includes
+    for member in workspace_members(&toml_value, base_dir, &vars).map_err(|e| format!("{} in '{}'", e, path.display()))? {
+        let canonical = member.canonicalize().unwrap_or(member);
+        if verbose {
+            println!("[CONFIG] Loading workspace member: {}", canonical.display());
+        }
+        parse_recursive(&canonical, all_targets, settings, false, verbose, &vars, overrides)?;
     }
 
     Ok(())
@@ -411,9 +1572,11 @@ fn propagate_dependency_properties(all_targets: &mut HashMap<String, ResolvedTar
             let target = all_targets.get(&target_name).unwrap().clone();
             let mut new_include_dirs = target.include_dirs.clone();
             let mut new_libs = target.libs.clone();
+            let mut new_frameworks = target.frameworks.clone();
             let mut new_flags = target.flags.clone();
             let mut new_compiler_flags = target.compiler_flags.clone();
             let mut new_linker_flags = target.linker_flags.clone();
+            let mut new_defines = target.defines.clone();
             let mut target_changed = false;
             
             // For each dependency, add its include_dirs, libs, flags, compiler_flags, linker_flags
@@ -433,6 +1596,12 @@ fn propagate_dependency_properties(all_targets: &mut HashMap<String, ResolvedTar
                             target_changed = true;
                         }
                     }
+                    for framework in &dep.frameworks {
+                        if !new_frameworks.contains(framework) {
+                            new_frameworks.push(framework.clone());
+                            target_changed = true;
+                        }
+                    }
                     // Add dependency's flags
                     for flag in &dep.flags {
                         if !new_flags.contains(flag) {
@@ -452,6 +1621,15 @@ fn propagate_dependency_properties(all_targets: &mut HashMap<String, ResolvedTar
                             target_changed = true;
                         }
                     }
+                    // An interface library's defines are usage requirements of its dependents
+                    if dep.target_type == TargetType::Interface {
+                        for define in &dep.defines {
+                            if !new_defines.contains(define) {
+                                new_defines.push(define.clone());
+                                target_changed = true;
+                            }
+                        }
+                    }
                 } else {
                     // Dependency not found - might be an external library or a target created by a function
                     // For known library patterns, add common include directories
@@ -480,73 +1658,207 @@ fn propagate_dependency_properties(all_targets: &mut HashMap<String, ResolvedTar
                 if let Some(target_mut) = all_targets.get_mut(&target_name) {
                     target_mut.include_dirs = new_include_dirs;
                     target_mut.libs = new_libs;
+                    target_mut.frameworks = new_frameworks;
                     target_mut.flags = new_flags;
                     target_mut.compiler_flags = new_compiler_flags;
                     target_mut.linker_flags = new_linker_flags;
+                    target_mut.defines = new_defines;
                 }
             }
         }
     }
 }
 
+/// Custom targets generating sources for `resolved` (flex/bison, Qt moc/uic/rcc, protoc); the generated
+/// sources, include dirs and the dependency on the generator are added to `resolved`.
+fn generator_targets(target: &TargetConfig, resolved: &mut ResolvedTarget) -> Result<Vec<ResolvedTarget>, String> {
+    let mut generators = Vec::new();
+    generators.extend(crate::grammar::generator(resolved));
+    if let Some(qt) = &target.qt {
+        generators.extend(crate::qt::generator(qt, resolved)?);
+    }
+    if let Some(protobuf) = &target.protobuf {
+        generators.extend(crate::protobuf::generator(protobuf, resolved)?);
+    }
+    Ok(generators)
+}
+
+/// Sources and custom-target inputs that are outputs of a custom target make the
+/// target depend on it, so generation runs before compilation. Any other source or
+/// include directory that doesn't exist is an error; all of them are reported at once.
+/// Include directories below an output directory or containing generated files are
+/// created by the build and not checked.
+fn resolve_generated_sources(all_targets: &mut HashMap<String, ResolvedTarget>) -> Result<(), String> {
+    let generated: HashMap<PathBuf, String> = all_targets
+        .values()
+        .filter(|t| t.target_type == TargetType::Custom)
+        .flat_map(|t| t.outputs.iter().map(|o| (normalize_path(o), t.name.clone())))
+        .collect();
+    let output_dirs: Vec<PathBuf> =
+        all_targets.values().map(|t| normalize_path(&t.output_dir)).filter(|d| !d.as_os_str().is_empty()).collect();
+    let mut missing = Vec::new();
+    for target in all_targets.values_mut() {
+        let mut generators = Vec::new();
+        for source in &target.sources {
+            if let Some(generator) = generated.get(&normalize_path(source)) {
+                generators.push(generator.clone());
+            } else if !source.exists() {
+                missing.push((target.name.clone(), format!("source file not found: {}", source.display())));
+            }
+        }
+        for dir in &target.include_dirs {
+            let dir_path = normalize_path(dir);
+            let created = output_dirs.iter().any(|o| dir_path.starts_with(o)) || generated.keys().any(|g| g.starts_with(&dir_path));
+            if !created && !dir.is_dir() {
+                missing.push((target.name.clone(), format!("include directory not found: {}", dir.display())));
+            }
+        }
+        generators.extend(target.inputs.iter().filter_map(|i| generated.get(&normalize_path(i)).cloned()));
+        for generator in generators {
+            if generator != target.name && !target.deps.contains(&generator) {
+                target.deps.push(generator);
+            }
+        }
+    }
+    if missing.is_empty() {
+        return Ok(());
+    }
+    missing.sort();
+    let lines: Vec<String> = missing.iter().map(|(target, problem)| format!("  target '{}': {}", target, problem)).collect();
+    Err(format!("{} missing path{}:\n{}", missing.len(), if missing.len() == 1 { "" } else { "s" }, lines.join("\n")))
+}
+
+/// `path` with `.` and `dir/..` components removed (without touching the file system),
+/// so differently spelled paths to the same generated file compare equal.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Argument list running `command` through the platform shell.
+fn shell_command(command: &str) -> Vec<String> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    vec![shell.to_string(), flag.to_string(), command.to_string()]
+}
+
+/// Custom target inputs: glob patterns are expanded, other paths are kept as-is
+/// (they may be produced by another target).
+fn expand_inputs(patterns: &[String], base_dir: &Path, sources: &SourceFinder) -> Result<Vec<PathBuf>, String> {
+    let mut inputs = Vec::new();
+    for pattern in patterns {
+        let path = base_dir.join(pattern);
+        if pattern.contains(['*', '?', '[']) {
+            let entries = sources.glob(&path).map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+            inputs.extend(entries);
+        } else {
+            inputs.push(path);
+        }
+    }
+    Ok(inputs)
+}
+
+/// Files the glob patterns among `patterns` match below `base_dir`, without those matching
+/// an `exclude` pattern. Direct paths are left out: they don't change with the directory tree.
+pub fn glob_sources(patterns: &[String], exclude: &[String], base_dir: &Path, sources: &SourceFinder) -> Result<Vec<PathBuf>, String> {
+    let excluded = exclude_patterns(exclude)?;
+    let mut matched = Vec::new();
+    for pattern in patterns.iter().filter(|p| p.contains(['*', '?', '['])) {
+        let entries = sources.glob(&base_dir.join(pattern)).map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+        matched.extend(entries);
+    }
+    matched.retain(|source| !is_excluded(source, base_dir, &excluded));
+    Ok(matched)
+}
+
+/// Compiles `exclude` patterns (relative to the target's build.toml directory).
+pub fn exclude_patterns(patterns: &[String]) -> Result<Vec<glob::Pattern>, String> {
+    patterns
+        .iter()
+        .map(|p| glob::Pattern::new(p).map_err(|e| format!("invalid exclude pattern '{}': {}", p, e)))
+        .collect()
+}
+
+/// True if `source` (below `base_dir`) matches one of the exclude patterns.
+/// `*` stays within a directory; `**` spans directories.
+fn is_excluded(source: &Path, base_dir: &Path, patterns: &[glob::Pattern]) -> bool {
+    let relative = source.strip_prefix(base_dir).unwrap_or(source);
+    let options = glob::MatchOptions { require_literal_separator: true, ..Default::default() };
+    patterns.iter().any(|p| p.matches_path_with(relative, options))
+}
+
+/// Expands `${name}` and `${env:NAME}` references in the target's paths, flags and libs.
+/// Per-compiler flag tables are reduced to the flags for the target's compiler.
+fn interpolate_target(target: &TargetConfig, vars: &Variables) -> Result<TargetConfig, String> {
+    let err = |e: String| format!("target '{}': {}", target.name, e);
+    Ok(TargetConfig {
+        sources: expand_all(&target.sources, vars).map_err(err)?,
+        exclude: expand_all(&target.exclude, vars).map_err(err)?,
+        include_dirs: expand_all(&target.include_dirs, vars).map_err(err)?,
+        lib_dirs: expand_all(&target.lib_dirs, vars).map_err(err)?,
+        libs: expand_all(&target.libs, vars).map_err(err)?,
+        frameworks: expand_all(&target.frameworks, vars).map_err(err)?,
+        defines: expand_all(&target.defines, vars).map_err(err)?,
+        flags: FlagList::List(expand_all(&target.flags.select(&target.compiler()), vars).map_err(err)?),
+        compiler_flags: FlagList::List(expand_all(&target.compiler_flags.select(&target.compiler()), vars).map_err(err)?),
+        linker_flags: FlagList::List(expand_all(&target.linker_flags.select(&target.compiler()), vars).map_err(err)?),
+        output_dir: target.output_dir.as_deref().map(|d| expand(d, vars)).transpose().map_err(err)?,
+        output_name: target.output_name.as_deref().map(|n| expand(n, vars)).transpose().map_err(err)?,
+        commands: expand_all(&target.commands, vars).map_err(err)?,
+        inputs: expand_all(&target.inputs, vars).map_err(err)?,
+        outputs: expand_all(&target.outputs, vars).map_err(err)?,
+        protobuf: target.protobuf.as_ref().map(|p| p.interpolate(vars)).transpose().map_err(err)?,
+        ..target.clone()
+    })
+}
+
 /// Converts a TargetConfig to ResolvedTarget. Expands globs and makes paths absolute.
 /// Note: This is called during parsing, before all targets are available, so dependency
 /// propagation happens later in a separate pass.
-fn resolve_target(target: &TargetConfig, base_dir: &Path) -> Result<ResolvedTarget, String> {
+fn resolve_target(target: &TargetConfig, base_dir: &Path, vars: &Variables, sources: &SourceFinder) -> Result<ResolvedTarget, String> {
+    let target = &interpolate_target(&target.with_platform_overrides(), vars)?;
     // Source files: expand glob patterns
     let mut resolved_sources = Vec::new();
     for pattern in &target.sources {
         let full_pattern = base_dir.join(pattern);
-        let pattern_str = full_pattern.to_string_lossy().to_string();
 
         // Check if pattern contains glob characters
         let has_glob = pattern.contains('*') || pattern.contains('?') || pattern.contains('[');
         
         if has_glob {
-            // It's a glob pattern
-            let entries = glob::glob(&pattern_str)
+            // It's a glob pattern; directory listings are shared across targets
+            let entries = sources
+                .glob(&full_pattern)
                 .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
-
-            let mut found = false;
-            for entry in entries {
-                match entry {
-                    Ok(path) => {
-                        resolved_sources.push(path);
-                        found = true;
-                    }
-                    Err(e) => {
-                        eprintln!(
-                            "[WARN] Glob entry read failed '{}': {}",
-                            pattern, e
-                        );
-                    }
-                }
-            }
-
-            if !found {
+            if entries.is_empty() {
                 eprintln!(
                     "[WARN] No source files for pattern '{}' (target: '{}')",
                     pattern, target.name
                 );
             }
+            resolved_sources.extend(entries);
         } else {
-            // It's a direct file path, check if it exists
-            if full_pattern.exists() {
-                resolved_sources.push(full_pattern);
-            } else {
-                // Try relative to base_dir as-is
-                if base_dir.join(pattern).exists() {
-                    resolved_sources.push(base_dir.join(pattern));
-                } else {
-                    eprintln!(
-                        "[WARN] Source file not found: '{}' (target: '{}')",
-                        pattern, target.name
-                    );
-                }
-            }
+            // A direct file path. It may not exist yet (output of a custom target);
+            // resolve_generated_sources checks it once all targets are known.
+            resolved_sources.push(full_pattern);
         }
     }
 
+    if !target.exclude.is_empty() {
+        let excluded = exclude_patterns(&target.exclude)
+            .map_err(|e| format!("target '{}': {}", target.name, e))?;
+        resolved_sources.retain(|source| !is_excluded(source, base_dir, &excluded));
+    }
+
     // Make include dirs absolute
     let resolved_include_dirs: Vec<PathBuf> = target
         .include_dirs
@@ -562,21 +1874,332 @@ fn resolve_target(target: &TargetConfig, base_dir: &Path) -> Result<ResolvedTarg
         .collect();
 
     // Make output dir absolute
-    let resolved_output_dir = base_dir.join(&target.output_dir);
+    let resolved_output_dir = base_dir.join(target.output_dir.clone().unwrap_or_else(default_output_dir));
 
     Ok(ResolvedTarget {
         name: target.name.clone(),
         target_type: target.target_type.clone(),
         sources: resolved_sources,
+        source_patterns: target.sources.clone(),
+        exclude: target.exclude.clone(),
         include_dirs: resolved_include_dirs,
         lib_dirs: resolved_lib_dirs,
         libs: target.libs.clone(),
-        flags: target.flags.clone(),
+        frameworks: target.frameworks.clone(),
+        defines: target.defines.clone(),
+        flags: target.flags.select(&target.compiler()),
         cxx_standard: target.cxx_standard,
-        compiler_flags: target.compiler_flags.clone(),
-        linker_flags: target.linker_flags.clone(),
+        c_standard: target.c_standard,
+        objc_arc: target.objc_arc.unwrap_or(false),
+        timeout_secs: target.timeout_secs,
+        link_memory: target.link_memory.unwrap_or(0),
+        compiler_launcher: None,
+        compilers: BTreeMap::new(),
+        compiler_flags: target.compiler_flags.select(&target.compiler()),
+        linker_flags: target.linker_flags.select(&target.compiler()),
         deps: target.deps.clone(),
-        compiler: target.compiler.clone(),
+        tags: target.tags.clone(),
+        exclude_from_all: target.exclude_from_all,
+        compiler: target.compiler(),
+        linker: target.linker.clone(),
         output_dir: resolved_output_dir,
+        output_name: target.output_name.clone(),
+        lib_prefix: target.lib_prefix.clone(),
+        lib_suffix: target.lib_suffix.clone(),
+        pkg_config: target.pkg_config,
+        cmake_export: target.cmake_export,
+        strip: target.strip.unwrap_or(false),
+        split_debug: target.split_debug.unwrap_or(false),
+        thin_archive: target.thin_archive,
+        prebuilt: false,
+        commands: target.commands.iter().map(|c| shell_command(c)).collect(),
+        inputs: expand_inputs(&target.inputs, base_dir, sources)?,
+        outputs: target.outputs.iter().map(|o| base_dir.join(o)).collect(),
+        args: target.args.clone(),
+        repetitions: target.repetitions,
+        test_timeout_secs: target.test_timeout_secs,
+        retries: target.retries,
+        working_dir: if base_dir.as_os_str().is_empty() { PathBuf::from(".") } else { base_dir.to_path_buf() },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_top_level_settings_without_project_table() {
+        let dir = TempDir::new("toplevel");
+        let settings = [
+            "name = \"demo\"",
+            "version = \"2.1.0\"",
+            "cxx_standard = 20",
+            "c_standard = \"gnu11\"",
+            "linker = \"lld\"",
+            "defines = [\"NDEBUG\"]",
+            "link_memory_limit = 4096",
+            "link_jobs = 2",
+            "timeout_secs = 30",
+        ];
+        let text = format!("{}\n\n[[target]]\nname = \"app\"\ntype = \"interface\"\n", settings.join("\n"));
+        dir.write("build.toml", text);
+        let project = parse_build_file(&dir.join("build.toml"), false).unwrap();
+        assert_eq!((project.name.as_str(), project.version.as_str()), ("demo", "2.1.0"));
+        assert_eq!(project.cxx_standard, Some(Standard { version: 20, gnu: false }));
+        assert_eq!(project.c_standard, Some(Standard { version: 11, gnu: true }));
+        assert_eq!(project.linker, Some(Linker::Lld));
+        assert_eq!(project.defines, vec!["NDEBUG"]);
+        assert_eq!(project.link_memory_limit, Some(4096));
+        assert_eq!(project.link_jobs, Some(2));
+        assert_eq!(project.targets["app"].timeout_secs, Some(30));
+    }
+
+    #[test]
+    fn test_platform_overrides_extend_lists_and_replace_values() {
+        let config: ProjectConfig = toml::from_str(
+            r#"
+            [[target]]
+            name = "app"
+            libs = ["m"]
+            output_dir = "build"
+
+            [target.linux]
+            libs = ["pthread"]
+            output_dir = "build-linux"
+
+            [target.macos]
+            libs = ["c++"]
+            output_dir = "build-mac"
+
+            [target.windows]
+            libs = ["ws2_32"]
+            output_dir = "build-win"
+            "#,
+        )
+        .unwrap();
+        let target = config.targets[0].with_platform_overrides();
+        let (lib, dir) = match std::env::consts::OS {
+            "linux" => ("pthread", "build-linux"),
+            "macos" => ("c++", "build-mac"),
+            "windows" => ("ws2_32", "build-win"),
+            _ => return,
+        };
+        assert_eq!(target.libs, vec!["m".to_string(), lib.to_string()]);
+        assert_eq!(target.output_dir.as_deref(), Some(dir));
+    }
+
+    #[test]
+    fn test_per_compiler_flags_follow_the_target_compiler() {
+        let config: ProjectConfig = toml::from_str(
+            r#"
+            [[target]]
+            name = "app"
+            compiler = "clang"
+            flags = ["-g"]
+
+            [target.compiler_flags]
+            all = ["-O2"]
+            gcc = ["-Wno-maybe-uninitialized"]
+            clang = ["-Wno-unused-private-field"]
+            "#,
+        )
+        .unwrap();
+        let target = &config.targets[0];
+        assert_eq!(target.flags.select(&target.compiler()), vec!["-g".to_string()]);
+        assert_eq!(target.compiler_flags.select(&Compiler::Clang), vec!["-O2".to_string(), "-Wno-unused-private-field".to_string()]);
+        assert_eq!(target.compiler_flags.select(&Compiler::Gpp), vec!["-O2".to_string(), "-Wno-maybe-uninitialized".to_string()]);
+        let merged = target.flags.merged(&target.compiler_flags);
+        assert_eq!(merged.select(&Compiler::Gcc), vec!["-g".to_string(), "-O2".to_string(), "-Wno-maybe-uninitialized".to_string()]);
+    }
+
+    #[test]
+    fn test_exclude_patterns_match_relative_to_the_target_directory() {
+        let patterns = exclude_patterns(&["src/experimental/**".to_string(), "**/*_win.cpp".to_string()]).unwrap();
+        let base = Path::new("/proj/lib");
+        assert!(is_excluded(&base.join("src/experimental/a/b.cpp"), base, &patterns));
+        assert!(is_excluded(&base.join("src/io_win.cpp"), base, &patterns));
+        assert!(is_excluded(&base.join("io_win.cpp"), base, &patterns));
+        assert!(!is_excluded(&base.join("src/io.cpp"), base, &patterns));
+        assert!(exclude_patterns(&["src/[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_language_standards_accept_numbers_and_gnu_names() {
+        let config: ProjectConfig = toml::from_str("cxx_standard = 17\nc_standard = \"gnu11\"\n[[target]]\nname = \"app\"\ncxx_standard = \"gnu++20\"").unwrap();
+        assert_eq!(config.cxx_standard.unwrap().flag(true), "-std=c++17");
+        assert_eq!(config.c_standard.unwrap().flag(false), "-std=gnu11");
+        assert_eq!(config.targets[0].cxx_standard.unwrap().flag(true), "-std=gnu++20");
+        assert_eq!(Standard::parse("c++2b").map(|s| s.version).ok(), None);
+    }
+
+    #[test]
+    fn test_generated_sources_depend_on_their_custom_target() {
+        let mut targets = HashMap::new();
+        targets.insert("gen".to_string(), ResolvedTarget {
+            name: "gen".to_string(),
+            target_type: TargetType::Custom,
+            outputs: vec![PathBuf::from("proj/build/gen/parser.cpp")],
+            ..Default::default()
+        });
+        targets.insert("app".to_string(), ResolvedTarget {
+            name: "app".to_string(),
+            sources: vec![PathBuf::from("proj/app/../build/./gen/parser.cpp"), PathBuf::from("proj/app/missing.cpp")],
+            ..Default::default()
+        });
+        assert_eq!(
+            resolve_generated_sources(&mut targets),
+            Err("1 missing path:\n  target 'app': source file not found: proj/app/missing.cpp".to_string())
+        );
+        assert_eq!(targets["app"].deps, vec!["gen".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_paths_are_reported_unless_the_build_creates_them() {
+        let dir = TempDir::new("generated_paths");
+        let source = dir.write("app/main.cpp", "");
+        let mut targets = HashMap::new();
+        targets.insert("gen".to_string(), ResolvedTarget {
+            name: "gen".to_string(),
+            target_type: TargetType::Custom,
+            outputs: vec![dir.join("gen/include/schema.h")],
+            ..Default::default()
+        });
+        targets.insert("app".to_string(), ResolvedTarget {
+            name: "app".to_string(),
+            sources: vec![source],
+            // Created by the build: below an output dir, or holding a generated output
+            include_dirs: vec![dir.join("out/include"), dir.join("gen/include")],
+            inputs: vec![dir.join("gen/include/schema.h")],
+            output_dir: dir.join("out"),
+            ..Default::default()
+        });
+        assert_eq!(resolve_generated_sources(&mut targets), Ok(()));
+        assert_eq!(targets["app"].deps, vec!["gen".to_string()]);
+
+        let app = targets.get_mut("app").unwrap();
+        app.sources.push(dir.join("app/util.cpp"));
+        app.include_dirs.push(dir.join("third_party"));
+        assert_eq!(
+            resolve_generated_sources(&mut targets),
+            Err(format!(
+                "2 missing paths:\n  target 'app': include directory not found: {}\n  target 'app': source file not found: {}",
+                dir.join("third_party").display(),
+                dir.join("app/util.cpp").display()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_workspace_members_are_found_by_glob() {
+        let dir = TempDir::new("workspace");
+        for lib in ["a", "b"] {
+            std::fs::create_dir_all(dir.join("libs").join(lib)).unwrap();
+            std::fs::write(dir.join("libs").join(lib).join("build.toml"), format!("[[target]]\nname = \"{}\"\ntype = \"interface\"\n", lib)).unwrap();
+        }
+        std::fs::create_dir_all(dir.join("libs/docs")).unwrap();
+        dir.write("build.toml", "[project]\nname = \"ws\"\n\n[workspace]\nmembers = [\"libs/*\"]\n");
+
+        let project = parse_build_file(&dir.join("build.toml"), false).unwrap();
+        let mut names: Vec<&String> = project.targets.keys().collect();
+        names.sort();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(project.config_files.len(), 3);
+        assert_eq!(find_workspace_root(&dir.join("libs/b/build.toml")), dir.join("build.toml").canonicalize().ok());
+    }
+
+    #[test]
+    fn test_root_markers_stop_the_workspace_search() {
+        let dir = TempDir::new("markers");
+        std::fs::create_dir_all(dir.join("app")).unwrap();
+        let app = dir.join("app/build.toml");
+        dir.write("build.toml", "includes = [\"app/build.toml\"]\n");
+        std::fs::write(&app, "[[target]]\nname = \"app\"\n").unwrap();
+        assert_eq!(find_workspace_root(&app), dir.join("build.toml").canonicalize().ok());
+
+        std::fs::write(dir.join("app").join(ROOT_MARKER_FILE), "").unwrap();
+        assert_eq!(find_workspace_root(&app), None);
+        std::fs::remove_file(dir.join("app").join(ROOT_MARKER_FILE)).unwrap();
+
+        std::fs::write(&app, "workspace = true\n[[target]]\nname = \"app\"\n").unwrap();
+        assert_eq!(find_workspace_root(&app), None);
+    }
+
+    #[test]
+    fn test_same_target_name_in_two_files_is_an_error() {
+        let dir = TempDir::new("duplicate");
+        std::fs::create_dir_all(dir.join("net")).unwrap();
+        dir.write("net/build.toml", "[[target]]\nname = \"util\"\ntype = \"interface\"\n");
+        // net/build.toml is included twice: loaded once, no collision with itself
+        dir.write("build.toml", "includes = [\"net/build.toml\", \"net/../net/build.toml\"]\n");
+        assert!(parse_build_file(&dir.join("build.toml"), false).is_ok());
+
+        dir.write("build.toml", "includes = [\"net/build.toml\"]\n[[target]]\nname = \"util\"\ntype = \"interface\"\n");
+        let err = parse_build_file(&dir.join("build.toml"), false).unwrap_err();
+        assert!(err.contains("Target 'util' is defined in both"), "{}", err);
+        assert!(err.contains(&dir.join("net/build.toml").canonicalize().unwrap().display().to_string()), "{}", err);
+    }
+
+    #[test]
+    fn test_defaults_are_merged_into_every_target() {
+        let dir = TempDir::new("defaults");
+        std::fs::create_dir_all(dir.join("include")).unwrap();
+        std::fs::create_dir_all(dir.join("lib/inc")).unwrap();
+        std::fs::write(
+            dir.join("build.toml"),
+            r#"
+            includes = ["lib/build.toml"]
+            [defaults]
+            compiler_flags = ["-Wall", "-Werror"]
+            include_dirs = ["include"]
+            defines = ["APP"]
+            compiler = "clang"
+            output_dir = "out"
+            [[target]]
+            name = "app"
+            type = "interface"
+            compiler_flags = ["-O2"]
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib/build.toml"),
+            "[[target]]\nname = \"core\"\ntype = \"interface\"\ncompiler = \"gcc\"\nno_default_flags = true\ninclude_dirs = [\"inc\"]\ncompiler_flags = [\"-O3\"]\n",
+        )
+        .unwrap();
+        let project = parse_build_file(&dir.join("build.toml"), false).unwrap();
+        let (app, core) = (&project.targets["app"], &project.targets["core"]);
+        assert_eq!(app.compiler_flags, vec!["-Wall", "-Werror", "-O2"]);
+        assert_eq!((&app.compiler, &core.compiler), (&Compiler::Clang, &Compiler::Gcc));
+        assert_eq!(app.defines, vec!["APP"]);
+        assert_eq!(app.output_dir, dir.join("out"));
+        assert_eq!(core.compiler_flags, vec!["-O3"]);
+        assert_eq!(core.output_dir, dir.join("lib/out"));
+        assert_eq!(core.include_dirs, vec![dir.join("lib/inc"), dir.join("include")]);
+    }
+
+    #[test]
+    fn test_remove_flags_apply_after_defaults_and_dependencies() {
+        let dir = TempDir::new("remove_flags");
+        std::fs::write(
+            dir.join("build.toml"),
+            r#"
+            [defaults]
+            compiler_flags = ["-Wall", "-Werror"]
+            [[target]]
+            name = "warnings"
+            type = "interface"
+            no_default_flags = true
+            compiler_flags = ["-Wextra", "-Werror=return-type"]
+            [[target]]
+            name = "legacy"
+            type = "interface"
+            deps = ["warnings"]
+            remove_flags = ["-Werror", "-Werror=return-type"]
+            "#,
+        )
+        .unwrap();
+        let project = parse_build_file(&dir.join("build.toml"), false).unwrap();
+        assert_eq!(project.targets["legacy"].compiler_flags, vec!["-Wall", "-Wextra"]);
+    }
+}
@@ -0,0 +1,360 @@
+// ============================================================================
+// daemon.rs — `ngm daemon`: resident build server (JSON-RPC over a socket)
+// ============================================================================
+//
+// Keeps the parsed project and its dependency graph in memory and serves
+// newline-delimited JSON-RPC 2.0 requests on a unix socket (default
+// `.oximake/daemon.sock`). The project is reparsed only when a build.toml
+// or a source directory changed or a source glob matches other files (see
+// snapshot.rs), so repeated incremental builds skip TOML parsing entirely.
+//
+// Methods:
+//   build    {targets?, jobs?, clean?, ignore_errors?} → stats; build events are
+//            streamed before the response as `build.event` notifications
+//   clean    {}                                        → {removed: [dirs]}
+//   query    {}                                        → project, targets, build order
+//   reload   {}                                        → forces a reparse
+//   shutdown {}                                        → stops the daemon
+// ============================================================================
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::builder;
+use crate::compiler;
+use crate::config::{self, ResolvedProject};
+use crate::dag::{self, BuildOrder};
+use crate::events::{BuildEvent, BuildStats};
+use crate::history;
+use crate::lock;
+use crate::options::BuildOptions;
+use crate::prune;
+use crate::snapshot;
+use crate::toolchain;
+
+/// Socket file name inside the project state directory.
+pub const SOCKET_NAME: &str = "daemon.sock";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const BUILD_ERROR: i64 = -32000;
+
+/// `.oximake/daemon.sock` for the project rooted at `root_dir`.
+pub fn default_socket_path(root_dir: &Path) -> PathBuf {
+    history::state_dir(root_dir).join(SOCKET_NAME)
+}
+
+// ---------------------------------------------------------------------------
+// Resident project snapshot
+// ---------------------------------------------------------------------------
+
+/// Parsed project plus the modification times it was derived from.
+struct Snapshot {
+    project: ResolvedProject,
+    order: BuildOrder,
+    /// Config files and source directories with their mtime at parse time.
+    /// A new or removed source changes its directory's mtime.
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Source globs and their matches, for new sources in directories without a stamp
+    globs: Vec<snapshot::SourceGlobs>,
+}
+
+impl Snapshot {
+    fn load(config_path: &Path) -> Result<Snapshot, String> {
+        let project = config::parse_build_file(config_path, false)?;
+        let order = dag::build_order(&project)?;
+        let stamps = snapshot::stamps(&project);
+        let globs = snapshot::source_globs(&project);
+        Ok(Snapshot { project, order, stamps, globs })
+    }
+
+    fn is_stale(&self) -> bool {
+        self.stamps.iter().any(|(path, time)| snapshot::mtime(path) != *time) || snapshot::globs_changed(&self.globs)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request handling
+// ---------------------------------------------------------------------------
+
+/// Daemon state shared by all connections.
+pub struct Daemon {
+    config_path: PathBuf,
+    snapshot: Mutex<Option<Snapshot>>,
+    /// Builds and cleans touch the same output dirs; run them one at a time
+    build_lock: Mutex<()>,
+    shutdown: AtomicBool,
+}
+
+/// A request line; `id` is echoed back, `params` depend on the method.
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Parameters of `build`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BuildParams {
+    targets: Option<Vec<String>>,
+    jobs: Option<usize>,
+    clean: bool,
+    ignore_errors: bool,
+}
+
+/// Result of `build`.
+#[derive(Serialize)]
+struct BuildResponse {
+    #[serde(flatten)]
+    stats: BuildStats,
+    wall_ms: u64,
+    reparsed: bool,
+}
+
+fn rpc_result(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn rpc_notification(method: &str, params: impl Serialize) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+impl Daemon {
+    pub fn new(config_path: &Path) -> Daemon {
+        Daemon {
+            config_path: config_path.to_path_buf(),
+            snapshot: Mutex::new(None),
+            build_lock: Mutex::new(()),
+            shutdown: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    /// Returns (project, order, reparsed), reparsing when the snapshot is missing or stale.
+    fn project(&self, force: bool) -> Result<(ResolvedProject, BuildOrder, bool), String> {
+        let mut guard = self.snapshot.lock().unwrap();
+        let reparse = force || guard.as_ref().map(Snapshot::is_stale).unwrap_or(true);
+        if reparse {
+            *guard = Some(Snapshot::load(&self.config_path)?);
+        }
+        let snap = guard.as_ref().unwrap();
+        Ok((snap.project.clone(), snap.order.clone(), reparse))
+    }
+
+    /// Handles one request line. Notifications (build events) are written to `out`
+    /// before the response is returned.
+    pub fn handle_line(&self, line: &str, out: &mut (dyn Write + Send)) -> Value {
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(value) => match Request::deserialize(&value) {
+                Ok(r) => r,
+                Err(e) => return rpc_error(&value["id"], INVALID_REQUEST, &format!("Invalid request: {}", e)),
+            },
+            Err(e) => return rpc_error(&Value::Null, PARSE_ERROR, &format!("Parse error: {}", e)),
+        };
+        let id = &request.id;
+        let Some(method) = request.method.as_deref() else {
+            return rpc_error(id, INVALID_PARAMS, "Missing method");
+        };
+        let result = match method {
+            "build" => self.build(&request.params, out),
+            "clean" => self.clean(),
+            "query" => self.query(),
+            "reload" => self.project(true).map(|(p, _, _)| json!({ "targets": p.targets.len() })),
+            "shutdown" => {
+                self.shutdown.store(true, Ordering::Relaxed);
+                Ok(Value::Null)
+            }
+            other => return rpc_error(id, METHOD_NOT_FOUND, &format!("Unknown method '{}'", other)),
+        };
+        match result {
+            Ok(r) => rpc_result(id, r),
+            Err(e) => rpc_error(id, BUILD_ERROR, &e),
+        }
+    }
+
+    fn build(&self, params: &Value, out: &mut (dyn Write + Send)) -> Result<Value, String> {
+        let BuildParams { targets, jobs, clean, ignore_errors } = match params {
+            Value::Null => BuildParams::default(),
+            params => BuildParams::deserialize(params).map_err(|e| format!("Invalid build params: {}", e))?,
+        };
+
+        let _guard = self.build_lock.lock().unwrap();
+        let (project, full_order, reparsed) = self.project(false)?;
+        let order = match targets.clone().or_else(|| dag::default_selection(&project)) {
+            Some(t) => dag::filter_order_for_targets(&project, &full_order, &t)?,
+            None => full_order,
+        };
+        compiler::verify_linkers(&project, &order.levels.concat())?;
+        toolchain::verify_requirements(&project, &order.levels.concat())?;
+        lock::update(&project.root_dir, &project.locked_packages)?;
+        if clean {
+            remove_output_dirs(&project);
+        }
+
+        let opts = BuildOptions {
+            config: self.config_path.clone(),
+            targets,
+            quiet: true,
+            no_ld_path: true,
+            jobs,
+            ignore_errors,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel::<BuildEvent>();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for event in rx {
+                    let note = rpc_notification("build.event", &event);
+                    let _ = writeln!(out, "{}", note);
+                    let _ = out.flush();
+                }
+            });
+            builder::build_project(&project, &order, &opts, Some(tx), None)
+        });
+        let _ = history::record_build(&project.root_dir, &result);
+
+        let stats = BuildStats {
+            success: result.success,
+            total: result.total_targets,
+            successful: result.successful_targets,
+            failed: result.failed_targets,
+            warnings: result.diagnostic_totals().warnings,
+            errors: result.diagnostic_totals().errors,
+        };
+        let response = BuildResponse { stats, wall_ms: result.wall_time.as_millis() as u64, reparsed };
+        serde_json::to_value(response).map_err(|e| e.to_string())
+    }
+
+    fn clean(&self) -> Result<Value, String> {
+        let _guard = self.build_lock.lock().unwrap();
+        let (project, _, _) = self.project(false)?;
+        let removed: Vec<String> = remove_output_dirs(&project).iter().map(|d| d.display().to_string()).collect();
+        Ok(json!({ "removed": removed }))
+    }
+
+    fn query(&self) -> Result<Value, String> {
+        let (project, order, _) = self.project(false)?;
+        let targets: Vec<Value> = order
+            .levels
+            .iter()
+            .flatten()
+            .filter_map(|name| project.targets.get(name))
+            .map(|t| {
+                json!({
+                    "name": t.name,
+                    "type": t.target_type.as_str(),
+                    "deps": t.deps,
+                    "sources": t.sources.len(),
+                })
+            })
+            .collect();
+        Ok(json!({
+            "name": project.name,
+            "version": project.version,
+            "targets": targets,
+            "levels": order.levels,
+        }))
+    }
+}
+
+fn remove_output_dirs(project: &ResolvedProject) -> Vec<PathBuf> {
+    prune::output_dirs(project)
+        .into_iter()
+        .filter(|d| d.exists() && std::fs::remove_dir_all(d).is_ok())
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Socket server
+// ---------------------------------------------------------------------------
+
+/// Serves requests on `socket_path` until a `shutdown` request arrives.
+/// Each connection is handled on its own thread; one request per line.
+#[cfg(unix)]
+pub fn serve(daemon: Arc<Daemon>, socket_path: &Path) -> Result<(), String> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if let Some(dir) = socket_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).is_ok() {
+            return Err(format!("A daemon is already listening on '{}'", socket_path.display()));
+        }
+        // Left over from a daemon that did not shut down cleanly
+        let _ = std::fs::remove_file(socket_path);
+    }
+    let listener = UnixListener::bind(socket_path)
+        .map_err(|e| format!("Failed to bind '{}': {}", socket_path.display(), e))?;
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        if daemon.is_shutting_down() {
+            break;
+        }
+        let daemon = Arc::clone(&daemon);
+        let socket_path = socket_path.to_path_buf();
+        std::thread::spawn(move || {
+            let Ok(reader) = stream.try_clone() else { return };
+            let mut writer = stream;
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let response = daemon.handle_line(&line, &mut writer);
+                if writeln!(writer, "{}", response).is_err() {
+                    break;
+                }
+                if daemon.is_shutting_down() {
+                    // Wake the accept loop so it sees the flag
+                    let _ = UnixStream::connect(&socket_path);
+                    break;
+                }
+            }
+        });
+    }
+    let _ = std::fs::remove_file(socket_path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve(_daemon: Arc<Daemon>, _socket_path: &Path) -> Result<(), String> {
+    Err("ngm daemon currently requires unix domain sockets (not available on this platform)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_method_and_bad_json() {
+        let daemon = Daemon::new(Path::new("does-not-exist.toml"));
+        let mut out: Vec<u8> = Vec::new();
+        let resp = daemon.handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"nope"}"#, &mut out);
+        assert_eq!(resp["id"], json!(7));
+        assert_eq!(resp["error"]["code"], json!(METHOD_NOT_FOUND));
+        let resp = daemon.handle_line("{not json", &mut out);
+        assert_eq!(resp["error"]["code"], json!(PARSE_ERROR));
+    }
+}
@@ -3,12 +3,13 @@
 // ============================================================================
 //
 // This module models target dependencies as a DAG and performs topological
-// ordering with Kahn's algorithm. Cyclic dependencies are reported as errors.
+// ordering with Kahn's algorithm. Cyclic dependencies are reported as errors
+// naming one concrete cycle (app → net → core → app).
 // ============================================================================
 
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::config::ResolvedProject;
+use crate::config::{ResolvedProject, TargetType};
 
 // ---------------------------------------------------------------------------
 // Topological order result: build levels
@@ -52,6 +53,32 @@ pub fn build_order(project: &ResolvedProject) -> Result<BuildOrder, String> {
         }
     }
 
+    let levels = kahn_levels(project);
+
+    // --- Step 4: Cycle check ---
+    let processed_count: usize = levels.iter().map(Vec::len).sum();
+    if processed_count != targets.len() {
+        let cycle = find_cycle(project).unwrap_or_default();
+        let processed: HashSet<String> = levels.iter().flatten().cloned().collect();
+        let mut blocked: Vec<&String> = targets
+            .keys()
+            .filter(|k| !processed.contains(k.as_str()) && !cycle.contains(k))
+            .collect();
+        blocked.sort();
+        let mut message = format!("Cyclic dependency detected: {} (→ = depends on)", cycle.join(" → "));
+        if !blocked.is_empty() {
+            message.push_str(&format!("; also not buildable because they depend on a cycle: {:?}", blocked));
+        }
+        return Err(message);
+    }
+
+    Ok(BuildOrder { levels })
+}
+
+/// Levels of Kahn's algorithm. Targets in or depending on a cycle are left out.
+fn kahn_levels(project: &ResolvedProject) -> Vec<Vec<String>> {
+    let targets = &project.targets;
+
     // --- Step 2: Compute in-degrees ---
     // in_degree[x] = number of deps of x (not number of dependents of x)
     let mut in_degree: HashMap<&str, usize> = HashMap::new();
@@ -79,7 +106,6 @@ pub fn build_order(project: &ResolvedProject) -> Result<BuildOrder, String> {
     // --- Step 3: Kahn algorithm, level-by-level ---
     let mut levels: Vec<Vec<String>> = Vec::new();
     let mut queue: VecDeque<&str> = VecDeque::new();
-    let mut processed_count = 0;
 
     // Enqueue nodes with in-degree 0
     for (name, &degree) in &in_degree {
@@ -96,7 +122,6 @@ pub fn build_order(project: &ResolvedProject) -> Result<BuildOrder, String> {
         for _ in 0..level_size {
             let node = queue.pop_front().unwrap();
             current_level.push(node.to_string());
-            processed_count += 1;
 
             // Decrease in-degree of targets that depend on this node
             if let Some(deps) = dependents.get(node) {
@@ -114,22 +139,64 @@ pub fn build_order(project: &ResolvedProject) -> Result<BuildOrder, String> {
         current_level.sort();
         levels.push(current_level);
     }
+    levels
+}
 
-    // --- Step 4: Cycle check ---
-    if processed_count != targets.len() {
-        let processed: HashSet<String> = levels.iter().flatten().cloned().collect();
-        let cyclic: Vec<&String> = targets
-            .keys()
-            .filter(|k| !processed.contains(k.as_str()))
-            .collect();
+/// One dependency cycle as target names, first name repeated at the end
+/// (`["app", "net", "core", "app"]`: app depends on net, ...). Targets and deps are
+/// visited in name order, so the same project always reports the same cycle.
+pub fn find_cycle(project: &ResolvedProject) -> Option<Vec<String>> {
+    // 0 = unvisited, 1 = on the current path, 2 = done
+    fn visit<'a>(project: &'a ResolvedProject, name: &'a str, state: &mut HashMap<&'a str, u8>, path: &mut Vec<&'a str>) -> Option<Vec<String>> {
+        state.insert(name, 1);
+        path.push(name);
+        let mut deps: Vec<&String> = project.targets.get(name).map(|t| t.deps.iter().collect()).unwrap_or_default();
+        deps.sort();
+        for dep in deps {
+            match state.get(dep.as_str()).copied().unwrap_or(0) {
+                1 => {
+                    let start = path.iter().position(|n| *n == dep.as_str()).unwrap_or(0);
+                    let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+                    cycle.push(dep.clone());
+                    return Some(cycle);
+                }
+                0 if project.targets.contains_key(dep) => {
+                    if let Some(cycle) = visit(project, dep, state, path) {
+                        return Some(cycle);
+                    }
+                }
+                _ => {}
+            }
+        }
+        path.pop();
+        state.insert(name, 2);
+        None
+    }
 
-        return Err(format!(
-            "Cyclic dependency detected! The following targets form a cycle: {:?}",
-            cyclic
-        ));
+    let mut names: Vec<&String> = project.targets.keys().collect();
+    names.sort();
+    let mut state = HashMap::new();
+    for name in names {
+        if !state.contains_key(name.as_str()) {
+            if let Some(cycle) = visit(project, name, &mut state, &mut Vec::new()) {
+                return Some(cycle);
+            }
+        }
     }
+    None
+}
 
-    Ok(BuildOrder { levels })
+/// Build levels for display when the graph has cycles: the orderable targets by
+/// level, then one last level with every target in or behind a cycle.
+pub fn partial_order(project: &ResolvedProject) -> BuildOrder {
+    let mut levels = kahn_levels(project);
+    let processed: HashSet<String> = levels.iter().flatten().cloned().collect();
+    let mut rest: Vec<String> = project.targets.keys().filter(|k| !processed.contains(*k)).cloned().collect();
+    if !rest.is_empty() {
+        rest.sort();
+        levels.push(rest);
+    }
+    BuildOrder { levels }
 }
 
 // ---------------------------------------------------------------------------
@@ -137,7 +204,8 @@ pub fn build_order(project: &ResolvedProject) -> Result<BuildOrder, String> {
 // ---------------------------------------------------------------------------
 
 /// Returns a build order containing only the targets in `target_names` and
-/// their dependencies. Empty list = all targets.
+/// their dependencies. Empty list = all targets. Names may be glob patterns
+/// (`test_*`, see expand_target_patterns).
 pub fn filter_order_for_targets(
     project: &ResolvedProject,
     order: &BuildOrder,
@@ -150,19 +218,11 @@ pub fn filter_order_for_targets(
     }
 
     let targets = &project.targets;
-    for name in target_names {
-        if !targets.contains_key(name) {
-            return Err(format!(
-                "Target '{}' not found. Defined targets: {:?}",
-                name,
-                targets.keys().collect::<Vec<_>>()
-            ));
-        }
-    }
+    let target_names = expand_target_patterns(project, target_names)?;
 
     // Requested targets + all transitive dependencies
-    let mut closure: HashSet<String> = target_names.to_vec().into_iter().collect();
-    let mut stack: Vec<String> = target_names.to_vec();
+    let mut closure: HashSet<String> = target_names.iter().cloned().collect();
+    let mut stack: Vec<String> = target_names.clone();
     while let Some(name) = stack.pop() {
         if let Some(t) = targets.get(&name) {
             for dep in &t.deps {
@@ -190,6 +250,119 @@ pub fn filter_order_for_targets(
     Ok(BuildOrder { levels })
 }
 
+/// Target names for `-t` arguments: a defined name stands for itself, one with
+/// glob characters (`*`, `?`, `[`) for every target it matches, in name order.
+/// A name or pattern that matches nothing is an error.
+pub fn expand_target_patterns(project: &ResolvedProject, names: &[String]) -> Result<Vec<String>, String> {
+    let mut defined: Vec<&String> = project.targets.keys().collect();
+    defined.sort();
+    let mut expanded: Vec<String> = Vec::new();
+    for name in names {
+        let matches: Vec<&String> = if project.targets.contains_key(name) {
+            vec![name]
+        } else if name.contains(['*', '?', '[']) {
+            let pattern = glob::Pattern::new(name).map_err(|e| format!("Invalid target pattern '{}': {}", name, e))?;
+            defined.iter().copied().filter(|t| pattern.matches(t)).collect()
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            return Err(format!("Target '{}' not found. Defined targets: {:?}", name, defined));
+        }
+        for m in matches {
+            if !expanded.contains(m) {
+                expanded.push(m.clone());
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// Targets whose name matches `pattern` (a regex, matched anywhere in the name), in name order.
+pub fn targets_matching_regex(project: &ResolvedProject, pattern: &str) -> Result<Vec<String>, String> {
+    let regex = regex::Regex::new(pattern).map_err(|e| format!("Invalid --target-regex '{}': {}", pattern, e))?;
+    let mut names: Vec<String> = project.targets.keys().filter(|t| regex.is_match(t)).cloned().collect();
+    if names.is_empty() {
+        return Err(format!("--target-regex '{}' matches no target", pattern));
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// What a build without -t builds: `[project] default_targets`, else every target
+/// without `exclude_from_all` (None when that is all targets). Their dependencies
+/// are built as usual, excluded or not.
+pub fn default_selection(project: &ResolvedProject) -> Option<Vec<String>> {
+    if !project.default_targets.is_empty() {
+        return Some(project.default_targets.clone());
+    }
+    if !project.targets.values().any(|t| t.exclude_from_all) {
+        return None;
+    }
+    let mut names: Vec<String> = project.targets.values().filter(|t| !t.exclude_from_all).map(|t| t.name.clone()).collect();
+    names.sort();
+    Some(names)
+}
+
+/// Targets of `target_type` named by `names` (patterns allowed), or all of them, in
+/// name order (`ngm bench`, `ngm test`). A pattern may match other targets too, but
+/// naming one of another type is an error.
+pub fn targets_of_type(project: &ResolvedProject, names: &[String], target_type: TargetType) -> Result<Vec<String>, String> {
+    let of_type = |name: &String| project.targets[name].target_type == target_type;
+    let mut selected = if names.is_empty() {
+        project.targets.keys().cloned().collect()
+    } else {
+        let expanded = expand_target_patterns(project, names)?;
+        if let Some(other) = expanded.iter().find(|n| !of_type(n) && names.contains(n)) {
+            return Err(format!("Target '{}' is a {}, not a {}", other, project.targets[other].target_type.as_str(), target_type.as_str()));
+        }
+        expanded
+    };
+    selected.retain(of_type);
+    selected.sort();
+    Ok(selected)
+}
+
+/// Targets with any of `tags` (`--tag`), in name order. A tag no target has is an error.
+pub fn targets_with_tags(project: &ResolvedProject, tags: &[String]) -> Result<Vec<String>, String> {
+    for tag in tags {
+        if !project.targets.values().any(|t| t.tags.contains(tag)) {
+            let mut used: Vec<&String> = project.targets.values().flat_map(|t| &t.tags).collect();
+            used.sort();
+            used.dedup();
+            return Err(format!("No target has tag '{}' (tags in use: {:?})", tag, used));
+        }
+    }
+    let mut names: Vec<String> = project.targets.values().filter(|t| t.tags.iter().any(|tag| tags.contains(tag))).map(|t| t.name.clone()).collect();
+    names.sort();
+    Ok(names)
+}
+
+/// `target_names` (names or patterns) plus every target that depends on one of them,
+/// directly or transitively (`--with-dependents`), sorted by name.
+pub fn with_dependents(project: &ResolvedProject, target_names: &[String]) -> Result<Vec<String>, String> {
+    let targets = &project.targets;
+    let target_names = expand_target_patterns(project, target_names)?;
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (name, target) in targets {
+        for dep in &target.deps {
+            dependents.entry(dep.as_str()).or_default().push(name.as_str());
+        }
+    }
+    let mut closure: HashSet<&str> = target_names.iter().map(String::as_str).collect();
+    let mut stack: Vec<&str> = closure.iter().copied().collect();
+    while let Some(name) = stack.pop() {
+        for &dependent in dependents.get(name).into_iter().flatten() {
+            if closure.insert(dependent) {
+                stack.push(dependent);
+            }
+        }
+    }
+    let mut names: Vec<String> = closure.into_iter().map(str::to_string).collect();
+    names.sort();
+    Ok(names)
+}
+
 // ---------------------------------------------------------------------------
 // Link order: correct -l order for executable/shared_lib
 // ---------------------------------------------------------------------------
@@ -217,7 +390,9 @@ pub fn transitive_deps_in_link_order(
     let mut result = Vec::new();
     for level in order.levels.iter().rev() {
         for name in level {
-            if closure.contains(name) {
+            // Interface libraries, custom targets and groups have nothing to link
+            let linkable = targets.get(name).is_some_and(|t| t.target_type.compiles_sources());
+            if closure.contains(name) && linkable {
                 result.push(name.clone());
             }
         }
@@ -225,24 +400,93 @@ pub fn transitive_deps_in_link_order(
     result
 }
 
+// ---------------------------------------------------------------------------
+// Dependency tree: `ngm deps --tree`
+// ---------------------------------------------------------------------------
+
+/// Indented dependency tree of `roots` (default: the targets nothing depends on),
+/// or of their dependents with `invert`. A subtree printed before is folded to
+/// `name (*)`.
+pub fn dependency_tree(project: &ResolvedProject, order: &BuildOrder, roots: &[String], invert: bool) -> Vec<String> {
+    let in_order: Vec<&String> = order.levels.iter().flatten().collect();
+    let children = |name: &str| -> Vec<String> {
+        if invert {
+            in_order
+                .iter()
+                .filter(|n| project.targets.get(n.as_str()).is_some_and(|t| t.deps.iter().any(|d| d == name)))
+                .map(|n| n.to_string())
+                .collect()
+        } else {
+            project.targets.get(name).map(|t| t.deps.clone()).unwrap_or_default()
+        }
+    };
+    let roots: Vec<String> = if roots.is_empty() {
+        // Top of the tree: targets without dependents (or, inverted, without deps)
+        let has_parent = |name: &str| match invert {
+            true => project.targets.get(name).is_some_and(|t| !t.deps.is_empty()),
+            false => project.targets.values().any(|t| t.deps.iter().any(|d| d == name)),
+        };
+        in_order.iter().filter(|n| !has_parent(n)).map(|n| n.to_string()).collect()
+    } else {
+        roots.to_vec()
+    };
+
+    let mut lines = Vec::new();
+    let mut expanded = HashSet::new();
+    for root in &roots {
+        tree_node(project, root, "", "", &children, &mut expanded, &mut lines);
+    }
+    lines
+}
+
+fn tree_node(
+    project: &ResolvedProject,
+    name: &str,
+    prefix: &str,
+    child_prefix: &str,
+    children: &dyn Fn(&str) -> Vec<String>,
+    expanded: &mut HashSet<String>,
+    lines: &mut Vec<String>,
+) {
+    let label = match project.targets.get(name) {
+        Some(t) => format!("{} ({})", name, t.target_type.as_str()),
+        None => format!("{} (unknown)", name),
+    };
+    let kids = children(name);
+    if !kids.is_empty() && !expanded.insert(name.to_string()) {
+        lines.push(format!("{}{} (*)", prefix, label));
+        return;
+    }
+    lines.push(format!("{}{}", prefix, label));
+    for (i, kid) in kids.iter().enumerate() {
+        let last = i + 1 == kids.len();
+        let (branch, next) = if last { ("`-- ", "    ") } else { ("|-- ", "|   ") };
+        tree_node(
+            project,
+            kid,
+            &format!("{}{}", child_prefix, branch),
+            &format!("{}{}", child_prefix, next),
+            children,
+            expanded,
+            lines,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Compiler, ResolvedTarget, TargetType};
+    use crate::config::{Compiler, ResolvedTarget};
     use std::path::PathBuf;
 
     fn make_target(name: &str, deps: Vec<&str>) -> ResolvedTarget {
         ResolvedTarget {
             name: name.to_string(),
             target_type: TargetType::Executable,
-            sources: vec![],
-            include_dirs: vec![],
-            lib_dirs: vec![],
-            libs: vec![],
-            flags: vec![],
             deps: deps.into_iter().map(String::from).collect(),
             compiler: Compiler::Gpp,
             output_dir: PathBuf::from("build"),
+            ..Default::default()
         }
     }
 
@@ -257,8 +501,8 @@ mod tests {
         let project = ResolvedProject {
             name: "test".to_string(),
             version: "0.1.0".to_string(),
-            cxx_standard: None,
             targets,
+            ..Default::default()
         };
 
         let order = build_order(&project).unwrap();
@@ -279,8 +523,8 @@ mod tests {
         let project = ResolvedProject {
             name: "test".to_string(),
             version: "0.1.0".to_string(),
-            cxx_standard: None,
             targets,
+            ..Default::default()
         };
 
         let order = build_order(&project).unwrap();
@@ -299,12 +543,150 @@ mod tests {
         let project = ResolvedProject {
             name: "test".to_string(),
             version: "0.1.0".to_string(),
-            cxx_standard: None,
             targets,
+            ..Default::default()
         };
 
         let result = build_order(&project);
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Cyclic dependency"));
     }
+
+    #[test]
+    fn test_target_patterns_expand_to_matching_names() {
+        let mut targets = HashMap::new();
+        for name in ["test_io", "test_net", "app", "bench_io"] {
+            targets.insert(name.to_string(), make_target(name, vec![]));
+        }
+        let project = ResolvedProject { targets, ..Default::default() };
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(expand_target_patterns(&project, &names(&["test_*", "app"])).unwrap(), vec!["test_io", "test_net", "app"]);
+        assert!(expand_target_patterns(&project, &names(&["fuzz_*"])).unwrap_err().contains("'fuzz_*' not found"));
+        assert_eq!(targets_matching_regex(&project, "_io$").unwrap(), vec!["bench_io", "test_io"]);
+        assert!(targets_matching_regex(&project, "(").is_err());
+        let order = filter_order_for_targets(&project, &build_order(&project).unwrap(), &names(&["test_*"])).unwrap();
+        assert_eq!(order.levels, vec![vec!["test_io", "test_net"]]);
+    }
+
+    #[test]
+    fn test_default_targets_and_exclude_from_all_limit_a_bare_build() {
+        let mut targets = HashMap::new();
+        targets.insert("core".to_string(), ResolvedTarget { exclude_from_all: true, ..make_target("core", vec![]) });
+        targets.insert("app".to_string(), make_target("app", vec!["core"]));
+        targets.insert("bench".to_string(), ResolvedTarget { exclude_from_all: true, ..make_target("bench", vec!["core"]) });
+        let mut project = ResolvedProject { targets, ..Default::default() };
+
+        assert_eq!(default_selection(&project), Some(vec!["app".to_string()]));
+        let order = filter_order_for_targets(&project, &build_order(&project).unwrap(), &default_selection(&project).unwrap()).unwrap();
+        assert_eq!(order.levels, vec![vec!["core"], vec!["app"]]);
+        project.default_targets = vec!["bench".to_string()];
+        assert_eq!(default_selection(&project), Some(vec!["bench".to_string()]));
+        project.default_targets.clear();
+        project.targets.values_mut().for_each(|t| t.exclude_from_all = false);
+        assert_eq!(default_selection(&project), None);
+    }
+
+    #[test]
+    fn test_tags_select_targets() {
+        let mut targets = HashMap::new();
+        for (name, tags) in [("test_io", vec!["tests"]), ("gen", vec!["tools", "tests"]), ("app", vec![])] {
+            let target = ResolvedTarget { tags: tags.into_iter().map(String::from).collect(), ..make_target(name, vec![]) };
+            targets.insert(name.to_string(), target);
+        }
+        let project = ResolvedProject { targets, ..Default::default() };
+        assert_eq!(targets_with_tags(&project, &["tests".to_string()]).unwrap(), vec!["gen", "test_io"]);
+        assert_eq!(targets_with_tags(&project, &["bench".to_string()]).unwrap_err(), "No target has tag 'bench' (tags in use: [\"tests\", \"tools\"])");
+    }
+
+    #[test]
+    fn test_dependents_are_collected_transitively() {
+        let mut targets = HashMap::new();
+        targets.insert("base".to_string(), make_target("base", vec![]));
+        targets.insert("mylib".to_string(), make_target("mylib", vec!["base"]));
+        targets.insert("net".to_string(), make_target("net", vec!["mylib"]));
+        targets.insert("app".to_string(), make_target("app", vec!["net"]));
+        targets.insert("tool".to_string(), make_target("tool", vec!["base"]));
+        let project = ResolvedProject { targets, ..Default::default() };
+
+        assert_eq!(with_dependents(&project, &["mylib".to_string()]).unwrap(), vec!["app", "mylib", "net"]);
+        let order = filter_order_for_targets(&project, &build_order(&project).unwrap(), &with_dependents(&project, &["mylib".to_string()]).unwrap()).unwrap();
+        assert_eq!(order.levels, vec![vec!["base"], vec!["mylib"], vec!["net"], vec!["app"]]);
+        assert!(with_dependents(&project, &["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_cycles_are_reported_as_a_path() {
+        // app → net → core → net, plus tool → app which only depends on the cycle
+        let mut targets = HashMap::new();
+        targets.insert("app".to_string(), make_target("app", vec!["net"]));
+        targets.insert("net".to_string(), make_target("net", vec!["core"]));
+        targets.insert("core".to_string(), make_target("core", vec!["net", "base"]));
+        targets.insert("base".to_string(), make_target("base", vec![]));
+        targets.insert("tool".to_string(), make_target("tool", vec!["app"]));
+        let project = ResolvedProject { targets, ..Default::default() };
+
+        assert_eq!(find_cycle(&project), Some(vec!["net".to_string(), "core".to_string(), "net".to_string()]));
+        let err = build_order(&project).unwrap_err();
+        assert!(err.starts_with("Cyclic dependency detected: net → core → net"), "{}", err);
+        assert!(err.ends_with("[\"app\", \"tool\"]"), "{}", err);
+        assert_eq!(partial_order(&project).levels, vec![vec!["base".to_string()], vec!["app".to_string(), "core".to_string(), "net".to_string(), "tool".to_string()]]);
+    }
+
+    #[test]
+    fn test_interface_deps_are_not_linked() {
+        let mut targets = HashMap::new();
+        targets.insert("app".to_string(), make_target("app", vec!["core", "headers"]));
+        targets.insert("core".to_string(), make_target("core", vec!["headers"]));
+        let mut headers = make_target("headers", vec![]);
+        headers.target_type = TargetType::Interface;
+        targets.insert("headers".to_string(), headers);
+
+        let project = ResolvedProject { targets, ..Default::default() };
+        let order = build_order(&project).unwrap();
+        assert_eq!(transitive_deps_in_link_order(&project, "app", &order), vec!["core"]);
+    }
+
+    #[test]
+    fn test_groups_build_their_deps_and_link_through() {
+        let mut targets = HashMap::new();
+        targets.insert("app".to_string(), make_target("app", vec!["libs"]));
+        targets.insert("core".to_string(), make_target("core", vec![]));
+        targets.insert("net".to_string(), make_target("net", vec!["core"]));
+        targets.insert("tool".to_string(), make_target("tool", vec![]));
+        let mut libs = make_target("libs", vec!["core", "net"]);
+        libs.target_type = TargetType::Group;
+        targets.insert("libs".to_string(), libs);
+
+        let project = ResolvedProject { targets, ..Default::default() };
+        let order = build_order(&project).unwrap();
+        let libs_only = filter_order_for_targets(&project, &order, &["libs".to_string()]).unwrap();
+        assert_eq!(libs_only.levels, vec![vec!["core".to_string()], vec!["net".to_string()], vec!["libs".to_string()]]);
+        assert_eq!(transitive_deps_in_link_order(&project, "app", &order), vec!["net", "core"]);
+    }
+
+    #[test]
+    fn test_dependency_tree_folds_repeated_subtrees() {
+        let mut targets = HashMap::new();
+        targets.insert("app".to_string(), make_target("app", vec!["net", "base"]));
+        targets.insert("net".to_string(), make_target("net", vec!["base"]));
+        targets.insert("base".to_string(), make_target("base", vec!["core"]));
+        targets.insert("core".to_string(), make_target("core", vec![]));
+        let project = ResolvedProject { targets, ..Default::default() };
+        let order = build_order(&project).unwrap();
+        assert_eq!(
+            dependency_tree(&project, &order, &[], false),
+            vec![
+                "app (executable)",
+                "|-- net (executable)",
+                "|   `-- base (executable)",
+                "|       `-- core (executable)",
+                "`-- base (executable) (*)",
+            ]
+        );
+        assert_eq!(
+            dependency_tree(&project, &order, &["base".to_string()], true),
+            vec!["base (executable)", "|-- net (executable)", "|   `-- app (executable)", "`-- app (executable)"]
+        );
+    }
 }
@@ -0,0 +1,140 @@
+// ============================================================================
+// diagnostics.rs — Compiler diagnostic parsing
+// ============================================================================
+//
+// Extracts GCC/Clang style diagnostics (`file:line:col: error: message`)
+// from captured compiler output so they can be reported as structured data
+// instead of raw text.
+// ============================================================================
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+/// One `file:line[:col]: severity: message` line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+    /// How to fix it, when ngmake knows (e.g. the include_dir of a missing header, see hints.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// Number of warnings and errors in a job's or target's output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiagnosticCounts {
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+impl DiagnosticCounts {
+    pub fn count(diagnostics: &[Diagnostic]) -> DiagnosticCounts {
+        let mut counts = DiagnosticCounts::default();
+        for d in diagnostics {
+            match d.severity {
+                Severity::Warning => counts.warnings += 1,
+                Severity::Error => counts.errors += 1,
+                Severity::Note => {}
+            }
+        }
+        counts
+    }
+
+    pub fn add(&mut self, other: DiagnosticCounts) {
+        self.warnings += other.warnings;
+        self.errors += other.errors;
+    }
+
+    /// `, 42 warnings, 1 error` (empty when there are none), appended to summaries.
+    pub fn suffix(&self) -> String {
+        let mut text = String::new();
+        for (n, what) in [(self.warnings, "warning"), (self.errors, "error")] {
+            if n > 0 {
+                text.push_str(&format!(", {} {}{}", n, what, if n == 1 { "" } else { "s" }));
+            }
+        }
+        text
+    }
+}
+
+fn diagnostic_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:stderr:\s*)?([^\s:][^:]*):(\d+):(?:(\d+):)?\s*(fatal error|error|warning|note):\s*(.*)$")
+            .unwrap()
+    })
+}
+
+/// Parses all diagnostics in `output`. Duplicates (the same stderr often
+/// appears both in the log and in the error message) are reported once.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in output.lines() {
+        let Some(caps) = diagnostic_re().captures(line) else { continue };
+        let severity = match &caps[4] {
+            "warning" => Severity::Warning,
+            "note" => Severity::Note,
+            _ => Severity::Error,
+        };
+        let diagnostic = Diagnostic {
+            file: PathBuf::from(&caps[1]),
+            line: caps[2].parse().unwrap_or(0),
+            column: caps.get(3).and_then(|m| m.as_str().parse().ok()),
+            severity,
+            message: caps[5].trim().to_string(),
+            hint: None,
+        };
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gcc_and_clang_lines() {
+        let out = "    stderr: src/a.cpp:3:10: fatal error: foo.h: No such file or directory\n\
+                   src/a.cpp: In function 'int main()':\n\
+                   src/a.cpp:7:5: warning: unused variable 'x' [-Wunused-variable]\n\
+                   src/a.cpp:3:10: fatal error: foo.h: No such file or directory\n\
+                   ld: cannot find -lbar\n";
+        let diags = parse_diagnostics(out);
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert_eq!(diags[0].column, Some(10));
+        assert_eq!(diags[0].message, "foo.h: No such file or directory");
+        assert_eq!(diags[1].severity, Severity::Warning);
+        assert_eq!(diags[1].line, 7);
+        let counts = DiagnosticCounts::count(&diags);
+        assert_eq!(counts, DiagnosticCounts { warnings: 1, errors: 1 });
+        assert_eq!(counts.suffix(), ", 1 warning, 1 error");
+    }
+}
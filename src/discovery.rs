@@ -0,0 +1,133 @@
+// ============================================================================
+// discovery.rs — Parallel source discovery for glob patterns
+// ============================================================================
+//
+// glob::glob walks the directory tree of every pattern on its own, one
+// directory at a time. In a monorepo many targets glob overlapping trees
+// (`src/**/*.cpp`, `src/net/*.cpp`, ...), so the same directories are read
+// again and again, which is slow on network filesystems.
+//
+// SourceFinder is shared by all targets of a parse: every directory is read
+// at most once, and the subdirectories of a recursive pattern are read in
+// parallel (rayon). Patterns are then matched against the cached listings
+// with the same rules as glob::glob (`*` stays within a directory, `**`
+// spans directories).
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+
+/// Files and subdirectories of one directory.
+#[derive(Debug, Default)]
+struct Listing {
+    files: Vec<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+/// Directory listings shared by the source globs of all targets.
+#[derive(Debug, Default)]
+pub struct SourceFinder {
+    listings: Mutex<HashMap<PathBuf, Arc<Listing>>>,
+}
+
+impl SourceFinder {
+    pub fn new() -> SourceFinder {
+        SourceFinder::default()
+    }
+
+    /// Files matching `pattern` (e.g. `lib/src/**/*.cpp`), sorted.
+    pub fn glob(&self, pattern: &Path) -> Result<Vec<PathBuf>, String> {
+        let text = pattern.to_string_lossy();
+        let matcher = glob::Pattern::new(&text).map_err(|e| e.to_string())?;
+        let options = glob::MatchOptions { require_literal_separator: true, ..glob::MatchOptions::new() };
+
+        // Read only below the literal part of the pattern
+        let components: Vec<Component> = pattern.components().collect();
+        let literal = components
+            .iter()
+            .position(|c| c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+            .unwrap_or(components.len());
+        let base: PathBuf = components[..literal].iter().collect();
+        let candidates = if literal + 1 == components.len() {
+            self.listing(&base).files.clone()
+        } else {
+            self.files_below(&base)
+        };
+
+        let mut matched: Vec<PathBuf> = candidates.into_iter().filter(|f| matcher.matches_path_with(f, options)).collect();
+        matched.sort();
+        Ok(matched)
+    }
+
+    /// All files in `dir` and its subdirectories; subdirectories are read in parallel.
+    fn files_below(&self, dir: &Path) -> Vec<PathBuf> {
+        let listing = self.listing(dir);
+        let nested: Vec<Vec<PathBuf>> = listing.dirs.par_iter().map(|d| self.files_below(d)).collect();
+        listing.files.iter().cloned().chain(nested.into_iter().flatten()).collect()
+    }
+
+    /// Cached listing of `dir` (empty if it can't be read).
+    fn listing(&self, dir: &Path) -> Arc<Listing> {
+        if let Some(listing) = self.listings.lock().unwrap().get(dir) {
+            return listing.clone();
+        }
+        let listing = Arc::new(read_listing(dir));
+        self.listings.lock().unwrap().insert(dir.to_path_buf(), listing.clone());
+        listing
+    }
+}
+
+fn read_listing(dir: &Path) -> Listing {
+    let read_dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let entries = match std::fs::read_dir(read_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("[WARN] Failed to read directory '{}': {}", read_dir.display(), e);
+            }
+            return Listing::default();
+        }
+    };
+    let mut listing = Listing::default();
+    for entry in entries.flatten() {
+        // Paths keep the form of `dir` (no "./" prefix for the current directory)
+        let path = dir.join(entry.file_name());
+        let is_dir = match entry.file_type() {
+            Ok(t) if t.is_symlink() => path.is_dir(),
+            Ok(t) => t.is_dir(),
+            Err(_) => false,
+        };
+        if is_dir {
+            listing.dirs.push(path);
+        } else {
+            listing.files.push(path);
+        }
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_matches_like_glob_and_reads_each_directory_once() {
+        let dir = TempDir::new("discovery");
+        for file in ["src/main.cpp", "src/util.h", "src/net/http.cpp", "src/net/tls/tls.cpp", "test/test.cpp"] {
+            std::fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+            dir.write(file, "");
+        }
+
+        let finder = SourceFinder::new();
+        for pattern in ["src/*.cpp", "src/**/*.cpp", "src/*/*.cpp", "*/main.cpp", "src/net/**/*.cpp"] {
+            let expected: Vec<PathBuf> = glob::glob(&dir.join(pattern).to_string_lossy()).unwrap().flatten().collect();
+            assert_eq!(finder.glob(&dir.join(pattern)).unwrap(), expected, "{}", pattern);
+        }
+        assert!(finder.listings.lock().unwrap().contains_key(&dir.join("src/net/tls")));
+        assert_eq!(finder.glob(&dir.join("missing/*.cpp")).unwrap(), Vec::<PathBuf>::new());
+    }
+}
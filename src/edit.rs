@@ -0,0 +1,392 @@
+// ============================================================================
+// edit.rs — Structured build.toml editing (toml_edit)
+// ============================================================================
+//
+// Small, targeted edits of one build.toml: add/remove/rename targets, add
+// dependencies and [dependencies] packages, set flag lists. The file is edited as a toml_edit document,
+// so comments, key order and whitespace the user wrote are kept; only the
+// touched keys change.
+//
+// Edits apply to the given file only: a target defined in an included
+// build.toml must be edited in that file (ResolvedProject::config_files).
+//
+// Generated files (ngm convert, ngm init) are written with
+// write_config_file: when the file already exists, the regenerated content is
+// merged into it key by key. Values that did not change keep their exact text,
+// changed values keep their surrounding comments, and [[target]] tables are
+// matched by name, so hand-written documentation survives a re-run.
+// ============================================================================
+
+use std::path::Path;
+
+use serde::Deserialize;
+use toml_edit::{value, Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::config::TargetType;
+use crate::prebuilt::DependencyConfig;
+
+/// Which flag list of a target `set_flags` replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagKind {
+    /// Legacy `flags` (compile step)
+    Flags,
+    CompilerFlags,
+    LinkerFlags,
+}
+
+impl FlagKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            FlagKind::Flags => "flags",
+            FlagKind::CompilerFlags => "compiler_flags",
+            FlagKind::LinkerFlags => "linker_flags",
+        }
+    }
+}
+
+/// A build.toml opened for editing.
+pub struct ConfigDocument {
+    doc: DocumentMut,
+}
+
+impl ConfigDocument {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<DocumentMut>()
+            .map_err(|e| format!("TOML parse error: {}", e))?;
+        Ok(Self { doc })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| format!("{} in '{}'", e, path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.doc.to_string())
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+
+    /// Names of the `[[target]]` tables in this file, in file order.
+    pub fn target_names(&self) -> Vec<String> {
+        self.targets()
+            .map(|t| t.iter().filter_map(|t| t.get("name")?.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Appends a new `[[target]]` table.
+    pub fn add_target(&mut self, name: &str, target_type: &TargetType, sources: &[String]) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("Target name must not be empty".to_string());
+        }
+        if self.target_names().iter().any(|n| n == name) {
+            return Err(format!("Target '{}' already exists", name));
+        }
+        let mut table = Table::new();
+        table["name"] = value(name);
+        table["type"] = value(target_type.as_str());
+        table["sources"] = value(string_array(sources));
+        match self.doc.get_mut("target") {
+            None => {
+                let mut targets = ArrayOfTables::new();
+                targets.push(table);
+                self.doc.insert("target", Item::ArrayOfTables(targets));
+            }
+            Some(item) => item
+                .as_array_of_tables_mut()
+                .ok_or("'target' is not an array of tables ([[target]])")?
+                .push(table),
+        }
+        Ok(())
+    }
+
+    /// Removes a target and drops it from the `deps` of the other targets in this file.
+    pub fn remove_target(&mut self, name: &str) -> Result<(), String> {
+        let index = self.target_index(name)?;
+        let targets = self.targets_mut()?;
+        targets.remove(index);
+        for t in targets.iter_mut() {
+            if let Some(deps) = t.get_mut("deps").and_then(Item::as_array_mut) {
+                deps.retain(|d| d.as_str() != Some(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds `dep` to the target's `deps` (no-op if already listed).
+    pub fn add_dependency(&mut self, target: &str, dep: &str) -> Result<(), String> {
+        if target == dep {
+            return Err(format!("Target '{}' cannot depend on itself", target));
+        }
+        let table = self.target_mut(target)?;
+        if !table.contains_key("deps") {
+            table["deps"] = value(Array::new());
+        }
+        let deps = table["deps"]
+            .as_array_mut()
+            .ok_or_else(|| format!("'deps' of target '{}' is not an array", target))?;
+        if !deps.iter().any(|d| d.as_str() == Some(dep)) {
+            deps.push(dep);
+        }
+        Ok(())
+    }
+
+    /// Sets `[dependencies] <name> = { path = ... }` (or `archive`, `url`), replacing an existing entry.
+    pub fn set_package_dependency(&mut self, name: &str, dependency: &DependencyConfig) -> Result<(), String> {
+        if name.trim().is_empty() || name.contains("::") {
+            return Err(format!("Invalid dependency name '{}'", name));
+        }
+        let mut entry = InlineTable::new();
+        for (key, location) in [("path", &dependency.path), ("archive", &dependency.archive), ("url", &dependency.url), ("sha256", &dependency.sha256)] {
+            if let Some(location) = location {
+                entry.insert(key, location.as_str().into());
+            }
+        }
+        // Kept where the user wrote it: under [project] or as a top-level table
+        let table = match self.doc.get_mut("project").and_then(Item::as_table_mut).filter(|p| p.contains_key("dependencies")) {
+            Some(project) => &mut project["dependencies"],
+            None => self.doc.entry("dependencies").or_insert_with(toml_edit::table),
+        };
+        let table = table.as_table_like_mut().ok_or("'dependencies' is not a table")?;
+        table.insert(name, value(entry));
+        Ok(())
+    }
+
+    /// Replaces one flag list of a target; an empty list removes the key.
+    pub fn set_flags(&mut self, target: &str, kind: FlagKind, flags: &[String]) -> Result<(), String> {
+        let table = self.target_mut(target)?;
+        if flags.is_empty() {
+            table.remove(kind.key());
+        } else {
+            table[kind.key()] = value(string_array(flags));
+        }
+        Ok(())
+    }
+
+    /// Renames a target and updates the `deps` of the other targets in this file.
+    pub fn rename_target(&mut self, old: &str, new: &str) -> Result<(), String> {
+        if new.trim().is_empty() {
+            return Err("Target name must not be empty".to_string());
+        }
+        if old != new && self.target_names().iter().any(|n| n == new) {
+            return Err(format!("Target '{}' already exists", new));
+        }
+        self.target_mut(old)?["name"] = value(new);
+        for t in self.targets_mut()?.iter_mut() {
+            if let Some(deps) = t.get_mut("deps").and_then(Item::as_array_mut) {
+                for i in 0..deps.len() {
+                    if deps.get(i).and_then(|d| d.as_str()) == Some(old) {
+                        deps.replace(i, new);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn targets(&self) -> Option<&ArrayOfTables> {
+        self.doc.get("target")?.as_array_of_tables()
+    }
+
+    fn targets_mut(&mut self) -> Result<&mut ArrayOfTables, String> {
+        self.doc
+            .get_mut("target")
+            .and_then(Item::as_array_of_tables_mut)
+            .ok_or_else(|| "No [[target]] tables in this file".to_string())
+    }
+
+    fn target_index(&self, name: &str) -> Result<usize, String> {
+        self.targets()
+            .and_then(|t| t.iter().position(|t| t.get("name").and_then(Item::as_str) == Some(name)))
+            .ok_or_else(|| format!("Target '{}' not found", name))
+    }
+
+    fn target_mut(&mut self, name: &str) -> Result<&mut Table, String> {
+        let index = self.target_index(name)?;
+        self.targets_mut()?
+            .get_mut(index)
+            .ok_or_else(|| format!("Target '{}' not found", name))
+    }
+}
+
+impl std::fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+fn string_array(items: &[String]) -> Array {
+    items.iter().map(String::as_str).collect()
+}
+
+/// Loads `path`, applies `edit` and writes the file back.
+pub fn edit_config_file<F>(path: &Path, edit: F) -> Result<(), String>
+where
+    F: FnOnce(&mut ConfigDocument) -> Result<(), String>,
+{
+    let mut doc = ConfigDocument::load(path)?;
+    edit(&mut doc).map_err(|e| format!("{} ({})", e, path.display()))?;
+    doc.save(path)
+}
+
+/// Merges generated config text into the existing text: the generated keys and
+/// targets win, but comments, key order and formatting of `existing` are kept
+/// wherever the content is unchanged.
+pub fn merge_generated(existing: &str, generated: &str) -> Result<String, String> {
+    let mut old = existing
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("TOML parse error in existing file: {}", e))?;
+    let new = generated
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("TOML parse error in generated config: {}", e))?;
+    merge_table(old.as_table_mut(), new.as_table());
+    Ok(old.to_string())
+}
+
+/// Writes a generated build.toml. An existing file is merged (see merge_generated)
+/// instead of overwritten. Returns the text written.
+pub fn write_config_file(path: &Path, generated: &str) -> Result<String, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(existing) => merge_generated(&existing, generated)
+            .map_err(|e| format!("{} ('{}')", e, path.display()))?,
+        Err(_) => generated.to_string(),
+    };
+    std::fs::write(path, &content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(content)
+}
+
+fn merge_table(old: &mut Table, new: &Table) {
+    let stale: Vec<String> = old.iter().map(|(k, _)| k.to_string()).filter(|k| !new.contains_key(k)).collect();
+    for key in stale {
+        old.remove(&key);
+    }
+    for (key, new_item) in new.iter() {
+        match (old.get_mut(key), new_item) {
+            (Some(Item::Table(o)), Item::Table(n)) => merge_table(o, n),
+            (Some(Item::ArrayOfTables(o)), Item::ArrayOfTables(n)) => merge_targets(o, n),
+            (Some(Item::Value(o)), Item::Value(n)) => {
+                if !same_value(o, n) {
+                    let decor = o.decor().clone();
+                    *o = n.clone();
+                    *o.decor_mut() = decor;
+                }
+            }
+            _ => {
+                old.insert(key, new_item.clone());
+            }
+        }
+    }
+}
+
+/// Existing tables (matched by `name`) are merged in place and keep their position;
+/// generated tables without a match are appended, tables no longer generated are dropped.
+fn merge_targets(old: &mut ArrayOfTables, new: &ArrayOfTables) {
+    let name = |t: &Table| t.get("name").and_then(Item::as_str).map(String::from);
+    let mut merged = ArrayOfTables::new();
+    let mut used = vec![false; new.len()];
+    for o in old.iter() {
+        let found = new
+            .iter()
+            .enumerate()
+            .find(|(i, n)| !used[*i] && name(o).is_some() && name(n) == name(o));
+        if let Some((i, n)) = found {
+            used[i] = true;
+            let mut table = o.clone();
+            merge_table(&mut table, n);
+            merged.push(table);
+        }
+    }
+    for (i, n) in new.iter().enumerate() {
+        if !used[i] {
+            merged.push(n.clone());
+        }
+    }
+    *old = merged;
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(x), Value::String(y)) => x.value() == y.value(),
+        (Value::Integer(x), Value::Integer(y)) => x.value() == y.value(),
+        (Value::Float(x), Value::Float(y)) => x.value() == y.value(),
+        (Value::Boolean(x), Value::Boolean(y)) => x.value() == y.value(),
+        (Value::Datetime(x), Value::Datetime(y)) => x.value() == y.value(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(p, q)| same_value(p, q))
+        }
+        (Value::InlineTable(x), Value::InlineTable(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| same_value(v, w)))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Demo project
+[project]
+name = "demo"
+
+# Core library
+[[target]]
+name = "util"
+type = "static_lib"
+sources = ["lib/*.cpp"]  # all sources
+
+[[target]]
+name = "app"
+sources = ["src/*.cpp"]
+deps = ["util"]
+"#;
+
+    #[test]
+    fn test_edits_keep_comments_and_update_deps() {
+        let mut doc = ConfigDocument::parse(CONFIG).unwrap();
+        doc.add_target("net", &TargetType::SharedLib, &["net/*.cpp".to_string()]).unwrap();
+        doc.add_dependency("app", "net").unwrap();
+        doc.add_dependency("app", "net").unwrap();
+        doc.set_flags("app", FlagKind::CompilerFlags, &["-O2".to_string()]).unwrap();
+        doc.rename_target("util", "core").unwrap();
+        assert!(doc.add_target("app", &TargetType::Executable, &[]).is_err());
+
+        let text = doc.to_string();
+        assert!(text.starts_with("# Demo project\n"));
+        assert!(text.contains("# Core library\n[[target]]\nname = \"core\""));
+        assert!(text.contains("sources = [\"lib/*.cpp\"]  # all sources"));
+        assert!(text.contains("deps = [\"core\", \"net\"]"));
+        assert!(text.contains("compiler_flags = [\"-O2\"]"));
+        assert_eq!(doc.target_names(), vec!["core", "app", "net"]);
+
+        doc.remove_target("net").unwrap();
+        assert!(doc.to_string().contains("deps = [\"core\"]"));
+        assert!(doc.remove_target("net").is_err());
+    }
+
+    #[test]
+    fn test_package_dependencies_are_added_and_replaced() {
+        let mut doc = ConfigDocument::parse(CONFIG).unwrap();
+        doc.set_package_dependency("core", &DependencyConfig { path: Some("../core".into()), ..Default::default() }).unwrap();
+        doc.set_package_dependency("net", &DependencyConfig { archive: Some("net.tar.gz".into()), ..Default::default() }).unwrap();
+        doc.set_package_dependency("core", &DependencyConfig { path: Some("../core2".into()), ..Default::default() }).unwrap();
+        let text = doc.to_string();
+        assert!(text.contains("[dependencies]\ncore = { path = \"../core2\" }\nnet = { archive = \"net.tar.gz\" }\n"));
+        assert!(text.starts_with("# Demo project\n[project]\nname = \"demo\"\n"));
+        assert!(doc.set_package_dependency("a::b", &DependencyConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_regenerated_config_keeps_comments() {
+        let existing = "# Demo project\n[project]\nname = \"demo\"  # shown in reports\nversion = \"1.0\"\n\n# Core library\n[[target]]\nname = \"util\"\nsources = [ \"lib/a.cpp\" ]  # hand-aligned\n\n[[target]]\nname = \"old\"\n";
+        let generated = "[project]\nname = \"demo\"\nversion = \"2.0\"\n\n[[target]]\nname = \"app\"\n\n[[target]]\nname = \"util\"\nsources = [\"lib/a.cpp\"]\ndeps = []\n";
+        let merged = merge_generated(existing, generated).unwrap();
+        assert!(merged.starts_with("# Demo project\n[project]\nname = \"demo\"  # shown in reports\nversion = \"2.0\"\n"));
+        assert!(merged.contains("# Core library\n[[target]]\nname = \"util\"\nsources = [ \"lib/a.cpp\" ]  # hand-aligned\ndeps = []\n"));
+        assert!(merged.contains("name = \"app\""));
+        assert!(!merged.contains("\"old\""));
+        assert!(merged.find("\"util\"") < merged.find("\"app\""));
+    }
+}
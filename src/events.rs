@@ -0,0 +1,278 @@
+// ============================================================================
+// events.rs — Build events (GUI channel and --log-format json)
+// ============================================================================
+//
+// BuildEvent is what the builder sends over its output channel to library
+// consumers (the GUI). TargetStatus events track each target through
+// queued → compiling i/j → linking → succeeded / failed (or skipped), so a
+// graph view doesn't have to infer state from the log text. `legacy_line()` maps it back to the older string
+// protocol (`[TARGET:name] ...`, `__ngmake_TOTAL__`, `__ngmake_FINISH__`)
+// for consumers that still parse lines.
+//
+// With `--log-format json` all human-oriented output is suppressed and one
+// JSON object per line is written to stdout instead:
+//   {"event":"job_started","time_ms":..,"kind":"compile","target":..,"source":..,"worker":..}
+//   {"event":"job_finished",..,"duration_ms":..,"success":..,"up_to_date":..}
+//   {"event":"diagnostic","target":..,"file":..,"line":..,"column":..,"severity":..,"message":..}
+//   {"event":"build_finished","success":..,"total_targets":..,"successful_targets":..,..}
+// ============================================================================
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::builder::{BuildResult, JobKind, JobTiming};
+use crate::config::TargetType;
+use crate::diagnostics::Diagnostic;
+
+// ---------------------------------------------------------------------------
+// Typed events (builder → library consumers)
+// ---------------------------------------------------------------------------
+
+/// Final counts of a build (or clean).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildStats {
+    pub success: bool,
+    pub total: usize,
+    pub successful: usize,
+    pub failed: usize,
+    /// Compiler/linker warnings and errors parsed from the job output
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+/// Where a target is in the build, for live status displays (the GUI graph).
+/// Serialized with a `state` tag, e.g. `{"state":"compiling","done":3,"total":12}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TargetState {
+    /// Waiting for its dependencies
+    Queued,
+    /// `done` of `total` sources compiled (or found up to date)
+    Compiling { done: usize, total: usize },
+    Linking,
+    Succeeded,
+    Failed,
+    /// Never started: a dependency failed, or the build was cancelled or stopped
+    Skipped,
+}
+
+impl TargetState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetState::Queued => "queued",
+            TargetState::Compiling { .. } => "compiling",
+            TargetState::Linking => "linking",
+            TargetState::Succeeded => "succeeded",
+            TargetState::Failed => "failed",
+            TargetState::Skipped => "skipped",
+        }
+    }
+}
+
+/// One build event. Serialized with a `type` tag, e.g.
+/// `{"type":"file_compiled","target":"app","source":"src/main.cpp",...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuildEvent {
+    /// Scheduling begins; `total_targets` targets will be built
+    Started { total_targets: usize },
+    /// First job of a target was queued
+    TargetStarted { target: String, target_type: TargetType },
+    /// A target changed state (every target starts as `queued`)
+    TargetStatus {
+        target: String,
+        #[serde(flatten)]
+        status: TargetState,
+    },
+    /// A compile job finished (also sent for up-to-date sources)
+    FileCompiled {
+        target: String,
+        source: PathBuf,
+        success: bool,
+        up_to_date: bool,
+        duration_ms: u64,
+    },
+    /// Compiler/linker diagnostic parsed from a job's output
+    Diagnostic { target: String, diagnostic: Diagnostic },
+    /// A log line, attributed to a target when it belongs to one
+    Output { target: Option<String>, line: String },
+    Finished { stats: BuildStats },
+}
+
+impl BuildEvent {
+    pub fn output(target: Option<&str>, line: impl Into<String>) -> BuildEvent {
+        BuildEvent::Output {
+            target: target.map(String::from),
+            line: line.into(),
+        }
+    }
+
+    /// Line in the older string protocol, or None for events it had no equivalent for.
+    pub fn legacy_line(&self) -> Option<String> {
+        match self {
+            BuildEvent::Started { total_targets } => Some(format!("__ngmake_TOTAL__\t{}", total_targets)),
+            BuildEvent::TargetStarted { target, target_type } => Some(format!(
+                "[TARGET:{}] === Building target '{}' (type: {:?}) ===",
+                target, target, target_type
+            )),
+            BuildEvent::Output { target: Some(t), line } => Some(format!("[TARGET:{}] {}", t, line)),
+            BuildEvent::Output { target: None, line } => Some(line.clone()),
+            BuildEvent::Finished { stats } => Some(format!(
+                "__ngmake_FINISH__\t{}\t{}\t{}\t{}",
+                stats.success, stats.total, stats.successful, stats.failed
+            )),
+            BuildEvent::TargetStatus { .. } | BuildEvent::FileCompiled { .. } | BuildEvent::Diagnostic { .. } => None,
+        }
+    }
+}
+
+/// Forwards events as legacy lines until the event channel closes.
+pub fn forward_legacy_lines(events: mpsc::Receiver<BuildEvent>, lines: mpsc::Sender<String>) {
+    for event in events {
+        if let Some(line) = event.legacy_line() {
+            let _ = lines.send(line);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON lines (--log-format json)
+// ---------------------------------------------------------------------------
+
+/// One line of `--log-format json`, tagged by `event`. Times and durations in milliseconds
+/// since the build started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEvent {
+    JobStarted {
+        time_ms: f64,
+        kind: JobKind,
+        target: String,
+        source: Option<PathBuf>,
+        worker: usize,
+    },
+    JobFinished {
+        time_ms: f64,
+        kind: JobKind,
+        target: String,
+        source: Option<PathBuf>,
+        worker: usize,
+        duration_ms: f64,
+        success: bool,
+        up_to_date: bool,
+    },
+    Diagnostic {
+        target: String,
+        #[serde(flatten)]
+        diagnostic: Diagnostic,
+    },
+    BuildFinished {
+        time_ms: f64,
+        success: bool,
+        total_targets: usize,
+        successful_targets: usize,
+        failed_targets: usize,
+        not_attempted: Vec<String>,
+        warnings: usize,
+        errors: usize,
+    },
+}
+
+fn ms(d: Duration) -> f64 {
+    (d.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+pub fn job_started(kind: JobKind, target: &str, source: Option<&Path>, worker: usize, time: Duration) -> LogEvent {
+    LogEvent::JobStarted { time_ms: ms(time), kind, target: target.to_string(), source: source.map(Path::to_path_buf), worker }
+}
+
+pub fn job_finished(job: &JobTiming) -> LogEvent {
+    LogEvent::JobFinished {
+        time_ms: ms(job.end),
+        kind: job.kind,
+        target: job.target_name.clone(),
+        source: job.source.clone(),
+        worker: job.worker,
+        duration_ms: ms(job.duration()),
+        success: job.success,
+        up_to_date: job.up_to_date,
+    }
+}
+
+pub fn diagnostic(target: &str, d: &Diagnostic) -> LogEvent {
+    LogEvent::Diagnostic { target: target.to_string(), diagnostic: d.clone() }
+}
+
+pub fn build_finished(result: &BuildResult) -> LogEvent {
+    LogEvent::BuildFinished {
+        time_ms: ms(result.wall_time),
+        success: result.success,
+        total_targets: result.total_targets,
+        successful_targets: result.successful_targets,
+        failed_targets: result.failed_targets,
+        not_attempted: result.not_attempted.clone(),
+        warnings: result.diagnostic_totals().warnings,
+        errors: result.diagnostic_totals().errors,
+    }
+}
+
+/// Writes one event as a single line and flushes so consumers see it immediately.
+pub fn emit(event: &LogEvent) {
+    let Ok(line) = serde_json::to_string(event) else { return };
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_lines_match_old_protocol() {
+        let finished = BuildEvent::Finished {
+            stats: BuildStats { success: true, total: 2, successful: 2, failed: 0, warnings: 3, errors: 0 },
+        };
+        assert_eq!(finished.legacy_line().unwrap(), "__ngmake_FINISH__\ttrue\t2\t2\t0");
+        assert_eq!(
+            BuildEvent::output(Some("app"), "  [OK] done").legacy_line().unwrap(),
+            "[TARGET:app]   [OK] done"
+        );
+        assert_eq!(BuildEvent::Started { total_targets: 3 }.legacy_line().unwrap(), "__ngmake_TOTAL__\t3");
+    }
+
+    #[test]
+    fn test_target_status_json_has_state_and_progress() {
+        let event = BuildEvent::TargetStatus { target: "app".to_string(), status: TargetState::Compiling { done: 2, total: 5 } };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"type":"target_status","target":"app","state":"compiling","done":2,"total":5}"#);
+        assert_eq!(serde_json::from_str::<BuildEvent>(&json).unwrap(), event);
+        assert_eq!(event.legacy_line(), None);
+    }
+
+    #[test]
+    fn test_log_events_are_flat_json_lines() {
+        let diagnostic = Diagnostic {
+            file: PathBuf::from("src/a.cpp"),
+            line: 3,
+            column: Some(5),
+            severity: crate::diagnostics::Severity::Warning,
+            message: "unused variable 'x'".to_string(),
+            hint: None,
+        };
+        let event = super::diagnostic("app", &diagnostic);
+        let line = serde_json::to_string(&event).unwrap();
+        assert_eq!(line, r#"{"event":"diagnostic","target":"app","file":"src/a.cpp","line":3,"column":5,"severity":"warning","message":"unused variable 'x'"}"#);
+        assert_eq!(serde_json::from_str::<LogEvent>(&line).unwrap(), event);
+
+        let started = job_started(JobKind::Compile, "app", Some(Path::new("src/a.cpp")), 0, Duration::from_micros(1500));
+        assert_eq!(
+            serde_json::to_string(&started).unwrap(),
+            r#"{"event":"job_started","time_ms":1.5,"kind":"compile","target":"app","source":"src/a.cpp","worker":0}"#
+        );
+    }
+}
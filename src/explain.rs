@@ -0,0 +1,131 @@
+// ============================================================================
+// explain.rs — `ngm explain <target>`: why targets and objects are rebuilt
+// ============================================================================
+//
+// Runs the incremental checks of compiler.rs (object_dirty, link_dirty,
+// outputs_dirty) for a target and its dependencies without building, like
+// `ninja -d explain`. A target whose dependency is rebuilt is reported as
+// relinked, because the dependency's output will be newer after the build.
+//
+// The checks compare modification times of sources, objects and outputs
+// only: headers and changed flags are not tracked, so they never make a
+// target dirty (run `ngm --clean` after changing either).
+// ============================================================================
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::compiler::{self, Dirty};
+use crate::config::{ResolvedProject, TargetType};
+use crate::dag::BuildOrder;
+
+/// Why one target is rebuilt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub target: String,
+    /// Sources that are compiled again, with the reason
+    pub objects: Vec<(PathBuf, Dirty)>,
+    /// Why the output is linked (or the custom commands run) again; None = up to date
+    pub output: Option<String>,
+}
+
+impl Explanation {
+    pub fn is_dirty(&self) -> bool {
+        !self.objects.is_empty() || self.output.is_some()
+    }
+}
+
+/// Explains every target of `order` (in build order).
+pub fn explain(project: &ResolvedProject, order: &BuildOrder) -> Vec<Explanation> {
+    let outputs: HashMap<String, PathBuf> =
+        project.targets.values().map(|t| (t.name.clone(), compiler::output_path(t))).collect();
+    let mut rebuilt: HashSet<String> = HashSet::new();
+    let mut explanations = Vec::new();
+    for target in order.levels.iter().flatten().filter_map(|name| project.targets.get(name)) {
+        let rebuilt_dep = target.deps.iter().find(|d| rebuilt.contains(*d));
+        let mut objects = Vec::new();
+        let output = match target.target_type {
+            TargetType::Interface | TargetType::Group => None,
+            _ if target.prebuilt => None,
+            TargetType::Custom => compiler::outputs_dirty(&target.inputs, &target.outputs).map(|d| d.to_string()),
+            _ => {
+                let object_files: Vec<PathBuf> = target.sources.iter().map(|s| compiler::object_path(target, s)).collect();
+                for (source, obj) in target.sources.iter().zip(&object_files) {
+                    if let Some(dirty) = compiler::object_dirty(source, obj) {
+                        objects.push((source.clone(), dirty));
+                    }
+                }
+                let output_path = compiler::output_path(target);
+                if !objects.is_empty() {
+                    Some(format!("{} of {} objects are recompiled", objects.len(), object_files.len()))
+                } else if let Some(dep) = rebuilt_dep {
+                    Some(format!("dependency '{}' is rebuilt", dep))
+                } else {
+                    compiler::link_dirty(&output_path, &compiler::link_stamp_path(target), &object_files, &target.deps, &outputs).map(|d| d.to_string())
+                }
+            }
+        };
+        let explanation = Explanation { target: target.name.clone(), objects, output };
+        if explanation.is_dirty() {
+            rebuilt.insert(target.name.clone());
+        }
+        explanations.push(explanation);
+    }
+    explanations
+}
+
+/// Report lines: one per target, indented lines for its recompiled sources.
+pub fn format_explanations(explanations: &[Explanation]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for e in explanations {
+        match &e.output {
+            Some(reason) => lines.push(format!("{}: rebuilt — {}", e.target, reason)),
+            None => lines.push(format!("{}: up to date", e.target)),
+        }
+        for (source, dirty) in &e.objects {
+            lines.push(format!("  {}: {}", source.display(), dirty));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::ResolvedTarget;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_reports_changed_sources_and_relinked_dependents() {
+        let dir = TempDir::new("explain");
+        let mut project = ResolvedProject::default();
+        for (name, target_type, deps) in [("util", TargetType::StaticLib, vec![]), ("app", TargetType::Executable, vec!["util".to_string()])] {
+            let source = dir.join(format!("{}.cpp", name));
+            std::fs::write(&source, "").unwrap();
+            let target = ResolvedTarget { name: name.to_string(), target_type, sources: vec![source], deps, output_dir: dir.to_path_buf(), ..Default::default() };
+            project.targets.insert(name.to_string(), target);
+        }
+        let order = crate::dag::build_order(&project).unwrap();
+        let explanations = explain(&project, &order);
+        assert!(matches!(explanations[0].objects[0].1, Dirty::Missing(_)));
+
+        // Everything built after the sources were written: up to date
+        let later = SystemTime::now() + Duration::from_secs(10);
+        for t in project.targets.values() {
+            for path in [compiler::object_path(t, &t.sources[0]), compiler::output_path(t)] {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::File::create(&path).unwrap().set_modified(later).unwrap();
+            }
+        }
+        assert!(explain(&project, &order).iter().all(|e| !e.is_dirty()));
+
+        // A newer util.cpp recompiles util and relinks app
+        std::fs::File::options().write(true).open(dir.join("util.cpp")).unwrap().set_modified(later + Duration::from_secs(10)).unwrap();
+        let lines = format_explanations(&explain(&project, &order));
+        assert_eq!(lines[0], "util: rebuilt — 1 of 1 objects are recompiled");
+        let source = dir.join("util.cpp").display().to_string();
+        assert_eq!(lines[1], format!("  {}: {} is newer than {}", source, source, dir.join("obj/util/util.o").display()));
+        assert_eq!(lines[2], "app: rebuilt — dependency 'util' is rebuilt");
+    }
+}
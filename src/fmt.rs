@@ -0,0 +1,141 @@
+// ============================================================================
+// fmt.rs — `ngm fmt`: one layout for every build.toml
+// ============================================================================
+//
+// Rewrites the whitespace of a build.toml (toml_edit), keeping every comment
+// and every value as written:
+//
+//   - `key = value` with single spaces and no indentation
+//   - one blank line before each table header, at most one between keys
+//   - `name` and `type` first in every [[target]]
+//   - arrays on one line if the line fits in MAX_WIDTH columns, else one
+//     element per line with a trailing comma; arrays with comments inside
+//     are left as they are
+// ============================================================================
+
+use toml_edit::{Array, DocumentMut, Item, RawString, Table, Value};
+
+/// Longest `key = [...]` line before an array is split into one element per line.
+pub const MAX_WIDTH: usize = 100;
+
+/// `text` in the canonical layout.
+pub fn format_config(text: &str) -> Result<String, String> {
+    let mut doc = text.parse::<DocumentMut>().map_err(|e| format!("TOML parse error: {}", e))?;
+    format_table(doc.as_table_mut(), false);
+    let trailing = comments(Some(doc.trailing()), true);
+    doc.set_trailing(trailing);
+    Ok(format!("{}\n", doc.to_string().trim_start_matches('\n').trim_end()))
+}
+
+fn format_table(table: &mut Table, is_target: bool) {
+    if is_target {
+        let rank = |key: &str| ["name", "type"].iter().position(|k| *k == key).unwrap_or(2);
+        table.sort_values_by(|a, _, b, _| rank(a.get()).cmp(&rank(b.get())));
+    }
+    let mut first = true;
+    for (mut key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => {
+                let decor = key.leaf_decor_mut();
+                let prefix = comments(decor.prefix(), !first);
+                decor.set_prefix(prefix);
+                decor.set_suffix(" ");
+                format_value(value, key.get().len());
+                first = false;
+            }
+            Item::Table(sub) => {
+                if !sub.is_dotted() {
+                    let decor = sub.decor_mut();
+                    let prefix = format!("\n{}", comments(decor.prefix(), false));
+                    decor.set_prefix(prefix);
+                }
+                format_table(sub, false);
+            }
+            Item::ArrayOfTables(tables) => {
+                for sub in tables.iter_mut() {
+                    let decor = sub.decor_mut();
+                    let prefix = format!("\n{}", comments(decor.prefix(), false));
+                    decor.set_prefix(prefix);
+                    format_table(sub, key.get() == "target");
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+/// The comment lines of a decor prefix without their indentation. Blank lines
+/// collapse to one; a leading one is kept only if `blank_line`.
+fn comments(prefix: Option<&RawString>, blank_line: bool) -> String {
+    let raw = prefix.and_then(RawString::as_str).unwrap_or_default();
+    let mut out = String::new();
+    let mut blank = false;
+    // The last part is the indentation in front of the key or header
+    let lines: Vec<&str> = raw.split('\n').collect();
+    for line in &lines[..lines.len() - 1] {
+        let line = line.trim();
+        if line.is_empty() {
+            blank = true;
+            continue;
+        }
+        if blank && (blank_line || !out.is_empty()) {
+            out.push('\n');
+        }
+        blank = false;
+        out.push_str(line);
+        out.push('\n');
+    }
+    if blank && (blank_line || !out.is_empty()) {
+        out.push('\n');
+    }
+    out
+}
+
+fn format_value(value: &mut Value, key_width: usize) {
+    let comment = value.decor().suffix().and_then(RawString::as_str).unwrap_or_default().trim().to_string();
+    let decor = value.decor_mut();
+    decor.set_prefix(" ");
+    decor.set_suffix(if comment.is_empty() { String::new() } else { format!(" {}", comment) });
+    if let Value::Array(array) = value {
+        format_array(array, key_width);
+    }
+}
+
+fn format_array(array: &mut Array, key_width: usize) {
+    let has_comment = |decor: &toml_edit::Decor| {
+        [decor.prefix(), decor.suffix()].into_iter().flatten().any(|raw| raw.as_str().is_some_and(|s| s.contains('#')))
+    };
+    if array.iter().any(|v| has_comment(v.decor())) || array.trailing().as_str().is_some_and(|s| s.contains('#')) {
+        return;
+    }
+    // key = [a, b, c]
+    let width = key_width + 4 + array.iter().map(|v| v.to_string().trim().len() + 2).sum::<usize>();
+    let split = width > MAX_WIDTH && array.len() > 1;
+    for (i, element) in array.iter_mut().enumerate() {
+        let prefix = match (split, i) {
+            (true, _) => "\n    ",
+            (false, 0) => "",
+            (false, _) => " ",
+        };
+        element.decor_mut().set_prefix(prefix);
+        element.decor_mut().set_suffix("");
+    }
+    array.set_trailing_comma(split);
+    array.set_trailing(if split { "\n" } else { "" });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_layout_and_keeps_comments() {
+        let text = "\n\n[project]\n  name=\"demo\"   # the project\n\n\n\n# Targets\n[[target]]\nsources = [ \"a.cpp\",\"b.cpp\" ]\n    type = \"executable\"\nname = \"app\"\ndefines = [\n  \"A\", # first\n]\ncompiler_flags = [\"-Wall\", \"-Wextra\", \"-Wpedantic\", \"-Wshadow\", \"-Wconversion\", \"-Wsign-conversion\", \"-O2\"]\n";
+        let formatted = format_config(text).unwrap();
+        assert_eq!(
+            formatted,
+            "[project]\nname = \"demo\" # the project\n\n# Targets\n[[target]]\nname = \"app\"\ntype = \"executable\"\nsources = [\"a.cpp\", \"b.cpp\"]\ndefines = [\n  \"A\", # first\n]\ncompiler_flags = [\n    \"-Wall\",\n    \"-Wextra\",\n    \"-Wpedantic\",\n    \"-Wshadow\",\n    \"-Wconversion\",\n    \"-Wsign-conversion\",\n    \"-O2\",\n]\n"
+        );
+        assert_eq!(format_config(&formatted).unwrap(), formatted);
+    }
+}
@@ -0,0 +1,101 @@
+// ============================================================================
+// grammar.rs — flex / bison sources (`.l`, `.ll`, `.y`, `.yy`)
+// ============================================================================
+//
+// Lexer and grammar files listed in a target's `sources` are not compiled
+// directly. A companion custom target `<name>_grammar` runs the generators
+// into the target's object directory, and the generated files are compiled
+// instead:
+//
+//   flex   scanner.l   → scanner.yy.c     (.ll → .yy.cc)
+//   bison  parser.y    → parser.tab.c + parser.tab.h   (.yy → .tab.cc + .tab.hh)
+//
+// The object directory is added to the include path so the scanner can
+// `#include "parser.tab.h"`. The generators rerun when a grammar file changes.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use crate::config::ResolvedTarget;
+
+/// Moves the flex/bison files out of `target.sources`, adds the generated sources
+/// instead and returns the custom target running the generators (None if there are none).
+pub fn generator(target: &mut ResolvedTarget) -> Option<ResolvedTarget> {
+    let (grammars, sources): (Vec<PathBuf>, Vec<PathBuf>) =
+        target.sources.drain(..).partition(|s| kind(s).is_some());
+    target.sources = sources;
+    if grammars.is_empty() {
+        return None;
+    }
+
+    let gen_dir = target.output_dir.join("obj").join(&target.name);
+    let mut commands = Vec::new();
+    let mut outputs = Vec::new();
+    for grammar in &grammars {
+        let stem = grammar.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let input = grammar.to_string_lossy().to_string();
+        match kind(grammar) {
+            Some(Kind::Lexer { cxx }) => {
+                let source = gen_dir.join(format!("{}.yy.{}", stem, if cxx { "cc" } else { "c" }));
+                commands.push(vec!["flex".to_string(), "-o".to_string(), source.to_string_lossy().to_string(), input]);
+                target.sources.push(source.clone());
+                outputs.push(source);
+            }
+            Some(Kind::Parser { cxx }) => {
+                let (c, h) = if cxx { ("cc", "hh") } else { ("c", "h") };
+                let source = gen_dir.join(format!("{}.tab.{}", stem, c));
+                commands.push(vec!["bison".to_string(), "-d".to_string(), "-o".to_string(), source.to_string_lossy().to_string(), input]);
+                target.sources.push(source.clone());
+                outputs.push(source);
+                outputs.push(gen_dir.join(format!("{}.tab.{}", stem, h)));
+            }
+            None => {}
+        }
+    }
+
+    target.include_dirs.push(gen_dir);
+    Some(target.generator("grammar", commands, grammars, outputs))
+}
+
+enum Kind {
+    Lexer { cxx: bool },
+    Parser { cxx: bool },
+}
+
+fn kind(source: &Path) -> Option<Kind> {
+    match source.extension()?.to_str()? {
+        "l" => Some(Kind::Lexer { cxx: false }),
+        "ll" => Some(Kind::Lexer { cxx: true }),
+        "y" => Some(Kind::Parser { cxx: false }),
+        "yy" => Some(Kind::Parser { cxx: true }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replaces_grammar_files_with_generated_sources() {
+        let mut calc = ResolvedTarget {
+            name: "calc".to_string(),
+            sources: vec![PathBuf::from("src/main.c"), PathBuf::from("src/scanner.l"), PathBuf::from("src/parser.yy")],
+            output_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+        let gen = generator(&mut calc).unwrap();
+
+        let obj = PathBuf::from("build/obj/calc");
+        assert_eq!(gen.name, "calc_grammar");
+        assert_eq!(gen.inputs, vec![PathBuf::from("src/scanner.l"), PathBuf::from("src/parser.yy")]);
+        assert_eq!(gen.commands[0], vec!["flex", "-o", "build/obj/calc/scanner.yy.c", "src/scanner.l"]);
+        assert_eq!(gen.outputs, vec![obj.join("scanner.yy.c"), obj.join("parser.tab.cc"), obj.join("parser.tab.hh")]);
+        assert_eq!(calc.sources, vec![PathBuf::from("src/main.c"), obj.join("scanner.yy.c"), obj.join("parser.tab.cc")]);
+        assert_eq!(calc.include_dirs, vec![obj]);
+        assert_eq!(calc.deps, vec!["calc_grammar".to_string()]);
+
+        let mut plain = ResolvedTarget { sources: vec![PathBuf::from("main.c")], ..Default::default() };
+        assert!(generator(&mut plain).is_none());
+    }
+}
@@ -0,0 +1,162 @@
+// ============================================================================
+// hints.rs — remediation hints for failed compiles
+// ============================================================================
+//
+// A compile that fails with a missing include usually lacks a dependency or
+// an include_dir, and the header is somewhere in the workspace. The first
+// time a build sees such an error, the project tree is indexed once (hidden
+// directories such as .git and .oximake, and output directories, are
+// skipped) and the header is looked up by its path as written in the
+// #include:
+//
+//   #include "net/socket.h"  →  libs/net/include/net/socket.h
+//
+// The directory the #include is relative to (libs/net/include) is the
+// include_dir to add. If another target already declares it, the hint is
+// to depend on that target instead, which also brings its libs and defines.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::{ResolvedProject, ResolvedTarget};
+
+/// The header named by a missing-include error: gcc `foo.h: No such file or directory`,
+/// clang `'foo.h' file not found`.
+pub fn missing_include(message: &str) -> Option<&str> {
+    let header = match message.strip_suffix(": No such file or directory") {
+        Some(header) => header,
+        None => message.strip_suffix(" file not found")?.trim_matches('\''),
+    };
+    (!header.is_empty() && !header.contains(' ')).then_some(header)
+}
+
+/// Files of the project by file name, collected on first use.
+pub struct HeaderIndex {
+    root: PathBuf,
+    skip: Vec<PathBuf>,
+    files: OnceLock<HashMap<OsString, Vec<PathBuf>>>,
+}
+
+impl HeaderIndex {
+    pub fn new(project: &ResolvedProject) -> HeaderIndex {
+        let skip = project.targets.values().map(|t| canonical(&t.output_dir)).collect();
+        HeaderIndex { root: project.root_dir.clone(), skip, files: OnceLock::new() }
+    }
+
+    /// Files whose path ends with `header` (compared by components), sorted.
+    fn find(&self, header: &Path) -> Vec<PathBuf> {
+        let files = self.files.get_or_init(|| {
+            let mut files: HashMap<OsString, Vec<PathBuf>> = HashMap::new();
+            // "." (build.toml in the working directory) lists paths as the compiler shows them
+            let root = if self.root == Path::new(".") { Path::new("") } else { self.root.as_path() };
+            collect_files(root, &self.skip, &mut files);
+            files
+        });
+        let Some(name) = header.file_name() else { return Vec::new() };
+        let mut found: Vec<PathBuf> = files.get(name).into_iter().flatten().filter(|f| f.ends_with(header)).cloned().collect();
+        found.sort();
+        found
+    }
+}
+
+fn collect_files(dir: &Path, skip: &[PathBuf], files: &mut HashMap<OsString, Vec<PathBuf>>) {
+    // Paths keep the form of `dir` (no "./" prefix for the current directory)
+    let read_dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let Ok(entries) = std::fs::read_dir(read_dir) else { return };
+    for entry in entries.flatten() {
+        let path = dir.join(entry.file_name());
+        let name = entry.file_name();
+        let Ok(file_type) = entry.file_type() else { continue };
+        if file_type.is_dir() {
+            // .git, .oximake (state, cache and downloaded packages), build outputs
+            if !name.to_string_lossy().starts_with('.') && !skip.contains(&canonical(read_dir.join(&name).as_path())) {
+                collect_files(&path, skip, files);
+            }
+        } else {
+            files.entry(name).or_default().push(path);
+        }
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b || canonical(a) == canonical(b)
+}
+
+/// What to add to `target` so `header` is found, or None if it isn't in the project.
+pub fn missing_include_hint(project: &ResolvedProject, target: &ResolvedTarget, header: &str, index: &HeaderIndex) -> Option<String> {
+    let header_path = Path::new(header);
+    let found = index.find(header_path);
+    let first = found.first()?;
+    let depth = header_path.components().count();
+    let include_dir = first.ancestors().nth(depth)?.to_path_buf();
+    let others = match found.len() {
+        1 => String::new(),
+        n => format!(" ({} other copies in the project)", n - 1),
+    };
+    let mut owners: Vec<&ResolvedTarget> = project
+        .targets
+        .values()
+        .filter(|t| t.name != target.name && !target.deps.contains(&t.name))
+        .filter(|t| t.include_dirs.iter().any(|d| same_dir(d, &include_dir)))
+        .collect();
+    owners.sort_by(|a, b| a.name.cmp(&b.name));
+    let shown = include_dir.strip_prefix(&target.working_dir).unwrap_or(&include_dir);
+    let shown = if shown.as_os_str().is_empty() { Path::new(".") } else { shown };
+    Some(match owners.first() {
+        Some(owner) => format!(
+            "{} is {}{}: add \"{}\" to deps of '{}' (its include_dirs contain {})",
+            header,
+            first.display(),
+            others,
+            owner.name,
+            target.name,
+            shown.display()
+        ),
+        None => format!(
+            "{} is {}{}: add \"{}\" to include_dirs of '{}'",
+            header,
+            first.display(),
+            others,
+            shown.display(),
+            target.name
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_missing_headers_are_found_and_the_owning_target_suggested() {
+        assert_eq!(missing_include("net/socket.h: No such file or directory"), Some("net/socket.h"));
+        assert_eq!(missing_include("'net/socket.h' file not found"), Some("net/socket.h"));
+        assert_eq!(missing_include("expected ';' after expression"), None);
+
+        let dir = TempDir::new("hints");
+        let include = dir.join("libs/net/include");
+        std::fs::create_dir_all(include.join("net")).unwrap();
+        std::fs::write(include.join("net/socket.h"), "").unwrap();
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        let app = ResolvedTarget { name: "app".to_string(), working_dir: dir.to_path_buf(), output_dir: dir.join("build"), ..Default::default() };
+        project.targets.insert("app".to_string(), app.clone());
+
+        let index = HeaderIndex::new(&project);
+        let hint = missing_include_hint(&project, &app, "net/socket.h", &index).unwrap();
+        assert!(hint.ends_with("add \"libs/net/include\" to include_dirs of 'app'"), "{}", hint);
+
+        let net = ResolvedTarget { name: "net".to_string(), include_dirs: vec![include], ..Default::default() };
+        project.targets.insert("net".to_string(), net);
+        let hint = missing_include_hint(&project, &app, "net/socket.h", &index).unwrap();
+        assert!(hint.ends_with("add \"net\" to deps of 'app' (its include_dirs contain libs/net/include)"), "{}", hint);
+        assert_eq!(missing_include_hint(&project, &app, "zlib.h", &index), None);
+    }
+}
@@ -0,0 +1,333 @@
+// ============================================================================
+// history.rs — Persistent build history and `ngm stats`
+// ============================================================================
+//
+// Every build appends one record (duration, targets built, cache hits,
+// failures) to `.oximake/history.toml` next to the root build.toml. The file
+// is a plain TOML array of `[[build]]` tables, trimmed to the most recent
+// MAX_RECORDS entries, so it stays small and human-readable.
+//
+// `.oximake/durations.toml` keeps the last known duration of every compile
+// and link job; the progress line uses it to estimate the remaining time.
+// ============================================================================
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::builder::{BuildResult, JobKind, JobTiming};
+
+/// Per-project state directory (relative to the root build.toml).
+pub const STATE_DIR: &str = ".oximake";
+
+/// Oldest records are dropped beyond this count.
+pub const MAX_RECORDS: usize = 1000;
+
+/// One finished build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildRecord {
+    /// Unix time (seconds) when the build finished
+    pub timestamp: u64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub total_targets: usize,
+    pub successful_targets: usize,
+    pub failed_targets: usize,
+    /// Sources actually compiled
+    pub compiled_files: usize,
+    /// Sources skipped because their object file was up-to-date
+    pub cache_hits: usize,
+    pub jobs: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default, rename = "build")]
+    builds: Vec<BuildRecord>,
+}
+
+/// Last known job durations in milliseconds, keyed by source path (compile)
+/// and target name (link).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobDurations {
+    #[serde(default)]
+    pub compile: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub link: BTreeMap<String, u64>,
+}
+
+impl JobDurations {
+    /// Historical duration of `job`, if it ran before.
+    pub fn estimate(&self, kind: JobKind, key: &str) -> Option<u64> {
+        match kind {
+            JobKind::Compile => self.compile.get(key).copied(),
+            JobKind::Link => self.link.get(key).copied(),
+        }
+    }
+
+    /// Folds the timings of a finished build in. Up-to-date and failed jobs
+    /// say nothing about how long a real compile takes and are ignored.
+    pub fn update(&mut self, jobs: &[JobTiming]) {
+        for job in jobs.iter().filter(|j| j.success && !j.up_to_date) {
+            let ms = job.duration().as_millis() as u64;
+            let map = match job.kind {
+                JobKind::Compile => &mut self.compile,
+                JobKind::Link => &mut self.link,
+            };
+            // Average with the previous value to smooth out noisy runs
+            let entry = map.entry(job_key(job)).or_insert(ms);
+            *entry = (*entry + ms) / 2;
+        }
+    }
+}
+
+/// Key of a job in JobDurations: the source path for compiles, the target for links.
+pub fn job_key(job: &JobTiming) -> String {
+    match (&job.kind, &job.source) {
+        (JobKind::Compile, Some(src)) => src.display().to_string(),
+        _ => job.target_name.clone(),
+    }
+}
+
+/// `.oximake/` directory for the project rooted at `root_dir`.
+pub fn state_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(STATE_DIR)
+}
+
+/// `.oximake/history.toml`
+pub fn history_path(root_dir: &Path) -> PathBuf {
+    state_dir(root_dir).join("history.toml")
+}
+
+/// `.oximake/durations.toml`
+pub fn durations_path(root_dir: &Path) -> PathBuf {
+    state_dir(root_dir).join("durations.toml")
+}
+
+/// Summarizes a finished build into a record.
+pub fn record_from_result(result: &BuildResult) -> BuildRecord {
+    let compiles = result.jobs.iter().filter(|j| j.kind == JobKind::Compile);
+    let (hits, compiled): (Vec<_>, Vec<_>) = compiles.partition(|j| j.up_to_date);
+    BuildRecord {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        duration_ms: result.wall_time.as_millis() as u64,
+        success: result.success,
+        total_targets: result.total_targets,
+        successful_targets: result.successful_targets,
+        failed_targets: result.failed_targets,
+        compiled_files: compiled.len(),
+        cache_hits: hits.len(),
+        jobs: result.workers,
+    }
+}
+
+/// Loads all records (oldest first). A missing file is an empty history.
+pub fn load_history(root_dir: &Path) -> Result<Vec<BuildRecord>, String> {
+    let path = history_path(root_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let file: HistoryFile = toml::from_str(&content)
+        .map_err(|e| format!("Invalid build history '{}': {}", path.display(), e))?;
+    Ok(file.builds)
+}
+
+/// Appends a record, keeping at most MAX_RECORDS entries.
+pub fn append_record(root_dir: &Path, record: BuildRecord) -> Result<(), String> {
+    let dir = state_dir(root_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    // A corrupt history is replaced rather than blocking builds
+    let mut builds = load_history(root_dir).unwrap_or_default();
+    builds.push(record);
+    if builds.len() > MAX_RECORDS {
+        builds.drain(..builds.len() - MAX_RECORDS);
+    }
+    let content = toml::to_string(&HistoryFile { builds })
+        .map_err(|e| format!("Failed to serialize build history: {}", e))?;
+    let path = history_path(root_dir);
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Loads historical job durations. A missing or unreadable file yields no estimates.
+pub fn load_durations(root_dir: &Path) -> JobDurations {
+    std::fs::read_to_string(durations_path(root_dir))
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Records a finished build: appends it to the history and updates job durations.
+pub fn record_build(root_dir: &Path, result: &BuildResult) -> Result<(), String> {
+    append_record(root_dir, record_from_result(result))?;
+    let mut durations = load_durations(root_dir);
+    durations.update(&result.jobs);
+    let content = toml::to_string(&durations)
+        .map_err(|e| format!("Failed to serialize job durations: {}", e))?;
+    let path = durations_path(root_dir);
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+// ---------------------------------------------------------------------------
+// `ngm stats` output
+// ---------------------------------------------------------------------------
+
+/// Formats the last `last_n` builds with a bar chart of durations plus totals.
+pub fn format_stats(records: &[BuildRecord], last_n: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if records.is_empty() {
+        lines.push("  No builds recorded yet.".to_string());
+        return lines;
+    }
+    let shown = &records[records.len().saturating_sub(last_n)..];
+    let max_ms = shown.iter().map(|r| r.duration_ms).max().unwrap_or(0).max(1);
+    const BAR_WIDTH: u64 = 30;
+
+    lines.push(format!("  Last {} build(s):", shown.len()));
+    for r in shown {
+        let bar_len = (r.duration_ms * BAR_WIDTH).div_ceil(max_ms) as usize;
+        lines.push(format!(
+            "  {}  {:<30} {:>9}  {} {}/{} targets, {} compiled, {} cached",
+            format_timestamp(r.timestamp),
+            "█".repeat(bar_len),
+            format_ms(r.duration_ms),
+            if r.success { "✓" } else { "✗" },
+            r.successful_targets,
+            r.total_targets,
+            r.compiled_files,
+            r.cache_hits
+        ));
+    }
+
+    let n = records.len() as u64;
+    let total_ms: u64 = records.iter().map(|r| r.duration_ms).sum();
+    let ok = records.iter().filter(|r| r.success).count();
+    let compiled: usize = records.iter().map(|r| r.compiled_files).sum();
+    let hits: usize = records.iter().map(|r| r.cache_hits).sum();
+    let hit_rate = (hits * 100).checked_div(compiled + hits).unwrap_or(0);
+    lines.push(String::new());
+    lines.push(format!("  Builds        : {} ({} successful, {} failed)", n, ok, records.len() - ok));
+    lines.push(format!("  Average time  : {}", format_ms(total_ms / n)));
+    lines.push(format!(
+        "  Min / max     : {} / {}",
+        format_ms(records.iter().map(|r| r.duration_ms).min().unwrap_or(0)),
+        format_ms(records.iter().map(|r| r.duration_ms).max().unwrap_or(0))
+    ));
+    lines.push(format!("  Cache hit rate: {}% ({} of {} sources)", hit_rate, hits, compiled + hits));
+    lines
+}
+
+/// Unix seconds → `YYYY-MM-DD HH:MM` (UTC).
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, rem / 3600, (rem % 3600) / 60)
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms >= 60_000 {
+        format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000)
+    } else {
+        format!("{:.2}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn record(timestamp: u64, duration_ms: u64, success: bool, compiled_files: usize, cache_hits: usize) -> BuildRecord {
+        BuildRecord {
+            timestamp,
+            duration_ms,
+            success,
+            total_targets: 2,
+            successful_targets: if success { 2 } else { 1 },
+            failed_targets: if success { 0 } else { 1 },
+            compiled_files,
+            cache_hits,
+            jobs: 4,
+        }
+    }
+
+    #[test]
+    fn test_history_round_trips_through_toml() {
+        let dir = TempDir::new("history_round_trip");
+        assert_eq!(load_history(&dir).unwrap(), Vec::new());
+
+        let first = record(1_700_000_000, 1500, true, 3, 1);
+        let second = record(1_700_000_100, 800, false, 0, 4);
+        append_record(&dir, first.clone()).unwrap();
+        append_record(&dir, second.clone()).unwrap();
+
+        assert!(history_path(&dir).starts_with(dir.join(STATE_DIR)));
+        assert_eq!(load_history(&dir).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_corrupt_history_is_an_error_and_replaced_on_append() {
+        let dir = TempDir::new("history_corrupt");
+        dir.write(".oximake/history.toml", "[[build]]\ntimestamp = \"yesterday\"\n");
+        let err = load_history(&dir).unwrap_err();
+        assert!(err.starts_with("Invalid build history"), "{}", err);
+
+        let only = record(1_700_000_000, 1500, true, 3, 1);
+        append_record(&dir, only.clone()).unwrap();
+        assert_eq!(load_history(&dir).unwrap(), vec![only]);
+    }
+
+    #[test]
+    fn test_stats_aggregate_all_records() {
+        let records = [
+            record(1_700_000_000, 1000, true, 3, 1),
+            record(1_700_000_060, 3000, false, 1, 0),
+            record(1_700_000_120, 65_000, true, 0, 5),
+        ];
+        let lines = format_stats(&records, 10);
+
+        assert_eq!(lines[0], "  Last 3 build(s):");
+        assert!(lines[1].starts_with("  2023-11-14 22:13  █ "), "{}", lines[1]);
+        assert!(lines[2].contains("✗ 1/2 targets, 1 compiled, 0 cached"), "{}", lines[2]);
+        assert!(lines[3].contains(&"█".repeat(30)), "{}", lines[3]);
+        assert_eq!(
+            lines[5..],
+            [
+                "  Builds        : 3 (2 successful, 1 failed)",
+                "  Average time  : 23.00s",
+                "  Min / max     : 1.00s / 1m05s",
+                "  Cache hit rate: 60% (6 of 10 sources)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stats_list_only_the_last_builds_but_aggregate_all() {
+        let records = [record(0, 2000, true, 4, 0), record(60, 4000, true, 4, 0), record(120, 6000, true, 0, 0)];
+        let lines = format_stats(&records, 2);
+
+        assert_eq!(lines[0], "  Last 2 build(s):");
+        assert!(lines[1].starts_with("  1970-01-01 00:01"), "{}", lines[1]);
+        assert_eq!(lines[4], "  Builds        : 3 (3 successful, 0 failed)");
+        assert_eq!(lines[5], "  Average time  : 4.00s");
+        assert_eq!(lines[7], "  Cache hit rate: 0% (0 of 8 sources)");
+
+        assert_eq!(format_stats(&[], 10), ["  No builds recorded yet."]);
+    }
+}
@@ -0,0 +1,243 @@
+// ============================================================================
+// inputs.rs — declared-input verification (`ngm verify-inputs`)
+// ============================================================================
+//
+// Incremental builds and the object cache only see what build.toml declares:
+// a target's sources and include_dirs (plus those of its dependencies). A
+// compile that reads anything else inside the project — `#include
+// "../../net/src/detail.h"`, a header found through a stray -I in flags —
+// has an undeclared dependency that breaks incrementality and caching.
+//
+// This debug mode replays each source's exact compile command under a file
+// access tracer (the object goes to a temporary file) and reports every file
+// the compiler opened for reading that is inside the project root but not
+// a declared source, not under one of the target's include_dirs and not in
+// the directory of the source itself (which the compiler always searches
+// for `#include "..."`). Files outside the project (system and compiler
+// headers) are ignored.
+//
+// Tracers: strace on Linux. macOS (fs_usage needs root) and Windows (ETW)
+// have no tracer yet; `Tracer::detect` reports that instead of guessing.
+// ============================================================================
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rayon::prelude::*;
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget};
+use crate::dag::BuildOrder;
+
+/// How the file accesses of a compile command are observed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tracer {
+    /// `strace -f -e trace=open,openat,openat2` (Linux)
+    Strace,
+}
+
+impl Tracer {
+    /// The tracer of this platform, or why there is none.
+    pub fn detect() -> Result<Tracer, String> {
+        if !cfg!(target_os = "linux") {
+            return Err(format!(
+                "ngm verify-inputs needs a file access tracer; only strace (Linux) is supported, not {}",
+                std::env::consts::OS
+            ));
+        }
+        match Command::new("strace").arg("-V").output() {
+            Ok(out) if out.status.success() => Ok(Tracer::Strace),
+            _ => Err("ngm verify-inputs needs strace (e.g. apt install strace)".to_string()),
+        }
+    }
+
+    /// Runs `program args` and returns whether it succeeded (with its stderr) and the files it read.
+    fn trace(&self, program: &str, args: &[String], log: &Path) -> Result<(bool, String, Vec<PathBuf>), String> {
+        match self {
+            Tracer::Strace => {
+                let output = Command::new("strace")
+                    .args(["-f", "-qq", "-e", "trace=open,openat,openat2", "-o"])
+                    .arg(log)
+                    .arg("--")
+                    .arg(program)
+                    .args(args)
+                    .output()
+                    .map_err(|e| format!("Failed to run strace: {}", e))?;
+                let text = std::fs::read_to_string(log).unwrap_or_default();
+                let _ = std::fs::remove_file(log);
+                Ok((output.status.success(), String::from_utf8_lossy(&output.stderr).to_string(), parse_strace(&text)))
+            }
+        }
+    }
+}
+
+/// Files opened for reading, in order, from `strace -f` output. Failed opens, writes and
+/// directories are left out; calls split into `<unfinished ...>` / `<... resumed>` are joined.
+pub fn parse_strace(text: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut unfinished: HashMap<&str, &str> = HashMap::new();
+    for line in text.lines() {
+        let (pid, call) = match line.split_once(' ') {
+            Some((pid, rest)) if pid.chars().all(|c| c.is_ascii_digit()) => (pid, rest.trim_start()),
+            _ => ("", line),
+        };
+        let call = match call.strip_suffix("<unfinished ...>") {
+            Some(start) => {
+                unfinished.insert(pid, start);
+                continue;
+            }
+            None => call,
+        };
+        let joined;
+        let call = match call.strip_prefix("<... ").and_then(|r| r.split_once(" resumed>")) {
+            Some((_, rest)) => match unfinished.remove(pid) {
+                Some(start) => {
+                    joined = format!("{}{}", start, rest);
+                    joined.as_str()
+                }
+                None => continue,
+            },
+            None => call,
+        };
+        if !(call.starts_with("open(") || call.starts_with("openat(") || call.starts_with("openat2(")) {
+            continue;
+        }
+        let Some((args, result)) = call.rsplit_once(") = ") else { continue };
+        if result.starts_with('-') || ["O_WRONLY", "O_RDWR", "O_CREAT", "O_DIRECTORY"].iter().any(|f| args.contains(f)) {
+            continue;
+        }
+        // open("path", ...) or openat(dirfd, "path", ...); relative paths only with AT_FDCWD
+        let Some((before, rest)) = args.split_once('"') else { continue };
+        let Some((path, _)) = rest.split_once('"') else { continue };
+        if Path::new(path).is_relative() && before.contains("openat") && !before.contains("AT_FDCWD") {
+            continue;
+        }
+        files.push(PathBuf::from(path));
+    }
+    files
+}
+
+// ---------------------------------------------------------------------------
+// Verification
+// ---------------------------------------------------------------------------
+
+/// Undeclared reads of one target.
+#[derive(Debug, Clone, Default)]
+pub struct TargetInputs {
+    pub target_name: String,
+    pub sources: usize,
+    /// Undeclared file → sources whose compile read it
+    pub undeclared: BTreeMap<PathBuf, Vec<PathBuf>>,
+    /// Sources that could not be traced or failed to compile
+    pub errors: Vec<String>,
+}
+
+/// Traces every source of the targets in `order` (in build order), sources in parallel.
+pub fn verify_project(project: &ResolvedProject, order: &BuildOrder, tracer: Tracer) -> Vec<TargetInputs> {
+    let root = absolute(&project.root_dir);
+    order
+        .levels
+        .iter()
+        .flatten()
+        .filter_map(|name| project.targets.get(name))
+        .filter(|t| !t.prebuilt)
+        .map(|target| verify_target(target, &root, tracer))
+        .collect()
+}
+
+fn verify_target(target: &ResolvedTarget, root: &Path, tracer: Tracer) -> TargetInputs {
+    // Sources and everything under include_dirs; only the files directly in a source's directory
+    let mut declared: Vec<PathBuf> = target.sources.iter().map(|s| absolute(s)).collect();
+    declared.extend(target.include_dirs.iter().map(|d| absolute(d)));
+    let source_dirs: Vec<PathBuf> = target.sources.iter().map(|s| absolute(s)).filter_map(|s| s.parent().map(Path::to_path_buf)).collect();
+    let results: Vec<_> = target
+        .sources
+        .par_iter()
+        .enumerate()
+        .map(|(i, source)| {
+            let stem = format!("ngmake_inputs_{}_{}_{}", std::process::id(), target.name, i);
+            let object = std::env::temp_dir().join(format!("{}.o", stem));
+            let (program, args) = compiler::compile_command(target, source, &object);
+            let traced = tracer.trace(&program, &args, &std::env::temp_dir().join(format!("{}.trace", stem)));
+            let _ = std::fs::remove_file(&object);
+            (source, traced)
+        })
+        .collect();
+
+    let mut report = TargetInputs { target_name: target.name.clone(), sources: target.sources.len(), ..Default::default() };
+    for (source, traced) in results {
+        match traced {
+            Ok((true, _, files)) => {
+                for file in files.iter().map(|f| absolute(f)) {
+                    let in_source_dir = file.parent().is_some_and(|dir| source_dirs.iter().any(|d| d == dir));
+                    if file.starts_with(root) && !in_source_dir && !declared.iter().any(|d| file.starts_with(d)) {
+                        let readers = report.undeclared.entry(file).or_default();
+                        if !readers.contains(source) {
+                            readers.push(source.clone());
+                        }
+                    }
+                }
+            }
+            Ok((false, stderr, _)) => report.errors.push(format!("{} failed to compile:\n{}", source.display(), stderr.trim())),
+            Err(e) => report.errors.push(e),
+        }
+    }
+    report
+}
+
+/// Absolute path with symlinks resolved when it exists, so traced and declared paths compare.
+fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| std::env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf()))
+}
+
+/// One block per target with undeclared reads or errors, then a summary.
+pub fn format_report(reports: &[TargetInputs], root: &Path) -> Vec<String> {
+    let root = absolute(root);
+    let show = |p: &Path| p.strip_prefix(&root).unwrap_or(p).display().to_string();
+    let mut lines = Vec::new();
+    for report in reports {
+        if report.undeclared.is_empty() && report.errors.is_empty() {
+            lines.push(format!("  [OK] {} ({} sources)", report.target_name, report.sources));
+            continue;
+        }
+        lines.push(format!("=== Undeclared inputs of target '{}' ===", report.target_name));
+        for (file, readers) in &report.undeclared {
+            let readers: Vec<String> = readers.iter().map(|s| show(&absolute(s))).collect();
+            lines.push(format!("  {} (read by {})", show(file), readers.join(", ")));
+        }
+        for e in &report.errors {
+            for l in e.split('\n') {
+                lines.push(format!("  [ERROR] {}", l));
+            }
+        }
+    }
+    let undeclared: usize = reports.iter().map(|r| r.undeclared.len()).sum();
+    let targets = reports.iter().filter(|r| !r.undeclared.is_empty()).count();
+    lines.push(format!(
+        "--- {} sources traced, {} undeclared inputs in {} targets (add their directories to include_dirs or a dependency) ---",
+        reports.iter().map(|r| r.sources).sum::<usize>(),
+        undeclared,
+        targets
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strace_reads_are_collected_and_resumed_calls_joined() {
+        let log = r#"4711 openat(AT_FDCWD, "/usr/include/stdio.h", O_RDONLY|O_NOCTTY) = 3
+4711 openat(AT_FDCWD, "include/missing.h", O_RDONLY|O_NOCTTY) = -1 ENOENT (No such file or directory)
+4712 openat(AT_FDCWD, "src/util.h", O_RDONLY|O_NOCTTY <unfinished ...>
+4711 openat(AT_FDCWD, "/tmp/ngmake.o", O_WRONLY|O_CREAT|O_TRUNC, 0666) = 4
+4712 <... openat resumed>) = 5
+4711 openat(AT_FDCWD, "src", O_RDONLY|O_DIRECTORY) = 6
+4711 open("/etc/ld.so.cache", O_RDONLY|O_CLOEXEC) = 3
+4711 openat(7, "relative.h", O_RDONLY) = 8
+"#;
+        assert_eq!(parse_strace(log), vec![PathBuf::from("/usr/include/stdio.h"), PathBuf::from("src/util.h"), PathBuf::from("/etc/ld.so.cache")]);
+    }
+}
@@ -0,0 +1,114 @@
+// ============================================================================
+// interpolate.rs — `${...}` references in build.toml values
+// ============================================================================
+//
+// `${env:NAME}` is replaced with the environment variable NAME during config
+// resolution (paths, flags, libs), so machine-specific locations such as
+// SDK roots don't have to be hard-coded. An unset variable is an error.
+//
+// `${name}` refers to an entry of the `[variables]` table. Each build.toml
+// sees the variables of the file that included it plus its own; values given
+// on the command line (`--set name=value`) override both.
+//
+// `$$` produces a literal `$`.
+// ============================================================================
+
+use std::collections::BTreeMap;
+
+/// User-defined variables (`[variables]` and `--set`), name → value.
+pub type Variables = BTreeMap<String, String>;
+
+/// Expands all `${env:NAME}` and `${name}` references in `input`.
+pub fn expand(input: &str, vars: &Variables) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("unterminated '${{' in '{}'", input))?;
+            out.push_str(&lookup(&body[..end], input, vars)?);
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn lookup(reference: &str, input: &str, vars: &Variables) -> Result<String, String> {
+    match reference.split_once(':') {
+        Some(("env", name)) if !name.is_empty() => std::env::var(name)
+            .map_err(|_| format!("environment variable '{}' is not set (used in '{}')", name, input)),
+        None if !reference.is_empty() => vars
+            .get(reference)
+            .cloned()
+            .ok_or_else(|| format!("undefined variable '{}' (used in '{}'; define it in [variables] or pass --set {}=...)", reference, input, reference)),
+        _ => Err(format!("unknown reference '${{{}}}' in '{}' (expected ${{name}} or ${{env:NAME}})", reference, input)),
+    }
+}
+
+/// Expands every string of a list.
+pub fn expand_all(items: &[String], vars: &Variables) -> Result<Vec<String>, String> {
+    items.iter().map(|s| expand(s, vars)).collect()
+}
+
+/// Variables of a build.toml: `inherited` plus the entries of its `[variables]` table.
+/// Values may reference inherited variables and the environment. Names in `overrides`
+/// (command line) keep their overriding value.
+pub fn file_variables(table: Option<&toml::Value>, inherited: &Variables, overrides: &Variables) -> Result<Variables, String> {
+    let mut vars = inherited.clone();
+    let Some(table) = table else { return Ok(vars) };
+    let table = table.as_table().ok_or("[variables] must be a table")?;
+    for (name, value) in table {
+        if overrides.contains_key(name) {
+            continue;
+        }
+        let value = match value {
+            toml::Value::String(s) => expand(s, inherited)?,
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => value.to_string(),
+            _ => return Err(format!("variable '{}' must be a string", name)),
+        };
+        vars.insert(name.clone(), value);
+    }
+    Ok(vars)
+}
+
+/// Parses `--set name=value` arguments.
+pub fn parse_overrides(args: &[String]) -> Result<Variables, String> {
+    args.iter()
+        .map(|arg| match arg.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+            _ => Err(format!("invalid --set '{}' (expected name=value)", arg)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expands_environment_and_user_variables() {
+        std::env::set_var("NGMAKE_TEST_SDK", "/opt/sdk");
+        let none = Variables::new();
+        assert_eq!(expand("${env:NGMAKE_TEST_SDK}/include", &none).unwrap(), "/opt/sdk/include");
+        assert_eq!(expand("-DPRICE=$$5 $HOME", &none).unwrap(), "-DPRICE=$5 $HOME");
+        assert!(expand("${env:NGMAKE_TEST_UNSET_VAR}", &none).unwrap_err().contains("'NGMAKE_TEST_UNSET_VAR' is not set"));
+        assert!(expand("${env:NGMAKE_TEST_SDK", &none).is_err());
+        assert!(expand("${sdk}", &none).unwrap_err().contains("undefined variable 'sdk'"));
+
+        let table: toml::Value = toml::from_str("sdk = \"${env:NGMAKE_TEST_SDK}\"\nmode = \"debug\"\nlevel = 2").unwrap();
+        let overrides = parse_overrides(&["mode=release".to_string()]).unwrap();
+        let vars = file_variables(Some(&table), &overrides, &overrides).unwrap();
+        assert_eq!(expand("${sdk}/lib/${mode}-O${level}", &vars).unwrap(), "/opt/sdk/lib/release-O2");
+        assert!(parse_overrides(&["novalue".to_string()]).is_err());
+    }
+}
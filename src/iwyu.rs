@@ -0,0 +1,262 @@
+// ============================================================================
+// iwyu.rs — include-what-you-use analysis mode
+// ============================================================================
+//
+// Replays the exact compile command of every source with the compiler
+// swapped for include-what-you-use, parses its suggestions and groups them
+// per target so they can be printed as one consolidated report.
+// No object files are produced; iwyu only analyzes the translation unit.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rayon::prelude::*;
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget};
+use crate::dag::BuildOrder;
+
+// ---------------------------------------------------------------------------
+// Report structures
+// ---------------------------------------------------------------------------
+
+/// Suggestions iwyu made for one file (a source or a header it analyzed).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileSuggestions {
+    pub file: PathBuf,
+    /// Lines iwyu wants added (e.g. `#include <string>  // for string`)
+    pub add: Vec<String>,
+    /// Lines iwyu wants removed (e.g. `#include <vector>  // lines 3-3`)
+    pub remove: Vec<String>,
+}
+
+impl FileSuggestions {
+    pub fn is_clean(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+}
+
+/// iwyu results for one target.
+#[derive(Debug, Clone, Default)]
+pub struct TargetReport {
+    pub target_name: String,
+    pub files: Vec<FileSuggestions>,
+    /// Sources iwyu could not analyze (tool missing, crashed, ...)
+    pub errors: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Running iwyu
+// ---------------------------------------------------------------------------
+
+/// Runs iwyu on every source of the targets in `order` (in build order) and
+/// returns one report per target. Sources are analyzed in parallel.
+pub fn analyze_project(project: &ResolvedProject, order: &BuildOrder, iwyu: &str) -> Vec<TargetReport> {
+    let mut reports = Vec::new();
+    for name in order.levels.iter().flatten() {
+        if let Some(target) = project.targets.get(name) {
+            reports.push(analyze_target(target, iwyu));
+        }
+    }
+    reports
+}
+
+/// Runs iwyu on each source of `target` using the target's exact compile arguments.
+pub fn analyze_target(target: &ResolvedTarget, iwyu: &str) -> TargetReport {
+    let results: Vec<Result<Vec<FileSuggestions>, String>> = target
+        .sources
+        .par_iter()
+        .map(|source| analyze_source(target, source, iwyu))
+        .collect();
+
+    let mut report = TargetReport {
+        target_name: target.name.clone(),
+        ..Default::default()
+    };
+    for res in results {
+        match res {
+            Ok(files) => {
+                for file in files {
+                    merge_file(&mut report.files, file);
+                }
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+    report
+}
+
+fn analyze_source(target: &ResolvedTarget, source: &Path, iwyu: &str) -> Result<Vec<FileSuggestions>, String> {
+    let obj_path = compiler::object_path(target, source);
+    let mut cmd = Command::new(iwyu);
+    cmd.args(compiler::compile_args(target, source, &obj_path));
+    let ld_path = compiler::build_ld_library_path(&target.lib_dirs);
+    if !ld_path.is_empty() {
+        cmd.env("LD_LIBRARY_PATH", &ld_path);
+    }
+    // iwyu exits non-zero even on success (older versions always do), so the
+    // output is what matters; only a failure to spawn is an error.
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run '{}' on {}: {}", iwyu, source.display(), e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let files = parse_iwyu_output(&format!("{}\n{}", stderr, stdout));
+    if files.is_empty() && !output.status.success() && stderr.contains("error:") {
+        return Err(format!("iwyu failed on {}:\n{}", source.display(), stderr.trim()));
+    }
+    Ok(files)
+}
+
+/// Headers shared by several sources are reported once per target.
+fn merge_file(files: &mut Vec<FileSuggestions>, file: FileSuggestions) {
+    if let Some(existing) = files.iter_mut().find(|f| f.file == file.file) {
+        for line in file.add {
+            if !existing.add.contains(&line) {
+                existing.add.push(line);
+            }
+        }
+        for line in file.remove {
+            if !existing.remove.contains(&line) {
+                existing.remove.push(line);
+            }
+        }
+    } else {
+        files.push(file);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Output parsing
+// ---------------------------------------------------------------------------
+
+/// Parses iwyu's textual output. Recognized blocks:
+///   `<file> should add these lines:` / `<file> should remove these lines:`
+///   `(<file> has correct #includes/fwd-decls)`
+/// The `The full include-list for <file>:` block is skipped.
+pub fn parse_iwyu_output(output: &str) -> Vec<FileSuggestions> {
+    enum Section {
+        None,
+        Add,
+        Remove,
+    }
+
+    let mut files: Vec<FileSuggestions> = Vec::new();
+    let mut section = Section::None;
+    let mut current: Option<usize> = None;
+
+    fn file_index(files: &mut Vec<FileSuggestions>, name: &str) -> usize {
+        let path = PathBuf::from(name.trim());
+        match files.iter().position(|f| f.file == path) {
+            Some(i) => i,
+            None => {
+                files.push(FileSuggestions { file: path, ..Default::default() });
+                files.len() - 1
+            }
+        }
+    }
+
+    for line in output.lines() {
+        let trimmed = line.trim_end();
+        if let Some(name) = trimmed.strip_suffix(" should add these lines:") {
+            current = Some(file_index(&mut files, name));
+            section = Section::Add;
+        } else if let Some(name) = trimmed.strip_suffix(" should remove these lines:") {
+            current = Some(file_index(&mut files, name));
+            section = Section::Remove;
+        } else if let Some(rest) = trimmed.strip_prefix('(') {
+            if let Some(name) = rest.strip_suffix(" has correct #includes/fwd-decls)") {
+                file_index(&mut files, name);
+            }
+            section = Section::None;
+        } else if trimmed.is_empty() || trimmed == "---" || trimmed.starts_with("The full include-list for ") {
+            section = Section::None;
+        } else if let Some(idx) = current {
+            match section {
+                Section::Add => files[idx].add.push(trimmed.trim().to_string()),
+                Section::Remove => {
+                    let entry = trimmed.trim().trim_start_matches("- ").to_string();
+                    files[idx].remove.push(entry);
+                }
+                Section::None => {}
+            }
+        }
+    }
+    files
+}
+
+// ---------------------------------------------------------------------------
+// Report formatting
+// ---------------------------------------------------------------------------
+
+/// Formats the consolidated report, one block per target.
+pub fn format_report(reports: &[TargetReport]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut analyzed = 0usize;
+    let mut with_suggestions = 0usize;
+    let mut n_add = 0usize;
+    let mut n_remove = 0usize;
+
+    for report in reports {
+        lines.push(format!("=== IWYU report for target '{}' ===", report.target_name));
+        if report.files.is_empty() && report.errors.is_empty() {
+            lines.push("  (no sources analyzed)".to_string());
+        }
+        for file in &report.files {
+            analyzed += 1;
+            if file.is_clean() {
+                lines.push(format!("  [OK] {}", file.file.display()));
+                continue;
+            }
+            with_suggestions += 1;
+            n_add += file.add.len();
+            n_remove += file.remove.len();
+            lines.push(format!("  {}", file.file.display()));
+            for l in &file.add {
+                lines.push(format!("    + {}", l));
+            }
+            for l in &file.remove {
+                lines.push(format!("    - {}", l));
+            }
+        }
+        for e in &report.errors {
+            for l in e.split('\n') {
+                lines.push(format!("  [ERROR] {}", l));
+            }
+        }
+    }
+    lines.push(format!(
+        "--- {} files analyzed, {} with suggestions ({} missing, {} unnecessary includes) ---",
+        analyzed, with_suggestions, n_add, n_remove
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_add_remove_and_correct() {
+        let out = "\
+src/a.cpp should add these lines:
+#include <string>                       // for string
+
+src/a.cpp should remove these lines:
+- #include <vector>  // lines 3-3
+
+The full include-list for src/a.cpp:
+#include <string>                       // for string
+---
+
+(include/b.h has correct #includes/fwd-decls)
+";
+        let files = parse_iwyu_output(out);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].file, PathBuf::from("src/a.cpp"));
+        assert_eq!(files[0].add, vec!["#include <string>                       // for string"]);
+        assert_eq!(files[0].remove, vec!["#include <vector>  // lines 3-3"]);
+        assert!(files[1].is_clean());
+    }
+}
@@ -2,49 +2,122 @@
 // lib.rs — Library API (shared by CLI and GUI)
 // ============================================================================
 
+pub mod bench;
 pub mod config;
 pub mod dag;
 pub mod compiler;
 pub mod builder;
+pub mod bin_dir;
+pub mod cache;
 pub mod options;
+pub mod package;
+pub mod pkg_config;
+pub mod cmake_export;
+pub mod prebuilt;
+pub mod preset;
+pub mod lock;
+pub mod memcheck;
+pub mod offline;
+pub mod sha256;
 pub mod cmake_converter;
+pub mod compdb;
+pub mod color;
+pub mod daemon;
+pub mod diagnostics;
+pub mod discovery;
+pub mod edit;
+pub mod events;
+pub mod explain;
+pub mod fmt;
+pub mod grammar;
+pub mod hints;
+pub mod history;
+pub mod inputs;
+pub mod interpolate;
+pub mod iwyu;
+pub mod lint;
+pub mod native_package;
+pub mod progress;
+pub mod protobuf;
+pub mod prune;
+pub mod qt;
+pub mod query;
+pub mod remote;
+pub mod report;
+pub mod run;
+pub mod schema;
+pub mod test_runner;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod snapshot;
+pub mod timing;
+pub mod toolchain;
+pub mod trace;
+pub mod validate;
 
-pub use config::{find_workspace_root, parse_build_file, Compiler, ResolvedProject, ResolvedTarget, TargetType};
+pub use config::{find_workspace_root, parse_build_file, Compiler, Linker, ResolvedProject, ResolvedTarget, TargetType};
 pub use dag::{build_order, filter_order_for_targets, BuildOrder};
+pub use builder::BuildControl;
+pub use events::{BuildEvent, BuildStats, TargetState};
 pub use options::BuildOptions;
 pub use cmake_converter::{parse_cmake_lists, convert_to_toml, convert_cmake_to_toml_files};
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, mpsc};
 
-/// Cleans the project: removes all output dirs (containing .o, .a, .so/.dll and built exes).
-/// Sends progress lines to `output_tx` and a final `__ngmake_FINISH__\ttrue\t0\t0\t0`.
-pub fn clean_project_and_stream_output(
+/// Cleans the project: removes the output dirs (containing .o, .a, .so/.dll and built exes)
+/// of all targets but prebuilt `[dependencies]` libraries.
+/// Sends progress lines to `events_tx` and a final `BuildEvent::Finished`.
+pub fn clean_project_and_stream_events(
     config_path: &Path,
-    output_tx: mpsc::Sender<String>,
+    events_tx: mpsc::Sender<BuildEvent>,
 ) -> Result<(), String> {
     let path = config_path.to_path_buf();
     let project = parse_build_file(&path, false)?;
-    let mut dirs: Vec<_> = project.targets.values().map(|t| t.output_dir.clone()).collect();
-    dirs.sort();
-    dirs.dedup();
-    let _ = output_tx.send("[CLEAN] Cleaning project output directories.".to_string());
+    let dirs = prune::output_dirs(&project);
+    let _ = events_tx.send(BuildEvent::output(None, "[CLEAN] Cleaning project output directories."));
     for d in &dirs {
         if d.exists() {
-            let _ = output_tx.send(format!("[CLEAN] Removing {}", d.display()));
+            let _ = events_tx.send(BuildEvent::output(None, format!("[CLEAN] Removing {}", d.display())));
             if let Err(e) = std::fs::remove_dir_all(d) {
-                let _ = output_tx.send(format!("[ERROR] Failed to remove {}: {}", d.display(), e));
-                let _ = output_tx.send("__ngmake_FINISH__\tfalse\t0\t0\t1".to_string());
+                let _ = events_tx.send(BuildEvent::output(None, format!("[ERROR] Failed to remove {}: {}", d.display(), e)));
+                let _ = events_tx.send(BuildEvent::Finished {
+                    stats: BuildStats { success: false, failed: 1, ..Default::default() },
+                });
                 return Err(e.to_string());
             }
         }
     }
-    let _ = output_tx.send("[CLEAN] Done.".to_string());
-    let _ = output_tx.send("__ngmake_FINISH__\ttrue\t0\t0\t0".to_string());
+    let _ = events_tx.send(BuildEvent::output(None, "[CLEAN] Done."));
+    let _ = events_tx.send(BuildEvent::Finished {
+        stats: BuildStats { success: true, ..Default::default() },
+    });
     Ok(())
 }
 
+/// Line-based variant of `clean_project_and_stream_events` (legacy string protocol,
+/// ending with `__ngmake_FINISH__\t{success}\t0\t0\t{failed}`).
+pub fn clean_project_and_stream_output(
+    config_path: &Path,
+    output_tx: mpsc::Sender<String>,
+) -> Result<(), String> {
+    with_legacy_lines(output_tx, |events_tx| clean_project_and_stream_events(config_path, events_tx))
+}
+
+/// Runs `f` with an event channel whose events are forwarded to `output_tx` as legacy lines.
+fn with_legacy_lines<T>(
+    output_tx: mpsc::Sender<String>,
+    f: impl FnOnce(mpsc::Sender<BuildEvent>) -> T,
+) -> T {
+    let (events_tx, events_rx) = mpsc::channel();
+    let forwarder = std::thread::spawn(move || events::forward_legacy_lines(events_rx, output_tx));
+    let result = f(events_tx);
+    // The sender was moved into `f` and is dropped by now, so the forwarder drains and exits
+    let _ = forwarder.join();
+    result
+}
+
 /// Project info and DAG graph data for the GUI
 #[derive(serde::Serialize)]
 pub struct ProjectInfo {
@@ -52,6 +125,10 @@ pub struct ProjectInfo {
     pub build_order: BuildOrder,
     pub graph_nodes: Vec<GraphNode>,
     pub graph_edges: Vec<GraphEdge>,
+    /// Edges of one dependency cycle (same direction as graph_edges), empty if there is none.
+    /// With a cycle the graph is still returned so it can be highlighted; building fails with `error`.
+    pub cycle_edges: Vec<GraphEdge>,
+    pub error: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -71,7 +148,15 @@ pub struct GraphEdge {
 /// Parses build.toml and produces node/edge lists for graph display.
 pub fn parse_and_graph(path: &Path) -> Result<ProjectInfo, String> {
     let project = parse_build_file(path, false)?;
-    let order = build_order(&project)?;
+    let (order, cycle, error) = match build_order(&project) {
+        Ok(order) => (order, Vec::new(), None),
+        Err(e) => match dag::find_cycle(&project) {
+            Some(cycle) => (dag::partial_order(&project), cycle, Some(e)),
+            None => return Err(e),
+        },
+    };
+    // The cycle lists dependents first: app → net means the edge net → app
+    let cycle_edges = cycle.windows(2).map(|pair| GraphEdge { from: pair[1].clone(), to: pair[0].clone() }).collect();
 
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
@@ -79,11 +164,7 @@ pub fn parse_and_graph(path: &Path) -> Result<ProjectInfo, String> {
     for (level_idx, level) in order.levels.iter().enumerate() {
         for name in level {
             if let Some(t) = project.targets.get(name) {
-                let target_type = match &t.target_type {
-                    config::TargetType::Executable => "executable",
-                    config::TargetType::StaticLib => "static_lib",
-                    config::TargetType::SharedLib => "shared_lib",
-                };
+                let target_type = t.target_type.as_str();
                 nodes.push(GraphNode {
                     id: name.clone(),
                     label: name.clone(),
@@ -105,6 +186,8 @@ pub fn parse_and_graph(path: &Path) -> Result<ProjectInfo, String> {
         build_order: order,
         graph_nodes: nodes,
         graph_edges: edges,
+        cycle_edges,
+        error,
     })
 }
 
@@ -119,19 +202,19 @@ pub fn build_and_collect_output(
     let path = config_path.to_path_buf();
     let project = parse_build_file(&path, false)?;
     let full_order = build_order(&project)?;
-    let order = match &targets {
-        Some(t) => filter_order_for_targets(&project, &full_order, t)?,
+    let order = match targets.clone().or_else(|| dag::default_selection(&project)) {
+        Some(t) => filter_order_for_targets(&project, &full_order, &t)?,
         None => full_order,
     };
     if order.levels.is_empty() {
         return Ok((true, vec!["[INFO] No targets to build.".to_string()]));
     }
+    compiler::verify_linkers(&project, &order.levels.concat())?;
+    toolchain::verify_requirements(&project, &order.levels.concat())?;
+    lock::update(&project.root_dir, &project.locked_packages)?;
 
     if clean {
-        let mut dirs: Vec<_> = project.targets.values().map(|t| t.output_dir.clone()).collect();
-        dirs.sort();
-        dirs.dedup();
-        for d in &dirs {
+        for d in &prune::output_dirs(&project) {
             if d.exists() {
                 let _ = std::fs::remove_dir_all(d);
             }
@@ -139,18 +222,21 @@ pub fn build_and_collect_output(
     }
 
     let opts = BuildOptions {
-        command: None,
         config: path,
         targets,
-        clean: false,
-        verbose: true,
-        quiet: false,
+        verbose: 2,
         no_ld_path: true,
         jobs,
         ignore_errors,
+        ..Default::default()
     };
 
     let result = builder::build_project(&project, &order, &opts, None, None);
+    let _ = history::record_build(&project.root_dir, &result);
+    if result.success {
+        pkg_config::write_build_tree(&project, &order.levels.concat())?;
+        bin_dir::gather_outputs(&project, &order.levels.concat())?;
+    }
     let mut lines = Vec::new();
     for r in &result.results {
         for msg in &r.messages {
@@ -166,56 +252,139 @@ pub fn build_and_collect_output(
     Ok((result.success, lines))
 }
 
-/// Runs the build in the current thread and sends each output line to `output_tx`.
-/// Sends a final line `__ngmake_FINISH__\t{success}\t{total}\t{successful}\t{failed}` before closing the channel.
+/// Runs the build in the current thread and sends build events to `events_tx`.
+/// Sends a final `BuildEvent::Finished` before closing the channel.
 /// Call from a background thread; another thread should receive from the paired receiver and emit to the GUI.
 /// `jobs`: parallel job count; None = auto. `ignore_errors`: continue building after a target fails (like make -i).
-/// `cancel`: when Some and set to true, build stops after the current job.
-pub fn build_and_stream_output(
+/// `preset`: a `[presets]` entry applied on top (`targets` and `jobs` take precedence over it).
+/// `control`: when Some, lets another thread cancel (kills running compiler/linker
+/// processes) or pause/resume the build.
+#[allow(clippy::too_many_arguments)]
+pub fn build_and_stream_events(
     config_path: &Path,
     targets: Option<Vec<String>>,
     clean: bool,
     jobs: Option<usize>,
     ignore_errors: bool,
-    output_tx: mpsc::Sender<String>,
-    cancel: Option<Arc<AtomicBool>>,
+    preset: Option<&str>,
+    events_tx: mpsc::Sender<BuildEvent>,
+    control: Option<Arc<BuildControl>>,
 ) -> Result<bool, String> {
     let path = config_path.to_path_buf();
-    let project = parse_build_file(&path, false)?;
+    let mut project = parse_build_file(&path, false)?;
+    let mut opts = BuildOptions {
+        config: path,
+        targets,
+        verbose: 2,
+        no_ld_path: true,
+        jobs,
+        ignore_errors,
+        ..Default::default()
+    };
+    if let Some(name) = preset {
+        preset::apply(&mut project, &mut opts, name)?;
+    }
     let full_order = build_order(&project)?;
-    let order = match &targets {
-        Some(t) => filter_order_for_targets(&project, &full_order, t)?,
+    let order = match opts.targets.clone().or_else(|| dag::default_selection(&project)) {
+        Some(t) => filter_order_for_targets(&project, &full_order, &t)?,
         None => full_order,
     };
     if order.levels.is_empty() {
-        let _ = output_tx.send("[INFO] No targets to build.".to_string());
-        let _ = output_tx.send("__ngmake_FINISH__\ttrue\t0\t0\t0".to_string());
+        let _ = events_tx.send(BuildEvent::output(None, "[INFO] No targets to build."));
+        let _ = events_tx.send(BuildEvent::Finished {
+            stats: BuildStats { success: true, ..Default::default() },
+        });
         return Ok(true);
     }
+    compiler::verify_linkers(&project, &order.levels.concat())?;
+    toolchain::verify_requirements(&project, &order.levels.concat())?;
+    lock::update(&project.root_dir, &project.locked_packages)?;
 
     if clean {
-        let mut dirs: Vec<_> = project.targets.values().map(|t| t.output_dir.clone()).collect();
-        dirs.sort();
-        dirs.dedup();
-        for d in &dirs {
+        for d in &prune::output_dirs(&project) {
             if d.exists() {
                 let _ = std::fs::remove_dir_all(d);
             }
         }
     }
 
-    let opts = BuildOptions {
-        command: None,
-        config: path,
-        targets,
-        clean: false,
-        verbose: true,
-        quiet: false,
-        no_ld_path: true,
-        jobs,
-        ignore_errors,
-    };
-
-    let result = builder::build_project(&project, &order, &opts, Some(output_tx), cancel);
+    let result = builder::build_project(&project, &order, &opts, Some(events_tx), control);
+    let _ = history::record_build(&project.root_dir, &result);
+    if result.success {
+        pkg_config::write_build_tree(&project, &order.levels.concat())?;
+        bin_dir::gather_outputs(&project, &order.levels.concat())?;
+    }
     Ok(result.success)
 }
+
+/// Command that runs the executable `target` of the project at `config_path` with `args`
+/// (working directory = project root, LD_LIBRARY_PATH set for its shared library deps).
+/// The target must have been built already, e.g. with `build_and_stream_events` and the
+/// same `preset`.
+pub fn target_run_command(config_path: &Path, target: &str, args: &[String], preset: Option<&str>) -> Result<std::process::Command, String> {
+    let mut project = parse_build_file(config_path, false)?;
+    if let Some(name) = preset {
+        preset::apply(&mut project, &mut BuildOptions::default(), name)?;
+    }
+    run::run_command(&project, target, args)
+}
+
+/// Line-based variant of `build_and_stream_events` for consumers of the legacy string
+/// protocol: `[TARGET:name] line`, `__ngmake_TOTAL__\t{n}` and a final
+/// `__ngmake_FINISH__\t{success}\t{total}\t{successful}\t{failed}`.
+/// `cancel`: when Some and set to true, running processes are killed and the build stops.
+pub fn build_and_stream_output(
+    config_path: &Path,
+    targets: Option<Vec<String>>,
+    clean: bool,
+    jobs: Option<usize>,
+    ignore_errors: bool,
+    output_tx: mpsc::Sender<String>,
+    cancel: Option<Arc<AtomicBool>>,
+) -> Result<bool, String> {
+    with_legacy_lines(output_tx, |events_tx| {
+        let control = cancel.map(|c| Arc::new(BuildControl::with_cancel_flag(c)));
+        build_and_stream_events(config_path, targets, clean, jobs, ignore_errors, None, events_tx, control)
+    })
+}
+
+/// The `[presets]` of the project at `config_path` (name → preset), e.g. for a preset picker.
+pub fn list_presets(config_path: &Path) -> Result<std::collections::BTreeMap<String, preset::Preset>, String> {
+    Ok(parse_build_file(config_path, false)?.presets)
+}
+
+/// Directory of the workspace root build.toml for `config_path` (where `.oximake/` lives).
+pub fn project_root_dir(config_path: &Path) -> PathBuf {
+    let root = find_workspace_root(config_path).unwrap_or_else(|| config_path.to_path_buf());
+    match root.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Writes compile_commands.json for the project at `config_path` to `out_path`
+/// (default: next to the root build.toml). Returns the file and its number of entries.
+pub fn export_compile_commands(config_path: &Path, out_path: Option<&Path>) -> Result<(PathBuf, usize), String> {
+    let project = parse_build_file(config_path, false)?;
+    let out = out_path.map(Path::to_path_buf).unwrap_or_else(|| compdb::default_path(&project));
+    let count = compdb::write(&project, &out)?;
+    Ok((out, count))
+}
+
+/// Removes the objects and outputs of `targets` (see compiler::invalidate_target) so the
+/// next build rebuilds them from scratch; the rest of the incremental state is kept.
+/// Returns the removed paths.
+pub fn force_rebuild(config_path: &Path, targets: &[String]) -> Result<Vec<PathBuf>, String> {
+    let project = parse_build_file(config_path, false)?;
+    let mut removed = Vec::new();
+    for name in targets {
+        let target = project.targets.get(name).ok_or_else(|| format!("Unknown target '{}' in --force-rebuild", name))?;
+        removed.extend(compiler::invalidate_target(target)?);
+    }
+    Ok(removed)
+}
+
+/// Build history of the project containing `config_path` (oldest first), for charting build times.
+pub fn load_build_history(config_path: &Path) -> Result<Vec<history::BuildRecord>, String> {
+    history::load_history(&project_root_dir(config_path))
+}
@@ -0,0 +1,274 @@
+// ============================================================================
+// lint.rs — `ngm lint`: build.toml hygiene
+// ============================================================================
+//
+// Reports everything validate.rs finds (unknown keys, globs matching no
+// files, deps on unknown targets, ...) and additionally, in [project],
+// [module] and every [[target]]:
+//
+//   - entries repeated in a list (sources, deps, defines, flags, ...); in
+//     flag lists an option and its separate value ("-include", "pch.h")
+//     count as one entry
+//   - absolute paths (sources, include_dirs, lib_dirs, inputs, outputs,
+//     includes), which break as soon as the project is checked out elsewhere
+//
+// `--fix` only makes the safe changes: repeated entries are removed and
+// absolute paths below the build.toml directory are made relative.
+// ============================================================================
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use toml_edit::{Array, DocumentMut, ImDocument, Item, TableLike};
+
+use crate::diagnostics::Severity;
+use crate::validate::{self, ConfigError};
+
+/// What a checked list holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Paths,
+    Flags,
+    Names,
+}
+
+/// Kind of the list at `key` (dotted, e.g. "linux.compiler_flags.gcc"), or None if not checked.
+fn list_kind(key: &str) -> Option<ListKind> {
+    let segments: Vec<&str> = key.split('.').collect();
+    let mut name = segments[segments.len() - 1];
+    if segments.len() > 1 && validate::FLAG_KEYS.iter().any(|k| k.name == name) {
+        name = segments[segments.len() - 2];
+    }
+    match name {
+        "sources" | "include_dirs" | "lib_dirs" | "inputs" | "outputs" | "includes" => Some(ListKind::Paths),
+        "flags" | "compiler_flags" | "linker_flags" => Some(ListKind::Flags),
+        "libs" | "frameworks" | "defines" | "deps" => Some(ListKind::Names),
+        _ => None,
+    }
+}
+
+/// Validation problems plus lint warnings of the root build.toml and every included file.
+pub fn lint(root: &Path) -> Vec<ConfigError> {
+    let mut problems = validate::validate_config(root);
+    for file in validate::config_files(root) {
+        let Ok(text) = std::fs::read_to_string(&file) else { continue };
+        let Ok(doc) = ImDocument::parse(text.as_str()) else { continue };
+        let base_dir = base_dir(&file);
+        for (context, key, array) in lists(doc.as_table()) {
+            let kind = list_kind(&key).unwrap_or(ListKind::Names);
+            let place = if context.is_empty() { key.clone() } else { format!("{} of {}", key, context) };
+            let items = strings(array);
+            for unit in repeated(&items, kind == ListKind::Flags) {
+                let span = array.get(unit.start).and_then(|v| v.span());
+                let entry = items[unit].join(" ");
+                problems.push(validate::locate(&file, &text, span, Severity::Warning, format!("'{}' repeated in {}", entry, place)));
+            }
+            if kind != ListKind::Paths {
+                continue;
+            }
+            for (i, item) in items.iter().enumerate().filter(|(_, item)| Path::new(item).is_absolute()) {
+                let hint = match relative(item, &base_dir) {
+                    Some(_) => "; --fix makes it relative",
+                    None => "",
+                };
+                let message = format!("absolute path '{}' in {} (use a path relative to the build.toml){}", item, place, hint);
+                problems.push(validate::locate(&file, &text, array.get(i).and_then(|v| v.span()), Severity::Warning, message));
+            }
+        }
+    }
+    problems
+}
+
+/// Removes repeated list entries and makes absolute paths below the build.toml
+/// directory relative, in every file. Returns the number of changes.
+pub fn fix(root: &Path) -> Result<usize, String> {
+    let mut changes = 0;
+    for file in validate::config_files(root) {
+        let text = std::fs::read_to_string(&file).map_err(|e| format!("Failed to read '{}': {}", file.display(), e))?;
+        let Ok(mut doc) = text.parse::<DocumentMut>() else { continue };
+        let base_dir = base_dir(&file);
+        let mut file_changes = 0;
+        for (key, array) in lists_mut(doc.as_table_mut()) {
+            let kind = list_kind(&key).unwrap_or(ListKind::Names);
+            let items = strings(array);
+            let remove: Vec<usize> = repeated(&items, kind == ListKind::Flags).into_iter().flatten().collect();
+            for &i in remove.iter().rev() {
+                array.remove(i);
+            }
+            file_changes += remove.len();
+            if let Some(first) = array.get_mut(0).filter(|v| !v.decor().prefix().and_then(|p| p.as_str()).unwrap_or_default().contains('\n')) {
+                first.decor_mut().set_prefix("");
+            }
+            if kind == ListKind::Paths {
+                for value in array.iter_mut() {
+                    let Some(path) = value.as_str().and_then(|s| relative(s, &base_dir)) else { continue };
+                    let decor = value.decor().clone();
+                    *value = path.into();
+                    *value.decor_mut() = decor;
+                    file_changes += 1;
+                }
+            }
+        }
+        if file_changes > 0 {
+            std::fs::write(&file, doc.to_string()).map_err(|e| format!("Failed to write '{}': {}", file.display(), e))?;
+            changes += file_changes;
+        }
+    }
+    Ok(changes)
+}
+
+/// Directory of a build.toml ("." for a bare file name).
+fn base_dir(file: &Path) -> PathBuf {
+    file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or_else(|| Path::new(".")).to_path_buf()
+}
+
+/// `path` relative to `base_dir` if it is an absolute path below it.
+fn relative(path: &str, base_dir: &Path) -> Option<String> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+    let canonical = base_dir.canonicalize().ok();
+    let absolute_base = std::path::absolute(base_dir).ok();
+    let rest: PathBuf = [canonical, absolute_base].into_iter().flatten().find_map(|base| path.strip_prefix(base).ok().map(Path::to_path_buf))?;
+    Some(if rest.as_os_str().is_empty() { ".".to_string() } else { rest.to_string_lossy().to_string() })
+}
+
+/// Units of `items` that repeat an earlier one, as index ranges. In flag lists an
+/// option followed by a non-option value is one unit.
+fn repeated(items: &[String], flags: bool) -> Vec<Range<usize>> {
+    let mut seen = HashSet::new();
+    let mut units = Vec::new();
+    let mut i = 0;
+    while i < items.len() {
+        let paired = flags && items[i].starts_with('-') && items.get(i + 1).is_some_and(|next| !next.starts_with('-'));
+        let unit = i..i + if paired { 2 } else { 1 };
+        if !seen.insert(&items[unit.clone()]) {
+            units.push(unit.clone());
+        }
+        i = unit.end;
+    }
+    units
+}
+
+fn strings(array: &Array) -> Vec<String> {
+    array.iter().map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string().trim().to_string())).collect()
+}
+
+/// The lists lint checks in one build.toml: (context, dotted key, array).
+fn lists(root: &dyn TableLike) -> Vec<(String, String, &Array)> {
+    let mut out = Vec::new();
+    for (name, item) in root.iter() {
+        match (name, item) {
+            ("target", Item::ArrayOfTables(targets)) => {
+                for target in targets.iter() {
+                    let context = match target.get("name").and_then(Item::as_str) {
+                        Some(n) => format!("target '{}'", n),
+                        None => "[[target]]".to_string(),
+                    };
+                    collect(target, &context, "", &mut out);
+                }
+            }
+            ("project" | "module", item) => {
+                if let Some(table) = item.as_table_like() {
+                    collect(table, &format!("[{}]", name), "", &mut out);
+                }
+            }
+            ("includes", Item::Value(value)) => {
+                if let Some(array) = value.as_array() {
+                    out.push((String::new(), name.to_string(), array));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn collect<'a>(table: &'a dyn TableLike, context: &str, prefix: &str, out: &mut Vec<(String, String, &'a Array)>) {
+    for (name, item) in table.iter() {
+        let key = if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+        if let Some(sub) = item.as_table_like() {
+            collect(sub, context, &key, out);
+        } else if let Some(array) = item.as_array().filter(|_| list_kind(&key).is_some()) {
+            out.push((context.to_string(), key, array));
+        }
+    }
+}
+
+/// lists() of an editable document: (dotted key, array).
+fn lists_mut(root: &mut dyn TableLike) -> Vec<(String, &mut Array)> {
+    let mut out = Vec::new();
+    for (name, item) in root.iter_mut() {
+        match (name.get(), item) {
+            ("target", Item::ArrayOfTables(targets)) => {
+                for target in targets.iter_mut() {
+                    collect_mut(target, "", &mut out);
+                }
+            }
+            ("project" | "module", item) => {
+                if let Some(table) = item.as_table_like_mut() {
+                    collect_mut(table, "", &mut out);
+                }
+            }
+            ("includes", Item::Value(value)) => {
+                if let Some(array) = value.as_array_mut() {
+                    out.push(("includes".to_string(), array));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn collect_mut<'a>(table: &'a mut dyn TableLike, prefix: &str, out: &mut Vec<(String, &'a mut Array)>) {
+    for (name, item) in table.iter_mut() {
+        let key = if prefix.is_empty() { name.get().to_string() } else { format!("{}.{}", prefix, name.get()) };
+        if item.is_table_like() {
+            if let Some(sub) = item.as_table_like_mut() {
+                collect_mut(sub, &key, out);
+            }
+        } else if let Some(array) = item.as_array_mut().filter(|_| list_kind(&key).is_some()) {
+            out.push((key, array));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_repeated_entries_and_absolute_paths_are_reported_and_fixed() {
+        let dir = TempDir::new("lint");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        dir.write("src/main.cpp", "");
+        let dir = dir.canonicalize().unwrap();
+        let config = dir.join("build.toml");
+        std::fs::write(
+            &config,
+            format!(
+                "[project]\nname = \"demo\"\n\n[[target]]\nname = \"app\"\ntype = \"executable\"\nsources = [\"{}\"]\ndefines = [\"A\", \"B\", \"A\"]\ncompiler_flags = [\"-include\", \"a.h\", \"-include\", \"b.h\", \"-O2\", \"-O2\"]\n",
+                dir.join("src/main.cpp").display()
+            ),
+        )
+        .unwrap();
+
+        let messages: Vec<String> = lint(&config).into_iter().map(|p| p.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!("absolute path '{}' in sources of target 'app' (use a path relative to the build.toml); --fix makes it relative", dir.join("src/main.cpp").display()),
+                "'A' repeated in defines of target 'app'".to_string(),
+                "'-O2' repeated in compiler_flags of target 'app'".to_string(),
+            ]
+        );
+        assert_eq!(fix(&config).unwrap(), 3);
+        assert!(lint(&config).is_empty());
+        let text = std::fs::read_to_string(&config).unwrap();
+        assert!(text.contains("sources = [\"src/main.cpp\"]\ndefines = [\"A\", \"B\"]\ncompiler_flags = [\"-include\", \"a.h\", \"-include\", \"b.h\", \"-O2\"]\n"));
+    }
+}
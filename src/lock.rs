@@ -0,0 +1,182 @@
+// ============================================================================
+// lock.rs — `oximake.lock`: pinned [dependencies] archives
+// ============================================================================
+//
+// Every archive dependency (`archive` or `url`, see prebuilt.rs) is recorded
+// with the version from its manifest and the SHA-256 of the archive:
+//
+//   [[package]]
+//   name = "net"
+//   source = "url+https://example.com/net-2.0.1-linux-x86_64.tar.gz"
+//   version = "2.0.1"
+//   sha256 = "9f86d0..."
+//
+// The lock file lives next to the root build.toml. Resolving the project
+// only reads it; builds rewrite it whenever the resolved packages change,
+// except that a download must keep the checksum recorded on its first
+// fetch (prebuilt.rs refuses it otherwise). With `--locked` (CI) it is never
+// written: a missing lock file or any difference — a new, removed or
+// changed entry — is an error.
+// Path dependencies are directories the user controls and aren't locked.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Lock file name, next to the root build.toml.
+pub const LOCK_FILE: &str = "oximake.lock";
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// `--locked`: fail instead of writing the lock file, for the rest of the process.
+pub fn set_locked(locked: bool) {
+    LOCKED.store(locked, Ordering::Relaxed);
+}
+
+pub fn locked() -> bool {
+    LOCKED.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct LockedPackage {
+    /// Name in [dependencies]
+    pub name: String,
+    /// "archive+<path as written>" or "url+<url>"
+    pub source: String,
+    /// [project] version of the package (from its manifest)
+    pub version: String,
+    /// SHA-256 of the archive
+    pub sha256: String,
+}
+
+pub fn lock_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(LOCK_FILE)
+}
+
+/// The lock file in `root_dir` (None if there is none).
+pub fn read(root_dir: &Path) -> Result<Option<Lockfile>, String> {
+    let path = lock_path(root_dir);
+    let Ok(text) = std::fs::read_to_string(&path) else { return Ok(None) };
+    toml::from_str(&text).map(Some).map_err(|e| format!("Invalid '{}': {}", path.display(), e))
+}
+
+/// With `--locked`, an error listing the differences between the lock file and the
+/// resolved `packages`. Never writes the lock file.
+pub fn verify(root_dir: &Path, packages: &[LockedPackage]) -> Result<(), String> {
+    match outdated(root_dir, packages)? {
+        Some(existing) if locked() => Err(locked_error(root_dir, &existing, packages)),
+        _ => Ok(()),
+    }
+}
+
+/// Brings the lock file in line with the resolved `packages` (builds only): rewritten when
+/// they differ, or with `--locked` an error listing the differences.
+pub fn update(root_dir: &Path, packages: &[LockedPackage]) -> Result<(), String> {
+    let Some(existing) = outdated(root_dir, packages)? else { return Ok(()) };
+    if locked() {
+        return Err(locked_error(root_dir, &existing, packages));
+    }
+    let mut text = String::from("# Generated by ngmake: pinned [dependencies] archives. Commit it and build with --locked in CI.\n\n");
+    text.push_str(&toml::to_string(&Lockfile { packages: sorted(packages) }).map_err(|e| format!("Failed to write lock file: {}", e))?);
+    let path = lock_path(root_dir);
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+fn locked_error(root_dir: &Path, existing: &[LockedPackage], packages: &[LockedPackage]) -> String {
+    let changes = differences(existing, &sorted(packages));
+    format!(
+        "{} needs to be updated but --locked was given:\n{}",
+        lock_path(root_dir).display(),
+        if changes.is_empty() { "  (no lock file)".to_string() } else { changes.join("\n") }
+    )
+}
+
+fn sorted(packages: &[LockedPackage]) -> Vec<LockedPackage> {
+    let mut packages = packages.to_vec();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+/// The locked packages if the lock file doesn't match `packages` (empty if there is none),
+/// None if it is up to date.
+fn outdated(root_dir: &Path, packages: &[LockedPackage]) -> Result<Option<Vec<LockedPackage>>, String> {
+    let current = read(root_dir)?;
+    let packages = sorted(packages);
+    match current {
+        Some(lock) if lock.packages == packages => Ok(None),
+        None if packages.is_empty() => Ok(None),
+        current => Ok(Some(current.unwrap_or_default().packages)),
+    }
+}
+
+/// One line per added, removed or changed package.
+fn differences(old: &[LockedPackage], new: &[LockedPackage]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for package in new {
+        match old.iter().find(|p| p.name == package.name) {
+            None => lines.push(format!("  + {} {} ({})", package.name, package.version, package.source)),
+            Some(locked) if locked.source != package.source => lines.push(format!("  ~ {}: source {} → {}", package.name, locked.source, package.source)),
+            Some(locked) if locked.sha256 != package.sha256 => {
+                lines.push(format!("  ~ {}: sha256 {} → {} (version {} → {})", package.name, locked.sha256, package.sha256, locked.version, package.version))
+            }
+            _ => {}
+        }
+    }
+    for package in old.iter().filter(|p| !new.iter().any(|n| n.name == p.name)) {
+        lines.push(format!("  - {} {}", package.name, package.version));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn package(name: &str, sha256: &str) -> LockedPackage {
+        LockedPackage { name: name.to_string(), source: format!("archive+{}.tar.gz", name), version: "1.0.0".to_string(), sha256: sha256.to_string() }
+    }
+
+    #[test]
+    fn test_differences_list_added_removed_and_changed_packages() {
+        let old = vec![package("core", "aa"), package("net", "bb")];
+        let new = vec![package("core", "cc"), package("util", "dd")];
+        assert_eq!(
+            differences(&old, &new),
+            vec![
+                "  ~ core: sha256 aa → cc (version 1.0.0 → 1.0.0)".to_string(),
+                "  + util 1.0.0 (archive+util.tar.gz)".to_string(),
+                "  - net 1.0.0".to_string(),
+            ]
+        );
+        let text = toml::to_string(&Lockfile { packages: new.clone() }).unwrap();
+        assert_eq!(toml::from_str::<Lockfile>(&text).unwrap().packages, new);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parsing_reads_the_lock_file_and_builds_write_it() {
+        let dir = TempDir::new("lock");
+        dir.write("pkg/core-1.0.0/lib/libcore.a", "");
+        dir.write("pkg/core-1.0.0/lib/ngmake/core.toml", "name = \"core\"\nversion = \"1.0.0\"\n\n[[library]]\nname = \"core\"\ntype = \"static_lib\"\nfile = \"lib/libcore.a\"\n");
+        let status = std::process::Command::new("tar").args(["-czf", "core.tar.gz", "-C", "pkg", "core-1.0.0"]).current_dir(dir.path()).status().unwrap();
+        assert!(status.success());
+        let config = dir.write("build.toml", "[project]\nname = \"app\"\n\n[dependencies]\ncore = { archive = \"core.tar.gz\" }\n");
+
+        let project = crate::config::parse_build_file(&config, false).unwrap();
+        assert!(!lock_path(&dir).exists());
+        assert_eq!(project.locked_packages.len(), 1);
+        assert_eq!(project.locked_packages[0].source, "archive+core.tar.gz");
+
+        update(&project.root_dir, &project.locked_packages).unwrap();
+        assert_eq!(read(&dir).unwrap().unwrap().packages, project.locked_packages);
+    }
+}
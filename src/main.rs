@@ -2,18 +2,14 @@
 // main.rs — ngmake CLI entry point (binary: ngm)
 // ============================================================================
 
-mod config;
-mod dag;
-mod compiler;
-mod builder;
-mod options;
-mod cmake_converter;
+use ngmake::{bench, bin_dir, builder, cmake_converter, color, compiler, config, daemon, dag, edit, events, explain, fmt, history, inputs, interpolate, iwyu, lint, lock, memcheck, offline, options, package, pkg_config, prebuilt, preset, progress, prune, query, report, run, schema, snapshot, test_runner, timing, toolchain, trace, validate};
 
 use std::path::PathBuf;
 use std::process;
 use std::time::Instant;
 
 use clap::Parser;
+use memcheck::MemcheckConfig;
 use options::BuildOptions;
 
 fn main() {
@@ -50,7 +46,7 @@ fn main() {
                                 }
                             }
                             
-                            match fs::write(&full_path, content) {
+                            match edit::write_config_file(&full_path, content) {
                                 Ok(_) => {
                                     created_files.push(full_path.clone());
                                     if *verbose {
@@ -73,7 +69,7 @@ fn main() {
                         // If output was specified, also write the root build.toml there
                         if let Some(output_path) = output {
                             if let Some(root_toml) = toml_files.get("build.toml") {
-                                if let Err(e) = fs::write(&output_path, root_toml) {
+                                if let Err(e) = edit::write_config_file(output_path, root_toml) {
                                     eprintln!("✗ Error writing output file {}: {}", output_path.display(), e);
                                     std::process::exit(1);
                                 }
@@ -89,23 +85,125 @@ fn main() {
                     }
                 }
             }
-            options::Command::Build { config, target, clean, verbose, quiet, no_ld_path, jobs, ignore_errors } => {
+            options::Command::Build { config, target, target_regex, tag, exclude_tag, with_dependents, clean, force_rebuild, verbose, quiet, no_ld_path, jobs, ignore_errors, keep_going, trace, timings, timings_json, report, log_format, color, status_format, set, preset, no_workspace, locked, offline, link_memory_limit, link_jobs } => {
                 let build_options = BuildOptions {
                     command: None,
                     config: config.clone(),
                     targets: target.clone(),
+                    target_regex: target_regex.clone(),
+                    tags: tag.clone(),
+                    exclude_tags: exclude_tag.clone(),
+                    with_dependents: *with_dependents,
                     clean: *clean,
+                    force_rebuild: force_rebuild.clone(),
                     verbose: *verbose,
                     quiet: *quiet,
                     no_ld_path: *no_ld_path,
                     jobs: *jobs,
                     ignore_errors: *ignore_errors,
+                    keep_going: *keep_going,
+                    trace: trace.clone(),
+                    timings: *timings,
+                    timings_json: timings_json.clone(),
+                    report: report.clone(),
+                    log_format: *log_format,
+                    color: *color,
+                    status_format: status_format.clone(),
+                    set: set.clone(),
+                    preset: preset.clone(),
+                    no_workspace: *no_workspace,
+                    locked: *locked,
+                    offline: *offline,
+                    link_memory_limit: *link_memory_limit,
+                    link_jobs: *link_jobs,
                 };
                 run_build(build_options);
                 return;
             }
-            options::Command::Clean { config, verbose } => {
-                run_clean(config.as_path(), *verbose);
+            options::Command::Clean { config, verbose, prune } => {
+                run_clean(config.as_path(), *verbose, *prune);
+                return;
+            }
+            options::Command::Iwyu { config, target, iwyu } => {
+                run_iwyu(config.as_path(), target.as_deref(), iwyu);
+                return;
+            }
+            options::Command::VerifyInputs { config, target } => {
+                run_verify_inputs(config.as_path(), target.as_deref());
+                return;
+            }
+            options::Command::Explain { config, targets, preset } => {
+                run_explain(config.as_path(), targets, preset.as_deref());
+                return;
+            }
+            options::Command::Add { name, path, archive, url, sha256, target, libs, config } => {
+                let dependency = prebuilt::DependencyConfig { path: path.clone(), archive: archive.clone(), url: url.clone(), sha256: sha256.clone() };
+                run_add(config.as_path(), name, &dependency, target.as_deref(), libs);
+                return;
+            }
+            options::Command::Package { config, format, output, preset } => {
+                run_package(config.as_path(), *format, output.as_deref(), preset.as_deref());
+                return;
+            }
+            options::Command::Deps { config, target, tree, invert } => {
+                run_deps(config.as_path(), target.as_deref(), *tree, *invert);
+                return;
+            }
+            options::Command::Query { config, query } => {
+                run_query(config.as_path(), query);
+                return;
+            }
+            options::Command::Run { config, target, args, jobs, preset } => {
+                run_target(config.as_path(), target, args, *jobs, preset.as_deref());
+                return;
+            }
+            options::Command::Test { config, targets, junit, json, jobs, preset, test_jobs, timeout, retries, memcheck, memcheck_tool, suppressions, verbose } => {
+                let memcheck = memcheck.then(|| MemcheckConfig { tool: memcheck_tool.clone(), suppressions: suppressions.clone(), ..Default::default() });
+                let settings = test_runner::TestSettings { jobs: *test_jobs, timeout: timeout.map(std::time::Duration::from_secs), retries: *retries, memcheck };
+                run_tests(config.as_path(), targets, junit.as_deref(), json.as_deref(), *jobs, preset.as_deref(), settings, *verbose);
+                return;
+            }
+            options::Command::Bench { config, targets, repetitions, results, jobs, preset, verbose } => {
+                run_bench(config.as_path(), targets, *repetitions, results.as_deref(), *jobs, preset.as_deref(), *verbose);
+                return;
+            }
+            options::Command::Stats { config, last } => {
+                run_stats(config.as_path(), *last);
+                return;
+            }
+            options::Command::Daemon { config, socket } => {
+                run_daemon(config.as_path(), socket.as_deref());
+                return;
+            }
+            options::Command::Fmt { config, check } => {
+                run_fmt(config.as_path(), *check);
+                return;
+            }
+            options::Command::Lint { config, fix } => {
+                run_lint(config.as_path(), *fix);
+                return;
+            }
+            options::Command::Compdb { config, output } => {
+                match ngmake::export_compile_commands(config, output.as_deref()) {
+                    Ok((path, count)) => println!("  Wrote {} ({} entries)", path.display(), count),
+                    Err(e) => {
+                        eprintln!("[ERROR] {}", e);
+                        process::exit(1);
+                    }
+                }
+                return;
+            }
+            options::Command::Schema { output } => {
+                let schema = format!("{:#}", schema::build_toml_schema());
+                match output {
+                    Some(path) => {
+                        if let Err(e) = std::fs::write(path, schema + "\n") {
+                            eprintln!("[ERROR] Failed to write schema to {}: {}", path.display(), e);
+                            process::exit(1);
+                        }
+                    }
+                    None => println!("{}", schema),
+                }
                 return;
             }
             options::Command::Init { name, cpp_version, project_type, dir } => {
@@ -355,7 +453,7 @@ namespace {} {{
                 
                 // Write build.toml
                 let build_toml_path = project_path.join("build.toml");
-                fs::write(&build_toml_path, &toml_content).unwrap_or_else(|e| {
+                edit::write_config_file(&build_toml_path, &toml_content).unwrap_or_else(|e| {
                     eprintln!("✗ Error writing build.toml: {}", e);
                     std::process::exit(1);
                 });
@@ -375,248 +473,655 @@ namespace {} {{
     }
 
     // Legacy mode: no subcommand, use direct options
-    let build_path = &options.config;
-    if !build_path.exists() {
+    run_build(options);
+}
+
+fn run_clean(config_path: &std::path::Path, verbose: bool, prune: bool) {
+    if !config_path.exists() {
         eprintln!(
             "[ERROR] File '{}' not found!\n\
-             Usage: ngm --config <file> or ngm -c <file>",
-            build_path.display()
+             Usage: ngm clean --config <file> or ngm clean -c <file>",
+            config_path.display()
         );
         process::exit(1);
     }
-
-    let start_time = Instant::now();
-    let quiet = options.show_quiet_output();
-
-    // --- LD_LIBRARY_PATH (optional) ---
-    if !options.no_ld_path && !quiet && options.verbose {
-        println!("─────────────────────────────────────────────────────");
-        println!("  ngmake v{}", env!("CARGO_PKG_VERSION"));
-        println!("─────────────────────────────────────────────────────");
-        compiler::print_ld_library_path_info();
-        println!();
-    }
-
-    if !quiet && options.verbose {
-        println!("[1/3] Parsing configuration file: {}", build_path.display());
-    }
-
-    let project = match config::parse_build_file(build_path.as_path(), options.show_verbose_output()) {
-        Ok(p) => {
-            if !quiet && options.verbose {
-                println!(
-                    "  Project: {} v{} ({} targets loaded)",
-                    p.name,
-                    p.version,
-                    p.targets.len()
-                );
-            }
-            p
-        }
+    let project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
         Err(e) => {
-            eprintln!("[ERROR] Configuration parse error: {}", e);
+            eprintln!("[ERROR] {}", e);
             process::exit(1);
         }
     };
-
-    if project.targets.is_empty() {
-        if !quiet {
-            println!("[INFO] No targets defined. Nothing to do.");
+    if prune {
+        let stale = prune::stale_artifacts(&project);
+        for path in &stale {
+            if verbose {
+                println!("  Removing {}", path.display());
+            }
+            let result = if path.is_dir() { std::fs::remove_dir_all(path) } else { std::fs::remove_file(path) };
+            if let Err(e) = result {
+                eprintln!("[ERROR] Failed to remove {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+        match stale.len() {
+            0 => println!("  No stale build output."),
+            n => println!("  Pruned {} stale artifact{}.", n, if n == 1 { "" } else { "s" }),
         }
         return;
     }
-
-    if !quiet && options.verbose {
-        println!("\n  Defined targets:");
-        for (name, target) in &project.targets {
-            println!(
-                "    • {} ({:?}) - {} source file(s), deps: {:?}",
-                name,
-                target.target_type,
-                target.sources.len(),
-                target.deps
-            );
+    let dirs = prune::output_dirs(&project);
+    let mut removed = 0usize;
+    for d in &dirs {
+        if d.exists() {
+            if verbose {
+                println!("  Removing {}", d.display());
+            }
+            if let Err(e) = std::fs::remove_dir_all(d) {
+                eprintln!("[ERROR] Failed to remove {}: {}", d.display(), e);
+                process::exit(1);
+            }
+            removed += 1;
         }
-        println!();
     }
+    if removed == 0 {
+        println!("  No build output to clean.");
+    } else {
+        println!("  Cleaned {} director{}.", removed, if removed == 1 { "y" } else { "ies" });
+    }
+}
 
-    // --- Clean (remove output dirs first) ---
-    if options.clean {
-        let mut dirs: Vec<_> = project
-            .targets
-            .values()
-            .map(|t| t.output_dir.clone())
-            .collect();
-        dirs.sort();
-        dirs.dedup();
-        for d in &dirs {
-            if d.exists() {
-                if !quiet && options.verbose {
-                    println!("[CLEAN] Removing {}...", d.display());
-                }
-                let _ = std::fs::remove_dir_all(d);
+fn run_fmt(config_path: &std::path::Path, check: bool) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm fmt --config <file> or ngm fmt -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    let files = validate::config_files(config_path);
+    let mut unformatted = 0usize;
+    for file in &files {
+        let text = match std::fs::read_to_string(file) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("[ERROR] Failed to read {}: {}", file.display(), e);
+                process::exit(1);
             }
+        };
+        let formatted = match fmt::format_config(&text) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("[ERROR] {}: {}", file.display(), e);
+                process::exit(1);
+            }
+        };
+        if formatted == text {
+            continue;
         }
-        if !quiet && options.verbose {
-            println!();
+        unformatted += 1;
+        if check {
+            println!("  Not formatted: {}", file.display());
+        } else if let Err(e) = std::fs::write(file, formatted) {
+            eprintln!("[ERROR] Failed to write {}: {}", file.display(), e);
+            process::exit(1);
+        } else {
+            println!("  Formatted {}", file.display());
         }
     }
-
-    if !quiet && options.verbose {
-        println!("[2/3] Building dependency graph (DAG)...");
+    if unformatted == 0 {
+        println!("  {} file{} already formatted.", files.len(), if files.len() == 1 { "" } else { "s" });
+    } else if check {
+        process::exit(1);
     }
+}
 
-    let full_order = match dag::build_order(&project) {
-        Ok(order) => order,
-        Err(e) => {
-            eprintln!("[ERROR] Dependency resolution error: {}", e);
-            process::exit(1);
-        }
-    };
-
-    // Filter order for selected targets only
-    let build_order = match &options.targets {
-        Some(t) => match dag::filter_order_for_targets(&project, &full_order, t) {
-            Ok(o) => o,
+fn run_lint(config_path: &std::path::Path, fix: bool) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm lint --config <file> or ngm lint -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    if fix {
+        match lint::fix(config_path) {
+            Ok(0) => {}
+            Ok(n) => println!("  Fixed {} problem{}.", n, if n == 1 { "" } else { "s" }),
             Err(e) => {
                 eprintln!("[ERROR] {}", e);
                 process::exit(1);
             }
-        },
-        None => full_order,
-    };
-
-    if build_order.levels.is_empty() {
-        if !quiet {
-            println!("[INFO] No targets to build.");
         }
+    }
+    let problems = lint::lint(config_path);
+    if problems.is_empty() {
+        println!("  No problems found.");
         return;
     }
+    eprint!("{}", validate::render(&problems));
+    let errors = problems.iter().filter(|p| p.is_error()).count();
+    eprintln!("  {} error{}, {} warning{}.", errors, if errors == 1 { "" } else { "s" }, problems.len() - errors, if problems.len() - errors == 1 { "" } else { "s" });
+    process::exit(1);
+}
 
-    if !quiet && options.verbose {
-        println!("[DAG] Topological order:");
-        for (i, level) in build_order.levels.iter().enumerate() {
-            println!("  Level {}: {:?}", i, level);
-        }
-        println!();
+fn run_daemon(config_path: &std::path::Path, socket: Option<&std::path::Path>) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm daemon --config <file> or ngm daemon -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
     }
-
-    // --- Build ---
-    if !quiet && options.verbose {
-        println!("[3/3] Starting build...\n");
+    let socket_path = socket
+        .map(|s| s.to_path_buf())
+        .unwrap_or_else(|| daemon::default_socket_path(&ngmake::project_root_dir(config_path)));
+    println!("ngm daemon listening on {}", socket_path.display());
+    let server = std::sync::Arc::new(daemon::Daemon::new(config_path));
+    if let Err(e) = daemon::serve(server, &socket_path) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
     }
+}
 
-    let result = builder::build_project(&project, &build_order, &options, None, None);
-
-    // --- Report ---
-    let elapsed = start_time.elapsed();
-    if !quiet {
-        if options.verbose {
-            println!("\n─────────────────────────────────────────────────────");
-            println!("  Build Report");
-            println!("─────────────────────────────────────────────────────");
-            println!("  Total targets : {}", result.total_targets);
-            println!("  Successful    : {}", result.successful_targets);
-            println!("  Failed        : {}", result.failed_targets);
-            println!("  Duration      : {:.2?}", elapsed);
-            println!(
-                "  Status        : {}",
-                if result.success { "SUCCESS ✓" } else { "FAILED ✗" }
-            );
-            println!("─────────────────────────────────────────────────────");
-        } else if result.success {
-            println!("  {} targets in {:.2?}", result.successful_targets, elapsed);
-        } else {
-            println!(
-                "  {} OK, {} failed in {:.2?}",
-                result.successful_targets,
-                result.failed_targets,
-                elapsed
-            );
-        }
-    } else if result.success {
-        println!(
-            "OK {} targets, {:.2?}",
-            result.successful_targets,
-            elapsed
+fn run_stats(config_path: &std::path::Path, last: usize) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm stats --config <file> or ngm stats -c <file>",
+            config_path.display()
         );
-    }
-
-    if !result.success {
         process::exit(1);
     }
+    match ngmake::load_build_history(config_path) {
+        Ok(records) => {
+            for line in history::format_stats(&records, last) {
+                println!("{}", line);
+            }
+        }
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    }
 }
 
-fn run_clean(config_path: &std::path::Path, verbose: bool) {
+fn run_explain(config_path: &std::path::Path, targets: &[String], preset: Option<&str>) {
     if !config_path.exists() {
         eprintln!(
             "[ERROR] File '{}' not found!\n\
-             Usage: ngm clean --config <file> or ngm clean -c <file>",
+             Usage: ngm explain --config <file> or ngm explain -c <file>",
             config_path.display()
         );
         process::exit(1);
     }
-    let project = match config::parse_build_file(config_path, false) {
+    let mut project = match config::parse_build_file(config_path, false) {
         Ok(p) => p,
         Err(e) => {
-            eprintln!("[ERROR] {}", e);
+            eprintln!("[ERROR] Configuration parse error: {}", e);
             process::exit(1);
         }
     };
-    let mut dirs: Vec<_> = project.targets.values().map(|t| t.output_dir.clone()).collect();
-    dirs.sort();
-    dirs.dedup();
-    let mut removed = 0usize;
-    for d in &dirs {
-        if d.exists() {
-            if verbose {
-                println!("  Removing {}", d.display());
-            }
-            if let Err(e) = std::fs::remove_dir_all(d) {
-                eprintln!("[ERROR] Failed to remove {}: {}", d.display(), e);
+    apply_preset(&mut project, preset);
+    let full_order = match dag::build_order(&project) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("[ERROR] Dependency resolution error: {}", e);
+            process::exit(1);
+        }
+    };
+    let order = if targets.is_empty() {
+        full_order
+    } else {
+        match dag::filter_order_for_targets(&project, &full_order, targets) {
+            Ok(o) => o,
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
                 process::exit(1);
             }
-            removed += 1;
         }
-    }
-    if removed == 0 {
-        println!("  No build output to clean.");
-    } else {
-        println!("  Cleaned {} director{}.", removed, if removed == 1 { "y" } else { "ies" });
+    };
+    for line in explain::format_explanations(&explain::explain(&project, &order)) {
+        println!("{}", line);
     }
 }
 
-fn run_build(options: BuildOptions) {
-    let build_path = &options.config;
-    if !build_path.exists() {
+fn run_add(config_path: &std::path::Path, name: &str, dependency: &prebuilt::DependencyConfig, target: Option<&str>, libs: &[String]) {
+    if !config_path.exists() {
         eprintln!(
             "[ERROR] File '{}' not found!\n\
-             Usage: ngm --config <file> or ngm -c <file>",
-            build_path.display()
+             Usage: ngm add <name> --path <dir> --config <file>",
+            config_path.display()
         );
         process::exit(1);
     }
-
-    let start_time = Instant::now();
-    let quiet = options.show_quiet_output();
-
-    // --- LD_LIBRARY_PATH (only in verbose) ---
-    if !options.no_ld_path && !quiet && options.verbose {
-        println!("─────────────────────────────────────────────────────");
-        println!("  ngmake v{}", env!("CARGO_PKG_VERSION"));
-        println!("─────────────────────────────────────────────────────");
-        compiler::print_ld_library_path_info();
-        println!();
-    }
-
-    // --- Parse configuration ---
-    if !quiet && options.verbose {
-        println!("[1/3] Parsing configuration file: {}", build_path.display());
+    if let Some(root) = config::find_workspace_root(config_path) {
+        eprintln!("[ERROR] [dependencies] is read from the root build.toml only: run `ngm add` with --config {}", root.display());
+        process::exit(1);
     }
-
-    let project = match config::parse_build_file(build_path.as_path(), options.show_verbose_output()) {
+    // Reading the package first catches a wrong path or name before build.toml changes
+    let base_dir = config_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    let imported = match prebuilt::import(name, dependency, base_dir) {
+        Ok(imported) => imported.targets,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    let available: Vec<String> = imported.iter().map(|t| t.name.clone()).collect();
+    let mut deps: Vec<String> = libs.iter().map(|lib| format!("{}::{}", name, lib)).collect();
+    if let Some(unknown) = deps.iter().find(|d| !available.contains(d)) {
+        eprintln!("[ERROR] Package '{}' has no library '{}' (available: {})", name, unknown, available.join(", "));
+        process::exit(1);
+    }
+    if deps.is_empty() {
+        deps = available.clone();
+    }
+    let result = edit::edit_config_file(config_path, |doc| {
+        doc.set_package_dependency(name, dependency)?;
+        if let Some(target) = target {
+            for dep in &deps {
+                doc.add_dependency(target, dep)?;
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+    println!("  Added '{}' to [dependencies] ({})", name, available.join(", "));
+    if let Some(target) = target {
+        println!("  Target '{}' now depends on {}", target, deps.join(", "));
+    }
+}
+
+fn run_package(config_path: &std::path::Path, format: options::PackageFormat, output: Option<&std::path::Path>, preset: Option<&str>) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm package --config <file> or ngm package -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    let mut project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    apply_preset(&mut project, preset);
+    let out_dir = output.map(PathBuf::from).unwrap_or_else(|| project.root_dir.join("dist"));
+    match package::create_archive(&project, format, &out_dir) {
+        Ok((archive, files)) => println!("  Packaged {} file(s) into {}", files, archive.display()),
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_deps(config_path: &std::path::Path, target: Option<&str>, tree: bool, invert: bool) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm deps --config <file> or ngm deps -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    let project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    let order = match dag::build_order(&project) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("[ERROR] Dependency resolution error: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Some(name) = target.filter(|name| !project.targets.contains_key(*name)) {
+        eprintln!("[ERROR] Unknown target '{}'", name);
+        process::exit(1);
+    }
+    let roots: Vec<String> = target.map(String::from).into_iter().collect();
+    if tree {
+        for line in dag::dependency_tree(&project, &order, &roots, invert) {
+            println!("{}", line);
+        }
+        return;
+    }
+    // Flat: one line per target with its direct deps (or dependents)
+    for name in order.levels.iter().flatten().filter(|n| roots.is_empty() || roots.contains(n)) {
+        let related: Vec<&str> = if invert {
+            order.levels.iter().flatten().filter(|n| project.targets[n.as_str()].deps.contains(name)).map(String::as_str).collect()
+        } else {
+            project.targets[name].deps.iter().map(String::as_str).collect()
+        };
+        println!("{}", format!("{}: {}", name, related.join(" ")).trim_end());
+    }
+}
+
+fn run_query(config_path: &std::path::Path, query: &options::Query) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm query --config <file> or ngm query -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    let project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    let order = match dag::build_order(&project) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("[ERROR] Dependency resolution error: {}", e);
+            process::exit(1);
+        }
+    };
+    let target = |name: &str| match project.targets.get(name) {
+        Some(target) => target,
+        None => {
+            eprintln!("[ERROR] Unknown target '{}'", name);
+            process::exit(1);
+        }
+    };
+    let result = match query {
+        options::Query::Targets => query::targets(&project, &order),
+        options::Query::Sources { target: name } => query::sources(target(name)),
+        options::Query::Flags { target: name } => query::flags(target(name)),
+        options::Query::Rdeps { target: name } => {
+            target(name);
+            query::rdeps(&project, &order, name)
+        }
+    };
+    println!("{:#}", result);
+}
+
+/// `ngm run`: builds `target`, then runs it in the terminal and exits with its exit code.
+fn run_target(config_path: &std::path::Path, target: &str, args: &[String], jobs: Option<usize>, preset: Option<&str>) {
+    let mut project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    apply_preset(&mut project, preset);
+    // Before building, so `ngm run mylib` doesn't build the library first
+    if let Err(e) = run::executable_path(&project, target) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+
+    // Exits on build failure
+    run_build(BuildOptions { config: config_path.to_path_buf(), targets: Some(vec![target.to_string()]), jobs, preset: preset.map(str::to_string), ..Default::default() });
+
+    let code = run::run_command(&project, target, args).and_then(run::run_in_terminal);
+    match code {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// `ngm test`: builds the tests, runs them in parallel and writes the requested reports.
+/// Exits 1 if a test fails.
+#[allow(clippy::too_many_arguments)]
+fn run_tests(config_path: &std::path::Path, names: &[String], junit: Option<&std::path::Path>, json: Option<&std::path::Path>, jobs: Option<usize>, preset: Option<&str>, mut settings: test_runner::TestSettings, verbose: bool) {
+    let mut project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    apply_preset(&mut project, preset);
+    let tests = match dag::targets_of_type(&project, names, config::TargetType::Test) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    if tests.is_empty() {
+        println!("[INFO] No test targets (type = \"test\") defined.");
+        return;
+    }
+    // --memcheck options on top of [memcheck]; checked before spending time on the build
+    settings.memcheck = settings.memcheck.map(|cli| project.memcheck.clone().unwrap_or_default().with_overrides(&cli));
+    if let Some(Err(e)) = settings.memcheck.as_ref().map(MemcheckConfig::check) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+
+    // Exits on build failure
+    run_build(BuildOptions { config: config_path.to_path_buf(), targets: Some(tests.clone()), jobs, preset: preset.map(str::to_string), ..Default::default() });
+
+    println!();
+    let start = Instant::now();
+    let results = test_runner::run_tests(&project, &tests, &settings, |result| {
+        // One block per test, even when tests finish at the same time
+        use std::io::Write;
+        let mut stdout = std::io::stdout().lock();
+        for line in test_runner::format_result(result, verbose) {
+            let _ = writeln!(stdout, "{}", color::line(&line));
+        }
+    });
+    let results = match results {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    println!("{}", color::line(&test_runner::summary(&results, start.elapsed())));
+    let reports = [
+        junit.map(|path| (path, test_runner::junit_xml(&project.name, &results))),
+        json.map(|path| (path, format!("{:#}\n", test_runner::results_json(&project.name, &results)))),
+    ];
+    for (path, content) in reports.into_iter().flatten() {
+        match test_runner::write_report(path, &content) {
+            Ok(()) => println!("  Results written to {}", path.display()),
+            Err(e) => eprintln!("[ERROR] {}", e),
+        }
+    }
+    if results.iter().any(|r| !r.passed) {
+        process::exit(1);
+    }
+}
+
+/// `ngm bench`: builds the benchmarks, runs them one after another, prints the timings
+/// and appends them to the results file. Exits 1 if a benchmark fails.
+fn run_bench(config_path: &std::path::Path, names: &[String], repetitions: Option<usize>, results: Option<&std::path::Path>, jobs: Option<usize>, preset: Option<&str>, verbose: bool) {
+    let mut project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    apply_preset(&mut project, preset);
+    let benchmarks = match dag::targets_of_type(&project, names, config::TargetType::Benchmark) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    if benchmarks.is_empty() {
+        println!("[INFO] No benchmark targets (type = \"benchmark\") defined.");
+        return;
+    }
+
+    // Exits on build failure
+    run_build(BuildOptions { config: config_path.to_path_buf(), targets: Some(benchmarks.clone()), jobs, preset: preset.map(str::to_string), ..Default::default() });
+
+    let mut outcomes = Vec::new();
+    for name in &benchmarks {
+        println!("[BENCH] {}", name);
+        let outcome = bench::run_benchmark(&project, name, repetitions);
+        if verbose && !outcome.output.is_empty() {
+            print!("{}", outcome.output);
+        }
+        if let Some(e) = &outcome.error {
+            for line in e.lines() {
+                eprintln!("  [ERROR] {}", line);
+            }
+        }
+        outcomes.push(outcome);
+    }
+
+    let path = results.map(std::path::Path::to_path_buf).unwrap_or_else(|| bench::results_path(&project.root_dir));
+    let previous = bench::previous_results(&path);
+    println!();
+    for line in bench::format_results(&outcomes, previous.as_ref()) {
+        println!("{}", line);
+    }
+    let commit = bench::git_commit(&project.root_dir);
+    match bench::append_results(&path, &bench::results_record(&project.name, commit.as_deref(), &outcomes)) {
+        Ok(()) => println!("  Results appended to {}", path.display()),
+        Err(e) => eprintln!("[ERROR] {}", e),
+    }
+    if outcomes.iter().any(|o| o.error.is_some()) {
+        process::exit(1);
+    }
+}
+
+/// `ngm verify-inputs`: traces every compile and exits 1 if any target reads undeclared files.
+fn run_verify_inputs(config_path: &std::path::Path, targets: Option<&[String]>) {
+    let tracer = match inputs::Tracer::detect() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    let project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    let order = match dag::build_order(&project).and_then(|full| match targets {
+        Some(t) => dag::filter_order_for_targets(&project, &full, t),
+        None => Ok(full),
+    }) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+
+    let reports = inputs::verify_project(&project, &order, tracer);
+    for line in inputs::format_report(&reports, &project.root_dir) {
+        println!("{}", line);
+    }
+    if reports.iter().any(|r| !r.undeclared.is_empty() || !r.errors.is_empty()) {
+        process::exit(1);
+    }
+}
+
+fn run_iwyu(config_path: &std::path::Path, targets: Option<&[String]>, iwyu_bin: &str) {
+    if !config_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm iwyu --config <file> or ngm iwyu -c <file>",
+            config_path.display()
+        );
+        process::exit(1);
+    }
+    let project = match config::parse_build_file(config_path, false) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[ERROR] Configuration parse error: {}", e);
+            process::exit(1);
+        }
+    };
+    let full_order = match dag::build_order(&project) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("[ERROR] Dependency resolution error: {}", e);
+            process::exit(1);
+        }
+    };
+    let order = match targets {
+        Some(t) => match dag::filter_order_for_targets(&project, &full_order, t) {
+            Ok(o) => o,
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
+                process::exit(1);
+            }
+        },
+        None => full_order,
+    };
+
+    let reports = iwyu::analyze_project(&project, &order, iwyu_bin);
+    for line in iwyu::format_report(&reports) {
+        println!("{}", line);
+    }
+    if reports.iter().any(|r| !r.errors.is_empty()) {
+        process::exit(1);
+    }
+}
+
+/// Applies `--preset` for a command that uses build outputs (run, test, bench, explain,
+/// package), so it finds them where `ngm build --preset` put them. Exits on an unknown preset.
+fn apply_preset(project: &mut config::ResolvedProject, preset: Option<&str>) {
+    let Some(name) = preset else { return };
+    if let Err(e) = preset::apply(project, &mut BuildOptions::default(), name) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+}
+
+/// Validates and parses the build configuration, exiting on errors. The result is saved
+/// as a snapshot for the next build unless validation reported warnings, so those keep
+/// being shown until they are fixed.
+fn parse_configuration(options: &BuildOptions, overrides: &interpolate::Variables) -> config::ResolvedProject {
+    let build_path = &options.config;
+    let quiet = options.show_quiet_output();
+    let problems = validate::validate_config_with_vars(build_path.as_path(), overrides);
+    let invalid = problems.iter().any(|p| p.is_error());
+    let no_problems = problems.is_empty();
+    let shown: Vec<_> = problems.into_iter().filter(|p| p.is_error() || !quiet).collect();
+    if !shown.is_empty() {
+        for line in validate::render(&shown).lines() {
+            eprintln!("{}", color::line(line));
+        }
+    }
+    if invalid {
+        eprintln!("[ERROR] Invalid configuration: {}", build_path.display());
+        process::exit(1);
+    }
+
+    let parsed = if options.no_workspace {
+        config::parse_build_file_standalone(build_path.as_path(), options.show_verbose_output(), overrides)
+    } else {
+        config::parse_build_file_with_vars(build_path.as_path(), options.show_verbose_output(), overrides)
+    };
+    let project = match parsed {
         Ok(p) => {
-            if !quiet && options.verbose {
+            if !quiet && options.verbose > 0 {
                 println!(
                     "  Project: {} v{} ({} targets loaded)",
                     p.name,
@@ -632,6 +1137,134 @@ fn run_build(options: BuildOptions) {
         }
     };
 
+    // Before the snapshot is saved, as it records the lock file's mtime
+    if let Err(e) = lock::update(&project.root_dir, &project.locked_packages) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+    if no_problems {
+        if let Err(e) = snapshot::save(&project, build_path, overrides, options.no_workspace) {
+            if options.show_verbose_output() {
+                eprintln!("[WARN] {}", e);
+            }
+        }
+    }
+    project
+}
+
+/// The targets `ngm build` was asked for: -t names and patterns plus the --target-regex
+/// and --tag matches (default: dag::default_selection), extended by --with-dependents,
+/// minus targets with an --exclude-tag tag. None = all targets.
+fn select_targets(project: &config::ResolvedProject, options: &BuildOptions) -> Result<Option<Vec<String>>, String> {
+    let mut selected = options.targets.clone();
+    let mut matched = Vec::new();
+    if let Some(pattern) = &options.target_regex {
+        matched.extend(dag::targets_matching_regex(project, pattern)?);
+    }
+    if !options.tags.is_empty() {
+        matched.extend(dag::targets_with_tags(project, &options.tags)?);
+    }
+    if options.target_regex.is_some() || !options.tags.is_empty() {
+        let names = selected.get_or_insert_with(Vec::new);
+        for name in matched {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    if selected.is_none() {
+        selected = dag::default_selection(project);
+    }
+    if options.with_dependents {
+        if let Some(names) = &selected {
+            selected = Some(dag::with_dependents(project, names)?);
+        }
+    }
+    if !options.exclude_tags.is_empty() {
+        let names = match selected {
+            Some(names) => dag::expand_target_patterns(project, &names)?,
+            None => {
+                let mut all: Vec<String> = project.targets.keys().cloned().collect();
+                all.sort();
+                all
+            }
+        };
+        let excluded = |name: &String| project.targets[name].tags.iter().any(|t| options.exclude_tags.contains(t));
+        selected = Some(names.into_iter().filter(|n| !excluded(n)).collect());
+    }
+    Ok(selected)
+}
+
+fn run_build(mut options: BuildOptions) {
+    if options.status_format.is_none() {
+        options.status_format = std::env::var("OXIMAKE_STATUS").ok().filter(|f| !f.is_empty());
+    }
+    if let Some(Err(e)) = options.status_format.as_deref().map(progress::check_status_format) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+    let build_path = &options.config;
+    if !build_path.exists() {
+        eprintln!(
+            "[ERROR] File '{}' not found!\n\
+             Usage: ngm --config <file> or ngm -c <file>",
+            build_path.display()
+        );
+        process::exit(1);
+    }
+
+    let start_time = Instant::now();
+    let quiet = options.show_quiet_output();
+    color::init(options.color);
+
+    // --- LD_LIBRARY_PATH (only in verbose) ---
+    if !options.no_ld_path && !quiet && options.verbose > 0 {
+        println!("  {}", color::bold(&format!("ngmake v{}", env!("CARGO_PKG_VERSION"))));
+        compiler::print_ld_library_path_info();
+        println!();
+    }
+
+    // --- Parse configuration ---
+    if !quiet && options.verbose > 0 {
+        println!("[1/3] Parsing configuration file: {}", build_path.display());
+    }
+
+    // Report all config problems with their locations (parse_build_file stops at the first one)
+    let overrides = match interpolate::parse_overrides(&options.set) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    // A snapshot saved by an earlier build skips validation and parsing (see snapshot.rs);
+    // --locked always parses, so the dependencies are checked against oximake.lock
+    lock::set_locked(options.locked);
+    offline::set_offline(options.offline);
+    let snapshot = if options.locked { None } else { snapshot::load(build_path, &overrides, options.no_workspace) };
+    let mut project = match snapshot {
+        Some(p) => {
+            if !quiet && options.verbose > 0 {
+                println!("  Project: {} v{} ({} targets, from snapshot)", p.name, p.version, p.targets.len());
+            }
+            p
+        }
+        None => parse_configuration(&options, &overrides),
+    };
+    if let Some(name) = options.preset.clone() {
+        if let Err(e) = preset::apply(&mut project, &mut options, &name) {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    }
+    if options.offline {
+        for disabled in offline::strip_network(&mut project) {
+            if !quiet && options.verbose > 0 {
+                println!("  [OFFLINE] Not using {}", disabled);
+            }
+        }
+    }
+
     if project.targets.is_empty() {
         if !quiet {
             println!("[INFO] No targets defined. Nothing to do.");
@@ -639,7 +1272,7 @@ fn run_build(options: BuildOptions) {
         return;
     }
 
-    if !quiet && options.verbose {
+    if !quiet && options.verbose > 0 {
         println!("\n  Defined targets:");
         for (name, target) in &project.targets {
             println!(
@@ -655,28 +1288,42 @@ fn run_build(options: BuildOptions) {
 
     // --- Clean (remove output dirs first) ---
     if options.clean {
-        let mut dirs: Vec<_> = project
-            .targets
-            .values()
-            .map(|t| t.output_dir.clone())
-            .collect();
-        dirs.sort();
-        dirs.dedup();
-        for d in &dirs {
+        for d in &prune::output_dirs(&project) {
             if d.exists() {
-                if !quiet && options.verbose {
+                if !quiet && options.verbose > 0 {
                     println!("[CLEAN] Removing {}...", d.display());
                 }
                 let _ = std::fs::remove_dir_all(d);
             }
         }
-        if !quiet && options.verbose {
+        if !quiet && options.verbose > 0 {
             println!();
         }
     }
 
+    // --- Force rebuild (remove the named targets' objects and outputs) ---
+    for name in &options.force_rebuild {
+        let Some(target) = project.targets.get(name) else {
+            eprintln!("[ERROR] Unknown target '{}' in --force-rebuild", name);
+            process::exit(1);
+        };
+        match compiler::invalidate_target(target) {
+            Ok(removed) => {
+                if !quiet && options.verbose > 0 {
+                    for path in removed {
+                        println!("[FORCE] Removed {}", path.display());
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
     // --- DAG and topological order ---
-    if !quiet && options.verbose {
+    if !quiet && options.verbose > 0 {
         println!("[2/3] Building dependency graph (DAG)...");
     }
 
@@ -688,8 +1335,22 @@ fn run_build(options: BuildOptions) {
         }
     };
 
+    let selected = match select_targets(&project, &options) {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            process::exit(1);
+        }
+    };
+    if let Some(names) = &selected {
+        if !quiet && options.verbose > 0 && selected != options.targets {
+            println!("[DAG] Selected targets: {}", names.join(", "));
+        }
+    }
+
     // Filter order for selected targets only
-    let build_order = match &options.targets {
+    let build_order = match &selected {
+        Some(t) if t.is_empty() => dag::BuildOrder { levels: Vec::new() },
         Some(t) => match dag::filter_order_for_targets(&project, &full_order, t) {
             Ok(o) => o,
             Err(e) => {
@@ -707,7 +1368,16 @@ fn run_build(options: BuildOptions) {
         return;
     }
 
-    if !quiet && options.verbose {
+    if let Err(e) = compiler::verify_linkers(&project, &build_order.levels.concat()) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+    if let Err(e) = toolchain::verify_requirements(&project, &build_order.levels.concat()) {
+        eprintln!("[ERROR] {}", e);
+        process::exit(1);
+    }
+
+    if !quiet && options.verbose > 0 {
         println!("[DAG] Topological order:");
         for (i, level) in build_order.levels.iter().enumerate() {
             println!("  Level {}: {:?}", i, level);
@@ -716,43 +1386,115 @@ fn run_build(options: BuildOptions) {
     }
 
     // --- Build ---
-    if !quiet && options.verbose {
+    if !quiet && options.verbose > 0 {
         println!("[3/3] Starting build...\n");
     }
 
     let result = builder::build_project(&project, &build_order, &options, None, None);
 
+    if let Err(e) = history::record_build(&project.root_dir, &result) {
+        eprintln!("[WARN] Could not record build history: {}", e);
+    }
+
+    if result.success {
+        let built = build_order.levels.concat();
+        let written = pkg_config::write_build_tree(&project, &built)
+            .and_then(|pc| Ok((pc, bin_dir::gather_outputs(&project, &built)?)));
+        match written {
+            Ok((pc_files, binaries)) if !quiet && options.verbose > 0 => {
+                for path in pc_files {
+                    println!("  [PC] {}", path.display());
+                }
+                for path in binaries {
+                    println!("  [BIN] {}", path.display());
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(trace_path) = &options.trace {
+        match trace::write_chrome_trace(trace_path, &project.name, &result.jobs) {
+            Ok(()) => {
+                if !quiet {
+                    println!("  Trace written to {}", trace_path.display());
+                }
+            }
+            Err(e) => eprintln!("[ERROR] {}", e),
+        }
+    }
+
+    if options.timings || options.timings_json.is_some() {
+        let report = timing::timing_report(&project, &build_order, &result);
+        if options.timings && !options.json_events() {
+            println!();
+            for line in timing::format_report(&report, 10) {
+                println!("{}", line);
+            }
+        }
+        if let Some(path) = &options.timings_json {
+            if let Err(e) = std::fs::write(path, format!("{:#}", timing::report_json(&report))) {
+                eprintln!("[ERROR] Failed to write timing report '{}': {}", path.display(), e);
+            }
+        }
+    }
+
+    if let Some(path) = &options.report {
+        match report::write_report(path, &report::build_report(&project, &build_order, &result)) {
+            Ok(()) => {
+                if !quiet && !options.json_events() {
+                    println!("  Report written to {}", path.display());
+                }
+            }
+            Err(e) => eprintln!("[ERROR] {}", e),
+        }
+    }
+
     let elapsed = start_time.elapsed();
-    if !quiet {
-        if options.verbose {
-            println!("\n─────────────────────────────────────────────────────");
-            println!("  Build Report");
-            println!("─────────────────────────────────────────────────────");
+    let diagnostics = result.diagnostic_totals();
+    if options.json_events() {
+        events::emit(&events::build_finished(&result));
+    } else if !quiet {
+        if options.verbose > 0 {
+            println!("\n  {}", color::bold("Build Report"));
             println!("  Total targets : {}", result.total_targets);
             println!("  Successful    : {}", result.successful_targets);
             println!("  Failed        : {}", result.failed_targets);
+            println!("  Not attempted : {}", result.not_attempted.len());
+            println!("  Warnings      : {}", diagnostics.warnings);
+            println!("  Errors        : {}", diagnostics.errors);
+            for (target, counts) in &result.diagnostics {
+                println!("    {:<20} {} warnings, {} errors", target, counts.warnings, counts.errors);
+            }
             println!("  Duration      : {:.2?}", elapsed);
             println!(
                 "  Status        : {}",
-                if result.success { "SUCCESS ✓" } else { "FAILED ✗" }
+                if result.success { color::green("SUCCESS") } else { color::red("FAILED") }
             );
-            println!("─────────────────────────────────────────────────────");
         } else if result.success {
-            println!("  {} targets in {:.2?}", result.successful_targets, elapsed);
+            println!("{}", color::green(&format!("  {} targets{} in {:.2?}", result.successful_targets, diagnostics.suffix(), elapsed)));
         } else {
             println!(
-                "  {} OK, {} failed in {:.2?}",
-                result.successful_targets,
-                result.failed_targets,
-                elapsed
+                "{}",
+                color::red(&format!(
+                    "  {} OK, {} failed, {} not attempted{} in {:.2?}",
+                    result.successful_targets,
+                    result.failed_targets,
+                    result.not_attempted.len(),
+                    diagnostics.suffix(),
+                    elapsed
+                ))
             );
+            if !result.not_attempted.is_empty() {
+                println!("  Not attempted: {}", result.not_attempted.join(", "));
+            }
         }
     } else if result.success {
-        println!(
-            "OK {} targets, {:.2?}",
-            result.successful_targets,
-            elapsed
-        );
+        println!("{}", color::green(&format!("OK {} targets, {:.2?}", result.successful_targets, elapsed)));
     }
 
     if !result.success {
@@ -0,0 +1,185 @@
+// ============================================================================
+// memcheck.rs — Running tests under valgrind (`ngm test --memcheck`)
+// ============================================================================
+//
+// With --memcheck every test runs as
+//
+//   valgrind --tool=memcheck --log-file=<tmp> --leak-check=full
+//            --show-leak-kinds=definite --errors-for-leak-kinds=definite
+//            [--suppressions=...] [args] <test> <test args>
+//
+// so invalid reads/writes, uses of uninitialised values and definite leaks
+// are counted in valgrind's ERROR SUMMARY, while possible and still-reachable
+// leaks are not. A test with errors fails even if it exited with 0. The
+// valgrind log goes to its own file, so the findings (first line of each
+// error with the first frame outside valgrind) are taken from there and
+// the test's own output stays as it was.
+//
+// The root build.toml configures it:
+//
+//   [memcheck]
+//   tool = "memcheck"                      # or helgrind, drd
+//   suppressions = ["tools/valgrind.supp"]  # relative to the root build.toml
+//   args = ["--track-origins=yes"]
+// ============================================================================
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// The `[memcheck]` table of the root build.toml (and the command-line overrides).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct MemcheckConfig {
+    /// valgrind executable (default: valgrind)
+    #[serde(default)]
+    pub valgrind: Option<String>,
+    /// Valgrind tool (default: memcheck)
+    #[serde(default)]
+    pub tool: Option<String>,
+    /// Suppression files, relative to the root build.toml (the tests' working directory)
+    #[serde(default)]
+    pub suppressions: Vec<String>,
+    /// Extra valgrind options
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// What valgrind found in one test run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemcheckReport {
+    /// Count from the ERROR SUMMARY
+    pub errors: usize,
+    /// "Invalid read of size 4 (at main (parser_test.cpp:12))", one per error context
+    pub findings: Vec<String>,
+}
+
+impl MemcheckConfig {
+    pub fn tool(&self) -> &str {
+        self.tool.as_deref().unwrap_or("memcheck")
+    }
+
+    fn valgrind(&self) -> &str {
+        self.valgrind.as_deref().unwrap_or("valgrind")
+    }
+
+    /// `cli`'s tool replaces this one; its suppressions are added.
+    pub fn with_overrides(&self, cli: &MemcheckConfig) -> MemcheckConfig {
+        let mut merged = self.clone();
+        if cli.tool.is_some() {
+            merged.tool = cli.tool.clone();
+        }
+        merged.suppressions.extend(cli.suppressions.iter().cloned());
+        merged
+    }
+
+    /// Fails with a hint when valgrind can't be started.
+    pub fn check(&self) -> Result<(), String> {
+        match Command::new(self.valgrind()).arg("--version").output() {
+            Ok(out) if out.status.success() => Ok(()),
+            _ => Err(format!("ngm test --memcheck needs valgrind ('{}' not found; e.g. apt install valgrind)", self.valgrind())),
+        }
+    }
+
+    /// `command` run under valgrind, with the valgrind log written to `log`.
+    pub fn wrap(&self, command: &Command, log: &Path) -> Command {
+        let mut wrapped = Command::new(self.valgrind());
+        wrapped.arg(format!("--tool={}", self.tool())).arg(format!("--log-file={}", log.display()));
+        if self.tool() == "memcheck" {
+            wrapped.args(["--leak-check=full", "--show-leak-kinds=definite", "--errors-for-leak-kinds=definite"]);
+        }
+        for file in &self.suppressions {
+            wrapped.arg(format!("--suppressions={}", file));
+        }
+        wrapped.args(&self.args).arg(command.get_program()).args(command.get_args());
+        if let Some(dir) = command.get_current_dir() {
+            wrapped.current_dir(dir);
+        }
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        wrapped
+    }
+}
+
+/// Findings and error count from a valgrind log; None without an ERROR SUMMARY
+/// (valgrind itself failed, e.g. on a bad suppressions file).
+pub fn parse_log(text: &str) -> Option<MemcheckReport> {
+    // "==4711== text" → "text"
+    let lines: Vec<&str> = text
+        .lines()
+        .filter_map(|l| l.strip_prefix("==")?.split_once("== ").map(|(_, rest)| rest).or(Some("")))
+        .collect();
+    let mut report = MemcheckReport::default();
+    let mut summary = false;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(rest) = line.strip_prefix("ERROR SUMMARY: ") {
+            report.errors = rest.split_whitespace().next()?.parse().ok()?;
+            summary = true;
+            continue;
+        }
+        // An error is an unindented line followed by its stack ("   at 0x...: main (t.cpp:5)")
+        if line.is_empty() || line.starts_with(' ') || !lines.get(i + 1).is_some_and(|n| n.trim_start().starts_with("at 0x")) {
+            continue;
+        }
+        let frames = lines[i + 1..].iter().map(|l| l.trim_start()).take_while(|l| l.starts_with("at 0x") || l.starts_with("by 0x"));
+        let mut frames = frames.filter_map(|f| f.split_once(": ").map(|(_, location)| location));
+        let first = frames.clone().next();
+        // Skip valgrind's malloc/free replacements
+        let frame = frames.find(|f| !f.contains("vg_replace_") && !f.contains("vgpreload")).or(first);
+        let what = line.split(" in loss record ").next().unwrap_or(line);
+        report.findings.push(match frame {
+            Some(frame) => format!("{} (at {})", what, frame),
+            None => what.to_string(),
+        });
+    }
+    summary.then_some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_and_definite_leaks_are_read_from_the_log() {
+        let log = "\
+==4711== Memcheck, a memory error detector
+==4711== Command: build/test_parser
+==4711==
+==4711== Invalid write of size 4
+==4711==    at 0x10916B: fill (parser_test.cpp:6)
+==4711==    by 0x109190: main (parser_test.cpp:11)
+==4711==  Address 0x4a4b068 is 0 bytes after a block of size 40 alloc'd
+==4711==    at 0x483B7F3: malloc (vg_replace_malloc.c:309)
+==4711==    by 0x109146: main (parser_test.cpp:9)
+==4711==
+==4711== HEAP SUMMARY:
+==4711==     in use at exit: 40 bytes in 1 blocks
+==4711==
+==4711== 40 bytes in 1 blocks are definitely lost in loss record 1 of 1
+==4711==    at 0x483B7F3: malloc (vg_replace_malloc.c:309)
+==4711==    by 0x109146: main (parser_test.cpp:9)
+==4711==
+==4711== ERROR SUMMARY: 2 errors from 2 contexts (suppressed: 0 from 0)
+";
+        let report = parse_log(log).unwrap();
+        assert_eq!(report.errors, 2);
+        assert_eq!(
+            report.findings,
+            vec!["Invalid write of size 4 (at fill (parser_test.cpp:6))", "40 bytes in 1 blocks are definitely lost (at main (parser_test.cpp:9))"]
+        );
+        assert_eq!(parse_log("valgrind: Bad option: --bogus\n"), None);
+
+        let config = MemcheckConfig { suppressions: vec!["a.supp".to_string()], ..Default::default() };
+        let cli = MemcheckConfig { tool: Some("helgrind".to_string()), suppressions: vec!["b.supp".to_string()], ..Default::default() };
+        let mut test = Command::new("build/test_parser");
+        test.arg("--fast").current_dir("/project");
+        let wrapped = config.with_overrides(&cli).wrap(&test, Path::new("/tmp/vg.log"));
+        let args: Vec<_> = wrapped.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+        assert_eq!(args, vec!["--tool=helgrind", "--log-file=/tmp/vg.log", "--suppressions=a.supp", "--suppressions=b.supp", "build/test_parser", "--fast"]);
+        assert_eq!(wrapped.get_current_dir(), Some(Path::new("/project")));
+    }
+}
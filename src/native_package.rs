@@ -0,0 +1,204 @@
+// ============================================================================
+// native_package.rs — `ngm package --format deb|rpm`: Linux OS packages
+// ============================================================================
+//
+// The archive contents (see package.rs) are installed below [package] prefix
+// (default /usr):
+//
+//   bin/x       → /usr/bin/x
+//   lib/x       → /usr/lib/x
+//   include/... → /usr/include/...
+//   LICENSE     → /usr/share/doc/<package>/LICENSE
+//
+// The DEBIAN/control file or the rpm spec is generated from [project] (name,
+// version) and [package] (maintainer, description, license, homepage,
+// depends / requires), and the package is built with `dpkg-deb` or
+// `rpmbuild`. Package names are the project name in lowercase with other
+// characters than [a-z0-9+.-] replaced by '-'.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::ResolvedProject;
+use crate::options::PackageFormat;
+use crate::package::{self, PackageConfig};
+
+/// Project name as a deb/rpm package name.
+pub fn package_name(project: &ResolvedProject) -> String {
+    project
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "+.-".contains(c) { c } else { '-' })
+        .collect()
+}
+
+/// Debian architecture of this machine.
+pub fn deb_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "i386",
+        "arm" => "armhf",
+        "powerpc64" => "ppc64el",
+        "riscv64" => "riscv64",
+        other => other,
+    }
+}
+
+/// The archive contents at their installed paths (relative to `/`).
+pub fn installed_files(project: &ResolvedProject, config: &PackageConfig) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let prefix = PathBuf::from(config.prefix.as_deref().unwrap_or("/usr").trim_start_matches('/'));
+    let doc_dir = prefix.join("share/doc").join(package_name(project));
+    let files = package::contents(project)?;
+    Ok(files
+        .into_iter()
+        .map(|(source, relative)| {
+            let installed = if relative.components().count() == 1 { doc_dir.join(&relative) } else { prefix.join(&relative) };
+            (source, installed)
+        })
+        .collect())
+}
+
+/// `Description:` / `%description` text (default: the project name).
+fn description(project: &ResolvedProject, config: &PackageConfig) -> String {
+    config.description.clone().unwrap_or_else(|| project.name.clone())
+}
+
+/// DEBIAN/control of the package.
+pub fn deb_control(project: &ResolvedProject, config: &PackageConfig, installed_kib: u64) -> Result<String, String> {
+    let maintainer = config.maintainer.as_deref().ok_or("[package] maintainer is required for deb packages (e.g. \"Jane Doe <jane@example.com>\")")?;
+    let text = description(project, config);
+    let mut lines = text.trim().lines();
+    let summary = lines.next().unwrap_or_default();
+    let mut control = format!(
+        "Package: {}\nVersion: {}\nArchitecture: {}\nMaintainer: {}\nInstalled-Size: {}\n",
+        package_name(project),
+        project.version,
+        deb_arch(),
+        maintainer,
+        installed_kib
+    );
+    if !config.depends.is_empty() {
+        control.push_str(&format!("Depends: {}\n", config.depends.join(", ")));
+    }
+    if let Some(homepage) = &config.homepage {
+        control.push_str(&format!("Homepage: {}\n", homepage));
+    }
+    control.push_str(&format!("Description: {}\n", summary));
+    for line in lines {
+        control.push_str(&format!(" {}\n", if line.trim().is_empty() { "." } else { line }));
+    }
+    Ok(control)
+}
+
+/// rpm spec that copies `root` (the staged files) into the build root.
+pub fn rpm_spec(project: &ResolvedProject, config: &PackageConfig, root: &Path, files: &[PathBuf]) -> String {
+    let text = description(project, config);
+    let summary = text.trim().lines().next().unwrap_or_default().to_string();
+    let mut spec = format!(
+        "%global debug_package %{{nil}}\n%global _build_id_links none\n\nName: {}\nVersion: {}\nRelease: 1\nSummary: {}\nLicense: {}\n",
+        package_name(project),
+        project.version.replace('-', "_"),
+        summary,
+        config.license.as_deref().unwrap_or("Unspecified")
+    );
+    if let Some(homepage) = &config.homepage {
+        spec.push_str(&format!("URL: {}\n", homepage));
+    }
+    if let Some(maintainer) = &config.maintainer {
+        spec.push_str(&format!("Packager: {}\n", maintainer));
+    }
+    for requirement in &config.requires {
+        spec.push_str(&format!("Requires: {}\n", requirement));
+    }
+    spec.push_str(&format!("\n%description\n{}\n\n%install\nmkdir -p %{{buildroot}}\ncp -a '{}'/. %{{buildroot}}/\n\n%files\n", text.trim(), root.display()));
+    for file in files {
+        spec.push_str(&format!("\"/{}\"\n", file.display()));
+    }
+    spec
+}
+
+/// Builds the deb or rpm into `out_dir`. Returns the package path and the number of files.
+pub fn create(project: &ResolvedProject, format: PackageFormat, out_dir: &Path) -> Result<(PathBuf, usize), String> {
+    let config = project.package.clone().unwrap_or_default();
+    let work = crate::history::state_dir(&project.root_dir).join("package");
+    let root = work.join("root");
+    let files = package::copy_files(&installed_files(project, &config)?, &root)?;
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create '{}': {}", out_dir.display(), e))?;
+    let out_dir = out_dir.canonicalize().map_err(|e| format!("Failed to resolve '{}': {}", out_dir.display(), e))?;
+    let name = package_name(project);
+
+    let (path, mut command) = match format {
+        PackageFormat::Deb => {
+            let size: u64 = files.iter().filter_map(|f| std::fs::metadata(root.join(f)).ok()).map(|m| m.len()).sum();
+            let control = deb_control(project, &config, size.div_ceil(1024))?;
+            let debian = root.join("DEBIAN");
+            std::fs::create_dir_all(&debian).map_err(|e| format!("Failed to create '{}': {}", debian.display(), e))?;
+            std::fs::write(debian.join("control"), control).map_err(|e| format!("Failed to write DEBIAN/control: {}", e))?;
+            let path = out_dir.join(format!("{}_{}_{}.deb", name, project.version, deb_arch()));
+            let mut command = Command::new("dpkg-deb");
+            command.arg("--root-owner-group").arg("--build").arg(&root).arg(&path);
+            (path, command)
+        }
+        PackageFormat::Rpm => {
+            let root = std::path::absolute(&root).map_err(|e| e.to_string())?;
+            let top = std::path::absolute(work.join("rpmbuild")).map_err(|e| e.to_string())?;
+            let spec_path = work.join(format!("{}.spec", name));
+            std::fs::write(&spec_path, rpm_spec(project, &config, &root, &files)).map_err(|e| format!("Failed to write '{}': {}", spec_path.display(), e))?;
+            let path = out_dir.join(format!("{}-{}-1.{}.rpm", name, project.version.replace('-', "_"), std::env::consts::ARCH));
+            let mut command = Command::new("rpmbuild");
+            command
+                .arg("-bb")
+                .arg("--define")
+                .arg(format!("_topdir {}", top.display()))
+                .arg("--define")
+                .arg(format!("_rpmdir {}", out_dir.display()))
+                .arg("--define")
+                .arg("_build_name_fmt %%{NAME}-%%{VERSION}-%%{RELEASE}.%%{ARCH}.rpm")
+                .arg(&spec_path);
+            (path, command)
+        }
+        PackageFormat::TarGz | PackageFormat::Zip => return Err(format!("'{}' is not an OS package format", format.extension())),
+    };
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command.output().map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+    let _ = std::fs::remove_dir_all(&work);
+    if !output.status.success() {
+        return Err(format!("'{}' failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok((path, files.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_and_spec_come_from_project_metadata() {
+        let project = ResolvedProject { name: "My_Service".to_string(), version: "1.2.0-rc1".to_string(), ..Default::default() };
+        let config = PackageConfig {
+            maintainer: Some("Jane Doe <jane@example.com>".to_string()),
+            description: Some("Order service\nHandles orders.\n\nFast.".to_string()),
+            license: Some("MIT".to_string()),
+            depends: vec!["libssl3".to_string()],
+            requires: vec!["openssl-libs".to_string()],
+            ..Default::default()
+        };
+        let control = deb_control(&project, &config, 42).unwrap();
+        assert_eq!(
+            control,
+            format!(
+                "Package: my-service\nVersion: 1.2.0-rc1\nArchitecture: {}\nMaintainer: Jane Doe <jane@example.com>\nInstalled-Size: 42\nDepends: libssl3\nDescription: Order service\n Handles orders.\n .\n Fast.\n",
+                deb_arch()
+            )
+        );
+        assert!(deb_control(&project, &PackageConfig::default(), 1).unwrap_err().contains("maintainer"));
+
+        let spec = rpm_spec(&project, &config, Path::new("/stage"), &[PathBuf::from("usr/bin/my_service")]);
+        assert!(spec.contains("Name: my-service\nVersion: 1.2.0_rc1\nRelease: 1\nSummary: Order service\nLicense: MIT\n"));
+        assert!(spec.contains("Requires: openssl-libs\n"));
+        assert!(spec.ends_with("cp -a '/stage'/. %{buildroot}/\n\n%files\n\"/usr/bin/my_service\"\n"));
+    }
+}
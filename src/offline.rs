@@ -0,0 +1,104 @@
+// ============================================================================
+// offline.rs — `--offline`: builds without network access
+// ============================================================================
+//
+// For air-gapped machines. With --offline nothing is downloaded or sent:
+//
+//   [dependencies] url    must already be downloaded (.oximake/deps/); all
+//                         missing archives are listed before anything runs
+//   [cache] remote        not used; the local cache directory still is
+//   [remote]              not used; every compile runs locally
+//   distcc / icecc / recc compiler_launcher is dropped (ccache is kept)
+// ============================================================================
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::ResolvedProject;
+use crate::prebuilt::DependencyConfig;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Launchers that send compiles to other machines.
+const DISTRIBUTING_LAUNCHERS: &[&str] = &["distcc", "icecc", "pump", "recc"];
+
+/// Forbids network access for the rest of the process.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Error listing the `url` dependencies that are not downloaded yet (None if all are).
+pub fn missing_downloads(dependencies: &BTreeMap<String, DependencyConfig>, base_dir: &Path) -> Option<String> {
+    let missing: Vec<String> = dependencies
+        .iter()
+        .filter_map(|(name, dependency)| {
+            let url = dependency.url.as_ref().filter(|_| dependency.path.is_none() && dependency.archive.is_none())?;
+            let cached = crate::prebuilt::downloaded(name, url, base_dir);
+            (!cached).then(|| format!("  {}: {}", name, url))
+        })
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "--offline: {} dependency archive(s) not downloaded (build once online, or copy .oximake/deps/downloads/ from a machine that did):\n{}",
+        missing.len(),
+        missing.join("\n")
+    ))
+}
+
+/// Removes the network settings from a resolved project (see the module comment).
+/// Returns what was turned off, for the verbose output.
+pub fn strip_network(project: &mut ResolvedProject) -> Vec<String> {
+    let mut disabled = Vec::new();
+    if project.remote.take().is_some() {
+        disabled.push("[remote] build hosts".to_string());
+    }
+    if let Some(remote) = project.cache.as_mut().and_then(|c| c.remote.take()) {
+        disabled.push(format!("[cache] remote {}", remote));
+    }
+    let distributing = |launcher: &String| {
+        Path::new(launcher).file_stem().is_some_and(|stem| DISTRIBUTING_LAUNCHERS.iter().any(|l| stem == *l))
+    };
+    if let Some(launcher) = project.compiler_launcher.take_if(|l| distributing(l)) {
+        for target in project.targets.values_mut() {
+            target.compiler_launcher = None;
+        }
+        project.launcher_jobs = None;
+        disabled.push(format!("compiler_launcher {}", launcher));
+    }
+    disabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+    use crate::config::ResolvedTarget;
+
+    #[test]
+    fn test_strips_remote_cache_and_distributing_launcher() {
+        let mut project = ResolvedProject {
+            cache: Some(CacheConfig { dir: None, remote: Some("s3://bucket/ngm".to_string()), read_only: false }),
+            compiler_launcher: Some("/usr/bin/distcc".to_string()),
+            ..Default::default()
+        };
+        let target = ResolvedTarget { name: "app".to_string(), compiler_launcher: Some("/usr/bin/distcc".to_string()), ..Default::default() };
+        project.targets.insert("app".to_string(), target);
+        assert_eq!(strip_network(&mut project), vec!["[cache] remote s3://bucket/ngm", "compiler_launcher /usr/bin/distcc"]);
+        assert!(project.cache.is_some_and(|c| c.remote.is_none()));
+        assert_eq!(project.targets["app"].compiler_launcher, None);
+
+        let mut project = ResolvedProject { compiler_launcher: Some("recc".to_string()), ..Default::default() };
+        assert_eq!(strip_network(&mut project), vec!["compiler_launcher recc"]);
+
+        let mut project = ResolvedProject { compiler_launcher: Some("ccache".to_string()), ..Default::default() };
+        assert!(strip_network(&mut project).is_empty());
+        assert_eq!(project.compiler_launcher.as_deref(), Some("ccache"));
+    }
+}
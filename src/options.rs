@@ -2,28 +2,106 @@
 // options.rs — Command-line arguments (CLI)
 // ============================================================================
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+/// Output style of the build.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per event on stdout (for CI and editor integrations)
+    Json,
+}
+
+/// When to color the output.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// On a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Archive format of `ngm package`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PackageFormat {
+    #[default]
+    #[value(name = "tar.gz")]
+    TarGz,
+    Zip,
+    /// Debian package (dpkg-deb)
+    Deb,
+    /// RPM package (rpmbuild)
+    Rpm,
+}
+
+impl PackageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PackageFormat::TarGz => "tar.gz",
+            PackageFormat::Zip => "zip",
+            PackageFormat::Deb => "deb",
+            PackageFormat::Rpm => "rpm",
+        }
+    }
+}
+
+/// What `ngm query` prints.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// All targets in build order
+    Targets,
+    /// Sources of a target and their object files
+    Sources { target: String },
+    /// Compiler, include dirs, defines and flags of a target
+    Flags { target: String },
+    /// Targets depending on a target, directly or indirectly
+    Rdeps { target: String },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Build targets from build.toml
+    #[command(group(clap::ArgGroup::new("selection").args(["target", "target_regex", "tag"]).multiple(true)))]
     Build {
         /// Configuration file path (default: build.toml)
         #[arg(short, long, default_value = "build.toml")]
         config: PathBuf,
         
-        /// Build only the specified targets (and their dependencies)
+        /// Build only the specified targets (and their dependencies); glob patterns such as 'test_*' select every matching target
         #[arg(short, long)]
         target: Option<Vec<String>>,
+
+        /// Also build the targets whose name matches this regex (e.g. '^test_.*_io$')
+        #[arg(long, value_name = "REGEX")]
+        target_regex: Option<String>,
+
+        /// Also build the targets with this tag (`tags = [...]` in build.toml; repeatable)
+        #[arg(long, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Leave out targets with this tag unless another selected target depends on them (repeatable)
+        #[arg(long, value_name = "TAG")]
+        exclude_tag: Vec<String>,
+
+        /// Also build every target that depends on the selected targets (what a change to them affects)
+        #[arg(long, requires = "selection")]
+        with_dependents: bool,
         
         /// Clean output directories before building
         #[arg(long)]
         clean: bool,
+
+        /// Remove this target's objects and output first, so it is rebuilt from scratch (repeatable)
+        #[arg(long, value_name = "TARGET")]
+        force_rebuild: Vec<String>,
         
-        /// Verbose output (compiler commands etc.)
-        #[arg(short, long)]
-        verbose: bool,
+        /// Verbose output: -v target progress, -vv compiler command lines, -vvv environment and cache details
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        verbose: u8,
         
         /// Quiet mode: only errors and summary
         #[arg(short, long)]
@@ -36,9 +114,69 @@ pub enum Command {
         /// Maximum number of targets to build in parallel (default: unlimited per level)
         #[arg(short, long)]
         jobs: Option<usize>,
-        /// Ignore errors and continue building remaining targets (like make -i)
+        /// Keep going after failures: same as --keep-going=0 (like make -i)
         #[arg(short = 'i', long = "ignore-errors")]
         ignore_errors: bool,
+
+        /// Stop starting new jobs after N failed jobs (0 or no value: never; default: 1)
+        #[arg(short = 'k', long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+        keep_going: Option<usize>,
+
+        /// Write a Chrome trace (chrome://tracing / Perfetto) of all compile and link jobs
+        #[arg(long, value_name = "FILE")]
+        trace: Option<PathBuf>,
+
+        /// Print per-target/per-file durations, parallelism efficiency and the critical path
+        #[arg(long)]
+        timings: bool,
+
+        /// Write the timing report as JSON
+        #[arg(long, value_name = "FILE")]
+        timings_json: Option<PathBuf>,
+
+        /// Write a build report (results, durations, warnings, cache statistics, dependency graph): HTML, or JSON for a .json file
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
+        /// Output format: text or json (JSON lines: job_started, job_finished, diagnostic, build_finished)
+        #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+        log_format: LogFormat,
+
+        /// Color output: auto (terminal without NO_COLOR), always or never
+        #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+        color: ColorChoice,
+
+        /// Progress line template (default: $OXIMAKE_STATUS); tokens {done} {total} {percent} {elapsed} {eta} {target} {files}
+        #[arg(long, value_name = "TEMPLATE")]
+        status_format: Option<String>,
+
+        /// Set a build.toml variable (overrides [variables]; repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE")]
+        set: Vec<String>,
+
+        /// Build with a [presets] entry of build.toml (targets, jobs, defines; -t and -j override it)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+
+        /// Use the given build.toml as the root, even if a parent build.toml includes it
+        #[arg(long)]
+        no_workspace: bool,
+
+        /// Fail instead of updating oximake.lock (reproducible CI builds)
+        #[arg(long)]
+        locked: bool,
+
+        /// No network access: no downloads, remote cache, build hosts or distributing launchers
+        #[arg(long)]
+        offline: bool,
+
+        /// Memory available to concurrent links in MiB (overrides [project] link_memory_limit)
+        #[arg(long, value_name = "MIB")]
+        link_memory_limit: Option<u64>,
+
+        /// Maximum number of concurrent links (overrides [project] link_jobs; default: -j)
+        #[arg(long, value_name = "N")]
+        link_jobs: Option<usize>,
     },
 
     /// Remove build output directories (object files, libraries, executables)
@@ -49,8 +187,300 @@ pub enum Command {
         /// Verbose: list each removed directory
         #[arg(short, long)]
         verbose: bool,
+        /// Only remove objects and outputs that no current source or target produces
+        #[arg(long)]
+        prune: bool,
     },
     
+    /// Analyze includes with include-what-you-use (uses the exact compile commands)
+    Iwyu {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Analyze only the specified targets (and their dependencies)
+        #[arg(short, long)]
+        target: Option<Vec<String>>,
+
+        /// include-what-you-use executable
+        #[arg(long, default_value = "include-what-you-use")]
+        iwyu: String,
+    },
+
+    /// Trace each compile (strace) and report files read inside the project that build.toml doesn't declare
+    VerifyInputs {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Verify only the specified targets (and their dependencies)
+        #[arg(short, long)]
+        target: Option<Vec<String>>,
+    },
+
+    /// Build a target and run it in the terminal; exits with the program's exit code
+    Run {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Executable target to run
+        target: String,
+
+        /// Arguments for the program, after `--` (e.g. ngm run app -- --port 8080)
+        #[arg(last = true, value_name = "ARGS")]
+        args: Vec<String>,
+
+        /// Number of parallel build jobs
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// Build and run with a [presets] entry of build.toml (its outputs, defines and jobs)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+    },
+
+    /// Build and run test targets; exits 1 if any fails
+    Test {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Tests to run; glob patterns allowed (default: all)
+        targets: Vec<String>,
+
+        /// Write the results as JUnit XML (GitLab, Jenkins, GitHub test reporters)
+        #[arg(long, value_name = "FILE")]
+        junit: Option<PathBuf>,
+
+        /// Write the results as JSON
+        #[arg(long, value_name = "FILE")]
+        json: Option<PathBuf>,
+
+        /// Number of parallel build jobs
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// Build and run the tests with a [presets] entry of build.toml (its outputs, defines and jobs)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+
+        /// Tests run at once (default: one per CPU); independent of -j
+        #[arg(long, value_name = "N")]
+        test_jobs: Option<usize>,
+
+        /// Kill a test running longer than this and count it as failed (overrides test_timeout_secs)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// Rerun a failed test up to N times before it counts as failed (overrides retries)
+        #[arg(long, value_name = "N")]
+        retries: Option<u32>,
+
+        /// Run the tests under valgrind; errors and definite leaks fail the test ([memcheck] in build.toml)
+        #[arg(long)]
+        memcheck: bool,
+
+        /// Valgrind tool to use instead of memcheck (e.g. helgrind, drd)
+        #[arg(long, value_name = "TOOL", requires = "memcheck")]
+        memcheck_tool: Option<String>,
+
+        /// Valgrind suppressions file, in addition to those of [memcheck] (repeatable)
+        #[arg(long, value_name = "FILE", requires = "memcheck")]
+        suppressions: Vec<String>,
+
+        /// Print the output of passing tests too
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Build and run benchmark targets, then record their timings in .oximake/bench.jsonl
+    Bench {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Benchmarks to run; glob patterns allowed (default: all)
+        targets: Vec<String>,
+
+        /// Runs per benchmark (default: the target's repetitions, else 5)
+        #[arg(short = 'n', long)]
+        repetitions: Option<usize>,
+
+        /// Append the results to this file instead of .oximake/bench.jsonl
+        #[arg(long, value_name = "FILE")]
+        results: Option<PathBuf>,
+
+        /// Number of parallel build jobs (benchmarks always run one at a time)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// Build and run the benchmarks with a [presets] entry of build.toml (its outputs, defines and jobs)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+
+        /// Print the output of each benchmark's last run
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Show build history (durations, cache hits, failures) recorded in .oximake/
+    Stats {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Number of recent builds to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        last: usize,
+    },
+
+    /// Explain why a target and its dependencies would be rebuilt (like ninja -d explain)
+    Explain {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Targets to explain (default: all)
+        targets: Vec<String>,
+
+        /// Explain a build with a [presets] entry of build.toml (its outputs and defines)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+    },
+
+    /// List the dependencies of targets (--tree: as an indented tree)
+    Deps {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Target to show (default: all)
+        target: Option<String>,
+
+        /// Print an indented tree; subtrees printed before are folded to "name (*)"
+        #[arg(long)]
+        tree: bool,
+
+        /// Show the targets depending on the target instead of its dependencies
+        #[arg(long)]
+        invert: bool,
+    },
+
+    /// Print the resolved build as JSON (targets, sources, flags, reverse deps) for scripts and editors
+    Query {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        #[command(subcommand)]
+        query: Query,
+    },
+
+    /// Keep the project resident and serve build/clean/query requests (JSON-RPC over a unix socket)
+    Daemon {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Socket path (default: .oximake/daemon.sock next to the root build.toml)
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+
+    /// Rewrite build.toml and included files in the canonical layout (comments are kept)
+    Fmt {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Only check: list files that are not formatted and exit with 1
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Report configuration problems, repeated list entries and absolute paths
+    Lint {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Remove repeated entries and make absolute paths inside the project relative
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Add a prebuilt ngmake package to [dependencies] and optionally link its libraries into a target
+    Add {
+        /// Package name (its [project] name); targets use its libraries as <name>::<library>
+        name: String,
+
+        /// Unpacked package directory or install prefix, relative to the build.toml
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["archive", "url"], required_unless_present_any = ["archive", "url"])]
+        path: Option<String>,
+
+        /// Package archive (.tar.gz or .zip), relative to the build.toml
+        #[arg(long, value_name = "FILE", conflicts_with = "url")]
+        archive: Option<String>,
+
+        /// URL of a package archive (downloaded with curl, pinned in oximake.lock)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Expected SHA-256 of the archive (--archive or --url)
+        #[arg(long, value_name = "HEX", conflicts_with = "path")]
+        sha256: Option<String>,
+
+        /// Add the package's libraries to the deps of this target
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// With --target: only these libraries of the package (default: all)
+        #[arg(long = "lib", value_name = "LIBRARY")]
+        libs: Vec<String>,
+
+        /// Root configuration file path
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+    },
+
+    /// Bundle built executables, libraries, headers and license files into a versioned archive or deb/rpm package
+    Package {
+        /// Configuration file path
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Archive format
+        #[arg(long, value_enum, default_value_t = PackageFormat::TarGz)]
+        format: PackageFormat,
+
+        /// Directory for the archive (default: dist/ next to build.toml)
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+
+        /// Package the outputs of a build with this [presets] entry (ngm build --preset)
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+    },
+
+    /// Write compile_commands.json (clangd, clang-tidy, IDEs) with the exact compile commands
+    Compdb {
+        /// Configuration file path (default: build.toml)
+        #[arg(short, long, default_value = "build.toml")]
+        config: PathBuf,
+
+        /// Output file (default: compile_commands.json next to the root build.toml)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Print the JSON Schema of build.toml (for editor completion/validation, e.g. taplo)
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
     /// Convert CMakeLists.txt to build.toml
     Convert {
         /// Path to CMakeLists.txt file
@@ -86,8 +516,9 @@ pub enum Command {
     },
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(name = "ngm")]
+#[command(group(clap::ArgGroup::new("selection").args(["targets", "target_regex", "tags"]).multiple(true)))]
 #[command(about = "ngmake - Modern C++ build tool with TOML configuration (DAG, parallel build)", long_about = None)]
 pub struct BuildOptions {
     #[command(subcommand)]
@@ -98,17 +529,37 @@ pub struct BuildOptions {
     #[arg(short, long, value_name = "FILE", default_value = "build.toml")]
     pub config: PathBuf,
 
-    /// Build only the specified targets (and their dependencies)
+    /// Build only the specified targets (and their dependencies); glob patterns such as 'test_*' select every matching target
     #[arg(short = 't', long = "target", value_name = "TARGET", num_args = 1..)]
     pub targets: Option<Vec<String>>,
 
+    /// Also build the targets whose name matches this regex (e.g. '^test_.*_io$')
+    #[arg(long, value_name = "REGEX")]
+    pub target_regex: Option<String>,
+
+    /// Also build the targets with this tag (`tags = [...]` in build.toml; repeatable)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Leave out targets with this tag unless another selected target depends on them (repeatable)
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    pub exclude_tags: Vec<String>,
+
+    /// Also build every target that depends on the selected targets (what a change to them affects)
+    #[arg(long, requires = "selection")]
+    pub with_dependents: bool,
+
     /// Clean output directories before building
     #[arg(long)]
     pub clean: bool,
 
-    /// Verbose output (compiler commands etc.)
-    #[arg(short, long)]
-    pub verbose: bool,
+    /// Remove this target's objects and output first, so it is rebuilt from scratch (repeatable)
+    #[arg(long, value_name = "TARGET")]
+    pub force_rebuild: Vec<String>,
+
+    /// Verbose output: -v target progress, -vv compiler command lines, -vvv environment and cache details
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
 
     /// Quiet mode: only errors and summary
     #[arg(short, long)]
@@ -121,18 +572,103 @@ pub struct BuildOptions {
     /// Maximum number of targets to build in parallel (default: unlimited per level)
     #[arg(short = 'j', long = "jobs", value_name = "N")]
     pub jobs: Option<usize>,
-    /// Ignore errors and continue building remaining targets (like make -i)
+    /// Keep going after failures: same as --keep-going=0 (like make -i)
     #[arg(short = 'i', long = "ignore-errors")]
     pub ignore_errors: bool,
+
+    /// Stop starting new jobs after N failed jobs (0 or no value: never; default: 1)
+    #[arg(short = 'k', long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+    pub keep_going: Option<usize>,
+
+    /// Write a Chrome trace (chrome://tracing / Perfetto) of all compile and link jobs
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+
+    /// Print per-target/per-file durations, parallelism efficiency and the critical path
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Write the timing report as JSON
+    #[arg(long, value_name = "FILE")]
+    pub timings_json: Option<PathBuf>,
+
+    /// Write a build report (results, durations, warnings, cache statistics, dependency graph): HTML, or JSON for a .json file
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// Output format: text or json (JSON lines: job_started, job_finished, diagnostic, build_finished)
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Color output: auto (terminal without NO_COLOR), always or never
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Progress line template (default: $OXIMAKE_STATUS); tokens {done} {total} {percent} {elapsed} {eta} {target} {files}
+    #[arg(long, value_name = "TEMPLATE")]
+    pub status_format: Option<String>,
+
+    /// Set a build.toml variable (overrides [variables]; repeatable)
+    #[arg(long = "set", value_name = "NAME=VALUE")]
+    pub set: Vec<String>,
+
+    /// Build with a [presets] entry of build.toml (targets, jobs, defines; -t and -j override it)
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Use the given build.toml as the root, even if a parent build.toml includes it
+    #[arg(long)]
+    pub no_workspace: bool,
+
+    /// Fail instead of updating oximake.lock (reproducible CI builds)
+    #[arg(long)]
+    pub locked: bool,
+
+    /// No network access: no downloads, remote cache, build hosts or distributing launchers
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Memory available to concurrent links in MiB (overrides [project] link_memory_limit)
+    #[arg(long, value_name = "MIB")]
+    pub link_memory_limit: Option<u64>,
+
+    /// Maximum number of concurrent links (overrides [project] link_jobs; default: -j)
+    #[arg(long, value_name = "N")]
+    pub link_jobs: Option<usize>,
 }
 
 impl BuildOptions {
     /// If both verbose and quiet are set, quiet takes precedence
     pub fn show_verbose_output(&self) -> bool {
-        self.verbose && !self.quiet
+        self.verbosity() > 0
     }
 
+    /// Verbosity level: 0 normal (or quiet), 1 target progress, 2 compiler command
+    /// lines, 3 environment, cache decisions and depfile details
+    pub fn verbosity(&self) -> u8 {
+        if self.show_quiet_output() {
+            0
+        } else {
+            self.verbose
+        }
+    }
+
+    /// JSON log format implies quiet: stdout carries only events
     pub fn show_quiet_output(&self) -> bool {
-        self.quiet
+        self.quiet || self.json_events()
+    }
+
+    pub fn json_events(&self) -> bool {
+        self.log_format == LogFormat::Json
+    }
+
+    /// Failed jobs after which no new jobs start; None = keep going until everything
+    /// buildable is built. Targets depending on a failed target are never started.
+    pub fn failure_limit(&self) -> Option<usize> {
+        match (self.ignore_errors, self.keep_going) {
+            (true, _) | (false, Some(0)) => None,
+            (false, Some(n)) => Some(n),
+            (false, None) => Some(1),
+        }
     }
 }
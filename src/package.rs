@@ -0,0 +1,282 @@
+// ============================================================================
+// package.rs — `ngm package`: versioned archive of the build outputs
+// ============================================================================
+//
+// Bundles what a user of the project needs into
+// <name>-<version>-<os>-<arch>.tar.gz (or .zip), named from [project]:
+//
+//   <name>-<version>-<os>-<arch>/
+//     bin/       executables (and Windows DLLs)
+//     lib/       static and shared libraries (and their pkg-config files in
+//                lib/pkgconfig/, see pkg_config.rs; CMake package files in
+//                lib/cmake/<name>/, see cmake_export.rs; the library
+//                manifest lib/ngmake/<name>.toml, see prebuilt.rs)
+//     include/   [package] headers: directories keep their layout below
+//                the directory, files and glob matches go in as is
+//     LICENSE    [package] license_files, at the top
+//
+// With [package] debug_info = true, the split debug info of each target
+// (compiler::debug_info_path) goes next to its output.
+//
+// The targets must have been built (`ngm build`); missing outputs are an
+// error. The archive is written with the system `tar` / `zip` programs;
+// deb and rpm packages are built by native_package.rs.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget, TargetType};
+use crate::options::PackageFormat;
+
+/// The `[package]` table of the root build.toml.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct PackageConfig {
+    /// Header directories, files or globs for include/, relative to the root build.toml
+    #[serde(default)]
+    pub headers: Vec<String>,
+    /// License and notice files for the top of the archive
+    #[serde(default)]
+    pub license_files: Vec<String>,
+    /// Targets to package (default: every executable and library)
+    #[serde(default)]
+    pub targets: Option<Vec<String>>,
+    /// Install prefix of deb/rpm packages (default: /usr)
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Package maintainer, "Name <email>" (required for deb)
+    #[serde(default)]
+    pub maintainer: Option<String>,
+    /// One-line summary, optionally followed by a longer description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// License name for rpm (e.g. "MIT")
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// Also package the split debug info of the targets (`split_debug`: .debug, .dSYM or .pdb)
+    #[serde(default)]
+    pub debug_info: bool,
+    /// deb package dependencies (e.g. "libssl3 (>= 3.0)")
+    #[serde(default)]
+    pub depends: Vec<String>,
+    /// rpm package dependencies (e.g. "openssl-libs")
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+/// `<name>-<version>-<os>-<arch>`
+pub fn archive_stem(project: &ResolvedProject) -> String {
+    format!("{}-{}-{}-{}", project.name, project.version, std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Whether `path` is a thin static archive (`thin_archive = true`), which only references its objects.
+fn is_thin_archive(path: &Path) -> bool {
+    let mut magic = [0u8; 8];
+    std::fs::File::open(path).and_then(|mut f| std::io::Read::read_exact(&mut f, &mut magic)).is_ok() && &magic == b"!<thin>\n"
+}
+
+/// Copies the package contents into `dir` (created; replaced if it exists).
+/// Returns the packaged files relative to `dir`, sorted.
+pub fn stage(project: &ResolvedProject, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let files = contents(project)?;
+    copy_files(&files, dir)
+}
+
+/// Every packaged file: (source, path in the archive layout).
+pub fn contents(project: &ResolvedProject) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let config = project.package.clone().unwrap_or_default();
+    let mut files: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    let mut names: Vec<String> = match &config.targets {
+        Some(names) => names.clone(),
+        None => project.targets.values().filter(|t| !t.prebuilt).map(|t| t.name.clone()).collect(),
+    };
+    names.sort();
+    let mut missing = Vec::new();
+    for name in &names {
+        let target = project.targets.get(name).ok_or_else(|| format!("[package] targets: unknown target '{}'", name))?;
+        let Some(packaged) = packaged_path(target) else { continue };
+        let output = compiler::output_path(target);
+        if !output.is_file() {
+            missing.push(format!("  target '{}': {}", name, output.display()));
+            continue;
+        }
+        if is_thin_archive(&output) {
+            return Err(format!(
+                "target '{}': {} is a thin archive (only usable in this build tree); set thin_archive = false and rebuild it to package it",
+                name,
+                output.display()
+            ));
+        }
+        if let Some(debug) = compiler::debug_info_path(target).filter(|_| config.debug_info) {
+            if !debug.exists() {
+                missing.push(format!("  target '{}': {}", name, debug.display()));
+                continue;
+            }
+            let packaged_debug = packaged.with_file_name(debug.file_name().unwrap_or_default());
+            if debug.is_dir() {
+                for file in walk(&debug) {
+                    let relative = file.strip_prefix(&debug).unwrap_or(&file).to_path_buf();
+                    files.push((file, packaged_debug.join(relative)));
+                }
+            } else {
+                files.push((debug, packaged_debug));
+            }
+        }
+        files.push((output, packaged));
+    }
+    if !missing.is_empty() {
+        return Err(format!("{} output(s) not built (run `ngm build` first):\n{}", missing.len(), missing.join("\n")));
+    }
+    let pc_dir = crate::history::state_dir(&project.root_dir).join("pkgconfig");
+    for target in crate::pkg_config::pc_targets(project, &names) {
+        let pc = crate::pkg_config::write_installed(project, target, &pc_dir)?;
+        files.push((pc, Path::new("lib/pkgconfig").join(format!("{}.pc", target.name))));
+    }
+    let cmake_dir = crate::history::state_dir(&project.root_dir).join("cmake");
+    files.extend(crate::cmake_export::write_installed(project, &names, &cmake_dir)?);
+    let manifest_dir = crate::history::state_dir(&project.root_dir).join("ngmake");
+    files.extend(crate::prebuilt::write_manifest(project, &names, &manifest_dir)?);
+
+    for pattern in &config.headers {
+        let path = project.root_dir.join(pattern);
+        if path.is_dir() {
+            for file in walk(&path) {
+                let relative = file.strip_prefix(&path).unwrap_or(&file).to_path_buf();
+                files.push((file, Path::new("include").join(relative)));
+            }
+        } else {
+            for file in matches(&path, "headers", pattern)? {
+                files.push((file.clone(), Path::new("include").join(file.file_name().unwrap_or_default())));
+            }
+        }
+    }
+    for pattern in &config.license_files {
+        for file in matches(&project.root_dir.join(pattern), "license_files", pattern)? {
+            files.push((file.clone(), PathBuf::from(file.file_name().unwrap_or_default())));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Path of the target's output in the archive layout (None: nothing to package).
+pub fn packaged_path(target: &ResolvedTarget) -> Option<PathBuf> {
+    let output = compiler::output_path(target);
+    let subdir = match target.target_type {
+        TargetType::Executable => "bin",
+        TargetType::SharedLib if output.extension().is_some_and(|e| e == "dll") => "bin",
+        TargetType::StaticLib | TargetType::SharedLib => "lib",
+        TargetType::Interface | TargetType::Custom | TargetType::Group | TargetType::Benchmark | TargetType::Test => return None,
+    };
+    Some(Path::new(subdir).join(output.file_name().unwrap_or_default()))
+}
+
+/// Copies `(source, relative)` files below `dir` (replaced if it exists).
+/// Returns the relative paths, sorted.
+pub fn copy_files(files: &[(PathBuf, PathBuf)], dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let _ = std::fs::remove_dir_all(dir);
+    for (source, relative) in files {
+        let dest = dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        std::fs::copy(source, &dest).map_err(|e| format!("Failed to copy '{}': {}", source.display(), e))?;
+    }
+    let mut packaged: Vec<PathBuf> = files.iter().map(|(_, relative)| relative.clone()).collect();
+    packaged.sort();
+    packaged.dedup();
+    Ok(packaged)
+}
+
+/// Files matching a path or glob; none is an error.
+fn matches(pattern: &Path, key: &str, written: &str) -> Result<Vec<PathBuf>, String> {
+    let entries = glob::glob(&pattern.to_string_lossy()).map_err(|e| format!("[package] {}: invalid pattern '{}': {}", key, written, e))?;
+    let files: Vec<PathBuf> = entries.flatten().filter(|p| p.is_file()).collect();
+    if files.is_empty() {
+        return Err(format!("[package] {}: '{}' matches no file", key, written));
+    }
+    Ok(files)
+}
+
+fn walk(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk(&path));
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Stages the package and writes the archive into `out_dir`. Returns the archive path
+/// and the number of packaged files.
+pub fn create_archive(project: &ResolvedProject, format: PackageFormat, out_dir: &Path) -> Result<(PathBuf, usize), String> {
+    if matches!(format, PackageFormat::Deb | PackageFormat::Rpm) {
+        return crate::native_package::create(project, format, out_dir);
+    }
+    let stem = archive_stem(project);
+    let staging_root = crate::history::state_dir(&project.root_dir).join("package");
+    let files = stage(project, &staging_root.join(&stem))?;
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create '{}': {}", out_dir.display(), e))?;
+    let out_dir = out_dir.canonicalize().map_err(|e| format!("Failed to resolve '{}': {}", out_dir.display(), e))?;
+    let archive = out_dir.join(format!("{}.{}", stem, format.extension()));
+    let _ = std::fs::remove_file(&archive);
+    let (program, args): (&str, Vec<String>) = match format {
+        PackageFormat::TarGz => ("tar", vec!["-czf".into(), archive.to_string_lossy().to_string(), stem.clone()]),
+        PackageFormat::Zip => ("zip", vec!["-qr".into(), archive.to_string_lossy().to_string(), stem.clone()]),
+        PackageFormat::Deb | PackageFormat::Rpm => unreachable!("built by native_package"),
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(&staging_root)
+        .output()
+        .map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+    let _ = std::fs::remove_dir_all(&staging_root);
+    if !output.status.success() {
+        return Err(format!("'{}' failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok((archive, files.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::ResolvedTarget;
+
+    #[test]
+    fn test_stages_outputs_headers_and_licenses() {
+        let dir = TempDir::new("package");
+        for file in ["build/app", "build/libutil.a", "include/util/util.h", "LICENSE"] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+        let mut project = ResolvedProject {
+            name: "demo".to_string(),
+            root_dir: dir.to_path_buf(),
+            package: Some(PackageConfig { headers: vec!["include".into()], license_files: vec!["LICENSE".into()], ..Default::default() }),
+            ..Default::default()
+        };
+        for (name, target_type) in [("app", TargetType::Executable), ("util", TargetType::StaticLib), ("headers", TargetType::Interface)] {
+            let target = ResolvedTarget { name: name.to_string(), target_type, output_dir: dir.join("build"), ..Default::default() };
+            project.targets.insert(name.to_string(), target);
+        }
+        let files = stage(&project, &dir.join("stage")).unwrap();
+        let expected: Vec<PathBuf> = ["LICENSE", "bin/app", "include/util/util.h", "lib/libutil.a", "lib/ngmake/demo.toml"].iter().map(PathBuf::from).collect();
+        assert_eq!(files, expected);
+        assert_eq!(std::fs::read_to_string(dir.join("stage/bin/app")).unwrap(), "build/app");
+
+        std::fs::remove_file(dir.join("build/app")).unwrap();
+        assert!(stage(&project, &dir.join("stage")).unwrap_err().contains("target 'app'"));
+    }
+}
@@ -0,0 +1,140 @@
+// ============================================================================
+// pkg_config.rs — pkg-config files for library targets (`pkg_config = true`)
+// ============================================================================
+//
+// Lets projects that don't use ngmake consume its libraries with
+// `pkg-config --cflags --libs <name>`. Two variants are written:
+//
+//   build tree   <output_dir>/pkgconfig/<name>.pc after every successful
+//                build, with absolute include and library paths; point
+//                PKG_CONFIG_PATH at that directory
+//   installed    lib/pkgconfig/<name>.pc in `ngm package` archives and
+//                deb/rpm packages, relative to the .pc file (${pcfiledir}),
+//                so the package can be unpacked anywhere
+//
+// Cflags are the target's include directories (in packages: include/);
+// Libs are the library itself, its library deps and its `libs`.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget, TargetType};
+
+/// Targets among `names` that get a .pc file: libraries with `pkg_config = true`.
+pub fn pc_targets<'a>(project: &'a ResolvedProject, names: &[String]) -> Vec<&'a ResolvedTarget> {
+    names
+        .iter()
+        .filter_map(|name| project.targets.get(name))
+        .filter(|t| t.pkg_config && matches!(t.target_type, TargetType::StaticLib | TargetType::SharedLib))
+        .collect()
+}
+
+/// `-lname` for a lib<name>.a/.so/.dll output, `-l:file` for any other file name.
+fn link_flag(target: &ResolvedTarget) -> String {
+    let output = compiler::output_path(target);
+    let file = output.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stem = file.strip_prefix("lib").and_then(|rest| [".a", ".so", ".dll"].iter().find_map(|ext| rest.strip_suffix(ext)));
+    match stem {
+        Some(name) => format!("-l{}", name),
+        None => format!("-l:{}", file),
+    }
+}
+
+/// Contents of the .pc file of `target`; `installed` selects the package variant.
+pub fn pc_file(project: &ResolvedProject, target: &ResolvedTarget, installed: bool) -> String {
+    let mut out = String::new();
+    let cflags: Vec<String>;
+    let mut libs: Vec<String> = Vec::new();
+    if installed {
+        out.push_str("prefix=${pcfiledir}/../..\nincludedir=${prefix}/include\nlibdir=${prefix}/lib\n\n");
+        cflags = vec!["-I${includedir}".to_string()];
+        libs.push("-L${libdir}".to_string());
+    } else {
+        let libdir = std::path::absolute(&target.output_dir).unwrap_or_else(|_| target.output_dir.clone());
+        out.push_str(&format!("libdir={}\n\n", libdir.display()));
+        cflags = target.include_dirs.iter().map(|d| format!("-I{}", std::path::absolute(d).unwrap_or_else(|_| d.clone()).display())).collect();
+        libs.push("-L${libdir}".to_string());
+        libs.extend(target.lib_dirs.iter().map(|d| format!("-L{}", d.display())));
+    }
+    libs.push(link_flag(target));
+    for dep in target.deps.iter().filter_map(|d| project.targets.get(d)) {
+        if matches!(dep.target_type, TargetType::StaticLib | TargetType::SharedLib) {
+            if !installed && dep.output_dir != target.output_dir {
+                libs.push(format!("-L{}", std::path::absolute(&dep.output_dir).unwrap_or_else(|_| dep.output_dir.clone()).display()));
+            }
+            libs.push(link_flag(dep));
+        }
+    }
+    libs.extend(target.libs.iter().map(|l| format!("-l{}", l)));
+    out.push_str(&format!(
+        "Name: {}\nDescription: {} library of {}\nVersion: {}\nCflags: {}\nLibs: {}\n",
+        target.name,
+        target.name,
+        project.name,
+        project.version,
+        cflags.join(" "),
+        libs.join(" ")
+    ));
+    out
+}
+
+/// Writes the build-tree .pc files of the built `targets`. Returns the files written.
+pub fn write_build_tree(project: &ResolvedProject, targets: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+    for target in pc_targets(project, targets) {
+        let path = target.output_dir.join("pkgconfig").join(format!("{}.pc", target.name));
+        write(&path, &pc_file(project, target, false))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Writes the installed .pc file of `target` into `dir` and returns its path.
+pub fn write_installed(project: &ResolvedProject, target: &ResolvedTarget, dir: &Path) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.pc", target.name));
+    write(&path, &pc_file(project, target, true))?;
+    Ok(path)
+}
+
+fn write(path: &Path, text: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+    }
+    std::fs::write(path, text).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pc_files_list_includes_and_libraries() {
+        let mut project = ResolvedProject { name: "demo".to_string(), version: "1.2.3".to_string(), ..Default::default() };
+        let base = ResolvedTarget {
+            name: "base".to_string(),
+            target_type: TargetType::StaticLib,
+            output_dir: PathBuf::from("/w/build"),
+            ..Default::default()
+        };
+        let net = ResolvedTarget {
+            name: "net".to_string(),
+            target_type: TargetType::SharedLib,
+            include_dirs: vec![PathBuf::from("/w/net/include")],
+            libs: vec!["ssl".to_string()],
+            deps: vec!["base".to_string()],
+            output_dir: PathBuf::from("/w/build"),
+            pkg_config: true,
+            ..Default::default()
+        };
+        project.targets.insert("base".to_string(), base);
+        project.targets.insert("net".to_string(), net);
+        let net = &project.targets["net"];
+        assert_eq!(
+            pc_file(&project, net, false),
+            "libdir=/w/build\n\nName: net\nDescription: net library of demo\nVersion: 1.2.3\nCflags: -I/w/net/include\nLibs: -L${libdir} -lnet -lbase -lssl\n"
+        );
+        assert!(pc_file(&project, net, true).ends_with("Cflags: -I${includedir}\nLibs: -L${libdir} -lnet -lbase -lssl\n"));
+        assert_eq!(pc_targets(&project, &["base".to_string(), "net".to_string()]).len(), 1);
+    }
+}
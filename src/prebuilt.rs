@@ -0,0 +1,354 @@
+// ============================================================================
+// prebuilt.rs — `[dependencies]`: libraries of other ngmake packages
+// ============================================================================
+//
+// A project can link the libraries of another ngmake project without
+// building its sources. The other project runs `ngm package`, which writes
+// a manifest of its libraries next to them:
+//
+//   lib/ngmake/<name>.toml   name, version and one [[library]] per packaged
+//                            library: type, file (relative to the package
+//                            root), library deps and libs
+//
+// The consuming root build.toml names the package by its [project] name and
+// points at an unpacked package (or install prefix), at the archive or at a
+// URL to download it from:
+//
+//   [dependencies]
+//   core = { path = "../core/dist/core-1.2.0-linux-x86_64" }
+//   net = { archive = "vendor/net-2.0.1-linux-x86_64.tar.gz" }
+//   log = { url = "https://example.com/log-0.3.0-linux-x86_64.tar.gz" }
+//
+// URLs are downloaded once (curl) into .oximake/deps/downloads/<name>/.
+// Before an archive is unpacked its SHA-256 must match the entry's `sha256`
+// or, for downloads without one, the checksum oximake.lock recorded on the
+// first fetch (see lock.rs); a mismatching download is deleted. Archives are
+// unpacked into .oximake/deps/<name>/ (again when the archive changes). Every library becomes
+// a prebuilt target `<name>::<library>`: dependents link its file and get
+// the package's include/ directory and its libs, but it is never compiled,
+// cleaned or packaged.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ResolvedProject, ResolvedTarget, TargetType};
+use crate::lock::LockedPackage;
+
+/// Directory of the manifests in a package, relative to its root.
+pub const MANIFEST_DIR: &str = "lib/ngmake";
+
+/// One entry of the `[dependencies]` table: exactly one of `path`, `archive` and `url`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct DependencyConfig {
+    /// Unpacked package or install prefix, relative to the root build.toml
+    #[serde(default)]
+    pub path: Option<String>,
+    /// `ngm package` archive (.tar.gz or .zip), relative to the root build.toml
+    #[serde(default)]
+    pub archive: Option<String>,
+    /// URL of an `ngm package` archive, downloaded with curl
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Expected SHA-256 of the archive (default: the one oximake.lock recorded)
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// The targets of a dependency and, for archives, its oximake.lock entry.
+#[derive(Debug, Clone, Default)]
+pub struct Imported {
+    pub targets: Vec<ResolvedTarget>,
+    pub locked: Option<LockedPackage>,
+}
+
+/// `lib/ngmake/<name>.toml`: the libraries of a package.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default, rename = "library")]
+    pub libraries: Vec<ManifestLibrary>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ManifestLibrary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub target_type: TargetType,
+    /// Library file relative to the package root (e.g. "lib/libnet.a")
+    pub file: String,
+    /// Libraries of the same package this one links
+    #[serde(default)]
+    pub deps: Vec<String>,
+    /// System libraries (-l) of the library and its deps
+    #[serde(default)]
+    pub libs: Vec<String>,
+}
+
+/// Manifest of the packaged libraries among `names` (prebuilt targets excluded).
+pub fn manifest(project: &ResolvedProject, names: &[String]) -> Manifest {
+    let packaged: Vec<&ResolvedTarget> = names
+        .iter()
+        .filter_map(|name| project.targets.get(name))
+        .filter(|t| !t.prebuilt && matches!(t.target_type, TargetType::StaticLib | TargetType::SharedLib))
+        .collect();
+    let libraries = packaged
+        .iter()
+        .filter_map(|target| {
+            let file = crate::package::packaged_path(target)?;
+            Some(ManifestLibrary {
+                name: target.name.clone(),
+                target_type: target.target_type.clone(),
+                file: file.to_string_lossy().replace('\\', "/"),
+                deps: target.deps.iter().filter(|d| packaged.iter().any(|t| &&t.name == d)).cloned().collect(),
+                libs: target.libs.clone(),
+            })
+        })
+        .collect();
+    Manifest { name: project.name.clone(), version: project.version.clone(), libraries }
+}
+
+/// Writes the manifest of the libraries among `names` into `dir`. Returns (file, path in
+/// the archive layout), or None when no library is packaged.
+pub fn write_manifest(project: &ResolvedProject, names: &[String], dir: &Path) -> Result<Option<(PathBuf, PathBuf)>, String> {
+    let manifest = manifest(project, names);
+    if manifest.libraries.is_empty() {
+        return Ok(None);
+    }
+    let text = toml::to_string(&manifest).map_err(|e| format!("Failed to write package manifest: {}", e))?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    let file_name = format!("{}.toml", project.name);
+    let path = dir.join(&file_name);
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(Some((path, Path::new(MANIFEST_DIR).join(file_name))))
+}
+
+/// The prebuilt targets of dependency `name`; `base_dir` is the directory of the root
+/// build.toml (archives are unpacked below its .oximake/).
+pub fn import(name: &str, dependency: &DependencyConfig, base_dir: &Path) -> Result<Imported, String> {
+    let (root, sha256) = package_root(name, dependency, base_dir)?;
+    let manifest_path = root.join(MANIFEST_DIR).join(format!("{}.toml", name));
+    let text = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("[dependencies] {}: failed to read '{}': {} (is it an `ngm package` of project '{}'?)", name, manifest_path.display(), e, name))?;
+    let manifest: Manifest = toml::from_str(&text).map_err(|e| format!("[dependencies] {}: invalid '{}': {}", name, manifest_path.display(), e))?;
+    let locked = match (sha256, dependency.source()) {
+        (Some(sha256), Some(source)) => Some(LockedPackage { name: name.to_string(), source, version: manifest.version.clone(), sha256 }),
+        _ => None,
+    };
+    Ok(Imported { targets: targets(name, &manifest, &root), locked })
+}
+
+impl DependencyConfig {
+    /// oximake.lock source of archive dependencies: "archive+<path>" or "url+<url>".
+    pub fn source(&self) -> Option<String> {
+        match (&self.archive, &self.url) {
+            (Some(archive), _) => Some(format!("archive+{}", archive)),
+            (None, Some(url)) => Some(format!("url+{}", url)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Prebuilt targets `<name>::<library>` for the libraries of `manifest`, unpacked at `root`.
+pub fn targets(name: &str, manifest: &Manifest, root: &Path) -> Vec<ResolvedTarget> {
+    let include_dir = root.join("include");
+    manifest
+        .libraries
+        .iter()
+        .map(|library| {
+            let file = root.join(&library.file);
+            ResolvedTarget {
+                name: format!("{}::{}", name, library.name),
+                target_type: library.target_type.clone(),
+                include_dirs: if include_dir.is_dir() { vec![include_dir.clone()] } else { Vec::new() },
+                libs: library.libs.clone(),
+                deps: library.deps.iter().map(|d| format!("{}::{}", name, d)).collect(),
+                output_dir: file.parent().unwrap_or(root).to_path_buf(),
+                output_name: file.file_name().map(|f| f.to_string_lossy().to_string()),
+                lib_prefix: Some(String::new()),
+                lib_suffix: Some(String::new()),
+                prebuilt: true,
+                working_dir: root.to_path_buf(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Directory holding the package (`path`, or the unpacked archive) and the verified
+/// SHA-256 of the archive.
+fn package_root(name: &str, dependency: &DependencyConfig, base_dir: &Path) -> Result<(PathBuf, Option<String>), String> {
+    let deps_dir = crate::history::state_dir(base_dir).join("deps");
+    let archive = match (&dependency.path, &dependency.archive, &dependency.url) {
+        (Some(path), None, None) => {
+            let root = base_dir.join(path);
+            if !root.is_dir() {
+                return Err(format!("[dependencies] {}: directory not found: {}", name, root.display()));
+            }
+            return Ok((root, None));
+        }
+        (None, Some(archive), None) => base_dir.join(archive),
+        (None, None, Some(url)) => download(url, &download_dir(name, base_dir)).map_err(|e| format!("[dependencies] {}: {}", name, e))?,
+        _ => return Err(format!("[dependencies] {}: set exactly one of 'path', 'archive' and 'url'", name)),
+    };
+    let sha256 = verify(name, dependency, &archive, base_dir)?;
+    let dest = deps_dir.join(name);
+    unpack(&archive, &dest).map_err(|e| format!("[dependencies] {}: {}", name, e))?;
+    // The archive holds a single <name>-<version>-<os>-<arch>/ directory
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(&dest).into_iter().flatten().flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    let root = if dirs.len() == 1 { dirs.remove(0) } else { dest };
+    Ok((root, Some(sha256)))
+}
+
+/// SHA-256 of the archive, checked before it is unpacked against the `sha256` of the
+/// entry, and for downloads against the one oximake.lock recorded on the first fetch.
+/// A download that doesn't match is deleted.
+fn verify(name: &str, dependency: &DependencyConfig, archive: &Path, base_dir: &Path) -> Result<String, String> {
+    let actual = crate::sha256::file_hex(archive).map_err(|e| format!("[dependencies] {}: {}", name, e))?;
+    let (expected, origin) = match (&dependency.sha256, &dependency.url) {
+        (Some(sha256), _) => (Some(sha256.to_ascii_lowercase()), "sha256 in build.toml"),
+        (None, Some(_)) => {
+            let source = dependency.source().unwrap_or_default();
+            let lock = crate::lock::read(base_dir)?.unwrap_or_default();
+            (lock.packages.into_iter().find(|p| p.name == name && p.source == source).map(|p| p.sha256), crate::lock::LOCK_FILE)
+        }
+        (None, None) => (None, ""),
+    };
+    match expected {
+        Some(expected) if expected != actual => {
+            if dependency.url.is_some() {
+                let _ = std::fs::remove_dir_all(download_dir(name, base_dir));
+            }
+            let hint = if dependency.sha256.is_none() { " (remove its oximake.lock entry to accept a new archive)" } else { "" };
+            Err(format!(
+                "[dependencies] {}: checksum mismatch for {}: expected {} ({}), got {}; refusing to use it{}",
+                name,
+                dependency.url.as_deref().unwrap_or_else(|| dependency.archive.as_deref().unwrap_or_default()),
+                expected,
+                origin,
+                actual,
+                hint
+            ))
+        }
+        _ => Ok(actual),
+    }
+}
+
+fn download_dir(name: &str, base_dir: &Path) -> PathBuf {
+    crate::history::state_dir(base_dir).join("deps").join("downloads").join(name)
+}
+
+fn download_file_name(url: &str) -> &str {
+    url.rsplit('/').next().filter(|f| !f.is_empty()).unwrap_or("package.tar.gz")
+}
+
+/// True if dependency `name` was downloaded from `url` before.
+pub fn downloaded(name: &str, url: &str, base_dir: &Path) -> bool {
+    let dir = download_dir(name, base_dir);
+    dir.join(download_file_name(url)).is_file() && std::fs::read_to_string(dir.join("url")).is_ok_and(|s| s == url)
+}
+
+/// Downloads `url` into `dir` unless it was downloaded there before. Returns the archive.
+fn download(url: &str, dir: &Path) -> Result<PathBuf, String> {
+    let file_name = download_file_name(url);
+    let archive = dir.join(file_name);
+    let source = dir.join("url");
+    if archive.is_file() && std::fs::read_to_string(&source).is_ok_and(|s| s == url) {
+        return Ok(archive);
+    }
+    if crate::offline::enabled() {
+        return Err(format!("{} is not downloaded and --offline was given", url));
+    }
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    let partial = dir.join(format!("{}.part", file_name));
+    let output = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(&partial)
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run 'curl': {}", e))?;
+    if !output.status.success() {
+        return Err(format!("download of {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    std::fs::rename(&partial, &archive).map_err(|e| format!("Failed to write '{}': {}", archive.display(), e))?;
+    std::fs::write(&source, url).map_err(|e| format!("Failed to write '{}': {}", source.display(), e))?;
+    Ok(archive)
+}
+
+/// Unpacks `archive` into `dest` unless it was unpacked there since it last changed.
+fn unpack(archive: &Path, dest: &Path) -> Result<(), String> {
+    let modified = std::fs::metadata(archive)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("archive not found: {}: {}", archive.display(), e))?;
+    let stamp = dest.with_extension("unpacked");
+    let unpacked = std::fs::metadata(&stamp).and_then(|m| m.modified()).is_ok_and(|t| t >= modified);
+    if unpacked && std::fs::read_to_string(&stamp).is_ok_and(|s| s == archive.to_string_lossy()) && dest.is_dir() {
+        return Ok(());
+    }
+    let _ = std::fs::remove_dir_all(dest);
+    std::fs::create_dir_all(dest).map_err(|e| format!("Failed to create '{}': {}", dest.display(), e))?;
+    let archive_arg = std::path::absolute(archive).unwrap_or_else(|_| archive.to_path_buf());
+    let (program, args): (&str, Vec<String>) = if archive.extension().is_some_and(|e| e == "zip") {
+        ("unzip", vec!["-q".into(), archive_arg.to_string_lossy().to_string()])
+    } else {
+        ("tar", vec!["-xzf".into(), archive_arg.to_string_lossy().to_string()])
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(dest)
+        .output()
+        .map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("'{}' failed on {}: {}", program, archive.display(), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    std::fs::write(&stamp, archive.to_string_lossy().as_bytes()).map_err(|e| format!("Failed to write '{}': {}", stamp.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::compiler;
+
+    #[test]
+    fn test_manifest_libraries_become_prebuilt_targets() {
+        let mut project = ResolvedProject { name: "core".to_string(), version: "1.2.0".to_string(), ..Default::default() };
+        for (name, deps) in [("base", vec![]), ("net", vec!["base".to_string()])] {
+            let target = ResolvedTarget {
+                name: name.to_string(),
+                target_type: TargetType::StaticLib,
+                deps,
+                libs: vec!["pthread".to_string()],
+                output_dir: PathBuf::from("/w/build"),
+                ..Default::default()
+            };
+            project.targets.insert(name.to_string(), target);
+        }
+        let manifest = manifest(&project, &["base".to_string(), "net".to_string()]);
+        let text = toml::to_string(&manifest).unwrap();
+        assert_eq!(toml::from_str::<Manifest>(&text).unwrap(), manifest);
+
+        let imported = targets("core", &manifest, Path::new("/deps/core"));
+        assert_eq!(DependencyConfig { url: Some("https://x/core.tar.gz".into()), ..Default::default() }.source().unwrap(), "url+https://x/core.tar.gz");
+        let net = imported.iter().find(|t| t.name == "core::net").unwrap();
+        assert!(net.prebuilt);
+        assert_eq!(net.deps, vec!["core::base".to_string()]);
+        assert_eq!(net.libs, vec!["pthread".to_string()]);
+        assert_eq!(compiler::output_path(net), PathBuf::from("/deps/core/lib/libnet.a"));
+    }
+
+    #[test]
+    fn test_archives_must_match_their_checksum() {
+        let dir = TempDir::new("prebuilt");
+        dir.write("core.tar.gz", "abc");
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let mut dependency = DependencyConfig { archive: Some("core.tar.gz".into()), sha256: Some(abc.to_uppercase()), ..Default::default() };
+        assert_eq!(verify("core", &dependency, &dir.join("core.tar.gz"), &dir).unwrap(), abc);
+        dependency.sha256 = Some("00".repeat(32));
+        assert!(verify("core", &dependency, &dir.join("core.tar.gz"), &dir).unwrap_err().contains("checksum mismatch"));
+    }
+}
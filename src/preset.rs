@@ -0,0 +1,131 @@
+// ============================================================================
+// preset.rs — `[presets]`: named build configurations (`ngm build --preset`)
+// ============================================================================
+//
+// A preset bundles the options a recurring build uses, so CI scripts and
+// the GUI select them by name instead of repeating flags:
+//
+//   [presets.ci]
+//   description = "Everything with assertions on"
+//   targets = ["app", "tests"]
+//   jobs = 8
+//   defines = ["ENABLE_ASSERTS"]
+//
+// Command-line options win: `-t` replaces the preset's targets and `-j`
+// its jobs. The preset's defines are added to every target (after the
+// target's own), like [project] defines. Presets are read from the root
+// build.toml only.
+//
+// Objects and outputs of a preset build go to `<output_dir>/<preset>/`, so
+// builds with different presets (e.g. a debug and a release preset) keep
+// their own objects instead of reusing ones compiled with other defines.
+// Custom targets (generators) keep their outputs: generated sources don't
+// depend on the preset and are shared.
+// ============================================================================
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ResolvedProject;
+use crate::options::BuildOptions;
+
+/// One `[presets.<name>]` entry.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Preset {
+    /// Shown next to the name in the GUI's preset list
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Targets to build (default: all)
+    #[serde(default)]
+    pub targets: Option<Vec<String>>,
+    /// Parallel jobs (default: auto)
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    /// Preprocessor definitions added to every target
+    #[serde(default)]
+    pub defines: Vec<String>,
+}
+
+/// The preset `name`, or an error listing the defined ones.
+pub fn find<'a>(presets: &'a BTreeMap<String, Preset>, name: &str) -> Result<&'a Preset, String> {
+    presets.get(name).ok_or_else(|| match presets.len() {
+        0 => format!("Unknown preset '{}': build.toml defines no [presets]", name),
+        _ => format!("Unknown preset '{}' (defined: {})", name, presets.keys().cloned().collect::<Vec<_>>().join(", ")),
+    })
+}
+
+/// Checks a preset name: it becomes a directory below every output_dir.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('.') || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+        return Err(format!("Invalid preset name '{}': use letters, digits, '-', '_' and '.'", name));
+    }
+    Ok(())
+}
+
+/// Applies the preset `name` to a parsed project and the build options (see the module comment).
+pub fn apply(project: &mut ResolvedProject, options: &mut BuildOptions, name: &str) -> Result<(), String> {
+    let preset = find(&project.presets, name)?.clone();
+    check_name(name)?;
+    if options.targets.is_none() {
+        options.targets = preset.targets;
+    }
+    if options.jobs.is_none() {
+        options.jobs = preset.jobs;
+    }
+    for target in project.targets.values_mut() {
+        if !target.prebuilt && target.target_type.compiles_sources() {
+            target.output_dir = target.output_dir.join(name);
+        }
+        for define in &preset.defines {
+            if !target.defines.contains(define) {
+                target.defines.push(define.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ResolvedTarget, TargetType};
+    use crate::test_util::TempDir;
+    use std::path::PathBuf;
+    use clap::Parser;
+
+    #[test]
+    fn test_command_line_wins_and_defines_are_added() {
+        let mut project = ResolvedProject::default();
+        let app = ResolvedTarget { name: "app".to_string(), defines: vec!["A".to_string()], output_dir: PathBuf::from("build"), ..Default::default() };
+        project.targets.insert("app".to_string(), app);
+        let gen = ResolvedTarget { name: "gen".to_string(), target_type: TargetType::Custom, output_dir: PathBuf::from("build"), ..Default::default() };
+        project.targets.insert("gen".to_string(), gen);
+        let preset = Preset { targets: Some(vec!["app".to_string()]), jobs: Some(8), defines: vec!["A".to_string(), "CI".to_string()], ..Default::default() };
+        project.presets.insert("ci".to_string(), preset);
+
+        let mut options = BuildOptions::parse_from(["ngm", "-j", "2"]);
+        apply(&mut project, &mut options, "ci").unwrap();
+        assert_eq!(options.targets, Some(vec!["app".to_string()]));
+        assert_eq!(options.jobs, Some(2));
+        assert_eq!(project.targets["app"].defines, vec!["A", "CI"]);
+        assert_eq!(project.targets["app"].output_dir, PathBuf::from("build/ci"));
+        assert_eq!(project.targets["gen"].output_dir, PathBuf::from("build"));
+        // ngm run --preset runs the preset's build of the executable
+        assert!(crate::run::executable_path(&project, "app").unwrap().starts_with("build/ci"));
+        assert_eq!(apply(&mut project, &mut options, "nightly").unwrap_err(), "Unknown preset 'nightly' (defined: ci)");
+    }
+
+    #[test]
+    fn test_invalid_preset_names_are_rejected_when_parsed() {
+        let dir = TempDir::new("preset_names");
+        dir.write("build.toml", "[project]\nname = \"p\"\n\n[presets.\"../release\"]\njobs = 4\n");
+        let err = crate::config::parse_build_file(&dir.join("build.toml"), false).unwrap_err();
+        assert!(err.starts_with("[presets] Invalid preset name '../release'"), "{}", err);
+
+        assert!(check_name("release-1.2_x").is_ok());
+        for name in ["", ".hidden", "a/b", "a b"] {
+            assert!(check_name(name).is_err(), "{:?}", name);
+        }
+    }
+}
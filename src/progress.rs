@@ -0,0 +1,296 @@
+// ============================================================================
+// progress.rs — Live progress line with ETA
+// ============================================================================
+//
+// Tracks compile/link job completions and estimates the remaining time from
+// the durations recorded by previous builds (see history::JobDurations).
+// Jobs never seen before are estimated with the average of the known ones.
+// On a terminal the line is redrawn in place (like ninja) whenever a job
+// starts or finishes, and every 100 ms for the elapsed time:
+//   [123/500] 42% 31.4s ~1m20s left, compiling foo.cpp, bar.cpp +2 more
+//
+// --status-format / $OXIMAKE_STATUS replace that layout (and the plain
+// per-target lines written when stdout is not a terminal) with a template:
+//   {done} {total} {percent} {elapsed} {eta} {target} {files}
+// e.g. OXIMAKE_STATUS="[{done}/{total}] {target}" for terse CI logs. In the
+// live line {target} and {files} are what is running right now; in plain
+// lines {target} is the target that just finished.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::builder::{JobKind, JobTiming};
+use crate::compiler;
+use crate::config::ResolvedProject;
+use crate::dag::BuildOrder;
+use crate::history::{self, JobDurations};
+
+/// Running files named in the status line; the rest are counted.
+const SHOWN_FILES: usize = 2;
+
+/// Tokens of a status format template, each written as `{name}`.
+pub const STATUS_TOKENS: &[&str] = &["done", "total", "percent", "elapsed", "eta", "target", "files"];
+
+/// Values substituted into a status format template.
+pub struct Status<'a> {
+    pub done: usize,
+    pub total: usize,
+    pub elapsed: Duration,
+    pub eta: Option<Duration>,
+    pub targets: &'a [&'a str],
+    pub files: &'a [&'a Path],
+}
+
+/// Estimated remaining work of a build, updated on every job completion.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    workers: usize,
+    /// Pending jobs → historical duration (None = never built before)
+    pending: HashMap<(JobKind, String), Option<Duration>>,
+    /// Sum and count of known durations, for estimating unknown jobs
+    known_sum: Duration,
+    known_count: u32,
+}
+
+impl Progress {
+    /// Collects all compile and link jobs of the targets in `order`. Sources whose
+    /// object file is already up-to-date are counted as free.
+    pub fn new(project: &ResolvedProject, order: &BuildOrder, history: &JobDurations, workers: usize) -> Progress {
+        let mut progress = Progress {
+            total: 0,
+            done: 0,
+            workers: workers.max(1),
+            pending: HashMap::new(),
+            known_sum: Duration::ZERO,
+            known_count: 0,
+        };
+        for name in order.levels.iter().flatten() {
+            let Some(target) = project.targets.get(name) else { continue };
+            for source in &target.sources {
+                let key = source.display().to_string();
+                let estimate = if compiler::object_up_to_date(source, &compiler::object_path(target, source)) {
+                    Some(Duration::ZERO)
+                } else {
+                    history.estimate(JobKind::Compile, &key).map(Duration::from_millis)
+                };
+                progress.add((JobKind::Compile, key), estimate);
+            }
+            let estimate = history.estimate(JobKind::Link, name).map(Duration::from_millis);
+            progress.add((JobKind::Link, name.clone()), estimate);
+        }
+        progress
+    }
+
+    fn add(&mut self, key: (JobKind, String), estimate: Option<Duration>) {
+        if let Some(d) = estimate.filter(|d| !d.is_zero()) {
+            self.known_sum += d;
+            self.known_count += 1;
+        }
+        self.total += 1;
+        self.pending.insert(key, estimate);
+    }
+
+    /// Marks a job finished. Its real duration refines the estimate for unknown jobs.
+    pub fn finish(&mut self, job: &JobTiming) {
+        self.pending.remove(&(job.kind, history::job_key(job)));
+        self.done = (self.done + 1).min(self.total);
+        if !job.up_to_date && !job.duration().is_zero() {
+            self.known_sum += job.duration();
+            self.known_count += 1;
+        }
+    }
+
+    pub fn percent(&self) -> usize {
+        (self.done * 100).checked_div(self.total).unwrap_or(100)
+    }
+
+    /// Remaining wall time assuming the pending work spreads over all workers.
+    /// None until at least one job duration is known.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.known_count == 0 || self.pending.is_empty() {
+            return None;
+        }
+        let average = self.known_sum / self.known_count;
+        let work: Duration = self.pending.values().map(|d| d.unwrap_or(average)).sum();
+        let parallel = self.workers.min(self.pending.len()).max(1) as u32;
+        Some(work / parallel)
+    }
+
+    /// `[123/500] 42% 31.4s ~1m20s left, compiling foo.cpp, bar.cpp +2 more`,
+    /// or `template` filled in with the running `targets` and `files`.
+    pub fn line(&self, template: Option<&str>, targets: &[&str], files: &[&Path], elapsed: Duration) -> String {
+        if let Some(template) = template {
+            let status = Status { done: self.done, total: self.total, elapsed, eta: self.eta(), targets, files };
+            return format_status(template, &status);
+        }
+        let mut line = format!("[{}/{}] {}% {}", self.done, self.total, self.percent(), format_elapsed(elapsed));
+        if let Some(eta) = self.eta() {
+            line.push_str(&format!(" ~{} left", format_eta(eta)));
+        }
+        if !files.is_empty() {
+            line.push_str(&format!(", compiling {}", file_names(files)));
+        }
+        line
+    }
+}
+
+/// `template` with every `{token}` replaced; anything else is copied as is.
+pub fn format_status(template: &str, status: &Status) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let token = rest[start + 1..].find('}').map(|end| &rest[start + 1..start + 1 + end]);
+        let value = match token {
+            Some("done") => status.done.to_string(),
+            Some("total") => status.total.to_string(),
+            Some("percent") => (status.done * 100).checked_div(status.total).unwrap_or(100).to_string(),
+            Some("elapsed") => format_elapsed(status.elapsed),
+            Some("eta") => status.eta.map(format_eta).unwrap_or_default(),
+            Some("target") => status.targets.join(", "),
+            Some("files") => file_names(status.files),
+            _ => {
+                out.push('{');
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+        out.push_str(&value);
+        rest = &rest[start + token.map_or(0, str::len) + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Error naming the first unknown `{token}` in a status format template.
+pub fn check_status_format(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else { break };
+        let token = &rest[start + 1..start + end];
+        if !STATUS_TOKENS.contains(&token) {
+            let known: Vec<String> = STATUS_TOKENS.iter().map(|t| format!("{{{}}}", t)).collect();
+            return Err(format!("Unknown token '{{{}}}' in status format (known: {})", token, known.join(" ")));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+/// `foo.cpp, bar.cpp +2 more`
+fn file_names(files: &[&Path]) -> String {
+    let names: Vec<String> = files.iter().filter_map(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).collect();
+    let mut text = names.iter().take(SHOWN_FILES).cloned().collect::<Vec<_>>().join(", ");
+    if names.len() > SHOWN_FILES {
+        text.push_str(&format!(" +{} more", names.len() - SHOWN_FILES));
+    }
+    text
+}
+
+/// `4.2s` below a minute, else like format_eta
+pub fn format_elapsed(d: Duration) -> String {
+    if d.as_secs() < 60 {
+        format!("{:.1}s", d.as_secs_f64())
+    } else {
+        format_eta(d)
+    }
+}
+
+/// `1h02m`, `1m20s`, `42s`
+pub fn format_eta(d: Duration) -> String {
+    let secs = d.as_secs_f64().ceil() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// A single terminal line redrawn in place, cut to the terminal width ($COLUMNS,
+/// else 80) so it never wraps. Disabled when stdout is not a terminal, so logs and
+/// pipes get plain per-target lines instead.
+pub struct StatusLine {
+    enabled: bool,
+    visible: bool,
+    width: usize,
+}
+
+impl StatusLine {
+    pub fn new() -> StatusLine {
+        StatusLine {
+            enabled: std::io::stdout().is_terminal(),
+            visible: false,
+            width: std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(80),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn update(&mut self, text: &str) {
+        if !self.enabled {
+            return;
+        }
+        let text: String = text.chars().take(self.width.saturating_sub(3)).collect();
+        print!("\r\x1b[K  {}", text);
+        let _ = std::io::stdout().flush();
+        self.visible = true;
+    }
+
+    /// Erases the line so regular output can be printed.
+    pub fn clear(&mut self) {
+        if self.visible {
+            print!("\r\x1b[K");
+            let _ = std::io::stdout().flush();
+            self.visible = false;
+        }
+    }
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        StatusLine::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta_uses_history_and_average_for_unknown() {
+        let mut progress = Progress {
+            total: 0,
+            done: 0,
+            workers: 2,
+            pending: HashMap::new(),
+            known_sum: Duration::ZERO,
+            known_count: 0,
+        };
+        assert_eq!(progress.eta(), None);
+        progress.add((JobKind::Compile, "a.cpp".into()), Some(Duration::from_secs(4)));
+        progress.add((JobKind::Compile, "b.cpp".into()), None);
+        progress.add((JobKind::Link, "app".into()), Some(Duration::from_secs(2)));
+        // 4 + 3 (average) + 2 over 2 workers
+        assert_eq!(progress.eta(), Some(Duration::from_millis(4500)));
+        assert_eq!(format_eta(Duration::from_secs(80)), "1m20s");
+        let running = [Path::new("src/a.cpp"), Path::new("src/b.cpp"), Path::new("src/c.cpp")];
+        assert_eq!(progress.line(None, &[], &running, Duration::from_millis(4200)), "[0/3] 0% 4.2s ~5s left, compiling a.cpp, b.cpp +1 more");
+    }
+
+    #[test]
+    fn test_status_format_fills_in_tokens() {
+        let files = [Path::new("src/a.cpp")];
+        let status = Status { done: 3, total: 12, elapsed: Duration::from_millis(1500), eta: None, targets: &["app", "util"], files: &files };
+        assert_eq!(format_status("{done}/{total} ({percent}%) {elapsed}{eta} {target}: {files} {x}", &status), "3/12 (25%) 1.5s app, util: a.cpp {x}");
+        assert_eq!(check_status_format("[{done}/{total}] {target}"), Ok(()));
+        assert!(check_status_format("{done} {bogus}").unwrap_err().contains("'{bogus}'"));
+    }
+}
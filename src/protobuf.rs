@@ -0,0 +1,148 @@
+// ============================================================================
+// protobuf.rs — Protocol Buffers code generation (`[target.protobuf]`)
+// ============================================================================
+//
+//   [[target]]
+//   name = "service"
+//   sources = ["src/*.cpp"]
+//
+//   [target.protobuf]
+//   sources = ["proto/*.proto"]
+//
+// A companion custom target `<name>_protobuf` runs protoc for every .proto
+// file into <output_dir>/protobuf/<target>/. The generated .pb.cc files are
+// compiled with the target, the directory is added to its include path and
+// libprotobuf is linked. protoc reruns when a .proto file changes.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::{normalize_path, ResolvedTarget};
+use crate::interpolate::{expand, expand_all, Variables};
+
+/// The `protobuf` table of a target. Paths are relative to the target's build.toml.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProtobufConfig {
+    /// .proto files (glob patterns supported)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Import search paths (protoc -I). Default: the build.toml directory.
+    /// Generated files keep each .proto's path relative to its import dir.
+    #[serde(default)]
+    pub import_dirs: Vec<String>,
+    /// protoc executable (default: "protoc" from PATH)
+    #[serde(default)]
+    pub protoc: Option<String>,
+}
+
+impl ProtobufConfig {
+    /// Copy with `${name}` / `${env:NAME}` references expanded.
+    pub fn interpolate(&self, vars: &Variables) -> Result<ProtobufConfig, String> {
+        Ok(ProtobufConfig {
+            sources: expand_all(&self.sources, vars)?,
+            import_dirs: expand_all(&self.import_dirs, vars)?,
+            protoc: self.protoc.as_deref().map(|p| expand(p, vars)).transpose()?,
+        })
+    }
+}
+
+/// Adds the generated sources, include dir and libprotobuf to `target` and returns
+/// the custom target running protoc (None if no .proto files matched).
+pub fn generator(config: &ProtobufConfig, target: &mut ResolvedTarget) -> Result<Option<ResolvedTarget>, String> {
+    let err = |e: String| format!("target '{}': {}", target.name, e);
+    let base_dir = target.working_dir.clone();
+    let mut protos = Vec::new();
+    for pattern in &config.sources {
+        let path = base_dir.join(pattern);
+        if pattern.contains(['*', '?', '[']) {
+            let entries = glob::glob(&path.to_string_lossy())
+                .map_err(|e| err(format!("invalid protobuf pattern '{}': {}", pattern, e)))?;
+            protos.extend(entries.flatten());
+        } else {
+            protos.push(path);
+        }
+    }
+    if protos.is_empty() {
+        return Ok(None);
+    }
+    let import_dirs: Vec<PathBuf> = if config.import_dirs.is_empty() {
+        vec![base_dir.clone()]
+    } else {
+        config.import_dirs.iter().map(|d| base_dir.join(d)).collect()
+    };
+
+    let gen_dir = target.output_dir.join("protobuf").join(&target.name);
+    let protoc = config.protoc.clone().unwrap_or_else(|| "protoc".to_string());
+    let mut commands = Vec::new();
+    let mut outputs = Vec::new();
+    for proto in &protos {
+        let relative = relative_to_import_dir(proto, &import_dirs)
+            .ok_or_else(|| err(format!("{} is not under any protobuf import_dir", proto.display())))?;
+        let mut args = vec![protoc.clone(), format!("--cpp_out={}", gen_dir.to_string_lossy())];
+        args.extend(import_dirs.iter().map(|d| format!("-I{}", d.to_string_lossy())));
+        args.push(proto.to_string_lossy().to_string());
+        commands.push(args);
+        let stem = relative.with_extension("");
+        let generated = gen_dir.join(stem);
+        let with_suffix = |suffix: &str| PathBuf::from(format!("{}{}", generated.to_string_lossy(), suffix));
+        target.sources.push(with_suffix(".pb.cc"));
+        outputs.push(with_suffix(".pb.cc"));
+        outputs.push(with_suffix(".pb.h"));
+    }
+
+    target.include_dirs.push(gen_dir);
+    if !target.libs.iter().any(|l| l == "protobuf") {
+        target.libs.push("protobuf".to_string());
+    }
+    Ok(Some(target.generator("protobuf", commands, protos, outputs)))
+}
+
+/// Path of `proto` relative to the first import dir containing it.
+fn relative_to_import_dir(proto: &Path, import_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let proto = normalize_path(proto);
+    import_dirs
+        .iter()
+        .find_map(|d| proto.strip_prefix(normalize_path(d)).ok().map(Path::to_path_buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_protoc_per_file_and_compiles_the_output() {
+        let mut service = ResolvedTarget {
+            name: "service".to_string(),
+            working_dir: PathBuf::from("/proj"),
+            output_dir: PathBuf::from("/proj/build"),
+            ..Default::default()
+        };
+        let config = ProtobufConfig {
+            sources: vec!["proto/api/user.proto".to_string()],
+            import_dirs: vec!["proto".to_string()],
+            protoc: None,
+        };
+        let gen = generator(&config, &mut service).unwrap().unwrap();
+
+        let gen_dir = PathBuf::from("/proj/build/protobuf/service");
+        assert_eq!(gen.name, "service_protobuf");
+        assert_eq!(
+            gen.commands,
+            vec![vec![
+                "protoc".to_string(),
+                format!("--cpp_out={}", gen_dir.display()),
+                "-I/proj/proto".to_string(),
+                "/proj/proto/api/user.proto".to_string(),
+            ]]
+        );
+        assert_eq!(gen.outputs, vec![gen_dir.join("api/user.pb.cc"), gen_dir.join("api/user.pb.h")]);
+        assert_eq!(service.sources, vec![gen_dir.join("api/user.pb.cc")]);
+        assert_eq!(service.include_dirs, vec![gen_dir]);
+        assert_eq!(service.deps, vec!["service_protobuf".to_string()]);
+
+        let outside = ProtobufConfig { sources: vec!["other/x.proto".to_string()], ..config };
+        assert!(generator(&outside, &mut service).unwrap_err().contains("not under any protobuf import_dir"));
+    }
+}
@@ -0,0 +1,136 @@
+// ============================================================================
+// prune.rs — `ngm clean --prune`: stale objects and outputs
+// ============================================================================
+//
+// Renaming a source or a target leaves its old object file / library behind.
+// Stale objects waste disk, and a stale library in a lib dir can still be
+// picked up by the linker. Pruning removes, in every output directory:
+//
+//   obj/<target>/*.o          objects no source of <target> compiles to
+//   obj|qt|protobuf/<name>/   build directories of targets that no longer
+//                             exist in that output directory
+//   <name>, lib<name>.a/.so   outputs of those removed targets
+//
+// Only files ngmake creates by these names are considered; everything else
+// in the output directories is left alone.
+// ============================================================================
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::compiler;
+use crate::config::ResolvedProject;
+
+/// Build directories per target below an output directory.
+const TARGET_DIRS: &[&str] = &["obj", "qt", "protobuf"];
+
+/// Output directories `ngm clean` removes, sorted. Prebuilt libraries live in their
+/// package, which isn't build output.
+pub fn output_dirs(project: &ResolvedProject) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = project.targets.values().filter(|t| !t.prebuilt).map(|t| t.output_dir.clone()).collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Objects and outputs in the project's output directories that no current
+/// source or target produces, sorted.
+pub fn stale_artifacts(project: &ResolvedProject) -> Vec<PathBuf> {
+    // Output directory → targets building into it
+    let mut dirs: BTreeMap<&Path, Vec<&str>> = BTreeMap::new();
+    for target in project.targets.values().filter(|t| !t.prebuilt) {
+        dirs.entry(target.output_dir.as_path()).or_default().push(&target.name);
+    }
+    let objects: HashSet<PathBuf> = project
+        .targets
+        .values()
+        .flat_map(|t| t.sources.iter().map(move |s| compiler::object_path(t, s)))
+        .collect();
+    let outputs: HashSet<PathBuf> = project.targets.values().map(compiler::output_path).collect();
+
+    let mut stale = Vec::new();
+    for (output_dir, names) in dirs {
+        let mut removed_targets = HashSet::new();
+        for kind in TARGET_DIRS {
+            for dir in subdirectories(&output_dir.join(kind)) {
+                let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+                if !names.contains(&name.as_str()) {
+                    stale.push(dir);
+                    removed_targets.insert(name);
+                } else if *kind == "obj" {
+                    stale.extend(files(&dir).filter(|f| f.extension().is_some_and(|e| e == "o") && !objects.contains(f)));
+                }
+            }
+        }
+        for name in removed_targets {
+            for file in [name.clone(), format!("lib{}.a", name), format!("lib{}.so", name), format!("lib{}.dll", name), format!("{}.dll", name)] {
+                let path = output_dir.join(file);
+                if path.is_file() && !outputs.contains(&path) {
+                    stale.push(path);
+                }
+            }
+        }
+    }
+    stale.sort();
+    stale
+}
+
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect()
+}
+
+fn files(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(dir).into_iter().flatten().flatten().map(|e| e.path()).filter(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::{ResolvedTarget, TargetType};
+
+    #[test]
+    fn test_renamed_sources_and_targets_leave_stale_artifacts() {
+        let dir = TempDir::new("prune");
+        for file in ["obj/util/util.o", "obj/util/old_name.o", "obj/util/parser.c", "obj/helpers/helpers.o", "libutil.a", "libhelpers.a", "notes.txt"] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let mut project = ResolvedProject::default();
+        let util = ResolvedTarget {
+            name: "util".to_string(),
+            target_type: TargetType::StaticLib,
+            sources: vec![dir.join("src/util.cpp")],
+            output_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        project.targets.insert("util".to_string(), util);
+        assert_eq!(
+            stale_artifacts(&project),
+            vec![dir.join("libhelpers.a"), dir.join("obj/helpers"), dir.join("obj/util/old_name.o")]
+        );
+    }
+
+    #[test]
+    fn test_clean_keeps_the_lib_dirs_of_prebuilt_dependencies() {
+        let dir = TempDir::new("clean-prebuilt");
+        dir.write("deps/core/lib/libcore.a", "");
+        dir.write("deps/core/lib/ngmake/core.toml", "name = \"core\"\nversion = \"1.0.0\"\n\n[[library]]\nname = \"core\"\ntype = \"static_lib\"\nfile = \"lib/libcore.a\"\n");
+        dir.write("src/main.cpp", "int main() { return 0; }\n");
+        dir.write("build/app", "");
+        let config = dir.write(
+            "build.toml",
+            "[project]\nname = \"app\"\n\n[dependencies]\ncore = { path = \"deps/core\" }\n\n[[target]]\nname = \"app\"\ntype = \"executable\"\nsources = [\"src/main.cpp\"]\ndeps = [\"core::core\"]\noutput_dir = \"build\"\n",
+        );
+
+        let project = crate::config::parse_build_file(&config, false).unwrap();
+        assert_eq!(output_dirs(&project), vec![dir.join("build")]);
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        crate::clean_project_and_stream_events(&config, tx).unwrap();
+        assert!(!dir.join("build").exists());
+        assert!(dir.join("deps/core/lib/libcore.a").exists());
+    }
+}
@@ -0,0 +1,227 @@
+// ============================================================================
+// qt.rs — Qt moc/uic/rcc integration (`qt = { modules = ["Widgets"] }`)
+// ============================================================================
+//
+// A target with a `qt` key gets a companion custom target `<name>_qt` that
+// runs the Qt code generators before the target is compiled:
+//
+//   moc  headers containing Q_OBJECT / Q_GADGET  → moc_<stem>.cpp (compiled)
+//   uic  .ui files listed in `sources`            → ui_<stem>.h    (included)
+//   rcc  .qrc files listed in `sources`           → qrc_<stem>.cpp (compiled)
+//
+// Generated files go to <output_dir>/qt/<target>/, which is added to the
+// include path. The Qt installation is found with `qmake -query` (qmake6,
+// qmake) or, failing that, pkg-config.
+// ============================================================================
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::config::ResolvedTarget;
+
+/// The `qt` table of a target.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QtConfig {
+    /// Qt modules to use, e.g. ["Widgets", "Network"] (Core is always added)
+    #[serde(default)]
+    pub modules: Vec<String>,
+    /// Run moc on headers of the target that contain Q_OBJECT / Q_GADGET (default: true)
+    #[serde(default = "default_auto_moc")]
+    pub auto_moc: bool,
+}
+
+fn default_auto_moc() -> bool {
+    true
+}
+
+/// Location of an installed Qt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QtInstall {
+    /// Major version (5 or 6), used for library names (Qt6Widgets)
+    pub major: u32,
+    pub headers: PathBuf,
+    pub libs: PathBuf,
+    /// Directories searched for moc/uic/rcc (bin, libexec)
+    pub tool_dirs: Vec<PathBuf>,
+}
+
+impl QtInstall {
+    /// Finds Qt once per process: qmake6 / qmake first, then pkg-config.
+    pub fn detect() -> Result<&'static QtInstall, String> {
+        static INSTALL: OnceLock<Option<QtInstall>> = OnceLock::new();
+        INSTALL
+            .get_or_init(|| from_qmake().or_else(from_pkg_config))
+            .as_ref()
+            .ok_or_else(|| "Qt not found (tried qmake6, qmake and pkg-config Qt6Core/Qt5Core)".to_string())
+    }
+
+    /// Full path of a Qt tool if it is in one of the tool dirs, else the bare name (PATH lookup).
+    fn tool(&self, name: &str) -> String {
+        self.tool_dirs
+            .iter()
+            .map(|d| d.join(name))
+            .find(|p| p.is_file())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string())
+    }
+}
+
+fn from_qmake() -> Option<QtInstall> {
+    ["qmake6", "qmake"].iter().find_map(|qmake| {
+        let output = Command::new(qmake).arg("-query").output().ok().filter(|o| o.status.success())?;
+        parse_qmake_query(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+/// Parses `qmake -query` output (KEY:value lines).
+fn parse_qmake_query(text: &str) -> Option<QtInstall> {
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(':'))
+            .map(|v| PathBuf::from(v.trim()))
+    };
+    let version = value("QT_VERSION")?;
+    let major = version.to_string_lossy().split('.').next()?.parse().ok()?;
+    Some(QtInstall {
+        major,
+        headers: value("QT_INSTALL_HEADERS")?,
+        libs: value("QT_INSTALL_LIBS")?,
+        tool_dirs: ["QT_INSTALL_LIBEXECS", "QT_INSTALL_BINS"].iter().filter_map(|k| value(k)).collect(),
+    })
+}
+
+fn from_pkg_config() -> Option<QtInstall> {
+    [6, 5].iter().find_map(|&major| {
+        let package = format!("Qt{}Core", major);
+        let variable = |name: &str| {
+            let output = Command::new("pkg-config")
+                .arg(format!("--variable={}", name))
+                .arg(&package)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())?;
+            let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (!value.is_empty()).then(|| PathBuf::from(value))
+        };
+        Some(QtInstall {
+            major,
+            headers: variable("includedir")?,
+            libs: variable("libdir")?,
+            tool_dirs: ["libexecdir", "host_bins", "bindir"].iter().filter_map(|v| variable(v)).collect(),
+        })
+    })
+}
+
+/// Sets up `target` for Qt (include/lib paths, libraries, generated sources) and
+/// returns the custom target that runs moc/uic/rcc, if there is anything to generate.
+pub fn generator(qt: &QtConfig, target: &mut ResolvedTarget) -> Result<Option<ResolvedTarget>, String> {
+    let install = QtInstall::detect().map_err(|e| format!("target '{}': {}", target.name, e))?;
+    Ok(generator_for(install, qt, target))
+}
+
+fn generator_for(install: &QtInstall, qt: &QtConfig, target: &mut ResolvedTarget) -> Option<ResolvedTarget> {
+    let gen_dir = target.output_dir.join("qt").join(&target.name);
+    let has_ext = |p: &Path, ext: &str| p.extension().is_some_and(|e| e == ext);
+
+    let mut commands = Vec::new();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut generated_sources = Vec::new();
+    let mut run = |tool: &str, input: &Path, extra: &[String], output: PathBuf, compiled: bool| {
+        let mut args = vec![install.tool(tool), input.to_string_lossy().to_string()];
+        args.extend(extra.iter().cloned());
+        args.extend(["-o".to_string(), output.to_string_lossy().to_string()]);
+        commands.push(args);
+        inputs.push(input.to_path_buf());
+        if compiled {
+            generated_sources.push(output.clone());
+        }
+        outputs.push(output);
+    };
+
+    let (forms_and_resources, sources): (Vec<PathBuf>, Vec<PathBuf>) =
+        target.sources.drain(..).partition(|s| has_ext(s, "ui") || has_ext(s, "qrc"));
+    target.sources = sources;
+    for file in &forms_and_resources {
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if has_ext(file, "ui") {
+            run("uic", file, &[], gen_dir.join(format!("ui_{}.h", stem)), false);
+        } else {
+            run("rcc", file, &["--name".to_string(), stem.clone()], gen_dir.join(format!("qrc_{}.cpp", stem)), true);
+        }
+    }
+    if qt.auto_moc {
+        for header in moc_headers(target) {
+            let stem = header.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            run("moc", &header, &[], gen_dir.join(format!("moc_{}.cpp", stem)), true);
+        }
+    }
+
+    let mut modules = vec!["Core".to_string()];
+    modules.extend(qt.modules.iter().map(|m| m.trim_start_matches("Qt").to_string()).filter(|m| m != "Core"));
+    target.include_dirs.push(gen_dir);
+    target.include_dirs.push(install.headers.clone());
+    target.include_dirs.extend(modules.iter().map(|m| install.headers.join(format!("Qt{}", m))));
+    target.lib_dirs.push(install.libs.clone());
+    target.libs.extend(modules.iter().map(|m| format!("Qt{}{}", install.major, m)));
+    // Qt is built with -reduce-relocations: code using it must be position independent
+    if !target.compiler_flags.iter().any(|f| f == "-fPIC") {
+        target.compiler_flags.push("-fPIC".to_string());
+    }
+
+    if commands.is_empty() {
+        return None;
+    }
+    target.sources.extend(generated_sources);
+    Some(target.generator("qt", commands, inputs, outputs))
+}
+
+/// Headers next to the target's sources and under its include dirs that declare a
+/// Q_OBJECT or Q_GADGET class.
+fn moc_headers(target: &ResolvedTarget) -> BTreeSet<PathBuf> {
+    let source_dirs: BTreeSet<PathBuf> = target.sources.iter().filter_map(|s| s.parent().map(Path::to_path_buf)).collect();
+    let patterns = source_dirs
+        .iter()
+        .flat_map(|d| ["*.h", "*.hpp", "*.hxx"].map(|ext| d.join(ext)))
+        .chain(target.include_dirs.iter().flat_map(|d| ["**/*.h", "**/*.hpp", "**/*.hxx"].map(|ext| d.join(ext))));
+    patterns
+        .filter_map(|p| glob::glob(&p.to_string_lossy()).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|h| std::fs::read_to_string(h).is_ok_and(|text| text.contains("Q_OBJECT") || text.contains("Q_GADGET")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_generates_moc_uic_and_rcc_steps() {
+        let dir = TempDir::new("qt");
+        dir.write("window.h", "class Window : public QWidget { Q_OBJECT };\n");
+        dir.write("plain.h", "struct Plain {};\n");
+
+        let install = parse_qmake_query("QT_VERSION:6.5.2\nQT_INSTALL_HEADERS:/qt/include\nQT_INSTALL_LIBS:/qt/lib\nQT_INSTALL_BINS:/qt/bin\n").unwrap();
+        assert_eq!(install.major, 6);
+        let mut app = ResolvedTarget {
+            name: "app".to_string(),
+            sources: vec![dir.join("main.cpp"), dir.join("window.ui"), dir.join("icons.qrc")],
+            output_dir: dir.join("build"),
+            ..Default::default()
+        };
+        let qt = QtConfig { modules: vec!["Widgets".to_string()], auto_moc: true };
+        let gen = generator_for(&install, &qt, &mut app).unwrap();
+
+        let gen_dir = dir.join("build/qt/app");
+        assert_eq!(gen.outputs, vec![gen_dir.join("ui_window.h"), gen_dir.join("qrc_icons.cpp"), gen_dir.join("moc_window.cpp")]);
+        assert_eq!(app.sources, vec![dir.join("main.cpp"), gen_dir.join("qrc_icons.cpp"), gen_dir.join("moc_window.cpp")]);
+        assert_eq!(app.libs, vec!["Qt6Core".to_string(), "Qt6Widgets".to_string()]);
+        assert!(app.include_dirs.contains(&PathBuf::from("/qt/include/QtWidgets")));
+        assert_eq!(app.deps, vec!["app_qt".to_string()]);
+    }
+}
@@ -0,0 +1,112 @@
+// ============================================================================
+// query.rs — `ngm query`: the resolved build as JSON
+// ============================================================================
+//
+// Scripts and editor plugins ask the resolved project (globs expanded,
+// variables interpolated, dependency properties propagated) instead of
+// parsing build.toml themselves:
+//
+//   ngm query targets           all targets in build order
+//   ngm query sources <target>  sources and their object files
+//   ngm query flags <target>    compiler, include dirs, defines, flags, libs
+//   ngm query rdeps <target>    targets depending on it, directly or not
+// ============================================================================
+
+use std::collections::BTreeSet;
+
+use serde_json::{json, Value};
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget};
+use crate::dag::BuildOrder;
+
+fn paths(paths: &[std::path::PathBuf]) -> Vec<String> {
+    paths.iter().map(|p| p.display().to_string()).collect()
+}
+
+/// Every target in build order: name, type, deps, output file and source count.
+pub fn targets(project: &ResolvedProject, order: &BuildOrder) -> Value {
+    let targets: Vec<Value> = order
+        .levels
+        .iter()
+        .flatten()
+        .filter_map(|name| project.targets.get(name))
+        .map(|t| {
+            json!({
+                "name": t.name,
+                "type": t.target_type.as_str(),
+                "deps": t.deps,
+                "output": compiler::output_path(t).display().to_string(),
+                "sources": t.sources.len(),
+            })
+        })
+        .collect();
+    Value::from(targets)
+}
+
+/// The sources of a target with the object file each compiles to.
+pub fn sources(target: &ResolvedTarget) -> Value {
+    let sources: Vec<Value> = target
+        .sources
+        .iter()
+        .map(|source| {
+            json!({
+                "source": source.display().to_string(),
+                "object": compiler::object_path(target, source).display().to_string(),
+            })
+        })
+        .collect();
+    Value::from(sources)
+}
+
+/// What a target is compiled and linked with (including properties propagated from its deps).
+pub fn flags(target: &ResolvedTarget) -> Value {
+    json!({
+        "compiler": target.compiler.command(),
+        "cxx_standard": target.cxx_standard.map(|s| s.flag(true)),
+        "c_standard": target.c_standard.map(|s| s.flag(false)),
+        "include_dirs": paths(&target.include_dirs),
+        "defines": target.defines,
+        "compiler_flags": target.compiler_flags.iter().chain(&target.flags).collect::<Vec<_>>(),
+        "linker_flags": target.linker_flags,
+        "lib_dirs": paths(&target.lib_dirs),
+        "libs": target.libs,
+        "frameworks": target.frameworks,
+    })
+}
+
+/// Targets that depend on `name` directly or through other targets, in build order.
+pub fn rdeps(project: &ResolvedProject, order: &BuildOrder, name: &str) -> Value {
+    let mut dependents = BTreeSet::new();
+    // Build order puts every target after its deps, so one pass finds all dependents
+    for target in order.levels.iter().flatten().filter_map(|n| project.targets.get(n)) {
+        if target.deps.iter().any(|d| d == name || dependents.contains(d)) {
+            dependents.insert(target.name.clone());
+        }
+    }
+    let ordered: Vec<String> = order.levels.iter().flatten().filter(|n| dependents.contains(*n)).cloned().collect();
+    Value::from(ordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ResolvedTarget;
+
+    #[test]
+    fn test_rdeps_are_transitive_and_in_build_order() {
+        let mut project = ResolvedProject::default();
+        for (name, deps) in [("base", vec![]), ("net", vec!["base"]), ("app", vec!["net"]), ("tool", vec![])] {
+            let target = ResolvedTarget {
+                name: name.to_string(),
+                deps: deps.into_iter().map(String::from).collect(),
+                ..Default::default()
+            };
+            project.targets.insert(name.to_string(), target);
+        }
+        let order = crate::dag::build_order(&project).unwrap();
+        assert_eq!(rdeps(&project, &order, "base"), json!(["net", "app"]));
+        assert_eq!(rdeps(&project, &order, "tool"), json!([]));
+        assert_eq!(targets(&project, &order).as_array().map(|t| t.len()), Some(4));
+    }
+}
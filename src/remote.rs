@@ -0,0 +1,261 @@
+// ============================================================================
+// remote.rs — Distributed compilation on SSH build hosts (`[remote]`)
+// ============================================================================
+//
+// With a `[remote]` table, compile jobs of C and C++ sources are split the
+// way distcc does it:
+//
+//   1. preprocess locally   <compiler> -E ... → obj/<target>/<stem>.ii
+//   2. compile remotely     ssh <host> <compiler> -x c++-cpp-output -c - ...
+//                           (the .ii is sent on stdin, the object comes back
+//                           on stdout)
+//
+// Only the preprocessed source travels, so the hosts need the same compiler
+// but neither the sources nor the headers. Each host runs up to `slots`
+// compiles; the builder adds all remote slots to its workers. A job that
+// finds no free slot, a source the hosts can't compile (Objective-C,
+// `[compilers]` rules) and a host that can't be reached (ssh exits with 255)
+// fall back to compiling locally; an unreachable host is not used again.
+// ============================================================================
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiler;
+use crate::config::ResolvedTarget;
+
+/// The `[remote]` table of the root build.toml.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RemoteConfig {
+    /// SSH destinations, optionally with their slot count: "build1", "me@build2/16"
+    pub hosts: Vec<String>,
+    /// Concurrent compiles per host without an explicit count
+    #[serde(default = "default_slots")]
+    pub slots: usize,
+    /// ssh executable
+    #[serde(default = "default_ssh")]
+    pub ssh: String,
+    /// Extra ssh options, e.g. ["-p", "2222"]
+    #[serde(default)]
+    pub ssh_args: Vec<String>,
+}
+
+fn default_slots() -> usize {
+    4
+}
+
+fn default_ssh() -> String {
+    "ssh".to_string()
+}
+
+/// One build host and its compile slots.
+#[derive(Debug)]
+struct Host {
+    destination: String,
+    slots: usize,
+    /// Set once ssh failed to reach the host
+    down: AtomicBool,
+}
+
+/// The build hosts of a build and their busy slots.
+#[derive(Debug)]
+pub struct RemoteHosts {
+    config: RemoteConfig,
+    hosts: Vec<Host>,
+    busy: Mutex<Vec<usize>>,
+}
+
+impl RemoteHosts {
+    pub fn new(config: &RemoteConfig) -> RemoteHosts {
+        let hosts: Vec<Host> = config
+            .hosts
+            .iter()
+            .map(|entry| {
+                let (destination, slots) = match entry.rsplit_once('/').map(|(d, n)| (d, n.parse::<usize>())) {
+                    Some((destination, Ok(slots))) => (destination, slots),
+                    _ => (entry.as_str(), config.slots),
+                };
+                Host { destination: destination.to_string(), slots, down: AtomicBool::new(false) }
+            })
+            .collect();
+        RemoteHosts { config: config.clone(), busy: Mutex::new(vec![0; hosts.len()]), hosts }
+    }
+
+    /// Compile slots of all hosts.
+    pub fn slots(&self) -> usize {
+        self.hosts.iter().map(|h| h.slots).sum()
+    }
+
+    /// Compiles `source` on the least busy host. None if the source can't be compiled
+    /// remotely, no slot is free or the host can't be reached: compile it locally.
+    pub fn compile(&self, target: &ResolvedTarget, source: &Path, obj_path: &Path) -> Option<Result<(PathBuf, Vec<String>), String>> {
+        let language = remote_language(target, source)?;
+        let host = self.acquire()?;
+        let result = self.compile_on(host, language, target, source, obj_path);
+        self.busy.lock().unwrap()[host] -= 1;
+        result
+    }
+
+    /// Index of the reachable host with the most free slots (marked busy), if any is free.
+    fn acquire(&self) -> Option<usize> {
+        let mut busy = self.busy.lock().unwrap();
+        let host = (0..self.hosts.len())
+            .filter(|&i| !self.hosts[i].down.load(Ordering::Relaxed) && busy[i] < self.hosts[i].slots)
+            .max_by_key(|&i| self.hosts[i].slots - busy[i])?;
+        busy[host] += 1;
+        Some(host)
+    }
+
+    fn compile_on(
+        &self,
+        host: usize,
+        language: &str,
+        target: &ResolvedTarget,
+        source: &Path,
+        obj_path: &Path,
+    ) -> Option<Result<(PathBuf, Vec<String>), String>> {
+        let destination = &self.hosts[host].destination;
+        let mut msgs = vec![format!("  [COMPILE] {} → {} (on {})", source.display(), obj_path.display(), destination)];
+        if let Some(parent) = obj_path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                return Some(Err(e.to_string()));
+            }
+        }
+
+        // 1. Preprocess locally
+        let preprocessed = obj_path.with_extension("ii");
+        let mut args = compiler::compile_args(target, source, &preprocessed);
+        args[0] = "-E".to_string();
+        let mut cmd = Command::new(target.compiler.command());
+        cmd.args(args);
+        msgs.push(format!("    Command: {:?}", cmd));
+        let output = match compiler::run_tracked_with_input(cmd, target.timeout(), Stdio::null()) {
+            Ok(output) => output,
+            Err(e) => return Some(Err(format!("Failed to run compiler: {}", e))),
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Some(Err(format!(
+                "Preprocessing of '{}' failed (exit {:?})\n{}",
+                source.display(),
+                output.status.code(),
+                stderr.trim()
+            )));
+        }
+
+        // 2. Compile remotely: the preprocessed source on stdin, the object on stdout
+        let input = match std::fs::File::open(&preprocessed) {
+            Ok(file) => file,
+            Err(e) => return Some(Err(format!("Failed to read '{}': {}", preprocessed.display(), e))),
+        };
+        let mut cmd = Command::new(&self.config.ssh);
+        cmd.args(&self.config.ssh_args).arg(destination).arg(remote_command(target, source, language));
+        msgs.push(format!("    Command: {:?}", cmd));
+        let output = compiler::run_tracked_with_input(cmd, target.timeout(), Stdio::from(input));
+        let _ = std::fs::remove_file(&preprocessed);
+        let output = match output {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => return Some(Err(format!("Failed to run compiler: {}", e))),
+            Err(e) => {
+                eprintln!("[WARN] Failed to run {}: {}; compiling locally", self.config.ssh, e);
+                self.hosts[host].down.store(true, Ordering::Relaxed);
+                return None;
+            }
+        };
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.code() == Some(255) {
+            eprintln!("[WARN] Remote host '{}' is unreachable; compiling locally\n{}", destination, stderr.trim());
+            self.hosts[host].down.store(true, Ordering::Relaxed);
+            return None;
+        }
+        if !stderr.is_empty() {
+            msgs.push(format!("    stderr: {}", stderr.trim()));
+        }
+        if !output.status.success() {
+            return Some(Err(format!(
+                "Compilation of '{}' on {} failed (exit {:?})\n{}",
+                source.display(),
+                destination,
+                output.status.code(),
+                stderr.trim()
+            )));
+        }
+        if let Err(e) = std::fs::write(obj_path, &output.stdout) {
+            return Some(Err(format!("Failed to write '{}': {}", obj_path.display(), e)));
+        }
+        Some(Ok((obj_path.to_path_buf(), msgs)))
+    }
+}
+
+/// `-x` language of the preprocessed `source`, if the hosts can compile it.
+fn remote_language(target: &ResolvedTarget, source: &Path) -> Option<&'static str> {
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    if target.compilers.contains_key(extension) {
+        return None;
+    }
+    match extension {
+        "c" => Some("cpp-output"),
+        "cpp" | "cc" | "cxx" | "c++" | "C" => Some("c++-cpp-output"),
+        _ => None,
+    }
+}
+
+/// Shell command run on the host: compiles stdin into a temporary object and prints it.
+/// Include directories and defines are left out (already applied by the preprocessor).
+fn remote_command(target: &ResolvedTarget, source: &Path, language: &str) -> String {
+    let mut args = vec![target.compiler.command().to_string(), "-x".to_string(), language.to_string(), "-c".to_string(), "-".to_string()];
+    // compile_args starts with: -c <source> -o <object>
+    let mut rest = compiler::compile_args(target, source, Path::new("-")).into_iter().skip(4);
+    while let Some(arg) = rest.next() {
+        if arg == "-I" {
+            rest.next();
+        } else if !arg.starts_with("-D") && !arg.starts_with("-I") {
+            args.push(arg);
+        }
+    }
+    let compile: Vec<String> = args.iter().map(|a| shell_quote(a)).collect();
+    format!(
+        "out=$(mktemp) && {} -o \"$out\" && cat \"$out\"; status=$?; rm -f \"$out\"; exit $status",
+        compile.join(" ")
+    )
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_=+./:,@".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Compiler, Standard};
+
+    #[test]
+    fn test_hosts_compile_preprocessed_sources_from_stdin() {
+        let config: RemoteConfig = toml::from_str("hosts = [\"build1\", \"me@build2/16\"]").unwrap();
+        let hosts = RemoteHosts::new(&config);
+        assert_eq!(hosts.slots(), 20);
+        assert_eq!(hosts.hosts[1].destination, "me@build2");
+
+        let target = ResolvedTarget {
+            compiler: Compiler::Gpp,
+            include_dirs: vec![PathBuf::from("/src/include")],
+            defines: vec!["NAME=\"a b\"".to_string()],
+            cxx_standard: Some(Standard { version: 17, gnu: false }),
+            compiler_flags: vec!["-O2".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            remote_command(&target, Path::new("/src/main.cpp"), "c++-cpp-output"),
+            "out=$(mktemp) && g++ -x c++-cpp-output -c - -std=c++17 -O2 -o \"$out\" && cat \"$out\"; status=$?; rm -f \"$out\"; exit $status"
+        );
+        assert_eq!(remote_language(&target, Path::new("view.mm")), None);
+    }
+}
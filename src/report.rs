@@ -0,0 +1,387 @@
+// ============================================================================
+// report.rs — `ngm build --report`: build report artifact for CI
+// ============================================================================
+//
+// One file summarizing a build: result and duration, per-target status,
+// compile/link times, warnings and errors, object cache statistics and the
+// dependency graph. `.json` paths get pretty-printed JSON, anything else a
+// standalone HTML page (inline CSS, graph as inline SVG, no scripts), so it
+// can be attached to a CI run as is.
+// ============================================================================
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::builder::{BuildResult, JobKind};
+use crate::config::ResolvedProject;
+use crate::dag::BuildOrder;
+use crate::diagnostics::Diagnostic;
+use crate::timing;
+
+/// One target in the report.
+#[derive(Debug, Clone)]
+pub struct TargetReport {
+    pub name: String,
+    pub target_type: &'static str,
+    /// "built", "failed", "not attempted" or "skipped"
+    pub status: &'static str,
+    pub deps: Vec<String>,
+    pub compile_time: Duration,
+    pub link_time: Duration,
+    pub compiled: usize,
+    pub up_to_date: usize,
+    pub cached: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildReport {
+    pub project: String,
+    pub success: bool,
+    pub wall_time: Duration,
+    pub workers: usize,
+    /// Targets in build order
+    pub targets: Vec<TargetReport>,
+    /// Build order levels, for laying out the graph
+    pub levels: Vec<Vec<String>>,
+    pub critical_path: Vec<String>,
+}
+
+impl BuildReport {
+    /// (compiled, up to date, restored from the cache) over all targets
+    pub fn cache_stats(&self) -> (usize, usize, usize) {
+        self.targets.iter().fold((0, 0, 0), |(c, u, h), t| (c + t.compiled, u + t.up_to_date, h + t.cached))
+    }
+
+    fn count(&self, status: &str) -> usize {
+        self.targets.iter().filter(|t| t.status == status).count()
+    }
+}
+
+/// Collects the report of a finished build.
+pub fn build_report(project: &ResolvedProject, order: &BuildOrder, result: &BuildResult) -> BuildReport {
+    let timings = timing::per_target_timings(&result.jobs);
+    let failed: HashSet<&str> = result
+        .results
+        .iter()
+        .filter(|r| !r.success)
+        .map(|r| r.target_name.as_str())
+        .chain(result.jobs.iter().filter(|j| !j.success).map(|j| j.target_name.as_str()))
+        .collect();
+    let linked: HashSet<&str> = result.jobs.iter().filter(|j| j.kind == JobKind::Link && j.success).map(|j| j.target_name.as_str()).collect();
+    let targets = order
+        .levels
+        .iter()
+        .flatten()
+        .filter_map(|name| project.targets.get(name))
+        .map(|target| {
+            let name = target.name.as_str();
+            let status = if failed.contains(name) {
+                "failed"
+            } else if linked.contains(name) {
+                "built"
+            } else if result.not_attempted.iter().any(|n| n == name) {
+                "not attempted"
+            } else {
+                "skipped"
+            };
+            let compiles: Vec<_> = result.jobs.iter().filter(|j| j.kind == JobKind::Compile && j.target_name == name).collect();
+            let timing = timings.get(name);
+            TargetReport {
+                name: target.name.clone(),
+                target_type: target.target_type.as_str(),
+                status,
+                deps: target.deps.clone(),
+                compile_time: timing.map(|t| t.compile_time).unwrap_or_default(),
+                link_time: timing.map(|t| t.link_time).unwrap_or_default(),
+                compiled: compiles.iter().filter(|j| !j.up_to_date).count(),
+                up_to_date: compiles.iter().filter(|j| j.up_to_date && !j.cached).count(),
+                cached: compiles.iter().filter(|j| j.cached).count(),
+                diagnostics: result.diagnostic_messages.get(name).cloned().unwrap_or_default(),
+            }
+        })
+        .collect();
+    BuildReport {
+        project: project.name.clone(),
+        success: result.success,
+        wall_time: result.wall_time,
+        workers: result.workers,
+        targets,
+        levels: order.levels.clone(),
+        critical_path: timing::timing_report(project, order, result).critical_path,
+    }
+}
+
+/// Writes the report as JSON (`.json`) or HTML (anything else).
+pub fn write_report(path: &Path, report: &BuildReport) -> Result<(), String> {
+    let text = if path.extension().is_some_and(|e| e == "json") { format!("{:#}", report_json(report)) } else { report_html(report) };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+    }
+    std::fs::write(path, text).map_err(|e| format!("Failed to write report '{}': {}", path.display(), e))
+}
+
+fn ms(d: Duration) -> f64 {
+    (d.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+/// Serializable form of the report (durations in milliseconds).
+pub fn report_json(report: &BuildReport) -> Value {
+    let targets: Vec<Value> = report
+        .targets
+        .iter()
+        .map(|t| {
+            let diagnostics: Vec<Value> = t
+                .diagnostics
+                .iter()
+                .map(|d| {
+                    json!({
+                        "severity": d.severity.as_str(),
+                        "file": d.file.display().to_string(),
+                        "line": d.line,
+                        "message": d.message,
+                    })
+                })
+                .collect();
+            json!({
+                "name": t.name,
+                "type": t.target_type,
+                "status": t.status,
+                "deps": t.deps,
+                "compile_ms": ms(t.compile_time),
+                "link_ms": ms(t.link_time),
+                "compiled": t.compiled,
+                "up_to_date": t.up_to_date,
+                "cached": t.cached,
+                "diagnostics": diagnostics,
+            })
+        })
+        .collect();
+    let (compiled, up_to_date, cached) = report.cache_stats();
+    json!({
+        "project": report.project,
+        "success": report.success,
+        "wall_ms": ms(report.wall_time),
+        "workers": report.workers,
+        "built": report.count("built"),
+        "failed": report.count("failed"),
+        "not_attempted": report.count("not attempted"),
+        "warnings": report.targets.iter().map(|t| warnings(t).0).sum::<usize>(),
+        "errors": report.targets.iter().map(|t| warnings(t).1).sum::<usize>(),
+        "cache": { "compiled": compiled, "up_to_date": up_to_date, "cache_hits": cached },
+        "critical_path": report.critical_path,
+        "targets": targets,
+    })
+}
+
+/// (warnings, errors) of a target.
+fn warnings(target: &TargetReport) -> (usize, usize) {
+    let errors = target.diagnostics.iter().filter(|d| d.severity == crate::diagnostics::Severity::Error).count();
+    (target.diagnostics.len() - errors, errors)
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+const STYLE: &str = "body{font:14px/1.4 system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin:1em 0}th,td{border:1px solid #ccc;padding:4px 10px;text-align:left}\
+th{background:#f3f3f3}td.num{text-align:right}.built{color:#1a7f37}.failed{color:#cf222e}\
+.skipped,.not-attempted{color:#888}.warning{color:#9a6700}.error{color:#cf222e}\
+pre{background:#f6f8fa;padding:8px;overflow-x:auto}";
+
+/// Node fill per status in the graph.
+fn status_fill(status: &str) -> &'static str {
+    match status {
+        "built" => "#dafbe1",
+        "failed" => "#ffebe9",
+        _ => "#eeeeee",
+    }
+}
+
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn seconds(d: Duration) -> String {
+    format!("{:.2}s", d.as_secs_f64())
+}
+
+/// Standalone HTML page of the report.
+pub fn report_html(report: &BuildReport) -> String {
+    let (compiled, up_to_date, cached) = report.cache_stats();
+    let result = if report.success { "<span class=\"built\">SUCCESS</span>" } else { "<span class=\"failed\">FAILED</span>" };
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Build report: {0}</title><style>{1}</style></head><body>\n<h1>Build report: {0}</h1>\n",
+        html(&report.project),
+        STYLE
+    );
+    out.push_str(&format!(
+        "<table><tr><th>Result</th><td>{}</td></tr><tr><th>Duration</th><td>{} ({} workers)</td></tr><tr><th>Targets</th><td>{} built, {} failed, {} not attempted</td></tr><tr><th>Objects</th><td>{} compiled, {} up to date, {} from cache</td></tr><tr><th>Critical path</th><td>{}</td></tr></table>\n",
+        result,
+        seconds(report.wall_time),
+        report.workers,
+        report.count("built"),
+        report.count("failed"),
+        report.count("not attempted"),
+        compiled,
+        up_to_date,
+        cached,
+        html(&report.critical_path.join(" → "))
+    ));
+
+    out.push_str("<h2>Targets</h2>\n<table><tr><th>Target</th><th>Type</th><th>Status</th><th>Compile</th><th>Link</th><th>Compiled</th><th>Up to date</th><th>Cached</th><th>Warnings</th><th>Errors</th></tr>\n");
+    for t in &report.targets {
+        let (w, e) = warnings(t);
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            html(&t.name),
+            t.target_type,
+            t.status.replace(' ', "-"),
+            t.status,
+            seconds(t.compile_time),
+            seconds(t.link_time),
+            t.compiled,
+            t.up_to_date,
+            t.cached,
+            w,
+            e
+        ));
+    }
+    out.push_str("</table>\n");
+
+    let with_diagnostics: Vec<&TargetReport> = report.targets.iter().filter(|t| !t.diagnostics.is_empty()).collect();
+    if !with_diagnostics.is_empty() {
+        out.push_str("<h2>Warnings and errors</h2>\n");
+        for t in with_diagnostics {
+            out.push_str(&format!("<h3>{}</h3>\n<pre>", html(&t.name)));
+            for d in &t.diagnostics {
+                out.push_str(&format!(
+                    "<span class=\"{0}\">{1}:{2}: {0}: {3}</span>\n",
+                    d.severity.as_str(),
+                    html(&d.file.display().to_string()),
+                    d.line,
+                    html(&d.message)
+                ));
+            }
+            out.push_str("</pre>\n");
+        }
+    }
+
+    out.push_str("<h2>Dependency graph</h2>\n");
+    out.push_str(&graph_svg(report));
+    out.push_str("\n</body></html>\n");
+    out
+}
+
+const NODE_WIDTH: usize = 160;
+const NODE_HEIGHT: usize = 30;
+const COLUMN: usize = 220;
+const ROW: usize = 50;
+
+/// Targets left to right by build level, an arrow from each dependency to its dependent.
+fn graph_svg(report: &BuildReport) -> String {
+    let position = |name: &str| {
+        report.levels.iter().enumerate().find_map(|(level, names)| {
+            names.iter().position(|n| n == name).map(|row| (20 + level * COLUMN, 20 + row * ROW))
+        })
+    };
+    let width = 40 + report.levels.len().saturating_sub(1) * COLUMN + NODE_WIDTH;
+    let height = 40 + report.levels.iter().map(Vec::len).max().unwrap_or(0).saturating_sub(1) * ROW + NODE_HEIGHT;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-size=\"12\"><defs><marker id=\"arrow\" markerWidth=\"8\" markerHeight=\"8\" refX=\"8\" refY=\"4\" orient=\"auto\"><path d=\"M0,0 L8,4 L0,8 z\" fill=\"#888\"/></marker></defs>\n",
+        width, height
+    );
+    for t in &report.targets {
+        let Some((x, y)) = position(&t.name) else { continue };
+        for dep in &t.deps {
+            let Some((dx, dy)) = position(dep) else { continue };
+            svg.push_str(&format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#888\" marker-end=\"url(#arrow)\"/>\n",
+                dx + NODE_WIDTH,
+                dy + NODE_HEIGHT / 2,
+                x,
+                y + NODE_HEIGHT / 2
+            ));
+        }
+    }
+    for t in &report.targets {
+        let Some((x, y)) = position(&t.name) else { continue };
+        svg.push_str(&format!(
+            "<g><title>{0} ({1}, {2})</title><rect x=\"{3}\" y=\"{4}\" width=\"{5}\" height=\"{6}\" rx=\"4\" fill=\"{7}\" stroke=\"#999\"/><text x=\"{8}\" y=\"{9}\" text-anchor=\"middle\">{0}</text></g>\n",
+            html(&t.name),
+            t.target_type,
+            t.status,
+            x,
+            y,
+            NODE_WIDTH,
+            NODE_HEIGHT,
+            status_fill(t.status),
+            x + NODE_WIDTH / 2,
+            y + NODE_HEIGHT / 2 + 4
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::JobTiming;
+    use crate::config::{ResolvedTarget, TargetType};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_report_covers_status_cache_and_graph() {
+        let mut project = ResolvedProject { name: "demo".to_string(), ..Default::default() };
+        for (name, deps) in [("util", vec![]), ("app", vec!["util".to_string()])] {
+            let target = ResolvedTarget { name: name.to_string(), target_type: TargetType::StaticLib, deps, ..Default::default() };
+            project.targets.insert(name.to_string(), target);
+        }
+        let order = BuildOrder { levels: vec![vec!["util".to_string()], vec!["app".to_string()]] };
+        let job = |kind, target: &str, source: Option<&str>, success, cached| JobTiming {
+            kind,
+            target_name: target.to_string(),
+            source: source.map(PathBuf::from),
+            worker: 0,
+            start: Duration::ZERO,
+            end: Duration::from_millis(10),
+            success,
+            up_to_date: cached,
+            cached,
+        };
+        let result = BuildResult {
+            success: false,
+            results: Vec::new(),
+            total_targets: 2,
+            successful_targets: 1,
+            failed_targets: 1,
+            jobs: vec![
+                job(JobKind::Compile, "util", Some("a.cpp"), true, true),
+                job(JobKind::Compile, "util", Some("b.cpp"), true, false),
+                job(JobKind::Link, "util", None, true, false),
+                job(JobKind::Link, "app", None, false, false),
+            ],
+            wall_time: Duration::from_millis(30),
+            workers: 2,
+            diagnostics: BTreeMap::new(),
+            diagnostic_messages: BTreeMap::new(),
+            not_attempted: Vec::new(),
+        };
+        let report = build_report(&project, &order, &result);
+        let statuses: Vec<(&str, &str)> = report.targets.iter().map(|t| (t.name.as_str(), t.status)).collect();
+        assert_eq!(statuses, vec![("util", "built"), ("app", "failed")]);
+        assert_eq!(report.cache_stats(), (1, 0, 1));
+        let json = report_json(&report);
+        assert_eq!(json.get("cache").and_then(|c| c.get("cache_hits")).and_then(Value::as_u64), Some(1));
+        let page = report_html(&report);
+        assert!(page.contains("<h1>Build report: demo</h1>"));
+        // One edge util → app
+        assert_eq!(page.matches("<line ").count(), 1);
+    }
+}
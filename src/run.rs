@@ -0,0 +1,158 @@
+// ============================================================================
+// run.rs — Launching built executables
+// ============================================================================
+//
+// Builds the command for running an executable target: the binary in the
+// target's output_dir, working directory = root of the project and a
+// LD_LIBRARY_PATH that finds the target's own lib_dirs and the output
+// directories of every shared library it (transitively) depends on.
+//
+// `ngm run app -- --port 8080 < input.txt` hands the terminal to the
+// program: stdin, stdout and stderr are inherited and ngm exits with the
+// program's exit code. On Unix ngm execs the program, so Ctrl-C, Ctrl-Z and
+// exit statuses (signals included) are exactly those of the program run
+// directly. On Windows the console sends Ctrl-C to ngm and the program alike;
+// ngm ignores it and waits for the program to stop, then exits with its code.
+// ============================================================================
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::compiler::{build_ld_library_path, output_path};
+use crate::config::{ResolvedProject, TargetType};
+use crate::dag::{build_order, transitive_deps_in_link_order};
+
+/// Path of the binary produced for an executable target.
+pub fn executable_path(project: &ResolvedProject, target: &str) -> Result<PathBuf, String> {
+    let t = project
+        .targets
+        .get(target)
+        .ok_or_else(|| format!("Unknown target '{}'", target))?;
+    if !t.target_type.is_executable() {
+        return Err(format!(
+            "Target '{}' is a {}, only executables, benchmarks and tests can be run",
+            target,
+            t.target_type.as_str()
+        ));
+    }
+    Ok(output_path(t))
+}
+
+/// Library directories the executable needs at runtime: its own lib_dirs, then the
+/// output directories of its shared library dependencies (deduplicated, in link order).
+pub fn runtime_library_dirs(project: &ResolvedProject, target: &str) -> Result<Vec<PathBuf>, String> {
+    let t = project
+        .targets
+        .get(target)
+        .ok_or_else(|| format!("Unknown target '{}'", target))?;
+    let order = build_order(project)?;
+    let mut dirs = t.lib_dirs.clone();
+    for dep in transitive_deps_in_link_order(project, target, &order) {
+        if let Some(d) = project.targets.get(&dep) {
+            if d.target_type == TargetType::SharedLib {
+                dirs.push(d.output_dir.clone());
+            }
+            dirs.extend(d.lib_dirs.iter().cloned());
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    dirs.retain(|d| seen.insert(d.clone()));
+    Ok(dirs)
+}
+
+/// Command that runs `target` with `args`, from the project root and with
+/// LD_LIBRARY_PATH set. The binary must already be built.
+pub fn run_command(project: &ResolvedProject, target: &str, args: &[String]) -> Result<Command, String> {
+    let exe = executable_path(project, target)?;
+    if !exe.exists() {
+        return Err(format!("Executable not found: {} (build the target first)", exe.display()));
+    }
+    let mut cmd = Command::new(&exe);
+    cmd.args(args).current_dir(&project.root_dir);
+    let ld_path = build_ld_library_path(&runtime_library_dirs(project, target)?);
+    if !ld_path.is_empty() {
+        cmd.env("LD_LIBRARY_PATH", &ld_path);
+    }
+    Ok(cmd)
+}
+
+/// Runs `command` attached to the terminal (see the module comment) and returns its
+/// exit code. On Unix this only returns if the program could not be started.
+#[cfg(unix)]
+pub fn run_in_terminal(mut command: Command) -> Result<i32, String> {
+    use std::os::unix::process::CommandExt;
+    let program = command.get_program().to_string_lossy().to_string();
+    // Nothing of ngm runs after exec, so buffered build output would be lost
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    let e = command.exec();
+    Err(format!("Failed to run '{}': {}", program, e))
+}
+
+#[cfg(windows)]
+pub fn run_in_terminal(mut command: Command) -> Result<i32, String> {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+    // A handler rather than SetConsoleCtrlHandler(None, TRUE): that would be
+    // inherited and the program itself would ignore Ctrl-C too
+    unsafe extern "system" fn ignore_ctrl_c(_: u32) -> i32 {
+        1
+    }
+    let program = command.get_program().to_string_lossy().to_string();
+    unsafe {
+        SetConsoleCtrlHandler(Some(ignore_ctrl_c), 1);
+    }
+    let status = command.status().map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::ResolvedTarget;
+
+    fn target(name: &str, target_type: TargetType, deps: &[&str]) -> ResolvedTarget {
+        ResolvedTarget {
+            name: name.to_string(),
+            target_type,
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            output_dir: PathBuf::from(format!("out/{}", name)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_runtime_dirs_include_shared_deps_only() {
+        let mut project = ResolvedProject::default();
+        for t in [
+            target("app", TargetType::Executable, &["net"]),
+            target("net", TargetType::SharedLib, &["util"]),
+            target("util", TargetType::StaticLib, &[]),
+        ] {
+            project.targets.insert(t.name.clone(), t);
+        }
+        assert_eq!(runtime_library_dirs(&project, "app").unwrap(), vec![PathBuf::from("out/net")]);
+        assert_eq!(executable_path(&project, "app").unwrap(), PathBuf::from("out/app/app"));
+        assert!(executable_path(&project, "net").is_err());
+    }
+
+    #[test]
+    fn test_program_arguments_follow_the_double_dash() {
+        use crate::options::{BuildOptions, Command as Cli};
+        use clap::Parser;
+        let options = BuildOptions::parse_from(["ngm", "run", "app", "-j", "4", "--preset", "ci", "--", "--port", "8080", "-c", "x.toml"]);
+        let Some(Cli::Run { config, target: name, args, jobs, preset }) = options.command else { panic!("not a run command") };
+        assert_eq!((config, name.as_str(), jobs, preset.as_deref()), (PathBuf::from("build.toml"), "app", Some(4), Some("ci")));
+        assert_eq!(args, vec!["--port", "8080", "-c", "x.toml"]);
+
+        let dir = TempDir::new("run");
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        let app = ResolvedTarget { output_dir: dir.to_path_buf(), ..target("app", TargetType::Executable, &[]) };
+        std::fs::write(output_path(&app), "").unwrap();
+        project.targets.insert("app".to_string(), app);
+        let command = run_command(&project, "app", &args).unwrap();
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["--port", "8080", "-c", "x.toml"]);
+        assert_eq!(command.get_current_dir(), Some(dir.path()));
+    }
+}
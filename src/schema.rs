@@ -0,0 +1,120 @@
+// ============================================================================
+// schema.rs — JSON Schema for build.toml (`ngm schema`)
+// ============================================================================
+//
+// Generated from the key tables in validate.rs, so the schema, the validator
+// and the config structs describe the same format. Editors with TOML schema
+// support (taplo / Even Better TOML) use it for completion and validation:
+//
+//   ngm schema -o build.schema.json
+//   # then in build.toml:  #:schema ./build.schema.json
+// ============================================================================
+
+use serde_json::{json, Map, Value};
+
+use crate::validate::{KeySpec, ValueKind, FLAG_KEYS, MODULE_KEYS, PROJECT_KEYS, TARGET_KEYS, WORKSPACE_KEYS};
+
+/// The JSON Schema (draft-07) describing build.toml.
+pub fn build_toml_schema() -> Value {
+    let mut root_properties = Map::new();
+    root_properties.insert("project".to_string(), table_schema("Root project settings", PROJECT_KEYS, &[]));
+    root_properties.insert("module".to_string(), table_schema("Settings of an included build.toml", MODULE_KEYS, &[]));
+    root_properties.insert(
+        "workspace".to_string(),
+        json!({
+            "description": "Marks the workspace root; the table lists child build.toml files found by glob",
+            "oneOf": [{ "type": "boolean" }, table_schema("Workspace members", WORKSPACE_KEYS, &[])],
+        }),
+    );
+    root_properties.insert(
+        "target".to_string(),
+        json!({
+            "description": "Build targets ([[target]] tables)",
+            "type": "array",
+            "items": table_schema("One build target", TARGET_KEYS, &["name"]),
+        }),
+    );
+    root_properties.insert(
+        "variables".to_string(),
+        json!({
+            "description": "User-defined variables, referenced as ${name} (override with --set name=value)",
+            "type": "object",
+            "additionalProperties": { "type": ["string", "integer", "number", "boolean"] },
+        }),
+    );
+    // Old files put the project settings at the top level
+    root_properties.extend(PROJECT_KEYS.iter().map(|k| (k.name.to_string(), key_schema(k))));
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "build.toml",
+        "description": "ngmake build configuration",
+        "type": "object",
+        "properties": root_properties,
+        "additionalProperties": false,
+    })
+}
+
+fn table_schema(description: &str, keys: &[KeySpec], required: &[&str]) -> Value {
+    let properties: Map<String, Value> = keys.iter().map(|k| (k.name.to_string(), key_schema(k))).collect();
+    let mut schema = json!({ "description": description, "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema["additionalProperties"] = json!(false);
+    schema
+}
+
+fn key_schema(key: &KeySpec) -> Value {
+    let mut schema = json!({ "description": key.doc });
+    match key.kind {
+        ValueKind::Table(keys) => return table_schema(key.doc, keys, &[]),
+        ValueKind::Map(keys) => {
+            schema["type"] = json!("object");
+            schema["additionalProperties"] = table_schema(key.doc, keys, &[]);
+        }
+        ValueKind::Flags => {
+            let per_compiler = table_schema("Flags per compiler, selected by the target's compiler", FLAG_KEYS, &[]);
+            schema["oneOf"] = json!([{ "type": "array", "items": { "type": "string" } }, per_compiler]);
+        }
+        ValueKind::String => schema["type"] = json!("string"),
+        ValueKind::Integer => schema["type"] = json!("integer"),
+        ValueKind::Boolean => schema["type"] = json!("boolean"),
+        ValueKind::Standard => {
+            schema["oneOf"] = json!([{ "type": "integer" }, { "type": "string", "pattern": "^(c\\+\\+|gnu\\+\\+|c|gnu)?[0-9]+$" }]);
+        }
+        ValueKind::StringArray => {
+            schema["type"] = json!("array");
+            schema["items"] = json!({ "type": "string" });
+        }
+        ValueKind::Enum(values) => {
+            schema["type"] = json!("string");
+            schema["enum"] = json!(values);
+        }
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_describes_targets() {
+        let schema: Value = serde_json::from_str(&format!("{:#}", build_toml_schema())).unwrap();
+        let target = schema.get("properties").and_then(|p| p.get("target")).and_then(|t| t.get("items")).unwrap();
+        assert_eq!(target.get("required").and_then(Value::as_array).map(|r| r.len()), Some(1));
+        let props = target.get("properties").unwrap();
+        let types: Vec<&str> = props
+            .get("type")
+            .and_then(|t| t.get("enum"))
+            .and_then(Value::as_array)
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        assert_eq!(types, vec!["executable", "static_lib", "shared_lib", "interface", "custom", "group", "benchmark", "test"]);
+        assert_eq!(props.get("sources").and_then(|s| s.get("type")).and_then(Value::as_str), Some("array"));
+        assert_eq!(props.get("cxx_standard").and_then(|s| s.get("oneOf")).and_then(Value::as_array).map(|o| o.len()), Some(2));
+    }
+}
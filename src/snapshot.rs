@@ -0,0 +1,334 @@
+// ============================================================================
+// snapshot.rs — Cached resolved project (`.oximake/snapshot.toml`)
+// ============================================================================
+//
+// In large projects TOML parsing, validation and glob expansion dominate the
+// startup of a no-op build. After a successful parse the resolved project is
+// saved together with what it was derived from:
+//
+//   - mtimes of every build.toml read and of every source directory (a new
+//     or removed source changes its directory's mtime), of [dependencies]
+//     archives and of oximake.lock
+//   - the files each target's source globs matched (after `exclude`): the
+//     globs are expanded again, so a source in a new subdirectory or the
+//     first match of an empty glob is noticed too
+//   - the values of the environment variables the build.toml files reference
+//   - the `--set` overrides, the requested config path and the ngmake version
+//
+// The next build reuses the snapshot when none of these changed. The daemon
+// uses the same stamps and globs to decide when to reparse.
+// WatchedFiles tells file watchers (the GUI) which changed files matter.
+// ============================================================================
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{glob_sources, ResolvedProject};
+use crate::discovery::SourceFinder;
+use crate::history;
+use crate::interpolate::Variables;
+
+/// Snapshot file name inside the project state directory.
+pub const SNAPSHOT_NAME: &str = "snapshot.toml";
+
+pub fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Files and directories the resolved project depends on, with their current mtime:
+/// the config files and the directories containing sources.
+pub fn stamps(project: &ResolvedProject) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut paths: Vec<PathBuf> = project.config_files.clone();
+    let mut dirs: Vec<PathBuf> = project
+        .targets
+        .values()
+        .flat_map(|t| t.sources.iter().filter_map(|s| s.parent().map(Path::to_path_buf)))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    paths.extend(dirs);
+    paths.extend(project.dependencies.values().filter_map(|d| d.archive.as_ref()).map(|a| project.root_dir.join(a)));
+    paths.push(crate::lock::lock_path(&project.root_dir));
+    paths.into_iter().map(|p| {
+        let t = mtime(&p);
+        (p, t)
+    }).collect()
+}
+
+/// The source globs of a target and the files they matched.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SourceGlobs {
+    dir: PathBuf,
+    patterns: Vec<String>,
+    exclude: Vec<String>,
+    matched: Vec<PathBuf>,
+}
+
+/// The source globs of every target with the files they match now.
+pub fn source_globs(project: &ResolvedProject) -> Vec<SourceGlobs> {
+    let finder = SourceFinder::new();
+    project
+        .targets
+        .values()
+        .filter(|t| t.source_patterns.iter().any(|p| p.contains(['*', '?', '['])))
+        .map(|t| SourceGlobs {
+            dir: t.working_dir.clone(),
+            patterns: t.source_patterns.clone(),
+            exclude: t.exclude.clone(),
+            matched: glob_sources(&t.source_patterns, &t.exclude, &t.working_dir, &finder).unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// True if a glob matches other files than it did in `globs` (a source was added or
+/// removed anywhere below it).
+pub fn globs_changed(globs: &[SourceGlobs]) -> bool {
+    let finder = SourceFinder::new();
+    globs
+        .iter()
+        .any(|g| glob_sources(&g.patterns, &g.exclude, &g.dir, &finder).unwrap_or_default() != g.matched)
+}
+
+/// Header extensions that are watched in every project (headers aren't listed as sources).
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx", "h++", "inl", "ipp", "tpp"];
+
+/// Which changed files affect a build of a project, for file watchers: its config
+/// files, sources and custom target inputs, plus any new file with the extension of
+/// a source, an input, a `[compilers]` rule or a header. Files in `.oximake/`, `.git/`,
+/// the output directories and custom target outputs are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct WatchedFiles {
+    files: HashSet<PathBuf>,
+    extensions: HashSet<String>,
+    ignored_dirs: Vec<PathBuf>,
+}
+
+impl WatchedFiles {
+    pub fn new(project: &ResolvedProject) -> Self {
+        let absolute = |p: &PathBuf| std::path::absolute(p).unwrap_or_else(|_| p.clone());
+        let targets = || project.targets.values().filter(|t| !t.prebuilt);
+        let files: HashSet<PathBuf> = project
+            .config_files
+            .iter()
+            .chain(targets().flat_map(|t| t.sources.iter().chain(&t.inputs)))
+            .map(absolute)
+            .collect();
+        let outputs: HashSet<PathBuf> = targets().flat_map(|t| &t.outputs).map(absolute).collect();
+        let extensions = files
+            .iter()
+            .filter(|f| !outputs.contains(*f) && f.file_name().is_none_or(|n| n != "build.toml"))
+            .filter_map(|f| f.extension().and_then(|e| e.to_str()).map(str::to_string))
+            .chain(project.compilers.keys().cloned())
+            .chain(HEADER_EXTENSIONS.iter().map(|e| e.to_string()))
+            .collect();
+        // An output directory containing the project (output_dir = ".") can't be ignored
+        let root = absolute(&project.root_dir);
+        let ignored_dirs = crate::prune::output_dirs(project).iter().map(absolute).filter(|d| !root.starts_with(d)).collect();
+        WatchedFiles { files: files.into_iter().filter(|f| !outputs.contains(f)).collect(), extensions, ignored_dirs }
+    }
+
+    /// True if a change of `path` (added, modified or removed) can affect the build.
+    /// Every build.toml is watched, so new includes are noticed even without a project.
+    pub fn contains(&self, path: &Path) -> bool {
+        if path.components().any(|c| c.as_os_str() == ".oximake" || c.as_os_str() == ".git") {
+            return false;
+        }
+        if path.file_name().is_some_and(|n| n == "build.toml") {
+            return true;
+        }
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if self.files.contains(&path) {
+            return true;
+        }
+        !self.ignored_dirs.iter().any(|d| path.starts_with(d))
+            && path.extension().and_then(|e| e.to_str()).is_some_and(|e| self.extensions.contains(e))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Stamp {
+    path: PathBuf,
+    /// Modification time as (seconds, nanoseconds) since the Unix epoch; None if missing
+    modified: Option<(u64, u32)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct EnvValue {
+    name: String,
+    value: Option<String>,
+}
+
+/// Everything a snapshot is valid for.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Inputs {
+    version: String,
+    config: PathBuf,
+    standalone: bool,
+    overrides: BTreeMap<String, String>,
+    env: Vec<EnvValue>,
+    stamps: Vec<Stamp>,
+    globs: Vec<SourceGlobs>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    inputs: Inputs,
+    project: ResolvedProject,
+}
+
+fn stamp((path, time): (PathBuf, Option<SystemTime>)) -> Stamp {
+    let modified = time
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| (d.as_secs(), d.subsec_nanos()));
+    Stamp { path, modified }
+}
+
+/// `${env:NAME}` references in the config files, with their current values.
+fn env_values(config_files: &[PathBuf]) -> Vec<EnvValue> {
+    let pattern = regex::Regex::new(r"\$\{env:([^}]+)\}").expect("valid regex");
+    let mut names: Vec<String> = config_files
+        .iter()
+        .filter_map(|f| std::fs::read_to_string(f).ok())
+        .flat_map(|text| pattern.captures_iter(&text).map(|c| c[1].to_string()).collect::<Vec<_>>())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| {
+            let value = std::env::var(&name).ok();
+            EnvValue { name, value }
+        })
+        .collect()
+}
+
+fn snapshot_path(root_dir: &Path) -> PathBuf {
+    history::state_dir(root_dir).join(SNAPSHOT_NAME)
+}
+
+/// The project saved for `config_path` by a previous build, if nothing it was derived
+/// from changed since. `standalone`: `config_path` is the root (`--no-workspace`).
+pub fn load(config_path: &Path, overrides: &Variables, standalone: bool) -> Option<ResolvedProject> {
+    let root_dir = if standalone {
+        config_path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf()
+    } else {
+        crate::project_root_dir(config_path)
+    };
+    let text = std::fs::read_to_string(snapshot_path(&root_dir)).ok()?;
+    let saved: SnapshotFile = toml::from_str(&text).ok()?;
+    let inputs = &saved.inputs;
+    let unchanged = inputs.version == env!("CARGO_PKG_VERSION")
+        && inputs.config == config_path
+        && inputs.standalone == standalone
+        && inputs.overrides == *overrides
+        && inputs.stamps.iter().all(|s| stamp((s.path.clone(), mtime(&s.path))) == *s)
+        && inputs.env.iter().all(|e| std::env::var(&e.name).ok() == e.value)
+        && !globs_changed(&inputs.globs);
+    unchanged.then_some(saved.project)
+}
+
+/// Saves `project` (parsed from `config_path`) for the next build.
+pub fn save(project: &ResolvedProject, config_path: &Path, overrides: &Variables, standalone: bool) -> Result<(), String> {
+    let inputs = Inputs {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config: config_path.to_path_buf(),
+        standalone,
+        overrides: overrides.clone(),
+        env: env_values(&project.config_files),
+        stamps: stamps(project).into_iter().map(stamp).collect(),
+        globs: source_globs(project),
+    };
+    let content = toml::to_string(&SnapshotFile { inputs, project: project.clone() })
+        .map_err(|e| format!("Failed to serialize project snapshot: {}", e))?;
+    let path = snapshot_path(&project.root_dir);
+    std::fs::create_dir_all(history::state_dir(&project.root_dir))
+        .map_err(|e| format!("Failed to create '{}': {}", history::state_dir(&project.root_dir).display(), e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::config::parse_build_file;
+
+    #[test]
+    fn test_snapshot_is_reused_until_an_input_changes() {
+        let dir = TempDir::new("snapshot");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("include")).unwrap();
+        dir.write("src/main.cpp", "int main() {}\n");
+        let config = dir.join("build.toml");
+        std::fs::write(&config, "cxx_standard = \"gnu++20\"\n[[target]]\nname = \"app\"\nsources = [\"src/*.cpp\"]\ninclude_dirs = [\"${env:NGMAKE_SNAPSHOT_TEST}\"]\n").unwrap();
+
+        std::env::set_var("NGMAKE_SNAPSHOT_TEST", "include");
+        let project = parse_build_file(&config, false).unwrap();
+        let vars = Variables::new();
+        save(&project, &config, &vars, false).unwrap();
+        let cached = load(&config, &vars, false).expect("fresh snapshot");
+        assert_eq!(cached.targets["app"].sources, project.targets["app"].sources);
+        assert_eq!(cached.targets["app"].cxx_standard, project.targets["app"].cxx_standard);
+
+        assert!(load(&config, &vars, true).is_none());
+        std::env::set_var("NGMAKE_SNAPSHOT_TEST", "include2");
+        assert!(load(&config, &vars, false).is_none());
+        std::env::set_var("NGMAKE_SNAPSHOT_TEST", "include");
+        // A new source file changes its directory's mtime
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        dir.write("src/util.cpp", "");
+        assert!(load(&config, &vars, false).is_none());
+        std::env::remove_var("NGMAKE_SNAPSHOT_TEST");
+    }
+
+    #[test]
+    fn test_snapshot_notices_sources_in_new_subdirectories() {
+        let dir = TempDir::new("snapshot-globs");
+        dir.write("src/app/main.cpp", "int main() {}\n");
+        dir.write("src/gen/skip.cpp", "");
+        let config = dir.write("build.toml", "[[target]]\nname = \"app\"\nsources = [\"src/**/*.cpp\"]\nexclude = [\"src/gen/**\"]\n");
+
+        let project = parse_build_file(&config, false).unwrap();
+        let vars = Variables::new();
+        save(&project, &config, &vars, false).unwrap();
+        assert!(load(&config, &vars, false).is_some());
+
+        // Excluded sources don't invalidate the snapshot
+        dir.write("src/gen/more.cpp", "");
+        assert!(load(&config, &vars, false).is_some());
+        // Only src/app is stamped: src/extra is new, but the glob matches more now
+        dir.write("src/extra/extra.cpp", "");
+        assert!(load(&config, &vars, false).is_none());
+    }
+
+    #[test]
+    fn test_watched_files_follow_the_project_sources_and_rules() {
+        let dir = TempDir::new("watched-files");
+        dir.write("src/main.mm", "");
+        dir.write("proto/api.proto", "");
+        let config = dir.write(
+            "build.toml",
+            "[compilers.ispc]\ncommand = \"ispc\"\n\n[[target]]\nname = \"app\"\nsources = [\"src/*.mm\"]\n\n\
+             [[target]]\nname = \"gen\"\ntype = \"custom\"\ncommands = [\"true\"]\ninputs = [\"proto/api.proto\", \"data/table.csv\"]\noutputs = [\"src/table.inc\"]\n",
+        );
+        let project = parse_build_file(&config, false).unwrap();
+        let watched = WatchedFiles::new(&project);
+
+        assert!(watched.contains(&config));
+        assert!(watched.contains(&dir.join("src/main.mm")));
+        assert!(watched.contains(&dir.join("src/other.mm")));
+        assert!(watched.contains(&dir.join("proto/new.proto")));
+        assert!(watched.contains(&dir.join("data/table.csv")));
+        assert!(watched.contains(&dir.join("kernels/blur.ispc")));
+        assert!(watched.contains(&dir.join("include/app.hpp")));
+        assert!(watched.contains(&dir.join("lib/build.toml")));
+        assert!(!watched.contains(&dir.join("src/table.inc")));
+        assert!(!watched.contains(&dir.join("notes.txt")));
+        assert!(!watched.contains(&dir.join("build/app.o")));
+        assert!(!watched.contains(&dir.join("build/main.mm")));
+        assert!(!watched.contains(&dir.join(".oximake/snapshot.toml")));
+        assert!(!WatchedFiles::default().contains(&dir.join("src/main.mm")));
+    }
+}
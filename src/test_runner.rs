@@ -0,0 +1,356 @@
+// ============================================================================
+// test_runner.rs — Test targets and `ngm test`
+// ============================================================================
+//
+// A `type = "test"` target is compiled and linked like an executable; `ngm
+// test` builds the selected tests, runs each with its `args` from the
+// project root and counts it as passed when it exits with 0. The output of
+// a failing test is printed; passing tests stay quiet unless -v.
+//
+// Tests run in parallel on a pool of `--test-jobs` threads (default: one
+// per CPU), independent of the build's -j. A test running longer than its
+// `test_timeout_secs` (or --timeout) is killed with its process group, and
+// a failed or timed-out test is rerun up to `retries` (or --retries) times;
+// one that passes on a rerun is reported as flaky. With --memcheck the
+// tests run under valgrind (see memcheck.rs) and its findings are added to
+// the results.
+//
+// For CI the results can also be written as JUnit XML (`--junit`), which
+// GitLab (artifacts:reports:junit), Jenkins (junit step) and GitHub actions
+// such as test-reporter render natively, and as JSON (`--json`):
+//
+//   <testsuites name="demo" tests="2" failures="1" errors="0" time="0.412">
+//     <testsuite name="demo" tests="2" failures="1" errors="0" skipped="0" time="0.412">
+//       <testcase name="test_core" classname="demo" time="0.012"/>
+//       <testcase name="test_net" classname="demo" time="0.400">
+//         <failure message="exit status: 1" type="exit">...output...</failure>
+//       </testcase>
+// ============================================================================
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+use crate::compiler;
+use crate::config::ResolvedProject;
+use crate::memcheck::{self, MemcheckConfig, MemcheckReport};
+use crate::run;
+
+/// Command-line settings of `ngm test`; `timeout` and `retries` override the targets' own.
+#[derive(Debug, Clone, Default)]
+pub struct TestSettings {
+    /// Tests run at once (None = one per CPU)
+    pub jobs: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub retries: Option<u32>,
+    /// Run the tests under valgrind
+    pub memcheck: Option<MemcheckConfig>,
+}
+
+/// Outcome of one test target.
+#[derive(Debug, Clone, Default)]
+pub struct TestResult {
+    pub target_name: String,
+    pub passed: bool,
+    pub duration: Duration,
+    /// How the test ended ("exit status: 1", "signal: 11 (SIGSEGV)", "timed out after 30s")
+    pub status: String,
+    /// How each failed run before the last one ended (only when retried)
+    pub failed_attempts: Vec<String>,
+    /// The test could not be started (JUnit <error> instead of <failure>)
+    pub error: Option<String>,
+    /// stdout followed by stderr
+    pub output: String,
+    /// valgrind's findings (--memcheck)
+    pub memcheck: Option<MemcheckReport>,
+}
+
+impl TestResult {
+    /// Passed, but only after failing first.
+    pub fn flaky(&self) -> bool {
+        self.passed && !self.failed_attempts.is_empty()
+    }
+}
+
+/// Runs `tests` in parallel (see the module comment) and returns their results in the
+/// same order. `on_done` is called from the worker threads as each test finishes.
+pub fn run_tests(project: &ResolvedProject, tests: &[String], settings: &TestSettings, on_done: impl Fn(&TestResult) + Sync) -> Result<Vec<TestResult>, String> {
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = settings.jobs {
+        pool = pool.num_threads(jobs.max(1));
+    }
+    let pool = pool.build().map_err(|e| format!("Failed to start test workers: {}", e))?;
+    Ok(pool.install(|| {
+        tests
+            .par_iter()
+            .map(|name| {
+                let result = run_test(project, name, settings);
+                on_done(&result);
+                result
+            })
+            .collect()
+    }))
+}
+
+/// Runs the built test `name` with its args (working directory = project root), rerunning
+/// it while it fails and retries are left.
+pub fn run_test(project: &ResolvedProject, name: &str, settings: &TestSettings) -> TestResult {
+    let target = project.targets.get(name);
+    let timeout = settings.timeout.or_else(|| target.and_then(|t| t.test_timeout_secs).map(Duration::from_secs));
+    let retries = settings.retries.or_else(|| target.and_then(|t| t.retries)).unwrap_or(0);
+    let mut result = run_once(project, name, timeout, settings.memcheck.as_ref());
+    let mut failed_attempts = Vec::new();
+    while !result.passed && result.error.is_none() && (failed_attempts.len() as u32) < retries {
+        failed_attempts.push(result.status.clone());
+        result = run_once(project, name, timeout, settings.memcheck.as_ref());
+    }
+    result.failed_attempts = failed_attempts;
+    result
+}
+
+fn run_once(project: &ResolvedProject, name: &str, timeout: Option<Duration>, memcheck: Option<&MemcheckConfig>) -> TestResult {
+    let mut result = TestResult { target_name: name.to_string(), ..Default::default() };
+    let args = project.targets.get(name).map(|t| t.args.clone()).unwrap_or_default();
+    let mut command = match run::run_command(project, name, &args) {
+        Ok(c) => c,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+    let log = std::env::temp_dir().join(format!("ngmake_memcheck_{}_{}.log", std::process::id(), name));
+    if let Some(memcheck) = memcheck {
+        command = memcheck.wrap(&command, &log);
+    }
+    let start = Instant::now();
+    // Killed with its process group on timeout, like compile commands
+    let output = compiler::run_tracked_with_input(command, timeout, Stdio::null());
+    result.duration = start.elapsed();
+    match output {
+        Ok(out) => {
+            result.passed = out.status.success();
+            result.status = out.status.to_string();
+            result.output = String::from_utf8_lossy(&out.stdout).to_string();
+            result.output.push_str(&String::from_utf8_lossy(&out.stderr));
+            if memcheck.is_some() {
+                let text = std::fs::read_to_string(&log).unwrap_or_default();
+                match memcheck::parse_log(&text) {
+                    // Errors fail the test even when it exited with 0
+                    Some(report) if report.errors > 0 && result.passed => {
+                        result.passed = false;
+                        result.status = format!("memcheck: {} errors", report.errors);
+                        result.memcheck = Some(report);
+                    }
+                    Some(report) => result.memcheck = Some(report),
+                    // valgrind itself failed; its log says why
+                    None => result.output.push_str(&text),
+                }
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            result.status = format!("timed out after {}s", timeout.unwrap_or_default().as_secs());
+        }
+        Err(e) => result.error = Some(format!("Failed to run '{}': {}", name, e)),
+    }
+    if memcheck.is_some() {
+        let _ = std::fs::remove_file(&log);
+    }
+    result
+}
+
+/// `[PASS]` / `[FAIL]` line of one test, followed by its output if it failed (or `verbose`).
+pub fn format_result(r: &TestResult, verbose: bool) -> Vec<String> {
+    let attempts = r.failed_attempts.len() + 1;
+    let mut lines = vec![match (&r.error, r.passed) {
+        (Some(e), _) => format!("  [ERROR] {}: {}", r.target_name, e),
+        (None, true) if r.flaky() => format!("  [PASS] {} ({:.2?}, flaky: passed on attempt {} after {})", r.target_name, r.duration, attempts, r.failed_attempts.join(", ")),
+        (None, true) => format!("  [PASS] {} ({:.2?})", r.target_name, r.duration),
+        (None, false) if attempts > 1 => format!("  [FAIL] {} ({}, {:.2?}, {} attempts)", r.target_name, r.status, r.duration, attempts),
+        (None, false) => format!("  [FAIL] {} ({}, {:.2?})", r.target_name, r.status, r.duration),
+    }];
+    let findings = r.memcheck.iter().flat_map(|m| &m.findings);
+    lines.extend(findings.map(|f| format!("    [MEMCHECK] {}", f)));
+    if verbose || !r.passed {
+        lines.extend(r.output.lines().map(|l| format!("    {}", l)));
+    }
+    lines
+}
+
+/// Totals line; `wall_time` is how long the (parallel) run took.
+pub fn summary(results: &[TestResult], wall_time: Duration) -> String {
+    let passed = results.iter().filter(|r| r.passed).count();
+    let flaky = results.iter().filter(|r| r.flaky()).count();
+    let flaky = if flaky > 0 { format!(" ({} flaky)", flaky) } else { String::new() };
+    format!("  {} tests: {} passed{}, {} failed in {:.2?}", results.len(), passed, flaky, results.len() - passed, wall_time)
+}
+
+/// Escapes text for XML content and attributes, dropping characters XML 1.0 doesn't allow
+/// (test output may contain terminal control codes).
+fn xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn failure_type(status: &str) -> &'static str {
+    if status.starts_with("timed out") {
+        "timeout"
+    } else if status.starts_with("memcheck") {
+        "memcheck"
+    } else {
+        "exit"
+    }
+}
+
+/// JUnit XML report: one <testsuite> named after the project, one <testcase> per test.
+pub fn junit_xml(project: &str, results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|r| r.error.is_none() && !r.passed).count();
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+    let time: f64 = results.iter().map(|r| r.duration.as_secs_f64()).sum();
+    let counts = format!("tests=\"{}\" failures=\"{}\" errors=\"{}\"", results.len(), failures, errors);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!("<testsuites name=\"{}\" {} time=\"{:.3}\">\n", xml(project), counts, time));
+    out.push_str(&format!("  <testsuite name=\"{}\" {} skipped=\"0\" time=\"{:.3}\">\n", xml(project), counts, time));
+    for r in results {
+        let case = format!("    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"", xml(&r.target_name), xml(project), r.duration.as_secs_f64());
+        match (&r.error, r.passed) {
+            (None, true) if r.output.is_empty() && !r.flaky() => out.push_str(&format!("{}/>\n", case)),
+            (None, true) => {
+                out.push_str(&format!("{}>\n", case));
+                // Rerun format of Maven surefire, shown by Jenkins as a flaky test
+                for status in &r.failed_attempts {
+                    out.push_str(&format!("      <flakyFailure message=\"{}\" type=\"{}\"/>\n", xml(status), failure_type(status)));
+                }
+                if !r.output.is_empty() {
+                    out.push_str(&format!("      <system-out>{}</system-out>\n", xml(&r.output)));
+                }
+                out.push_str("    </testcase>\n");
+            }
+            (None, false) => {
+                // valgrind's findings first, then the test's output
+                let mut text: String = r.memcheck.iter().flat_map(|m| &m.findings).map(|f| format!("{}\n", f)).collect();
+                text.push_str(&r.output);
+                out.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>\n",
+                    case,
+                    xml(&r.status),
+                    failure_type(&r.status),
+                    xml(&text)
+                ));
+            }
+            (Some(e), _) => out.push_str(&format!("{}>\n      <error message=\"{}\" type=\"run\"/>\n    </testcase>\n", case, xml(e))),
+        }
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+/// The same results as JSON (`--json`).
+pub fn results_json(project: &str, results: &[TestResult]) -> Value {
+    let passed = results.iter().filter(|r| r.passed).count();
+    let tests: Vec<Value> = results
+        .iter()
+        .map(|r| {
+            json!({
+                "name": r.target_name,
+                "passed": r.passed,
+                "duration_ms": r.duration.as_millis() as u64,
+                "status": r.status.as_str(),
+                "attempts": r.failed_attempts.len() + 1,
+                "flaky": r.flaky(),
+                "error": r.error,
+                "output": r.output,
+                "memcheck": r.memcheck.as_ref().map(|m| json!({ "errors": m.errors, "findings": m.findings })),
+            })
+        })
+        .collect();
+    json!({
+        "project": project,
+        "tests": results.len(),
+        "passed": passed,
+        "failed": results.len() - passed,
+        "duration_ms": results.iter().map(|r| r.duration).sum::<Duration>().as_millis() as u64,
+        "results": tests,
+    })
+}
+
+/// Writes `content` to `path`, creating its directory.
+pub fn write_report(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    std::fs::write(path, content).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_junit_xml_reports_failures_errors_and_escaped_output() {
+        let results = vec![
+            TestResult { target_name: "test_core".to_string(), passed: true, duration: Duration::from_millis(12), status: "exit status: 0".to_string(), ..Default::default() },
+            TestResult {
+                target_name: "test_net".to_string(),
+                duration: Duration::from_millis(400),
+                status: "exit status: 1".to_string(),
+                output: "expected <1> & got \"2\"\u{1b}[0m\n".to_string(),
+                ..Default::default()
+            },
+            TestResult { target_name: "test_io".to_string(), error: Some("Executable not found: build/test_io".to_string()), ..Default::default() },
+            TestResult {
+                target_name: "test_leak".to_string(),
+                status: "memcheck: 1 errors".to_string(),
+                memcheck: Some(MemcheckReport { errors: 1, findings: vec!["40 bytes in 1 blocks are definitely lost (at main (leak.cpp:9))".to_string()] }),
+                ..Default::default()
+            },
+        ];
+        let xml = junit_xml("demo", &results);
+        assert!(xml.contains("<testsuites name=\"demo\" tests=\"4\" failures=\"2\" errors=\"1\" time=\"0.412\">"), "{}", xml);
+        assert!(xml.contains("<testcase name=\"test_core\" classname=\"demo\" time=\"0.012\"/>"), "{}", xml);
+        assert!(xml.contains("<failure message=\"exit status: 1\" type=\"exit\">expected &lt;1&gt; &amp; got &quot;2&quot;[0m\n</failure>"), "{}", xml);
+        assert!(xml.contains("<error message=\"Executable not found: build/test_io\" type=\"run\"/>"), "{}", xml);
+        assert!(xml.contains("<failure message=\"memcheck: 1 errors\" type=\"memcheck\">40 bytes in 1 blocks are definitely lost (at main (leak.cpp:9))\n</failure>"), "{}", xml);
+
+        let json = results_json("demo", &results);
+        assert_eq!(json.get("failed").and_then(Value::as_u64), Some(3));
+        assert_eq!(summary(&results, Duration::from_millis(400)), "  4 tests: 1 passed, 3 failed in 400.00ms");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_flaky_tests_are_retried_and_hanging_ones_killed() {
+        use crate::config::{ResolvedTarget, TargetType};
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("tests");
+        let mut project = ResolvedProject { root_dir: dir.to_path_buf(), ..Default::default() };
+        // Fails on the first run only; never finishes
+        let scripts = [("test_flaky", "[ -e ran ] && exit 0; touch ran; echo first run; exit 1"), ("test_hang", "exec sleep 10")];
+        for (name, script) in scripts {
+            let path = dir.join(name);
+            std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            let target = ResolvedTarget { name: name.to_string(), target_type: TargetType::Test, output_dir: dir.to_path_buf(), ..Default::default() };
+            project.targets.insert(name.to_string(), target);
+        }
+
+        let settings = TestSettings { jobs: Some(2), timeout: Some(Duration::from_secs(1)), retries: Some(1), memcheck: None };
+        let tests = vec!["test_flaky".to_string(), "test_hang".to_string()];
+        let results = run_tests(&project, &tests, &settings, |_| {}).unwrap();
+        assert!(results[0].flaky(), "{:?}", results[0]);
+        assert_eq!(results[0].failed_attempts, vec!["exit status: 1"]);
+        assert!(!results[1].passed);
+        assert_eq!(results[1].status, "timed out after 1s");
+        assert_eq!(results[1].failed_attempts, vec!["timed out after 1s"]);
+        assert!(junit_xml("demo", &results).contains("<flakyFailure message=\"exit status: 1\" type=\"exit\"/>"));
+    }
+}
@@ -0,0 +1,52 @@
+// ============================================================================
+// test_util.rs — Fixtures shared by the unit tests
+// ============================================================================
+//
+// TempDir is a scratch directory below the system temp dir, named after the
+// test and the process ID so parallel test binaries don't collide. It is
+// removed when dropped, so a failing assertion doesn't leave it behind. It
+// derefs to its Path, so `dir.join("build.toml")` and `&dir` work as before.
+// ============================================================================
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty `ngmake_<name>_<pid>` directory (a leftover one is removed first).
+    pub fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("ngmake_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes `contents` to `path` inside the directory, creating parent directories.
+    pub fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let file = self.0.join(path);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&file, contents).unwrap();
+        file
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,281 @@
+// ============================================================================
+// timing.rs — Build timing report and critical path analysis
+// ============================================================================
+//
+// Summarizes the job timings recorded by the builder: per-target and
+// per-file durations, total wall time, parallelism efficiency (CPU time
+// spent in jobs vs. wall time × workers) and the critical path through the
+// target DAG, i.e. the dependency chain that bounds the build time.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::builder::{BuildResult, JobKind, JobTiming};
+use crate::config::ResolvedProject;
+use crate::dag::BuildOrder;
+
+/// Durations for one target.
+#[derive(Debug, Clone)]
+pub struct TargetTiming {
+    pub name: String,
+    /// Sum of all compile job durations
+    pub compile_time: Duration,
+    /// Longest single compile (compiles of one target run in parallel)
+    pub longest_compile: Duration,
+    pub link_time: Duration,
+    /// Per-source compile durations, slowest first
+    pub files: Vec<(PathBuf, Duration)>,
+}
+
+impl TargetTiming {
+    /// Time the target needs when its compiles run fully in parallel.
+    pub fn span(&self) -> Duration {
+        self.longest_compile + self.link_time
+    }
+
+    pub fn total(&self) -> Duration {
+        self.compile_time + self.link_time
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimingReport {
+    pub wall_time: Duration,
+    /// Sum of all job durations
+    pub cpu_time: Duration,
+    pub workers: usize,
+    /// Per-target timings, slowest (total) first
+    pub targets: Vec<TargetTiming>,
+    /// Dependency chain with the largest summed span, first built first
+    pub critical_path: Vec<String>,
+    pub critical_path_time: Duration,
+}
+
+impl TimingReport {
+    /// cpu_time / (wall_time × workers), 0.0..=1.0
+    pub fn efficiency(&self) -> f64 {
+        let capacity = self.wall_time.as_secs_f64() * self.workers.max(1) as f64;
+        if capacity > 0.0 {
+            (self.cpu_time.as_secs_f64() / capacity).min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Builds the timing report from a finished build.
+pub fn timing_report(project: &ResolvedProject, order: &BuildOrder, result: &BuildResult) -> TimingReport {
+    let targets = per_target_timings(&result.jobs);
+    let (critical_path, critical_path_time) = critical_path(project, order, &targets);
+    let mut sorted: Vec<TargetTiming> = targets.into_values().collect();
+    sorted.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.name.cmp(&b.name)));
+    TimingReport {
+        wall_time: result.wall_time,
+        cpu_time: result.jobs.iter().map(JobTiming::duration).sum(),
+        workers: result.workers,
+        targets: sorted,
+        critical_path,
+        critical_path_time,
+    }
+}
+
+/// Groups job timings by target.
+pub fn per_target_timings(jobs: &[JobTiming]) -> HashMap<String, TargetTiming> {
+    let mut map: HashMap<String, TargetTiming> = HashMap::new();
+    for job in jobs {
+        let entry = map.entry(job.target_name.clone()).or_insert_with(|| TargetTiming {
+            name: job.target_name.clone(),
+            compile_time: Duration::ZERO,
+            longest_compile: Duration::ZERO,
+            link_time: Duration::ZERO,
+            files: Vec::new(),
+        });
+        let d = job.duration();
+        match job.kind {
+            JobKind::Compile => {
+                entry.compile_time += d;
+                entry.longest_compile = entry.longest_compile.max(d);
+                if let Some(src) = &job.source {
+                    entry.files.push((src.clone(), d));
+                }
+            }
+            JobKind::Link => entry.link_time += d,
+        }
+    }
+    for t in map.values_mut() {
+        t.files.sort_by_key(|f| std::cmp::Reverse(f.1));
+    }
+    map
+}
+
+/// Longest path through the DAG where each target costs its span
+/// (longest compile + link). Returns the chain (dependencies first) and its length.
+pub fn critical_path(
+    project: &ResolvedProject,
+    order: &BuildOrder,
+    timings: &HashMap<String, TargetTiming>,
+) -> (Vec<String>, Duration) {
+    // finish[t] = span(t) + max(finish[dep]); levels are already topologically sorted
+    let mut finish: HashMap<&str, Duration> = HashMap::new();
+    let mut via: HashMap<&str, &str> = HashMap::new();
+    for name in order.levels.iter().flatten() {
+        let span = timings.get(name).map(TargetTiming::span).unwrap_or_default();
+        let mut best: Option<(&str, Duration)> = None;
+        if let Some(t) = project.targets.get(name) {
+            for dep in &t.deps {
+                if let Some(&f) = finish.get(dep.as_str()) {
+                    if best.map(|(_, b)| f > b).unwrap_or(true) {
+                        best = Some((dep.as_str(), f));
+                    }
+                }
+            }
+        }
+        let start = best.map(|(_, f)| f).unwrap_or_default();
+        if let Some((dep, _)) = best {
+            via.insert(name.as_str(), dep);
+        }
+        finish.insert(name.as_str(), start + span);
+    }
+
+    let Some((&end, &total)) = finish.iter().max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0))) else {
+        return (Vec::new(), Duration::ZERO);
+    };
+    let mut path = vec![end.to_string()];
+    let mut cur = end;
+    while let Some(&prev) = via.get(cur) {
+        path.push(prev.to_string());
+        cur = prev;
+    }
+    path.reverse();
+    (path, total)
+}
+
+/// Human-readable report lines. `top_files` limits the slowest-files list.
+pub fn format_report(report: &TimingReport, top_files: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    lines.push("─────────────────────────────────────────────────────".to_string());
+    lines.push("  Timing Report".to_string());
+    lines.push("─────────────────────────────────────────────────────".to_string());
+    lines.push(format!("  Wall time     : {:.2?}", report.wall_time));
+    lines.push(format!("  CPU time      : {:.2?} ({} workers)", report.cpu_time, report.workers));
+    lines.push(format!("  Parallelism   : {:.0}% efficiency", report.efficiency() * 100.0));
+    lines.push(format!(
+        "  Critical path : {:.2?} ({})",
+        report.critical_path_time,
+        report.critical_path.join(" → ")
+    ));
+    lines.push(String::new());
+    lines.push("  Targets (compile / link / total):".to_string());
+    for t in &report.targets {
+        lines.push(format!(
+            "    {:<30} {:>10.2?} {:>10.2?} {:>10.2?}",
+            t.name,
+            t.compile_time,
+            t.link_time,
+            t.total()
+        ));
+    }
+    let mut files: Vec<(&str, &Path, Duration)> = report
+        .targets
+        .iter()
+        .flat_map(|t| t.files.iter().map(move |(p, d)| (t.name.as_str(), p.as_path(), *d)))
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.2));
+    if !files.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("  Slowest files (top {}):", top_files.min(files.len())));
+        for (target, path, d) in files.into_iter().take(top_files) {
+            lines.push(format!("    {:>10.2?}  {} [{}]", d, path.display(), target));
+        }
+    }
+    lines.push("─────────────────────────────────────────────────────".to_string());
+    lines
+}
+
+fn ms(d: Duration) -> f64 {
+    (d.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+/// Serializable form of the report (durations in milliseconds).
+pub fn report_json(report: &TimingReport) -> Value {
+    let targets: Vec<Value> = report
+        .targets
+        .iter()
+        .map(|t| {
+            let files: Vec<Value> = t
+                .files
+                .iter()
+                .map(|(p, d)| json!({ "source": p.display().to_string(), "ms": ms(*d) }))
+                .collect();
+            json!({
+                "name": t.name,
+                "compile_ms": ms(t.compile_time),
+                "link_ms": ms(t.link_time),
+                "total_ms": ms(t.total()),
+                "files": files,
+            })
+        })
+        .collect();
+    json!({
+        "wall_ms": ms(report.wall_time),
+        "cpu_ms": ms(report.cpu_time),
+        "workers": report.workers,
+        "efficiency": report.efficiency(),
+        "critical_path": report.critical_path,
+        "critical_path_ms": ms(report.critical_path_time),
+        "targets": targets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ResolvedTarget, TargetType};
+
+    fn target(name: &str, deps: Vec<&str>) -> ResolvedTarget {
+        ResolvedTarget {
+            name: name.to_string(),
+            target_type: TargetType::StaticLib,
+            deps: deps.into_iter().map(String::from).collect(),
+            output_dir: PathBuf::from("build"),
+            ..Default::default()
+        }
+    }
+
+    fn link(name: &str, ms: u64) -> JobTiming {
+        JobTiming {
+            kind: JobKind::Link,
+            target_name: name.to_string(),
+            source: None,
+            worker: 0,
+            start: Duration::ZERO,
+            end: Duration::from_millis(ms),
+            success: true,
+            up_to_date: false,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_critical_path_follows_slowest_chain() {
+        // app depends on fast and slow; the path must go through slow
+        let mut targets = HashMap::new();
+        targets.insert("fast".to_string(), target("fast", vec![]));
+        targets.insert("slow".to_string(), target("slow", vec![]));
+        targets.insert("app".to_string(), target("app", vec!["fast", "slow"]));
+        let project = ResolvedProject {
+            name: "t".to_string(),
+            targets,
+            ..Default::default()
+        };
+        let order = crate::dag::build_order(&project).unwrap();
+        let timings = per_target_timings(&[link("fast", 10), link("slow", 50), link("app", 5)]);
+        let (path, total) = critical_path(&project, &order, &timings);
+        assert_eq!(path, vec!["slow", "app"]);
+        assert_eq!(total, Duration::from_millis(55));
+    }
+}
@@ -0,0 +1,296 @@
+// ============================================================================
+// toolchain.rs — compiler identity tracking and version requirements
+// ============================================================================
+//
+// Objects are up to date when they are newer than their sources, so after a
+// toolchain upgrade the old objects would be linked with new ones (ABI-mixed
+// builds). Every build therefore records which compiler compiled each
+// target, in .oximake/compilers.toml:
+//
+//   [compilers."g++"]
+//   path = "/usr/bin/x86_64-linux-gnu-g++-13"
+//   size = 1035840
+//   modified = 1718000000
+//   version = "g++ (Ubuntu 13.2.0-23ubuntu4) 13.2.0"
+//
+//   [targets]
+//   app = "g++ (Ubuntu 13.2.0-23ubuntu4) 13.2.0 [/usr/bin/x86_64-linux-gnu-g++-13]"
+//
+// A compiler's identity is the first line of `--version` and the resolved
+// binary (symlinks followed). `--version` only runs again when the binary's
+// path, size or modification time changed. When a target's identity differs
+// from the recorded one, its objects are removed before the build, so it is
+// recompiled and relinked. Compilers that can't be found or run aren't
+// tracked (the compile reports the real error).
+//
+// `requires = { gcc = ">=12", clang = ">=15" }` in [project] states the
+// compiler versions the project needs. Before a build, the compilers of the
+// targets being built are identified the same way and their version (the
+// first dotted number of `--version`) is checked, so an old compiler fails
+// with one clear message instead of a syntax error deep in a C++20 header.
+// "gcc" applies to gcc/g++ and "clang" to clang/clang++, whatever the
+// program is called (g++ that is really clang counts as clang).
+// ============================================================================
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiler;
+use crate::config::{ResolvedProject, ResolvedTarget, TargetType};
+use crate::dag::BuildOrder;
+use crate::history;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+struct State {
+    /// Compiler program (as in the compile command) → identity
+    #[serde(default)]
+    compilers: BTreeMap<String, CompilerId>,
+    /// Target → identities of the compilers its objects were compiled with
+    #[serde(default)]
+    targets: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+struct CompilerId {
+    path: String,
+    size: u64,
+    modified: u64,
+    version: String,
+}
+
+impl CompilerId {
+    fn label(&self) -> String {
+        format!("{} [{}]", self.version, self.path)
+    }
+}
+
+/// `.oximake/compilers.toml`
+pub fn state_path(root_dir: &Path) -> PathBuf {
+    history::state_dir(root_dir).join("compilers.toml")
+}
+
+/// Identity of `program`, reusing `known` while the binary is unchanged.
+fn identify(program: &str, known: Option<&CompilerId>) -> Option<CompilerId> {
+    let located = if Path::new(program).components().count() > 1 { Some(PathBuf::from(program)) } else { compiler::find_in_path(program) };
+    let path = std::fs::canonicalize(located?).ok()?;
+    let meta = std::fs::metadata(&path).ok()?;
+    let modified = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs());
+    let path = path.to_string_lossy().to_string();
+    if let Some(known) = known.filter(|k| k.path == path && k.size == meta.len() && k.modified == modified) {
+        return Some(known.clone());
+    }
+    let output = Command::new(&path).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let version = text.lines().next().unwrap_or_default().trim().to_string();
+    Some(CompilerId { path, size: meta.len(), modified, version })
+}
+
+/// Compiler programs of a target's sources (`[compilers]` rules included, launchers not).
+fn programs(target: &ResolvedTarget) -> BTreeSet<String> {
+    target
+        .sources
+        .iter()
+        .map(|source| compiler::compile_command(target, source, &compiler::object_path(target, source)).0)
+        .collect()
+}
+
+/// Targets whose recorded identity differs from the current one, with (recorded, current).
+fn changed<'a>(recorded: &'a BTreeMap<String, String>, current: &'a BTreeMap<String, String>) -> Vec<(&'a str, &'a str, &'a str)> {
+    current
+        .iter()
+        .filter_map(|(name, now)| recorded.get(name).filter(|was| *was != now).map(|was| (name.as_str(), was.as_str(), now.as_str())))
+        .collect()
+}
+
+/// Removes the objects of targets in `order` whose compiler changed since they were
+/// compiled and records the current compilers. Returns one line per invalidated target.
+pub fn invalidate_changed(project: &ResolvedProject, order: &BuildOrder) -> Vec<String> {
+    let path = state_path(&project.root_dir);
+    let mut state: State = std::fs::read_to_string(&path).ok().and_then(|text| toml::from_str(&text).ok()).unwrap_or_default();
+    let before = state.clone();
+
+    let mut current = BTreeMap::new();
+    let targets = order.levels.iter().flatten().filter_map(|name| project.targets.get(name));
+    for target in targets.filter(|t| !t.prebuilt && !t.sources.is_empty() && !matches!(t.target_type, TargetType::Custom | TargetType::Interface | TargetType::Group)) {
+        let mut labels = Vec::new();
+        for program in programs(target) {
+            let Some(id) = identify(&program, state.compilers.get(&program)) else { continue };
+            labels.push(id.label());
+            state.compilers.insert(program, id);
+        }
+        if !labels.is_empty() {
+            current.insert(target.name.clone(), labels.join("; "));
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (name, was, now) in changed(&state.targets, &current) {
+        let obj_dir = project.targets[name].output_dir.join("obj").join(name);
+        if obj_dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&obj_dir) {
+                lines.push(format!("[WARN] Failed to remove '{}': {}", obj_dir.display(), e));
+                continue;
+            }
+        }
+        lines.push(format!("[TOOLCHAIN] Compiler of '{}' changed, recompiling: {} → {}", name, was, now));
+    }
+    state.targets.extend(current);
+    if state != before {
+        let _ = std::fs::create_dir_all(history::state_dir(&project.root_dir));
+        if let Ok(text) = toml::to_string(&state) {
+            let _ = std::fs::write(&path, text);
+        }
+    }
+    lines
+}
+
+// ---------------------------------------------------------------------------
+// Version requirements
+// ---------------------------------------------------------------------------
+
+/// Compiler families that `requires` can name.
+pub const FAMILIES: &[&str] = &["gcc", "clang"];
+
+/// A parsed requirement such as ">=12" or ">=12.2, <15" (all comparisons must hold).
+/// Only the components a comparison names are compared: ">=12" accepts 12.1.0,
+/// "<15" rejects 15.0.1 and "=13" accepts any 13.x.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    comparisons: Vec<(Op, Vec<u64>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl VersionReq {
+    pub fn parse(text: &str) -> Result<VersionReq, String> {
+        let mut comparisons = Vec::new();
+        for part in text.split(',').map(str::trim) {
+            let (op, version) = [(">=", Op::Ge), ("<=", Op::Le), ("==", Op::Eq), (">", Op::Gt), ("<", Op::Lt), ("=", Op::Eq)]
+                .iter()
+                .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| (*op, rest.trim())))
+                .ok_or_else(|| format!("invalid version requirement '{}' (expected e.g. \">=12\")", text))?;
+            let version = version
+                .split('.')
+                .map(|c| c.parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid version '{}' in requirement '{}'", version, text))?;
+            comparisons.push((op, version));
+        }
+        Ok(VersionReq { comparisons })
+    }
+
+    pub fn matches(&self, version: &[u64]) -> bool {
+        self.comparisons.iter().all(|(op, wanted)| {
+            let actual: Vec<u64> = (0..wanted.len()).map(|i| version.get(i).copied().unwrap_or(0)).collect();
+            let ordering = actual.cmp(wanted);
+            match op {
+                Op::Eq => ordering.is_eq(),
+                Op::Lt => ordering.is_lt(),
+                Op::Le => ordering.is_le(),
+                Op::Gt => ordering.is_gt(),
+                Op::Ge => ordering.is_ge(),
+            }
+        })
+    }
+}
+
+/// The version in a `--version` line: its first dotted number
+/// ("g++ (Ubuntu 13.2.0-23ubuntu4) 13.2.0" → 13.2.0, "Apple clang version 15.0.0 (...)" → 15.0.0).
+fn parse_version(line: &str) -> Option<Vec<u64>> {
+    line.split(|c: char| c.is_whitespace() || c == '(' || c == ')').find_map(|word| {
+        let number: String = word.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        let number = number.trim_end_matches('.');
+        if !number.contains('.') {
+            return None;
+        }
+        number.split('.').map(|c| c.parse().ok()).collect()
+    })
+}
+
+/// The `requires` family of a compiler, from its `--version` line and program name.
+fn family(program: &str, version_line: &str) -> Option<&'static str> {
+    let line = version_line.to_lowercase();
+    let name = Path::new(program).file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if line.contains("clang") {
+        Some("clang")
+    } else if line.contains("gcc") || line.contains("g++") || name.contains("gcc") || name.contains("g++") {
+        Some("gcc")
+    } else {
+        None
+    }
+}
+
+/// Checks the compilers of the targets in `target_names` against `[project] requires`.
+/// Compilers that can't be found or whose version isn't recognized are not checked.
+pub fn verify_requirements(project: &ResolvedProject, target_names: &[String]) -> Result<(), String> {
+    if project.requires.is_empty() {
+        return Ok(());
+    }
+    let known: State = std::fs::read_to_string(state_path(&project.root_dir)).ok().and_then(|text| toml::from_str(&text).ok()).unwrap_or_default();
+    let mut checked = BTreeSet::new();
+    for name in target_names {
+        let Some(target) = project.targets.get(name) else { continue };
+        if target.prebuilt || matches!(target.target_type, TargetType::Custom | TargetType::Interface | TargetType::Group) {
+            continue;
+        }
+        for program in programs(target) {
+            if !checked.insert(program.clone()) {
+                continue;
+            }
+            let Some(id) = identify(&program, known.compilers.get(&program)) else { continue };
+            let Some(family) = family(&program, &id.version) else { continue };
+            let (Some(requirement), Some(version)) = (project.requires.get(family), parse_version(&id.version)) else { continue };
+            if !VersionReq::parse(requirement)?.matches(&version) {
+                let version = version.iter().map(u64::to_string).collect::<Vec<_>>().join(".");
+                return Err(format!(
+                    "build.toml requires {} {}, but target '{}' compiles with {} {} ({}). Install a newer compiler or point `compiler`/PATH at one",
+                    family, requirement, target.name, program, version, id.path
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_targets_with_a_recorded_different_compiler_change() {
+        let map = |entries: &[(&str, &str)]| entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>();
+        let recorded = map(&[("app", "g++ 12 [/usr/bin/g++-12]"), ("core", "gcc 12 [/usr/bin/gcc-12]")]);
+        let current = map(&[("app", "g++ 13 [/usr/bin/g++-13]"), ("core", "gcc 12 [/usr/bin/gcc-12]"), ("new", "g++ 13 [/usr/bin/g++-13]")]);
+        assert_eq!(changed(&recorded, &current), vec![("app", "g++ 12 [/usr/bin/g++-12]", "g++ 13 [/usr/bin/g++-13]")]);
+    }
+
+    #[test]
+    fn test_requirements_compare_the_named_components() {
+        let req = |text: &str| VersionReq::parse(text).unwrap();
+        assert!(req(">=12").matches(&[12, 1, 0]));
+        assert!(!req(">=12").matches(&[11, 4, 0]));
+        assert!(req(">=12.2, <15").matches(&[14, 0, 1]));
+        assert!(!req("<15").matches(&[15, 0, 1]));
+        assert!(req("=13").matches(&[13, 2, 0]));
+        assert!(!req(">12").matches(&[12, 9]));
+        assert!(VersionReq::parse("12").is_err());
+        assert!(VersionReq::parse(">=twelve").is_err());
+
+        assert_eq!(parse_version("g++ (Ubuntu 13.2.0-23ubuntu4) 13.2.0"), Some(vec![13, 2, 0]));
+        assert_eq!(parse_version("Apple clang version 15.0.0 (clang-1500.3.9.4)"), Some(vec![15, 0, 0]));
+        assert_eq!(family("g++", "Apple clang version 15.0.0 (clang-1500.3.9.4)"), Some("clang"));
+        assert_eq!(family("/usr/bin/g++-12", "g++-12 (Debian 12.2.0-14) 12.2.0"), Some("gcc"));
+        assert_eq!(family("nvcc", "nvcc: NVIDIA (R) Cuda compiler driver"), None);
+    }
+}
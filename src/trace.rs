@@ -0,0 +1,168 @@
+// ============================================================================
+// trace.rs — Chrome trace (chrome://tracing / Perfetto) output
+// ============================================================================
+//
+// Converts the per-job timings recorded by the builder into the Trace Event
+// Format: one complete ("X") event per compile/link job, one track per
+// worker thread. Gaps between events on a track are scheduling idle time.
+// ============================================================================
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::builder::{JobKind, JobTiming};
+
+/// A trace document: `{"traceEvents": [...], "displayTimeUnit": "ms"}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    pub trace_events: Vec<TraceEvent>,
+    pub display_time_unit: String,
+}
+
+/// A metadata event (`ph` "M": process and thread names) or a complete job event (`ph` "X",
+/// times in microseconds).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cat: Option<String>,
+    pub ph: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ts: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dur: Option<u64>,
+    pub pid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tid: Option<usize>,
+    pub args: TraceArgs,
+}
+
+/// `name` for metadata events; target, source and result for jobs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TraceArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+}
+
+fn metadata(name: &str, tid: Option<usize>, value: String) -> TraceEvent {
+    TraceEvent {
+        name: name.to_string(),
+        cat: None,
+        ph: "M".to_string(),
+        ts: None,
+        dur: None,
+        pid: 1,
+        tid,
+        args: TraceArgs { name: Some(value), ..Default::default() },
+    }
+}
+
+/// Builds the trace document for the given jobs.
+pub fn chrome_trace(project_name: &str, jobs: &[JobTiming]) -> Trace {
+    let mut events = vec![metadata("process_name", None, format!("ngmake: {}", project_name))];
+
+    let mut workers: Vec<usize> = jobs.iter().map(|j| j.worker).collect();
+    workers.sort_unstable();
+    workers.dedup();
+    for w in workers {
+        events.push(metadata("thread_name", Some(w), format!("worker {}", w)));
+    }
+
+    for job in jobs {
+        let (name, cat) = match job.kind {
+            JobKind::Compile => (
+                job.source
+                    .as_ref()
+                    .and_then(|s| s.file_name())
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                "compile",
+            ),
+            JobKind::Link => (format!("link {}", job.target_name), "link"),
+        };
+        events.push(TraceEvent {
+            name,
+            cat: Some(cat.to_string()),
+            ph: "X".to_string(),
+            ts: Some(job.start.as_micros() as u64),
+            dur: Some(job.duration().as_micros() as u64),
+            pid: 1,
+            tid: Some(job.worker),
+            args: TraceArgs {
+                target: Some(job.target_name.clone()),
+                source: job.source.as_ref().map(|s| s.display().to_string()),
+                success: Some(job.success),
+                ..Default::default()
+            },
+        });
+    }
+
+    Trace { trace_events: events, display_time_unit: "ms".to_string() }
+}
+
+/// Writes the trace to `path` (open it in chrome://tracing or ui.perfetto.dev).
+pub fn write_chrome_trace(path: &Path, project_name: &str, jobs: &[JobTiming]) -> Result<(), String> {
+    let text = serde_json::to_string(&chrome_trace(project_name, jobs))
+        .map_err(|e| format!("Failed to serialize trace: {}", e))?;
+    std::fs::write(path, text)
+        .map_err(|e| format!("Failed to write trace '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::BuildOptions;
+    use crate::test_util::TempDir;
+    use crate::{builder, config, dag};
+
+    #[test]
+    fn test_trace_of_a_build_has_one_event_per_job() {
+        let dir = TempDir::new("trace");
+        dir.write("src/a.cpp", "int a() { return 1; }\n");
+        dir.write("src/b.cpp", "int b() { return 2; }\n");
+        dir.write("src/main.cpp", "int a(); int b();\nint main() { return a() + b() - 3; }\n");
+        dir.write(
+            "build.toml",
+            "[project]\nname = \"traced\"\n\n[[target]]\nname = \"core\"\ntype = \"static_lib\"\nsources = [\"src/a.cpp\", \"src/b.cpp\"]\n\n\
+             [[target]]\nname = \"app\"\nsources = [\"src/main.cpp\"]\ndeps = [\"core\"]\n",
+        );
+        let project = config::parse_build_file(&dir.join("build.toml"), false).unwrap();
+        let order = dag::build_order(&project).unwrap();
+        let options = BuildOptions { quiet: true, jobs: Some(2), ..Default::default() };
+        let result = builder::build_project(&project, &order, &options, None, None);
+        assert!(result.success);
+
+        let path = dir.join("trace.json");
+        write_chrome_trace(&path, &project.name, &result.jobs).unwrap();
+        let trace: Trace = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let events = &trace.trace_events;
+
+        // Metadata first: the process name, then one name per worker track
+        assert_eq!(events[0].name, "process_name");
+        let (metadata, jobs): (Vec<&TraceEvent>, Vec<&TraceEvent>) = events.iter().partition(|e| e.ph == "M");
+        assert!(events.iter().take(metadata.len()).all(|e| e.ph == "M"));
+        assert!(jobs.iter().all(|e| e.ph == "X" && e.tid.is_some()));
+
+        let category = |e: &TraceEvent, cat: &str| e.cat.as_deref() == Some(cat);
+        let mut compiled: Vec<&str> = jobs.iter().filter(|e| category(e, "compile")).map(|e| e.name.as_str()).collect();
+        compiled.sort();
+        assert_eq!(compiled, vec!["a.cpp", "b.cpp", "main.cpp"]);
+        let links: Vec<&str> = jobs.iter().filter(|e| category(e, "link")).map(|e| e.name.as_str()).collect();
+        assert_eq!(links, vec!["link core", "link app"]);
+
+        // A link starts after the compiles of its target and the link of its dependency ended
+        let job = |name: &str| jobs.iter().find(|e| e.name == name).unwrap();
+        let end = |name: &str| job(name).ts.unwrap() + job(name).dur.unwrap();
+        let start = |name: &str| job(name).ts.unwrap();
+        assert!(start("link core") >= end("a.cpp").max(end("b.cpp")));
+        assert!(start("link app") >= end("main.cpp").max(end("link core")));
+    }
+}
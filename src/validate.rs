@@ -0,0 +1,869 @@
+// ============================================================================
+// validate.rs — build.toml validation with source locations
+// ============================================================================
+//
+// parse_build_file stops at the first problem and reports it as a plain
+// string; serde silently ignores misspelled keys. This pass walks the raw
+// TOML (toml_edit keeps byte spans) of the root build.toml and every
+// included file and reports all problems with file/line/column:
+//
+//   - TOML syntax errors
+//   - unknown keys (warning, with a "did you mean" suggestion)
+//   - values of the wrong type and invalid enum values (type, compiler, linker)
+//   - missing included files, unknown or duplicate targets
+//   - missing source files and include directories (errors, like
+//     parse_build_file) and library directories (warning)
+//
+// The key tables below describe the build.toml format; keep them in sync
+// with the structs in config.rs.
+// ============================================================================
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use toml_edit::{ImDocument, Item, Table, TableLike};
+
+use crate::config::{exclude_patterns, normalize_path, Standard};
+use crate::diagnostics::Severity;
+use crate::interpolate::{expand, Variables};
+
+/// Expected type of a build.toml value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Integer,
+    Boolean,
+    StringArray,
+    /// String restricted to the given values
+    Enum(&'static [&'static str]),
+    /// Sub-table with the given keys
+    Table(&'static [KeySpec]),
+    /// Table of user-named sub-tables, each with the given keys
+    Map(&'static [KeySpec]),
+    /// Language standard: integer (17) or name ("c++17", "gnu++17", "gnu11")
+    Standard,
+    /// Array of flags, or a table of per-compiler arrays (FLAG_KEYS)
+    Flags,
+}
+
+impl ValueKind {
+    fn describe(&self) -> String {
+        match self {
+            ValueKind::String => "a string".to_string(),
+            ValueKind::Integer => "an integer".to_string(),
+            ValueKind::Boolean => "a boolean".to_string(),
+            ValueKind::StringArray => "an array of strings".to_string(),
+            ValueKind::Enum(values) => format!("one of: {}", values.join(", ")),
+            ValueKind::Table(_) => "a table".to_string(),
+            ValueKind::Map(_) => "a table of tables".to_string(),
+            ValueKind::Standard => "an integer (e.g. 17) or a standard name (e.g. \"gnu++17\")".to_string(),
+            ValueKind::Flags => "an array of strings or a table with keys all, gcc, clang, msvc".to_string(),
+        }
+    }
+}
+
+/// One allowed key of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    pub name: &'static str,
+    pub kind: ValueKind,
+    pub doc: &'static str,
+}
+
+const fn key(name: &'static str, kind: ValueKind, doc: &'static str) -> KeySpec {
+    KeySpec { name, kind, doc }
+}
+
+pub const TARGET_TYPES: &[&str] = &["executable", "static_lib", "shared_lib", "interface", "custom", "group", "benchmark", "test"];
+pub const COMPILERS: &[&str] = &["gcc", "g++", "clang"];
+pub const LINKERS: &[&str] = &["bfd", "gold", "lld", "mold"];
+
+/// Keys of `[project]` (root build.toml; also allowed at the top level for old files).
+pub const PROJECT_KEYS: &[KeySpec] = &[
+    key("name", ValueKind::String, "Project name"),
+    key("version", ValueKind::String, "Project version"),
+    key("cxx_standard", ValueKind::Standard, "Default C++ standard (e.g. 17 → -std=c++17, \"gnu++17\" → -std=gnu++17)"),
+    key("c_standard", ValueKind::Standard, "Default C standard for .c sources (e.g. 11 → -std=c11, \"gnu11\")"),
+    key("linker", ValueKind::Enum(LINKERS), "Default linker for all targets"),
+    key("defines", ValueKind::StringArray, "Preprocessor definitions for every target (e.g. \"NDEBUG\")"),
+    key("compilers", ValueKind::Map(COMPILER_RULE_KEYS), "Source extension → compiler rule (e.g. [compilers.ispc])"),
+    key("timeout_secs", ValueKind::Integer, "Default timeout in seconds for every compile/link command"),
+    key("retry", ValueKind::Table(RETRY_KEYS), "Rerun failed compile/link jobs whose output matches a transient error"),
+    key("link_memory_limit", ValueKind::Integer, "MiB available to concurrent links (default: physical memory)"),
+    key("link_jobs", ValueKind::Integer, "Maximum number of concurrent links, e.g. 2 with -j 16 (default: -j)"),
+    key("compiler_launcher", ValueKind::String, "Program wrapping every compile, e.g. \"ccache\", \"distcc\", \"icecc\" or \"recc\" (Remote Execution API)"),
+    key("launcher_jobs", ValueKind::Integer, "Workers while the launcher distributes compiles (default: distcc -j)"),
+    key("bin_dir", ValueKind::String, "Directory collecting all executables after a successful build (shared libraries go to lib/ next to it)"),
+    key("bin_symlinks", ValueKind::Boolean, "Symlink into bin_dir instead of copying (default: false)"),
+    key("remote", ValueKind::Table(REMOTE_KEYS), "SSH build hosts that compile preprocessed sources ([remote])"),
+    key("cache", ValueKind::Table(CACHE_KEYS), "Content-addressed object cache shared across checkouts ([cache])"),
+    key("memcheck", ValueKind::Table(MEMCHECK_KEYS), "valgrind settings of `ngm test --memcheck` ([memcheck])"),
+    key("package", ValueKind::Table(PACKAGE_KEYS), "Contents of `ngm package` archives ([package])"),
+    key("dependencies", ValueKind::Map(DEPENDENCY_KEYS), "Prebuilt ngmake packages whose libraries targets can use as <name>::<library>"),
+    key("presets", ValueKind::Map(PRESET_KEYS), "Named build configurations for `ngm build --preset <name>`"),
+    key("defaults", ValueKind::Table(DEFAULTS_KEYS), "Flags, include_dirs, defines, compiler and output_dir merged into every target ([defaults])"),
+    key("default_targets", ValueKind::StringArray, "Targets a build without -t builds (default: all without exclude_from_all)"),
+    key("requires", ValueKind::Table(REQUIRES_KEYS), "Required compiler versions, checked before building (e.g. { gcc = \">=12\" })"),
+    key("includes", ValueKind::StringArray, "Paths to submodule build.toml files"),
+];
+
+/// Keys of the `[package]` table.
+pub const PACKAGE_KEYS: &[KeySpec] = &[
+    key("headers", ValueKind::StringArray, "Header directories, files or globs for include/ (directories keep their layout)"),
+    key("license_files", ValueKind::StringArray, "License and notice files for the top of the archive"),
+    key("targets", ValueKind::StringArray, "Targets to package (default: every executable and library)"),
+    key("prefix", ValueKind::String, "Install prefix of deb/rpm packages (default: /usr)"),
+    key("maintainer", ValueKind::String, "Package maintainer, \"Name <email>\" (required for deb)"),
+    key("description", ValueKind::String, "One-line summary, optionally followed by a longer description"),
+    key("license", ValueKind::String, "License name for rpm (e.g. \"MIT\")"),
+    key("homepage", ValueKind::String, "Project homepage URL"),
+    key("debug_info", ValueKind::Boolean, "Also package split debug info (.debug, .dSYM or .pdb) next to the outputs"),
+    key("depends", ValueKind::StringArray, "deb package dependencies (e.g. \"libssl3 (>= 3.0)\")"),
+    key("requires", ValueKind::StringArray, "rpm package dependencies (e.g. \"openssl-libs\")"),
+];
+
+/// Keys of a `[dependencies.<name>]` entry.
+pub const DEPENDENCY_KEYS: &[KeySpec] = &[
+    key("path", ValueKind::String, "Unpacked `ngm package` directory or install prefix"),
+    key("archive", ValueKind::String, "`ngm package` archive (.tar.gz or .zip), unpacked into .oximake/deps/"),
+    key("url", ValueKind::String, "URL of an `ngm package` archive, downloaded with curl and pinned in oximake.lock"),
+    key("sha256", ValueKind::String, "Expected SHA-256 of the archive (default: checked against oximake.lock)"),
+];
+
+/// Keys of the `[defaults]` table.
+pub const DEFAULTS_KEYS: &[KeySpec] = &[
+    key("flags", ValueKind::Flags, "Legacy flags before every target's own (skipped with no_default_flags)"),
+    key("compiler_flags", ValueKind::Flags, "Compiler-only flags before every target's own (skipped with no_default_flags)"),
+    key("linker_flags", ValueKind::Flags, "Linker-only flags before every target's own (skipped with no_default_flags)"),
+    key("include_dirs", ValueKind::StringArray, "Include directories (relative to the root build.toml) searched after every target's own"),
+    key("defines", ValueKind::StringArray, "Preprocessor definitions before every target's own"),
+    key("compiler", ValueKind::Enum(COMPILERS), "Compiler of targets that don't set one (default: g++)"),
+    key("output_dir", ValueKind::String, "Output directory of targets that don't set one, relative to their build.toml (default: \"build\")"),
+];
+
+/// Keys of `requires` (compiler family → version requirement).
+pub const REQUIRES_KEYS: &[KeySpec] = &[
+    key("gcc", ValueKind::String, "Version of gcc/g++, e.g. \">=12\" or \">=12.2, <15\""),
+    key("clang", ValueKind::String, "Version of clang/clang++, e.g. \">=15\""),
+];
+
+/// Keys of a `[presets.<name>]` entry.
+pub const PRESET_KEYS: &[KeySpec] = &[
+    key("description", ValueKind::String, "Shown next to the name in the GUI's preset list"),
+    key("targets", ValueKind::StringArray, "Targets to build (default: all; -t replaces them)"),
+    key("jobs", ValueKind::Integer, "Parallel jobs (-j overrides it)"),
+    key("defines", ValueKind::StringArray, "Preprocessor definitions added to every target"),
+];
+
+/// Keys of the `[memcheck]` table.
+pub const MEMCHECK_KEYS: &[KeySpec] = &[
+    key("valgrind", ValueKind::String, "valgrind executable (default: valgrind)"),
+    key("tool", ValueKind::String, "Valgrind tool (default: memcheck; e.g. helgrind, drd)"),
+    key("suppressions", ValueKind::StringArray, "Suppression files, relative to the root build.toml"),
+    key("args", ValueKind::StringArray, "Extra valgrind options (e.g. \"--track-origins=yes\")"),
+];
+
+/// Keys of the `[cache]` table.
+pub const CACHE_KEYS: &[KeySpec] = &[
+    key("dir", ValueKind::String, "Local cache directory (default: .oximake/cache)"),
+    key("remote", ValueKind::String, "Shared backend: an http(s):// URL (curl) or s3://bucket/prefix (aws s3 cp)"),
+    key("read_only", ValueKind::Boolean, "Only download from remote, never upload (default: false)"),
+];
+
+/// Keys of the `[remote]` table.
+pub const REMOTE_KEYS: &[KeySpec] = &[
+    key("hosts", ValueKind::StringArray, "SSH destinations, optionally with a slot count (e.g. \"me@build2/16\")"),
+    key("slots", ValueKind::Integer, "Concurrent compiles per host without a count (default: 4)"),
+    key("ssh", ValueKind::String, "ssh executable (default: ssh)"),
+    key("ssh_args", ValueKind::StringArray, "Extra ssh options (e.g. [\"-p\", \"2222\"])"),
+];
+
+/// Keys of the `[project.retry]` table.
+pub const RETRY_KEYS: &[KeySpec] = &[
+    key("count", ValueKind::Integer, "Extra attempts per failed job (default: 2)"),
+    key("matches", ValueKind::StringArray, "Case-insensitive regexes for transient errors (default: file locks, EAGAIN, ...)"),
+    key("delay_ms", ValueKind::Integer, "Wait before the n-th retry: n × delay_ms (default: 500)"),
+];
+
+/// Keys of a `[compilers.<extension>]` rule.
+pub const COMPILER_RULE_KEYS: &[KeySpec] = &[
+    key("command", ValueKind::String, "Program compiling sources with this extension"),
+    key("args", ValueKind::StringArray, "Argument template: {source}, {object}, {include_dirs}, {defines}, {flags}"),
+];
+
+/// Keys of `[workspace]`.
+pub const WORKSPACE_KEYS: &[KeySpec] = &[
+    key("members", ValueKind::StringArray, "Directories with a build.toml to load, as glob patterns (e.g. \"libs/*\")"),
+];
+
+/// Keys of `[module]` (included build.toml files).
+pub const MODULE_KEYS: &[KeySpec] = &[
+    key("name", ValueKind::String, "Module name"),
+    key("includes", ValueKind::StringArray, "Paths to submodule build.toml files"),
+];
+
+/// Keys of the per-OS override tables of a target (`[target.linux]` etc.).
+pub const PLATFORM_KEYS: &[KeySpec] = &[
+    key("sources", ValueKind::StringArray, "Additional source files"),
+    key("exclude", ValueKind::StringArray, "Additional source exclusion patterns"),
+    key("include_dirs", ValueKind::StringArray, "Additional include directories (-I)"),
+    key("lib_dirs", ValueKind::StringArray, "Additional library search directories (-L)"),
+    key("libs", ValueKind::StringArray, "Additional libraries to link (-l)"),
+    key("frameworks", ValueKind::StringArray, "Additional macOS frameworks to link (-framework)"),
+    key("defines", ValueKind::StringArray, "Additional preprocessor definitions"),
+    key("flags", ValueKind::Flags, "Additional legacy flags"),
+    key("compiler_flags", ValueKind::Flags, "Additional compiler-only flags"),
+    key("linker_flags", ValueKind::Flags, "Additional linker-only flags"),
+    key("remove_flags", ValueKind::StringArray, "Additional flags to remove"),
+    key("deps", ValueKind::StringArray, "Additional dependencies"),
+    key("cxx_standard", ValueKind::Standard, "C++ standard on this OS"),
+    key("c_standard", ValueKind::Standard, "C standard on this OS"),
+    key("objc_arc", ValueKind::Boolean, "-fobjc-arc for .m / .mm sources on this OS"),
+    key("compiler", ValueKind::Enum(COMPILERS), "Compiler on this OS"),
+    key("linker", ValueKind::Enum(LINKERS), "Linker on this OS"),
+    key("output_dir", ValueKind::String, "Output directory on this OS"),
+    key("output_name", ValueKind::String, "Output file base name on this OS"),
+    key("lib_prefix", ValueKind::String, "Library file prefix on this OS"),
+    key("lib_suffix", ValueKind::String, "Library file suffix on this OS"),
+    key("strip", ValueKind::Boolean, "Strip symbols after linking on this OS"),
+    key("split_debug", ValueKind::Boolean, "Split debug info into a separate file on this OS"),
+];
+
+/// Keys of a `[[target]]` table.
+pub const TARGET_KEYS: &[KeySpec] = &[
+    key("name", ValueKind::String, "Unique target name"),
+    key("type", ValueKind::Enum(TARGET_TYPES), "Target type"),
+    key("sources", ValueKind::StringArray, "Source files (glob patterns supported, e.g. \"src/**/*.cpp\")"),
+    key("exclude", ValueKind::StringArray, "Patterns removed from the expanded sources (e.g. \"**/*_win.cpp\")"),
+    key("include_dirs", ValueKind::StringArray, "Include directories (-I)"),
+    key("lib_dirs", ValueKind::StringArray, "Library search directories (-L)"),
+    key("libs", ValueKind::StringArray, "Libraries to link (-l)"),
+    key("frameworks", ValueKind::StringArray, "macOS frameworks to link (e.g. \"Cocoa\" → -framework Cocoa)"),
+    key("defines", ValueKind::StringArray, "Preprocessor definitions (e.g. \"FOO_VERSION=3\" → -DFOO_VERSION=3)"),
+    key("flags", ValueKind::Flags, "Legacy flags, applied at compile time"),
+    key("cxx_standard", ValueKind::Standard, "C++ standard (e.g. 17 → -std=c++17, \"gnu++20\" → -std=gnu++20)"),
+    key("c_standard", ValueKind::Standard, "C standard for .c and .m sources (e.g. 11 → -std=c11, \"gnu11\")"),
+    key("objc_arc", ValueKind::Boolean, "Compile .m / .mm sources with automatic reference counting (-fobjc-arc)"),
+    key("timeout_secs", ValueKind::Integer, "Seconds after which a compile/link command is killed and fails"),
+    key("link_memory", ValueKind::Integer, "Estimated peak link memory in MiB (counted against link_memory_limit)"),
+    key("compiler_flags", ValueKind::Flags, "Compiler-only flags (e.g. \"-O2\")"),
+    key("linker_flags", ValueKind::Flags, "Linker-only flags (e.g. \"-Wl,--as-needed\")"),
+    key("no_default_flags", ValueKind::Boolean, "Don't prepend the [defaults] flags, compiler_flags and linker_flags"),
+    key("remove_flags", ValueKind::StringArray, "Flags removed after [defaults] and dependency flags are merged in (e.g. \"-Werror\")"),
+    key("deps", ValueKind::StringArray, "Other targets this target depends on"),
+    key("tags", ValueKind::StringArray, "Groups for `ngm build --tag` / `--exclude-tag` (e.g. \"tests\")"),
+    key("exclude_from_all", ValueKind::Boolean, "Not built without -t (examples, benchmarks) unless a built target depends on it"),
+    key("compiler", ValueKind::Enum(COMPILERS), "Compiler to use"),
+    key("linker", ValueKind::Enum(LINKERS), "Linker for executables and shared libs (-fuse-ld)"),
+    key("output_dir", ValueKind::String, "Output directory (default: \"build\")"),
+    key("output_name", ValueKind::String, "Output file base name (default: the target name)"),
+    key("lib_prefix", ValueKind::String, "Library file prefix (default: \"lib\")"),
+    key("lib_suffix", ValueKind::String, "Library file suffix (default: \".a\", \".so\" or \".dll\")"),
+    key("pkg_config", ValueKind::Boolean, "Libraries: write a pkg-config .pc file (also installed by ngm package)"),
+    key("cmake_export", ValueKind::Boolean, "Libraries: export for find_package() in ngm package's CMake config files"),
+    key("strip", ValueKind::Boolean, "Executables and shared libs: strip symbols after linking"),
+    key("split_debug", ValueKind::Boolean, "Executables and shared libs: move debug info to <output>.debug (macOS: .dSYM) with a debuglink"),
+    key("thin_archive", ValueKind::Boolean, "Static libraries: thin archive referencing the object files (development only)"),
+    key("commands", ValueKind::StringArray, "Custom targets: shell commands, run in the build.toml directory"),
+    key("inputs", ValueKind::StringArray, "Custom targets: files the commands read (glob patterns supported)"),
+    key("outputs", ValueKind::StringArray, "Custom targets: files the commands write"),
+    key("args", ValueKind::StringArray, "Benchmarks and tests: arguments `ngm bench` / `ngm test` run the program with"),
+    key("repetitions", ValueKind::Integer, "Benchmarks: runs per `ngm bench` (default: 5)"),
+    key("test_timeout_secs", ValueKind::Integer, "Tests: seconds after which `ngm test` kills the test and fails it"),
+    key("retries", ValueKind::Integer, "Tests: reruns of a failed test before it counts as failed"),
+    key("qt", ValueKind::Table(QT_KEYS), "Qt support: runs moc/uic/rcc and adds Qt include/lib paths"),
+    key("protobuf", ValueKind::Table(PROTOBUF_KEYS), "Protocol Buffers: runs protoc and compiles the generated .pb.cc files"),
+    key("linux", ValueKind::Table(PLATFORM_KEYS), "Overrides on Linux: lists are appended, single values replace"),
+    key("macos", ValueKind::Table(PLATFORM_KEYS), "Overrides on macOS: lists are appended, single values replace"),
+    key("windows", ValueKind::Table(PLATFORM_KEYS), "Overrides on Windows: lists are appended, single values replace"),
+];
+
+/// Keys of a target's `qt` table.
+pub const QT_KEYS: &[KeySpec] = &[
+    key("modules", ValueKind::StringArray, "Qt modules, e.g. [\"Widgets\", \"Network\"] (Core is always used)"),
+    key("auto_moc", ValueKind::Boolean, "Run moc on headers containing Q_OBJECT / Q_GADGET (default: true)"),
+];
+
+/// Keys of a target's `protobuf` table.
+pub const PROTOBUF_KEYS: &[KeySpec] = &[
+    key("sources", ValueKind::StringArray, ".proto files (glob patterns supported)"),
+    key("import_dirs", ValueKind::StringArray, "protoc import paths (default: the build.toml directory)"),
+    key("protoc", ValueKind::String, "protoc executable (default: protoc from PATH)"),
+];
+
+/// Keys of a per-compiler flag table (`[target.compiler_flags]` with `gcc = [...]`).
+pub const FLAG_KEYS: &[KeySpec] = &[
+    key("all", ValueKind::StringArray, "Flags for every compiler"),
+    key("gcc", ValueKind::StringArray, "Flags used only with gcc / g++"),
+    key("clang", ValueKind::StringArray, "Flags used only with clang"),
+    key("msvc", ValueKind::StringArray, "Flags used only with MSVC"),
+];
+
+/// Keys whose values may contain `${name}` / `${env:NAME}` (expanded during config resolution).
+pub const INTERPOLATED_KEYS: &[&str] = &[
+    "includes", "sources", "exclude", "include_dirs", "lib_dirs", "libs", "frameworks", "defines", "flags", "compiler_flags", "linker_flags", "output_dir", "output_name", "commands", "inputs", "outputs", "import_dirs", "protoc",
+];
+
+/// One validation problem, located in a build.toml.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigError {
+    pub file: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    /// Length of the offending text on that line (for underlining; at least 1)
+    pub length: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// A build.toml being validated: path and text, to turn byte spans into line/column.
+struct Source<'a> {
+    path: &'a Path,
+    text: &'a str,
+}
+
+impl Source<'_> {
+    fn error(&self, span: Option<Range<usize>>, severity: Severity, message: String) -> ConfigError {
+        let span = span.unwrap_or(0..0);
+        let start = span.start.min(self.text.len());
+        let before = &self.text[..start];
+        let line = before.matches('\n').count() as u32 + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = self.text[line_start..start].chars().count() as u32 + 1;
+        let line_end = self.text[start..].find('\n').map_or(self.text.len(), |i| start + i);
+        let end = span.end.clamp(start, line_end);
+        let length = (self.text[start..end].chars().count() as u32).max(1);
+        ConfigError { file: self.path.to_path_buf(), line, column, length, severity, message }
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<ConfigError>,
+    visited: HashSet<PathBuf>,
+    /// Target name → where it is defined
+    targets: HashMap<String, ConfigError>,
+    /// Dependencies to resolve once all files are read: (name, location)
+    deps: Vec<(String, ConfigError)>,
+    /// Names in the root `[dependencies]`; their targets are `<name>::<library>`
+    packages: HashSet<String>,
+    /// Literal sources and include dirs that don't exist: reported at the end unless a
+    /// custom target generates them (or files in them)
+    missing_sources: Vec<(PathBuf, ConfigError)>,
+    missing_dirs: Vec<(PathBuf, ConfigError)>,
+    /// Declared outputs of custom targets (normalized paths)
+    generated: HashSet<PathBuf>,
+    /// Output directories of targets (normalized paths); the build creates them
+    output_dirs: HashSet<PathBuf>,
+    /// `${name}` variables of the file being validated
+    vars: Variables,
+    /// `--set name=value` values (win over [variables])
+    overrides: Variables,
+    /// Files read, in order
+    files: Vec<PathBuf>,
+}
+
+/// Validates the root build.toml and all included files. Returns every problem
+/// found (errors and warnings): per file in order, then missing sources and
+/// unresolved dependencies.
+pub fn validate_config(root: &Path) -> Vec<ConfigError> {
+    validate_config_with_vars(root, &Variables::new())
+}
+
+/// validate_config with `--set name=value` overrides (see parse_build_file_with_vars).
+pub fn validate_config_with_vars(root: &Path, overrides: &Variables) -> Vec<ConfigError> {
+    let mut v = Validator { vars: overrides.clone(), overrides: overrides.clone(), ..Default::default() };
+    v.file(root, true);
+    for (path, location) in std::mem::take(&mut v.missing_sources) {
+        if !v.generated.contains(&normalize_path(&path)) {
+            v.errors.push(ConfigError { message: format!("source file not found: {}{}", path.display(), location.message), ..location });
+        }
+    }
+    for (dir, location) in std::mem::take(&mut v.missing_dirs) {
+        let dir_path = normalize_path(&dir);
+        let created = v.output_dirs.iter().any(|o| dir_path.starts_with(o)) || v.generated.iter().any(|g| g.starts_with(&dir_path));
+        if !created {
+            v.errors.push(ConfigError { message: format!("include directory not found: {}{}", dir.display(), location.message), ..location });
+        }
+    }
+    for (dep, location) in std::mem::take(&mut v.deps) {
+        let imported = dep.split_once("::").is_some_and(|(package, _)| v.packages.contains(package));
+        if !v.targets.contains_key(&dep) && !imported {
+            v.errors.push(ConfigError {
+                message: format!("unknown dependency '{}'{}", dep, suggestion(&dep, v.targets.keys().map(String::as_str))),
+                ..location
+            });
+        }
+    }
+    v.errors
+}
+
+/// The root build.toml and every file it includes or lists as a workspace member,
+/// in the order validation reads them.
+pub fn config_files(root: &Path) -> Vec<PathBuf> {
+    let mut v = Validator::default();
+    v.file(root, true);
+    v.files
+}
+
+/// A problem at byte `span` of `text`, the contents of `path`.
+pub(crate) fn locate(path: &Path, text: &str, span: Option<Range<usize>>, severity: Severity, message: String) -> ConfigError {
+    Source { path, text }.error(span, severity, message)
+}
+
+impl Validator {
+    fn file(&mut self, path: &Path, is_root: bool) {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !self.visited.insert(canonical) {
+            return;
+        }
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) => {
+                self.errors.push(ConfigError {
+                    file: path.to_path_buf(),
+                    line: 1,
+                    column: 1,
+                    length: 1,
+                    severity: Severity::Error,
+                    message: format!("failed to read file: {}", e),
+                });
+                return;
+            }
+        };
+        self.files.push(path.to_path_buf());
+        let src = Source { path, text: &text };
+        let doc = match ImDocument::parse(text.as_str()) {
+            Ok(d) => d,
+            Err(e) => {
+                self.errors.push(src.error(e.span(), Severity::Error, format!("TOML syntax error: {}", e.message())));
+                return;
+            }
+        };
+        let root = doc.as_table();
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+        let mut includes = Vec::new();
+        let mut members = Vec::new();
+        let vars = self.variables(&src, root.get("variables"));
+        let inherited = std::mem::replace(&mut self.vars, vars);
+
+        let settings_key = if is_root { "project" } else { "module" };
+        let settings_keys = if is_root { PROJECT_KEYS } else { MODULE_KEYS };
+        let mut top_level: Vec<&str> = vec!["project", "module", "workspace", "target", "variables"];
+        top_level.extend(settings_keys.iter().map(|k| k.name));
+        for (name, item) in root.iter() {
+            match name {
+                "variables" => {}
+                "project" | "module" => match item.as_table() {
+                    Some(table) if name == settings_key || (!is_root && name == "project") => {
+                        self.table(&src, table, settings_keys, &format!("[{}]", name));
+                        includes.extend(string_items(table.get("includes"), &self.vars));
+                        if is_root {
+                            self.packages.extend(table.get("dependencies").and_then(Item::as_table_like).into_iter().flat_map(|t| t.iter().map(|(k, _)| k.to_string())));
+                        }
+                    }
+                    Some(_) => self.errors.push(src.error(
+                        root.key(name).and_then(|k| k.span()),
+                        Severity::Warning,
+                        format!("[{}] is ignored in {} build.toml (use [{}])", name, if is_root { "the root" } else { "an included" }, settings_key),
+                    )),
+                    None => self.errors.push(src.error(item.span(), Severity::Error, format!("'{}' must be a table", name))),
+                },
+                "workspace" => match item.as_table() {
+                    Some(table) => {
+                        self.table(&src, table, WORKSPACE_KEYS, "[workspace]");
+                        for (member, span) in string_items(table.get("members"), &self.vars) {
+                            let pattern = base_dir.join(&member);
+                            match glob::glob(&pattern.to_string_lossy()) {
+                                Ok(entries) => {
+                                    let mut files: Vec<PathBuf> = entries
+                                        .flatten()
+                                        .map(|p| if p.is_dir() { p.join("build.toml") } else { p })
+                                        .filter(|p| p.is_file())
+                                        .collect();
+                                    if files.is_empty() {
+                                        self.errors.push(src.error(span, Severity::Warning, format!("workspace member pattern '{}' matches no build.toml", member)));
+                                    }
+                                    files.sort();
+                                    members.extend(files);
+                                }
+                                Err(e) => self.errors.push(src.error(span, Severity::Error, format!("invalid workspace member pattern '{}': {}", member, e))),
+                            }
+                        }
+                    }
+                    None if item.as_bool().is_some() => {}
+                    None => self.errors.push(src.error(item.span(), Severity::Error, "'workspace' must be a table or a boolean".to_string())),
+                },
+                "target" => match item.as_array_of_tables() {
+                    Some(targets) => {
+                        for t in targets.iter() {
+                            self.target(&src, t, &base_dir);
+                        }
+                    }
+                    None => self.errors.push(src.error(
+                        item.span().or_else(|| root.key(name).and_then(|k| k.span())),
+                        Severity::Error,
+                        "'target' must be an array of tables ([[target]])".to_string(),
+                    )),
+                },
+                _ => {
+                    if let Some(spec) = settings_keys.iter().find(|k| k.name == name) {
+                        self.value(&src, item, spec, "");
+                        if name == "includes" {
+                            includes.extend(string_items(Some(item), &self.vars));
+                        }
+                        if name == "dependencies" && is_root {
+                            self.packages.extend(item.as_table_like().into_iter().flat_map(|t| t.iter().map(|(k, _)| k.to_string())));
+                        }
+                    } else {
+                        self.unknown_key(&src, root, name, top_level.iter().copied(), "");
+                    }
+                }
+            }
+        }
+
+        for (include, span) in includes {
+            let include_path = base_dir.join(&include);
+            if include_path.is_file() {
+                self.file(&include_path, false);
+            } else {
+                self.errors.push(src.error(span, Severity::Error, format!("included file not found: {}", include_path.display())));
+            }
+        }
+        for member in members {
+            self.file(&member, false);
+        }
+        self.vars = inherited;
+    }
+
+    /// Variables visible in a file: the current ones plus its [variables] table.
+    fn variables(&mut self, src: &Source, item: Option<&Item>) -> Variables {
+        let mut vars = self.vars.clone();
+        let Some(item) = item else { return vars };
+        let Some(table) = item.as_table() else {
+            self.errors.push(src.error(item.span(), Severity::Error, "[variables] must be a table".to_string()));
+            return vars;
+        };
+        for (name, item) in table.iter() {
+            if self.overrides.contains_key(name) {
+                continue;
+            }
+            let value = item.as_value();
+            let expanded = match (value.and_then(|v| v.as_str()), value) {
+                (Some(s), _) => expand(s, &self.vars),
+                (None, Some(v)) if v.is_integer() || v.is_float() || v.is_bool() => {
+                    Ok(v.as_integer().map(|i| i.to_string()).or_else(|| v.as_float().map(|f| f.to_string())).unwrap_or_else(|| v.as_bool().unwrap_or(false).to_string()))
+                }
+                _ => Err(format!("variable '{}' must be a string", name)),
+            };
+            match expanded {
+                Ok(v) => {
+                    vars.insert(name.to_string(), v);
+                }
+                Err(e) => self.errors.push(src.error(item.span(), Severity::Error, e)),
+            }
+        }
+        vars
+    }
+
+    fn table(&mut self, src: &Source, table: &dyn TableLike, keys: &[KeySpec], context: &str) {
+        for (name, item) in table.iter() {
+            match keys.iter().find(|k| k.name == name) {
+                Some(spec) => self.value(src, item, spec, context),
+                None => self.unknown_key(src, table, name, keys.iter().map(|k| k.name), context),
+            }
+        }
+    }
+
+    fn target(&mut self, src: &Source, table: &Table, base_dir: &Path) {
+        let name = table.get("name").and_then(Item::as_str);
+        let context = match name {
+            Some(n) => format!("target '{}'", n),
+            None => "[[target]]".to_string(),
+        };
+        self.table(src, table, TARGET_KEYS, &context);
+
+        match name {
+            None => self.errors.push(src.error(table.span(), Severity::Error, "target without 'name'".to_string())),
+            Some(n) => {
+                let location = src.error(table.get("name").and_then(Item::span), Severity::Error, String::new());
+                if let Some(first) = self.targets.get(n) {
+                    self.errors.push(ConfigError {
+                        message: format!("duplicate target '{}' (first defined at {}:{})", n, first.file.display(), first.line),
+                        ..location
+                    });
+                } else {
+                    self.targets.insert(n.to_string(), location);
+                }
+            }
+        }
+
+        let target_type = table.get("type").and_then(Item::as_str);
+        if let (Some(kind @ ("interface" | "custom" | "group")), Some(sources)) = (target_type, table.get("sources")) {
+            self.errors.push(src.error(
+                sources.span(),
+                Severity::Warning,
+                format!("sources of {} {} are not compiled", kind, context),
+            ));
+        }
+        if target_type == Some("custom") && table.get("commands").is_none() {
+            self.errors.push(src.error(table.span(), Severity::Error, format!("custom {} has no 'commands'", context)));
+        }
+        if target_type == Some("group") && table.get("deps").is_none() {
+            self.errors.push(src.error(table.span(), Severity::Error, format!("group {} has no 'deps'", context)));
+        }
+        for (dep, span) in string_items(table.get("deps"), &self.vars) {
+            let location = src.error(span, Severity::Error, String::new());
+            self.deps.push((dep, location));
+        }
+        for (source, span) in string_items(table.get("sources"), &self.vars) {
+            let path = base_dir.join(&source);
+            if source.contains(['*', '?', '[']) {
+                let matched = glob::glob(&path.to_string_lossy()).map(|mut m| m.next().is_some()).unwrap_or(false);
+                if !matched {
+                    self.errors.push(src.error(span, Severity::Warning, format!("pattern '{}' matches no files", source)));
+                }
+            } else if !path.is_file() {
+                self.missing_sources.push((path, src.error(span, Severity::Error, in_context(&context))));
+            }
+        }
+        let output_dir = table.get("output_dir").and_then(Item::as_str).and_then(|d| expand(d, &self.vars).ok());
+        self.output_dirs.insert(normalize_path(&base_dir.join(output_dir.as_deref().unwrap_or("build"))));
+        if target_type == Some("custom") {
+            for (output, _) in string_items(table.get("outputs"), &self.vars) {
+                self.generated.insert(normalize_path(&base_dir.join(output)));
+            }
+        }
+        for (pattern, span) in string_items(table.get("exclude"), &self.vars) {
+            if let Err(e) = exclude_patterns(&[pattern]) {
+                self.errors.push(src.error(span, Severity::Error, e));
+            }
+        }
+        for (dir, span) in string_items(table.get("include_dirs"), &self.vars) {
+            if !base_dir.join(&dir).is_dir() {
+                self.missing_dirs.push((base_dir.join(&dir), src.error(span, Severity::Error, in_context(&context))));
+            }
+        }
+        for (dir, span) in string_items(table.get("lib_dirs"), &self.vars) {
+            if !base_dir.join(&dir).is_dir() {
+                self.errors.push(src.error(span, Severity::Warning, format!("directory not found: {}", dir)));
+            }
+        }
+    }
+
+    fn value(&mut self, src: &Source, item: &Item, spec: &KeySpec, context: &str) {
+        let value = item.as_value();
+        let strings = match item.as_array() {
+            Some(array) => array.iter().collect(),
+            None => value.into_iter().collect::<Vec<_>>(),
+        };
+        for s in strings.into_iter().filter(|_| INTERPOLATED_KEYS.contains(&spec.name) || FLAG_KEYS.contains(spec)) {
+            if let Some(Err(e)) = s.as_str().map(|s| expand(s, &self.vars)) {
+                self.errors.push(src.error(s.span(), Severity::Error, e));
+            }
+        }
+        let ok = match spec.kind {
+            ValueKind::Table(keys) => match item.as_table_like() {
+                Some(table) => {
+                    self.table(src, table, keys, &format!("{}.{}", context, spec.name));
+                    true
+                }
+                None => false,
+            },
+            ValueKind::Map(keys) => match item.as_table_like() {
+                Some(table) => {
+                    for (name, rule) in table.iter() {
+                        let context = format!("[{}.{}]", spec.name, name);
+                        match rule.as_table_like() {
+                            Some(rule) => self.table(src, rule, keys, &context),
+                            None => self.errors.push(src.error(rule.span(), Severity::Error, format!("{} must be a table", context))),
+                        }
+                    }
+                    true
+                }
+                None => false,
+            },
+            ValueKind::Flags => match item.as_table_like() {
+                Some(table) => {
+                    self.table(src, table, FLAG_KEYS, &format!("{}.{}", context, spec.name));
+                    true
+                }
+                None => self.string_array(src, item, spec, context),
+            },
+            ValueKind::String => value.is_some_and(|v| v.is_str()),
+            ValueKind::Integer => value.is_some_and(|v| v.is_integer()),
+            ValueKind::Boolean => value.is_some_and(|v| v.is_bool()),
+            ValueKind::Standard => match value.and_then(|v| v.as_str()) {
+                Some(s) => {
+                    if Standard::parse(s).is_err() {
+                        self.errors.push(src.error(
+                            item.span(),
+                            Severity::Error,
+                            format!("invalid {} '{}'{} (expected {})", spec.name, s, in_context(context), spec.kind.describe()),
+                        ));
+                    }
+                    true
+                }
+                None => value.is_some_and(|v| v.is_integer()),
+            },
+            ValueKind::StringArray => self.string_array(src, item, spec, context),
+            ValueKind::Enum(values) => match value.and_then(|v| v.as_str()) {
+                Some(s) if values.contains(&s) => true,
+                Some(s) => {
+                    self.errors.push(src.error(
+                        item.span(),
+                        Severity::Error,
+                        format!("invalid {} '{}'{} (expected {}){}", spec.name, s, in_context(context), spec.kind.describe(), suggestion(s, values.iter().copied())),
+                    ));
+                    true
+                }
+                None => false,
+            },
+        };
+        if !ok {
+            self.errors.push(src.error(
+                item.span(),
+                Severity::Error,
+                format!("'{}'{} must be {}", spec.name, in_context(context), spec.kind.describe()),
+            ));
+        }
+    }
+
+    /// Checks an array of strings; false if `item` is not an array.
+    fn string_array(&mut self, src: &Source, item: &Item, spec: &KeySpec, context: &str) -> bool {
+        let Some(array) = item.as_array() else { return false };
+        for element in array.iter().filter(|e| !e.is_str()) {
+            self.errors.push(src.error(
+                element.span(),
+                Severity::Error,
+                format!("'{}'{} must contain only strings", spec.name, in_context(context)),
+            ));
+        }
+        true
+    }
+
+    fn unknown_key<'k>(&mut self, src: &Source, table: &dyn TableLike, name: &str, known: impl Iterator<Item = &'k str>, context: &str) {
+        self.errors.push(src.error(
+            table.key(name).and_then(|k| k.span()),
+            Severity::Warning,
+            format!("unknown key '{}'{}{}", name, in_context(context), suggestion(name, known)),
+        ));
+    }
+}
+
+fn in_context(context: &str) -> String {
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" in {}", context)
+    }
+}
+
+/// String elements of an array item (`${...}` expanded), with their spans.
+/// Elements that fail to expand are skipped; value() reports them.
+fn string_items(item: Option<&Item>, vars: &Variables) -> Vec<(String, Option<Range<usize>>)> {
+    item.and_then(Item::as_array)
+        .map(|a| a.iter().filter_map(|v| Some((expand(v.as_str()?, vars).ok()?, v.span()))).collect())
+        .unwrap_or_default()
+}
+
+/// ", did you mean 'x'?" for the closest candidate within edit distance 2.
+fn suggestion<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    candidates
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, _)| *d <= 2)
+        .min()
+        .map(|(_, c)| format!(", did you mean '{}'?", c))
+        .unwrap_or_default()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Renders problems compiler-style, with the offending line and an underline:
+///
+/// ```text
+/// error: unknown dependency 'utl', did you mean 'util'?
+///   --> build.toml:12:9
+///    |
+/// 12 | deps = ["utl"]
+///    |         ^^^^^
+/// ```
+pub fn render(errors: &[ConfigError]) -> String {
+    let mut sources: HashMap<&Path, Option<String>> = HashMap::new();
+    let mut out = String::new();
+    for e in errors {
+        let text = sources.entry(e.file.as_path()).or_insert_with(|| std::fs::read_to_string(&e.file).ok());
+        let line_text = text.as_deref().and_then(|t| t.lines().nth(e.line as usize - 1));
+        out.push_str(&format!("{}: {}\n", e.severity.as_str(), e.message));
+        let number = e.line.to_string();
+        let pad = " ".repeat(number.len());
+        out.push_str(&format!("{}--> {}:{}:{}\n", pad, e.file.display(), e.line, e.column));
+        if let Some(line_text) = line_text {
+            out.push_str(&format!("{} |\n{} | {}\n", pad, number, line_text));
+            out.push_str(&format!(
+                "{} | {}{}\n",
+                pad,
+                " ".repeat(e.column as usize - 1),
+                "^".repeat(e.length as usize)
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_reports_located_problems() {
+        let dir = TempDir::new("validate");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        dir.write("src/main.cpp", "int main() {}\n");
+        let config = dir.join("build.toml");
+        std::fs::write(
+            &config,
+            "[project]\nname = \"demo\"\ncxx_standard = \"c++2x\"\n\n[[target]]\nname = \"app\"\ntype = \"exe\"\nsorces = [\"src/main.cpp\"]\nsources = [\"src/main.cpp\", \"src/missing.cpp\"]\ndeps = [\"utl\"]\n",
+        )
+        .unwrap();
+
+        let errors = validate_config(&config);
+        let found: Vec<(u32, u32, &str)> = errors.iter().map(|e| (e.line, e.column, e.message.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, 16, "invalid cxx_standard 'c++2x' in [project] (expected an integer (e.g. 17) or a standard name (e.g. \"gnu++17\"))"),
+                (7, 8, "invalid type 'exe' in target 'app' (expected one of: executable, static_lib, shared_lib, interface, custom, group, benchmark, test)"),
+                (8, 1, "unknown key 'sorces' in target 'app', did you mean 'sources'?"),
+                (9, 28, &*format!("source file not found: {} in target 'app'", dir.join("src/missing.cpp").display())),
+                (10, 9, "unknown dependency 'utl'"),
+            ]
+        );
+        assert_eq!(errors[2].severity, Severity::Warning);
+        assert_eq!(errors[3].severity, Severity::Error);
+        assert_eq!(errors[2].length, 6);
+        assert!(render(&errors[..1]).contains("3 | cxx_standard = \"c++2x\"\n  |                ^^^^^^^\n"));
+    }
+}